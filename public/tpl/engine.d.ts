@@ -306,6 +306,12 @@ declare const cv: {
     }
 }
 
+/** 可取消异步内置函数的选项 */
+interface AbortOptions {
+    /** 中止信号；中止后 Promise 以 `signal.reason`（默认 AbortError）拒绝 */
+    signal?: AbortSignal
+}
+
 /** 高精度计时器 */
 declare class Timer {
     /** 重置定时器 */
@@ -313,13 +319,15 @@ declare class Timer {
     /**
      * 等待指定时间（异步）
      * @param ms 延迟（毫秒）
+     * @param options 可选 `{ signal }`，中止时提前拒绝
      */
-    sleep(ms: number): Promise<void>
+    sleep(ms: number, options?: AbortOptions): Promise<void>
     /**
      * 等待到指定时间（异步）
      * @param ms 相对时间（毫秒）
+     * @param options 可选 `{ signal }`，中止时提前拒绝
      */
-    sleepUntil(ms: number): Promise<void>
+    sleepUntil(ms: number, options?: AbortOptions): Promise<void>
     /**
      * 获取已过去时间（毫秒）
      */
@@ -442,7 +450,7 @@ declare module "cap" {
             filterTolerance?: number
         ): boolean
         /** 等待客户区指定坐标达到颜色条件 */
-        waitColor(x: number, y: number, color: number, tolerance: number, timeout?: number): Promise<boolean>
+        waitColor(x: number, y: number, color: number, tolerance: number, timeout?: number, options?: WaitColorOptions): Promise<boolean>
         /** 播放 DSL 宏并返回可手动中断的 Promise */
         play(dsl: string): StoppablePromise<void>
         /** 停止当前 DSL 播放 */
@@ -609,8 +617,9 @@ declare function s(ms: number): void
 /**
  * 延迟等待（异步）
  * @param ms 毫秒数
+ * @param options 可选 `{ signal }`，中止时提前拒绝
  */
-declare function sleep(ms: number): Promise<void>
+declare function sleep(ms: number, options?: AbortOptions): Promise<void>
 
 /**
  * 播放 OK 外部 mod 宏。
@@ -857,13 +866,37 @@ declare function captureWindowWGC(hwnd: number, x?: number, y?: number, w?: numb
 declare function winGetClientPos(hwnd: number): ROI | undefined
 
 /**
- * 从文件加载模板Mat对象(有缓存)
- * @param path 模板路径
+ * 从文件加载模板Mat对象(有缓存，文件修改时间或大小变化后自动重新加载)
+ * @param path 模板路径；`atlas.json#name` / `atlas.toml#name` 形式时从模板图集中按名称取出
  * @returns Mat对象
  * @throws 加载模板失败时抛出错误
  */
 declare function getTemplate(path: string): Mat
 
+/** 图集模板元数据 */
+interface TemplateMeta {
+    /** 模板名称 */
+    name: string
+    /** 图集中的区域 [x, y, w, h] */
+    rect: [number, number, number, number]
+    /** 默认匹配阈值 */
+    threshold?: number
+    /** 建议搜索区域 [x, y, w, h] */
+    roi?: [number, number, number, number]
+    /** 透明色（0xRRGGBB），该颜色像素在模板中被忽略 */
+    colorKey?: number
+    /** 点击偏移 [dx, dy]（相对模板左上角） */
+    offset?: [number, number]
+}
+
+/**
+ * 获取图集模板的元数据
+ * @param path `atlas.json#name` 形式的图集引用
+ * @returns 元数据；普通模板路径返回 undefined
+ * @throws 清单无效或模板不存在时抛出错误
+ */
+declare function getTemplateMeta(path: string): TemplateMeta | undefined
+
 /**
  * 从 base64 字符串加载模板Mat对象
  * @param b64Str base64 编码的图片字符串
@@ -872,6 +905,42 @@ declare function getTemplate(path: string): Mat
  */
 declare function getTemplateB64(b64Str: string): Mat
 
+/** 模板缓存统计信息 */
+interface TemplateCacheStats {
+    /** 缓存条目数 */
+    entries: number
+    /** 文件模板条目数 */
+    fileEntries: number
+    /** base64 模板条目数 */
+    base64Entries: number
+    /** 图集模板条目数 */
+    atlasEntries: number
+    /** 当前占用字节数（像素数据 + 键长度） */
+    bytes: number
+    /** 字节预算，超出后按最近最少使用淘汰 */
+    budgetBytes: number
+    /** 命中次数 */
+    hits: number
+    /** 未命中次数（含失效） */
+    misses: number
+    /** 因超出预算被淘汰的次数 */
+    evictions: number
+    /** 因文件修改时间/大小变化而失效的次数 */
+    invalidations: number
+}
+
+/**
+ * 清空模板缓存（getTemplate / getTemplateB64 共用）与已解析的图集清单，命中统计保留
+ * @returns 清除的条目数
+ */
+declare function clearTemplateCache(): number
+
+/**
+ * 获取模板缓存统计信息
+ * @returns 缓存条目、占用字节与命中/未命中等计数
+ */
+declare function templateCacheStats(): TemplateCacheStats
+
 /**
  * 从文件加载模板Mat对象
  * @param path 模板路径
@@ -921,10 +990,11 @@ declare function readText(path: string, url?: string): string | undefined
  * @param url 下载地址
  * @param filename 保存文件名或路径（相对路径会按脚本目录解析）
  * @param force 是否强制覆盖下载，默认 false；当 false 且文件已存在时跳过下载
+ * @param options 可选 `{ signal }`，中止时拒绝且不写入文件
  * @returns Promise<void>
  * @throws 下载失败时抛出错误
  */
-declare function downloadFile(url: string, filename: string, force?: boolean): Promise<void>
+declare function downloadFile(url: string, filename: string, force?: boolean, options?: AbortOptions): Promise<void>
 
 /**
  * 删除文件。
@@ -947,18 +1017,88 @@ declare function existsFile(path: string): boolean
  * @param localRootDir 本地资源目录（可选，默认使用程序数据目录）
  * @param cdnBaseUrl CDN 根地址（可选，默认 https://cdn.dna-builder.cn/ocr）
  * @param numThread 识别线程数（可选，默认 2）
- * @returns 实际使用的本地资源目录
+ * @param maxBatchSize 识别/分类单次推理的最大文本块数（可选，默认 8，1 为逐块推理）
+ * @returns 实际使用的本地资源目录（目录、CDN 地址与线程数不变时复用已加载的模型，缺失资源会重新下载）
  * @throws 初始化失败时抛出错误
  */
-declare function initOcr(localRootDir?: string, cdnBaseUrl?: string, numThread?: number): string
+declare function initOcr(localRootDir?: string, cdnBaseUrl?: string, numThread?: number, maxBatchSize?: number): string
+
+/** OCR 预置字符集 */
+type OcrCharsetName = "digits" | "latin" | "punct" | "punctuation"
+
+/** OCR 解码约束选项 */
+interface OcrDecodeOptions {
+    /**
+     * 限定解码字符集：预置字符集名，可传数组组合；其余字符在取最大值前被屏蔽，
+     * 可避免数字被误识别为形近汉字。未知名称或最终白名单为空时抛出错误
+     */
+    charset?: OcrCharsetName | OcrCharsetName[]
+    /** 按字面加入白名单的自定义字符（如 `"+-/%"`），可与 `charset` 组合 */
+    customChars?: string
+    /** 文本块识别结果必须满足的正则，不满足的文本块被丢弃 */
+    pattern?: string | RegExp
+}
 
 /**
  * OCR 文字识别（输入 Mat，返回文本）。
  * @param imgMat 图像 Mat（支持 1/3/4 通道）
+ * @param options 可选 `{ charset, customChars, pattern }`
  * @returns 识别文本（失败或无结果时可能为空字符串）
  * @throws OCR 未初始化或识别失败时抛出错误
  */
-declare function ocrText(imgMat: Mat): string
+declare function ocrText(imgMat: Mat, options?: OcrDecodeOptions): string
+
+/** 结构化 OCR 选项 */
+interface OcrOptions extends OcrDecodeOptions {
+    /** 是否输出逐字符偏移 `offsets`，默认 false */
+    chars?: boolean
+}
+
+/** OCR 文本块 */
+interface OcrTextBlock {
+    text: string
+    /** 字符平均置信度 */
+    confidence: number
+    /** 检测框得分 */
+    score: number
+    /** 旋转文本框四点（左上、右上、右下、左下） */
+    box: [number, number][]
+    /** 外接矩形 `[x, y, w, h]` */
+    bbox: [number, number, number, number]
+    /** 所在行号（阅读顺序，从 0 开始） */
+    line: number
+    /** 各字符中心沿文字方向相对块起点的像素偏移（仅 `chars: true` 时存在） */
+    offsets?: number[]
+}
+
+/** OCR 文本行 */
+interface OcrLine {
+    /** 行内各块文本以空格连接 */
+    text: string
+    bbox: [number, number, number, number]
+    /** 行内文本块在 `blocks` 中的下标（从左到右） */
+    blocks: number[]
+}
+
+/** OCR 各阶段耗时（毫秒） */
+interface OcrTimings {
+    det: number
+    cls: number
+    rec: number
+    /** 识别模型推理批次数 */
+    batches: number
+}
+
+/**
+ * 结构化 OCR：返回文本块、置信度与按阅读顺序分组的行（需先调用 initOcr）
+ * @param imgMat 图像 Mat（支持 1/3/4 通道）
+ * @returns `text` 为各行以换行连接的全文
+ * @throws OCR 未初始化或识别失败时抛出错误
+ */
+declare function ocr(
+    imgMat: Mat,
+    options?: OcrOptions
+): { text: string; blocks: OcrTextBlock[]; lines: OcrLine[]; timings: OcrTimings }
 
 /**
  * 保存Mat对象到文件
@@ -1004,19 +1144,57 @@ declare function selectroi(
     printNotice?: boolean
 ): Promise<[number, number, number, number] | undefined>
 
+/**
+ * 模板匹配算法（得分统一为越高越好）
+ * - `ccoeff_normed`（默认）/ `ccorr_normed` / `sqdiff_normed`：得分在 [0, 1]（ccoeff 为 [-1, 1]）
+ * - `sqdiff`：按模板像素数与 255² 归一化；`ccoeff` / `ccorr`：除以模板自身的匹配得分
+ * - 各算法完全一致时得分均为 1；`ccoeff` / `ccorr` 无上界，更亮或对比度更高的区域可能略高于 1
+ */
+type MatchMethod = "ccoeff_normed" | "ccoeff" | "ccorr_normed" | "ccorr" | "sqdiff_normed" | "sqdiff"
+
+/** 模板匹配预处理名称（源图像与模板做相同处理） */
+type MatchPreprocessName = "none" | "gray" | "canny" | "clahe" | "lightness" | "channel" | "b" | "g" | "r"
+
+/** 模板匹配预处理（可带参数） */
+type MatchPreprocess =
+    | MatchPreprocessName
+    | {
+          type: MatchPreprocessName
+          /** Canny 低阈值，默认 50 */
+          low?: number
+          /** Canny 高阈值，默认 150 */
+          high?: number
+          /** CLAHE 对比度限制，默认 2 */
+          clipLimit?: number
+          /** CLAHE 网格大小，默认 8 */
+          tileSize?: number
+          /** 单通道索引（0=B, 1=G, 2=R），默认 0 */
+          channel?: number
+      }
+
+/** 模板匹配算法与预处理选项 */
+interface MatchOptions extends AbortOptions {
+    /** 匹配算法，默认 `ccoeff_normed` */
+    method?: MatchMethod
+    /** 预处理，默认 `none`（BGR 原图） */
+    preprocess?: MatchPreprocess
+}
+
 /**
  * 颜色和模板匹配（使用两个Mat对象）
  * @param imgMat 图像Mat对象
  * @param templateMat 模板Mat对象
  * @param color 颜色值
  * @param tolerance 容差
+ * @param options 可选 `{ method, preprocess, signal }`；预处理作用于颜色二值图，仅 `canny` 有效果
  * @returns 匹配结果 [x, y] 或 undefined
  */
 declare function findColorAndMatchTemplate(
     imgMat: Mat,
     templateMat: Mat,
     color: number,
-    tolerance: number
+    tolerance: number,
+    options?: MatchOptions
 ): Promise<[number, number] | undefined>
 
 /**
@@ -1024,9 +1202,93 @@ declare function findColorAndMatchTemplate(
  * @param imgMat 图像Mat对象（BGR格式）
  * @param templateMat 模板Mat对象（BGR或BGRA格式，BGRA格式会自动使用alpha通道作为权重）
  * @param tolerance 匹配置信度阈值
+ * @param options 可选 `{ method, preprocess, signal }`
  * @returns 匹配结果 [x, y] 或 undefined
  */
-declare function matchTemplate(imgMat: Mat, templateMat: Mat, tolerance: number): Promise<[number, number] | undefined>
+declare function matchTemplate(
+    imgMat: Mat,
+    templateMat: Mat,
+    tolerance: number,
+    options?: MatchOptions
+): Promise<[number, number] | undefined>
+
+/** 多目标模板匹配结果 */
+interface TemplateMatchResult {
+    x: number
+    y: number
+    w: number
+    h: number
+    /** 匹配得分（TM_CCOEFF_NORMED，越高越好） */
+    score: number
+}
+
+/** 多目标模板匹配选项 */
+interface MatchTemplateAllOptions extends MatchOptions {
+    /** 最多返回数量，默认 32，0 表示不限制 */
+    maxResults?: number
+    /** 非极大值抑制的 IoU 阈值，默认 0.3 */
+    nmsIou?: number
+    /** 搜索区域 [x, y, w, h]，返回坐标仍相对于整张图 */
+    roi?: [number, number, number, number]
+}
+
+/**
+ * 多目标模板匹配（返回所有高于阈值的位置，并做 IoU 非极大值抑制）
+ * @param imgMat 图像Mat对象（BGR格式）
+ * @param templateMat 模板Mat对象（BGR或BGRA格式）
+ * @param threshold 匹配得分阈值，默认 0.8
+ * @param options 可选 `{ maxResults, nmsIou, roi, method, preprocess, signal }`
+ * @returns 按得分降序排列的匹配结果
+ */
+declare function matchTemplateAll(
+    imgMat: Mat,
+    templateMat: Mat,
+    threshold?: number,
+    options?: MatchTemplateAllOptions
+): Promise<TemplateMatchResult[]>
+
+/** 多尺度 / 多角度模板匹配结果 */
+interface ScaledTemplateMatchResult extends TemplateMatchResult {
+    /** 命中的缩放比例（相对模板原始尺寸） */
+    scale: number
+    /** 命中的旋转角度（度，逆时针为正） */
+    angle: number
+}
+
+/** 多尺度 / 多角度模板匹配选项 */
+interface MatchTemplateScaledOptions extends MatchOptions {
+    /** 缩放比例列表，优先于 minScale/maxScale/scaleStep */
+    scales?: number[]
+    /** 最小缩放比例，默认 0.5 */
+    minScale?: number
+    /** 最大缩放比例，默认 2.0 */
+    maxScale?: number
+    /** 缩放步长，默认 0.1 */
+    scaleStep?: number
+    /** 旋转角度列表（度，逆时针为正），默认 [0] */
+    angles?: number[]
+    /** 粗匹配阶段的图像缩放比例，默认 0.5，1 表示关闭粗匹配 */
+    coarseScale?: number
+    /** 进入全分辨率精修的候选数，默认 3 */
+    candidates?: number
+    /** 搜索区域 [x, y, w, h]，返回坐标仍相对于整张图 */
+    roi?: [number, number, number, number]
+}
+
+/**
+ * 多尺度 / 多角度模板匹配（由粗到细，适配不同分辨率与 UI 缩放）
+ * @param imgMat 图像Mat对象（BGR格式）
+ * @param templateMat 模板Mat对象（BGR或BGRA格式）
+ * @param threshold 匹配得分阈值，默认 0.8
+ * @param options 可选缩放/角度候选、粗匹配比例、roi、匹配算法/预处理与 signal
+ * @returns 最佳匹配（含缩放与角度）或 undefined
+ */
+declare function matchTemplateScaled(
+    imgMat: Mat,
+    templateMat: Mat,
+    threshold?: number,
+    options?: MatchTemplateScaledOptions
+): Promise<ScaledTemplateMatchResult | undefined>
 
 /**
  * AHK 风格 DLL 动态调用
@@ -1039,14 +1301,38 @@ declare function matchTemplate(imgMat: Mat, templateMat: Mat, tolerance: number)
  */
 declare function dllCall(func: number | string | { Ptr: number }, ...typeAndValue: any[]): number | string | undefined
 
+/**
+ * 颜色距离模式
+ * - `rgb`：逐通道容差（默认）
+ * - `deltaE76`：CIELAB 欧氏色差
+ * - `deltaE2000`：CIEDE2000 色差，对高饱和颜色与泛光提亮更稳定
+ */
+type ColorDistanceMode = "rgb" | "deltaE76" | "deltaE2000"
+
+/** 颜色距离选项 */
+interface ColorDistanceOptions {
+    /** 颜色距离模式，默认 `rgb`；ΔE 模式下容差参数表示最大色差（通常 2-15） */
+    distance?: ColorDistanceMode
+}
+
+/** batchMatchColor 选项 */
+interface BatchMatchColorOptions extends ColorDistanceOptions {
+    /** ΔE 模式下命中区域与模板的最大平均色差，默认 10 */
+    maxDelta?: number
+}
+
+/** waitColor 选项 */
+interface WaitColorOptions extends AbortOptions, ColorDistanceOptions {}
+
 /**
  * 色键过滤并返回灰度二值图
  * @param mat 源图像 Mat
  * @param colors 色键数组（例如 [0xffffff, 0xff0000]）
- * @param tolerance 颜色容差（0-255），可传单个数值或与 `colors` 一一对应的数值数组
+ * @param tolerance 颜色容差（0-255；ΔE 模式下为最大色差），可传单个数值或与 `colors` 一一对应的数值数组
+ * @param options 可选 `{ distance }`
  * @returns 灰度二值图 Mat（命中为255，未命中为0）
  */
-declare function colorFilter(mat: Mat, colors: number[], tolerance: number | number[]): Mat
+declare function colorFilter(mat: Mat, colors: number[], tolerance: number | number[], options?: ColorDistanceOptions): Mat
 
 /**
  * 使用 HSL 加权差进行色键过滤并返回灰度二值图
@@ -1063,19 +1349,32 @@ declare function colorFilterHSL(mat: Mat, colors: number[], tolerance: number, w
  * @param mat 源图像 Mat
  * @param colors 色键数组（例如 [0xffffff, 0xff0000]）
  * @param minMean 最小 mean 阈值（0-255），低于该值返回 -1，默认 0
- * @param tolerance 颜色容差（0-255），可传单个数值或与 `colors` 一一对应的数值数组，默认 0
+ * @param tolerance 颜色容差（0-255；ΔE 模式下为最大色差），可传单个数值或与 `colors` 一一对应的数值数组，默认 0
+ * @param options 可选 `{ distance }`
  * @returns 命中的最佳索引；未命中返回 -1
  */
-declare function colorKeyMatch(mat: Mat, colors: number[], minMean?: number, tolerance?: number | number[]): number
+declare function colorKeyMatch(
+    mat: Mat,
+    colors: number[],
+    minMean?: number,
+    tolerance?: number | number[],
+    options?: ColorDistanceOptions
+): number
 
 /**
  * 批量模板匹配（并行）
  * @param src 源图像 Mat
  * @param tpls 模板 Mat 数组
  * @param cap 匹配置信度阈值（0-1）
+ * @param options 可选 `{ distance, maxDelta }`；ΔE 模式下还要求命中区域与模板的平均色差不超过 `maxDelta`
  * @returns 首个命中结果 { pos: [x, y], index } 或 undefined
  */
-declare function batchMatchColor(src: Mat, tpls: Mat[], cap: number): { pos: [number, number]; index: number } | undefined
+declare function batchMatchColor(
+    src: Mat,
+    tpls: Mat[],
+    cap: number,
+    options?: BatchMatchColorOptions
+): { pos: [number, number]; index: number } | undefined
 
 /**
  * ORB 特征比较，返回优质匹配数量
@@ -1126,6 +1425,84 @@ declare function preprocessMinimapForSift(
     headingDeg?: number
 ): Mat
 
+/** 小地图定位器选项 */
+interface MinimapLocalizerOptions {
+    /** 特征算法，默认 `sift` */
+    kind?: FeatureKind
+    /** 跟踪时围绕预测位置的搜索半径（大地图像素，连续失败时按次数放大），默认 320 */
+    searchRadius?: number
+    /** 连续失败多少帧后判定丢失并改用全图搜索，默认 3 */
+    lostAfter?: number
+    /** 接受一次定位所需的最少 RANSAC 内点数，默认 10 */
+    minInliers?: number
+    /** 跟踪时单帧允许的最大位移（大地图像素），超出视为误匹配，默认 160 */
+    maxJump?: number
+    /** 位置/朝向平滑系数（0-1，越大越信任新观测），默认 0.6 */
+    alpha?: number
+    /** 速度更新系数（0-1），默认 0.2 */
+    beta?: number
+    /** 允许的最小缩放（小地图像素到大地图像素），默认 0.25 */
+    minScale?: number
+    /** 允许的最大缩放，默认 4 */
+    maxScale?: number
+    /** Lowe Ratio Test 阈值，默认 0.75 */
+    ratio?: number
+}
+
+/** 小地图定位结果 */
+interface MinimapPose {
+    /** 小地图中心在大地图上的 X 坐标（平滑后） */
+    x: number
+    /** 小地图中心在大地图上的 Y 坐标（平滑后） */
+    y: number
+    /** 小地图相对大地图的旋转角（度，图像坐标系顺时针为正，范围 (-180, 180]） */
+    heading: number
+    /** 小地图像素到大地图像素的缩放 */
+    scale: number
+    /** 置信度（0-1），外推帧逐帧衰减 */
+    confidence: number
+    /** 本帧 RANSAC 内点数（失败时为 0） */
+    inliers: number
+    /** 是否处于丢失状态 */
+    lost: boolean
+    /**
+     * 位姿来源
+     * - `track`：局部窗口跟踪成功
+     * - `global`：全图搜索（重定位）成功
+     * - `predicted`：本帧失败，按运动模型外推
+     * - `none`：尚未定位或已丢失
+     */
+    source: "track" | "global" | "predicted" | "none"
+}
+
+/**
+ * 小地图连续定位器：保留上一帧位姿，只在预测位置附近的窗口内匹配，丢失后自动全图重定位，
+ * 并用常速度模型平滑位置与朝向。
+ */
+declare class MinimapLocalizer {
+    /**
+     * 提取大地图特征并创建定位器
+     * @param map 参考大地图 Mat
+     */
+    constructor(map: Mat, options?: MinimapLocalizerOptions)
+    /**
+     * 输入一帧小地图（建议先经 `preprocessMinimapForSift` 处理，中心即角色位置）
+     * @returns 平滑后的位姿
+     */
+    update(minimap: Mat): MinimapPose
+    /** 上一次 `update` 的结果，尚未更新时为 null */
+    pose(): MinimapPose | null
+    /** 是否处于丢失状态（尚未定位也视为丢失） */
+    isLost(): boolean
+    /**
+     * 手动指定位置（例如传送后已知坐标），下一帧从该位置开始窗口跟踪
+     * @param heading 朝向（度），省略时保留当前朝向
+     */
+    setPose(x: number, y: number, heading?: number): void
+    /** 清空跟踪状态，下一帧走全图搜索 */
+    reset(): void
+}
+
 /**
  * SIFT 自动拼接：将 patch 对齐并融合到 base，必要时自动扩展画布。
  * @param base 当前大图 Mat
@@ -1153,13 +1530,226 @@ declare function siftStitch(
       }
     | undefined
 
+/** 世界地图拼图选项 */
+interface WorldMosaicOptions {
+    /** 瓦片边长（像素），默认 512；打开已有拼图时以索引中记录的值为准 */
+    tileSize?: number
+    /** 跟踪搜索时在上一帧外扩的边距（像素，0 表示取帧的长边），默认 0 */
+    searchMargin?: number
+    /** 最少优质匹配数，默认 12 */
+    minGoodMatches?: number
+    /** 最少 RANSAC 内点数，默认 10 */
+    minInliers?: number
+    /** 最低内点比例（内点数 / 优质匹配数），默认 0.3 */
+    minInlierRatio?: number
+    /** 允许的最大缩放变化（`1/x ~ x`），默认 1.3 */
+    maxScaleChange?: number
+    /** 单应矩阵透视项绝对值上限，默认 0.002 */
+    maxPerspective?: number
+    /** RANSAC 重投影阈值（像素），默认 3.0 */
+    ransacThreshold?: number
+    /** 内存中最多缓存的瓦片数（超出时淘汰最久未用的瓦片，修改过的先写回磁盘），默认 64 */
+    maxCachedTiles?: number
+}
+
+/** 拼图加帧结果 */
+interface WorldMosaicFrameResult {
+    /** 是否已写入拼图 */
+    accepted: boolean
+    /** 被拒绝的原因（如内点不足、缩放异常、透视畸变过大等） */
+    reason?: string
+    /** 帧在全局坐标中的包围盒 `[x, y, w, h]`（拒绝时可能为 undefined） */
+    bbox?: [number, number, number, number]
+    goodMatches: number
+    inliers: number
+}
+
+/** 拼图定位结果（全局坐标） */
+interface WorldMosaicLocation {
+    /** 查询图像中心在拼图中的位置 */
+    pos: [number, number]
+    bbox: [number, number, number, number]
+    goodMatches: number
+    inliers: number
+    /** 查询图像四角（左上、右上、右下、左下） */
+    corners: [number, number][]
+}
+
 /**
- * 计算图像感知哈希（pHash）
+ * 持久化世界地图拼图：基于 SIFT 拼接把连续截图累积到磁盘上的瓦片画布（`tiles/` + `index.json`），
+ * 以首帧左上角为全局原点，单应矩阵检查不通过的帧不会写入。
+ */
+declare class WorldMosaic {
+    /**
+     * 打开（或新建）拼图目录
+     * @param dir 拼图目录，相对路径按脚本目录解析
+     */
+    constructor(dir: string, options?: WorldMosaicOptions)
+    /** 拼接一帧：先在上一帧附近配准，失败再在缩小的整张拼图上粗定位后就近精配准 */
+    addFrame(frame: Mat): WorldMosaicFrameResult
+    /**
+     * 查询图像在拼图中的位置（"我在哪"）
+     * @param options.x/y 预估位置，提供时先在其附近 `radius`（默认图像长边的 2 倍）范围内搜索
+     * @returns 配准失败时返回 undefined
+     */
+    locate(frame: Mat, options?: { x?: number; y?: number; radius?: number }): WorldMosaicLocation | undefined
+    /** 渲染全局坐标中的指定区域（未绘制部分为黑色） */
+    render(x: number, y: number, width: number, height: number): Mat
+    /**
+     * 导出整张拼图
+     * @param zoom 缩放倍率（0-4，默认 1）；导出图像素坐标 = (全局坐标 + offset) * zoom
+     */
+    export(zoom?: number): Mat
+    /** 将修改过的瓦片与索引写入磁盘 */
+    save(): void
+    /** 已绘制内容的全局包围盒 `[x, y, w, h]`，空拼图为 null */
+    bounds(): [number, number, number, number] | null
+    /** 全局坐标到导出图坐标的偏移 `[x, y]` */
+    offset(): [number, number]
+    /** 已接受的帧数 */
+    frameCount(): number
+    /** 瓦片数量 */
+    tileCount(): number
+}
+
+/**
+ * 感知哈希算法
+ * - `phash`：DCT 感知哈希（64 位），对缩放与轻微模糊稳定（默认）
+ * - `ahash`：均值哈希（64 位），最快，适合纯色块图标
+ * - `dhash`：差异哈希（64 位），对整体亮度变化稳定
+ * - `blockmean`：块均值哈希（256 位），细节更敏感，适合区分相似图标
+ */
+type ImageHashKind = "phash" | "ahash" | "dhash" | "blockmean"
+
+/**
+ * 计算图像感知哈希
  * @param imgMat 图像 Mat
  * @param color 是否启用彩色哈希（true 时按 B/G/R 三通道拼接，false 时灰度哈希），默认 false
- * @returns 十六进制哈希字符串（灰度 16 字符，彩色 48 字符）
+ * @param kind 哈希算法，默认 `phash`
+ * @returns 十六进制哈希字符串（64 位算法灰度 16 字符、彩色 48 字符；blockmean 为其 4 倍）
+ */
+declare function perceptualHash(imgMat: Mat, color?: boolean, kind?: ImageHashKind): string
+
+/** 哈希索引查询结果 */
+interface HashIndexMatch {
+    /** 标注名称 */
+    label: string
+    /** 汉明距离 */
+    distance: number
+}
+
+/** 哈希索引查询选项 */
+interface HashIndexQueryOptions {
+    /** 最多返回的结果数，默认 1 */
+    k?: number
+    /** 最大汉明距离，默认 10 */
+    maxDistance?: number
+}
+
+/** 感知哈希索引（BK 树），用于在大量已知图标/物品哈希中快速查找最近标注 */
+declare class HashIndex {
+    /**
+     * 创建空索引
+     * @param kind 哈希算法，默认 `phash`（用 Mat 查询时按此算法计算哈希）
+     * @param color 是否为彩色哈希，默认 false
+     */
+    constructor(kind?: ImageHashKind, color?: boolean)
+    /** 添加一条标注（同一索引内哈希长度必须一致） */
+    add(label: string, hash: string): void
+    /**
+     * 查询最近的标注
+     * @param hashOrMat 十六进制哈希，或按索引算法计算哈希的图像 Mat
+     * @returns 按距离升序排列的结果（距离相同按标注名排序）
+     */
+    query(hashOrMat: string | Mat, options?: HashIndexQueryOptions): HashIndexMatch[]
+    /** 按索引的算法与颜色模式计算图像哈希 */
+    hash(mat: Mat): string
+    /** 标注总数 */
+    size(): number
+    /** 哈希算法 */
+    kind(): ImageHashKind
+}
+
+/**
+ * 从标注 JSON 文件加载哈希索引（相对路径按当前脚本目录解析）
+ *
+ * 支持格式：
+ * - `{ "label": "hash" }` 或 `{ "label": ["hash1", "hash2"] }`
+ * - `[{ "label": "...", "hash": "..." }]`
+ * - `{ "kind": "dhash", "color": false, "entries": <以上任一格式> }`
+ * @param path JSON 文件路径
+ */
+declare function loadHashIndex(path: string): HashIndex
+
+/** 特征库算法 */
+type FeatureKind = "orb" | "sift"
+
+/** 特征库识别结果 */
+interface FeatureLibraryMatch {
+    /** 标注名称 */
+    label: string
+    /** 单应性 RANSAC 内点数 */
+    inliers: number
+    /** 通过 Ratio Test 的匹配数 */
+    goodMatches: number
+    /** 内点数占查询特征点数的比例（0-1） */
+    score: number
+}
+
+/** 特征库识别选项 */
+interface FeatureLibraryIdentifyOptions {
+    /** 最多返回的标注数，默认 5 */
+    topK?: number
+    /** FLANN 投票后进入几何校验的候选条目数，默认 8 */
+    candidates?: number
+    /** 最少 RANSAC 内点数，默认 8 */
+    minInliers?: number
+    /** Lowe Ratio Test 阈值，默认 0.75 */
+    ratio?: number
+}
+
+/** 持久化的 ORB/SIFT 特征库，用于在大量已知图片中批量识别查询图像 */
+declare class FeatureLibrary {
+    /**
+     * 创建空特征库
+     * @param kind 特征算法，默认 `orb`
+     */
+    constructor(kind?: FeatureKind)
+    /**
+     * 提取图像特征并加入特征库（同一标注可添加多张图）
+     * @returns 关键点数量
+     */
+    add(label: string, mat: Mat): number
+    /**
+     * 识别查询图像：FLANN（ORB 为 LSH）全库投票筛选候选，再做单应性 RANSAC 校验
+     * @returns 按内点数降序排列的结果，每个标注只出现一次
+     */
+    identify(mat: Mat, options?: FeatureLibraryIdentifyOptions): FeatureLibraryMatch[]
+    /** 保存为压缩特征库文件（相对路径按当前脚本目录解析） */
+    save(path: string): void
+    /** 去重后的标注列表 */
+    labels(): string[]
+    /** 条目总数 */
+    size(): number
+    /** 特征算法 */
+    kind(): FeatureKind
+}
+
+/**
+ * 从图片目录构建特征库（相对路径按当前脚本目录解析）
+ *
+ * 根目录图片以文件名（去扩展名）为标注；子目录内图片以子目录相对路径为标注（`/` 分隔）。
+ * @param dir 图片目录（支持 png/jpg/jpeg/bmp/webp）
+ * @param kind 特征算法，默认 `orb`
+ * @returns 特征库，`skipped` 为无法读取或检测不到特征点而跳过的文件
  */
-declare function perceptualHash(imgMat: Mat, color?: boolean): string
+declare function buildFeatureLibrary(dir: string, kind?: FeatureKind): FeatureLibrary & { skipped: string[] }
+
+/**
+ * 从文件加载特征库（相对路径按当前脚本目录解析）
+ * @param path `FeatureLibrary.save` 保存的文件路径
+ */
+declare function loadFeatureLibrary(path: string): FeatureLibrary
 
 /**
  * 计算图像 ORB 特征字符串（压缩后的原始 ORB 描述子）
@@ -1238,6 +1828,131 @@ declare function findContours(
  */
 declare function segmentChars(imgMat: Mat, minGapWidth?: number, minCharWidth?: number): [number, number, number, number][]
 
+/**
+ * 字形集：由标注样本构建的轻量字形模板，用于识别 HUD 数字、计时器等固定字体文本（不依赖 OCR 模型）
+ */
+declare class GlyphSet {
+    constructor()
+    /**
+     * 加入单个字形样本（整张图视为一个字符，建议保留少量背景边距）
+     * @param label 字形标注
+     */
+    add(label: string, sample: Mat): void
+    /**
+     * 从一行文本样本批量加入字形，分割出的字符数须与 `text`（忽略空白）一致
+     * @param minGapWidth 最小分割空隙宽度（默认 1）
+     * @param minCharWidth 最小字符宽度（默认 1）
+     * @returns 加入的样本数量
+     */
+    addLine(line: Mat, text: string, minGapWidth?: number, minCharWidth?: number): number
+    /** 去重后的标注列表 */
+    labels(): string[]
+    /** 样本数量 */
+    size(): number
+}
+
+/** 字形识别选项 */
+interface GlyphReadOptions {
+    /** 比对方式：`ncc` 归一化互相关（默认）/ `phash` 感知哈希汉明距离 */
+    method?: "ncc" | "phash"
+    /** 低于该置信度的字符输出为 `unknown`，默认 0.6 */
+    minConfidence?: number
+    /** 字符分割的最小空隙宽度（像素列），默认 1 */
+    minGapWidth?: number
+    /** 字符分割的最小字符宽度（像素），默认 1 */
+    minCharWidth?: number
+    /** 无法识别时的占位文本，默认 `?` */
+    unknown?: string
+}
+
+/** 字形识别结果 */
+interface GlyphReading {
+    /** 识别文本 */
+    text: string
+    /** 各字符置信度的最小值（0-1），没有字符时为 0 */
+    confidence: number
+    /** 逐字符结果（从左到右），`char` 为 null 表示置信度不足 */
+    chars: { char: string | null; confidence: number; bbox: [number, number, number, number] }[]
+}
+
+/**
+ * 从目录加载字形集：子目录名即标注（目录内每张图片为一个样本），根目录图片以文件名为标注；
+ * 无法作为文件名的字符可用别名 `colon` `dot` `slash` `minus` `plus` `percent` `comma`
+ * @param dir 目录路径，相对路径按脚本目录解析
+ * @returns 字形集，`skipped` 为无法加载的文件
+ */
+declare function loadGlyphSet(dir: string): GlyphSet & { skipped: string[] }
+
+/**
+ * 识别单行文本：按 `segmentChars` 分割后逐字与字形集比对
+ * @param imgMat 单行文本图像
+ */
+declare function readGlyphs(imgMat: Mat, glyphs: GlyphSet, options?: GlyphReadOptions): GlyphReading
+
+/**
+ * 识别单行数字：只使用标注为数字（以及 `options.extra` 中字符，如 `".-"`）的字形比对
+ * @returns 识别结果，`value` 为解析出的数值（存在无法识别的字符或无法解析时为 null）
+ */
+declare function readDigits(
+    imgMat: Mat,
+    glyphs: GlyphSet,
+    options?: GlyphReadOptions & { extra?: string }
+): GlyphReading & { value: number | null }
+
+/** 调试报告步骤选项 */
+interface DebugReportStepOptions {
+    /** 该步骤的截图（单张或多张），会按 bboxes / contours 标注后内嵌到报告 */
+    image?: Mat | Mat[]
+    /** 需要框出的区域，支持 `[x, y, w, h]` 或 `{ bbox: [x, y, w, h] }` */
+    bboxes?: ([number, number, number, number] | { bbox: [number, number, number, number] })[]
+    /** 是否叠加外轮廓：true 绘制全部，数字为最小面积 */
+    contours?: boolean | number
+    /** 标注颜色 0xRRGGBB，默认 0x00FF00 */
+    color?: number
+    /** 匹配得分 */
+    score?: number
+    /** 判定阈值（与 score 同时提供时按 `score >= threshold` 判定通过） */
+    threshold?: number
+    /** 耗时（毫秒） */
+    elapsed?: number
+    /** 备注 */
+    note?: string
+    /** 附加字段，按属性顺序展示为表格行 */
+    data?: Record<string, unknown>
+}
+
+/** 视觉调试报告：按步骤记录标注帧、得分、阈值与耗时，生成单文件 HTML */
+declare class DebugReport {
+    /**
+     * 创建空报告
+     * @param title 报告标题，默认 `调试报告`
+     */
+    constructor(title?: string)
+    /**
+     * 追加一个步骤
+     * @returns 步骤序号（从 0 开始）
+     */
+    step(name: string, options?: DebugReportStepOptions): number
+    /** 生成 HTML 文本 */
+    html(): string
+    /**
+     * 写出单文件 HTML 报告（图片内嵌，自动创建目录）
+     * @param path 输出路径，相对路径按脚本目录解析
+     * @returns 实际写入的路径
+     */
+    save(path: string): string
+    /**
+     * 将报告摘要与最近的标注图挂到状态面板（同 setStatus，可被 MCP read_status 读取）
+     * @param title 状态标题，默认使用报告标题
+     * @param maxImages 携带的图片数量上限，默认 8
+     */
+    attachStatus(title?: string, maxImages?: number): void
+    /** 步骤数量 */
+    size(): number
+    /** 清空全部步骤 */
+    clear(): void
+}
+
 /**
  * 轮廓绘制（返回绘制后的 BGR 图像）
  * @param imgMat 输入图像 Mat
@@ -1300,11 +2015,205 @@ declare function cc(imgMat: Mat, x: number, y: number, color: number, tolerance:
  * @param x X坐标（窗口客户区）
  * @param y Y坐标（窗口客户区）
  * @param color 目标颜色（0xRRGGBB）
- * @param tolerance 容差（正数=等待符合，负数=等待不符合，按绝对值参与比较）
+ * @param tolerance 容差（正数=等待符合，负数=等待不符合，按绝对值参与比较；ΔE 模式下为最大色差）
  * @param timeout 超时时间（毫秒），默认 20000
+ * @param options 可选 `{ signal, distance }`，中止时以 `signal.reason` 拒绝
  * @returns 命中条件返回 true，超时返回 false
  */
-declare function waitColor(hwnd: number, x: number, y: number, color: number, tolerance: number, timeout?: number): Promise<boolean>
+declare function waitColor(
+    hwnd: number,
+    x: number,
+    y: number,
+    color: number,
+    tolerance: number,
+    timeout?: number,
+    options?: WaitColorOptions
+): Promise<boolean>
+
+/** 帧差分选项 */
+interface FrameDiffOptions {
+    /** 像素变化阈值（0-255，按 BGR 各通道差值的最大值判断），默认 24 */
+    threshold?: number
+    /** 比较前的高斯模糊核尺寸（0/1 表示不模糊），默认 3 */
+    blur?: number
+    /** 是否用 3x3 开运算去除孤立噪点，默认 true */
+    denoise?: boolean
+    /** 合并相邻变化区域的距离（像素），默认 4 */
+    merge?: number
+    /** 变化区域的最小像素数，小于该值视为噪声，默认 16 */
+    minArea?: number
+}
+
+/** `waitStable` 选项 */
+interface WaitStableOptions extends AbortOptions, FrameDiffOptions {
+    /** 视为静止时允许的最大变化像素比例（0-1），默认 0 */
+    maxRatio?: number
+}
+
+/**
+ * 计算两帧之间的差分掩码（两帧尺寸须一致）
+ * @returns 单通道掩码，变化像素为 255
+ */
+declare function frameDiff(prev: Mat, curr: Mat, options?: FrameDiffOptions): Mat
+
+/**
+ * 检测两帧之间的变化区域
+ * @returns `bboxes` 为 `[x, y, w, h]` 列表（按面积降序），`changedPixels` 为变化像素数，`ratio` 为其占整帧的比例
+ */
+declare function diffRegions(
+    prev: Mat,
+    curr: Mat,
+    options?: FrameDiffOptions
+): { bboxes: [number, number, number, number][]; changedPixels: number; ratio: number }
+
+/**
+ * 等待窗口区域画面静止（异步），适合等待加载、弹窗动画结束
+ * @param hwnd 窗口句柄
+ * @param roi 比较区域 `[x, y, w, h]`（相对客户区），省略或 null 表示整个窗口
+ * @param quietMs 需要连续静止的时长（毫秒），默认 500
+ * @param timeout 超时时间（毫秒），默认 20000
+ * @returns 静止返回 true，超时返回 false
+ */
+declare function waitStable(
+    hwnd: number,
+    roi?: [number, number, number, number] | null,
+    quietMs?: number,
+    timeout?: number,
+    options?: WaitStableOptions
+): Promise<boolean>
+
+/** 轮询等待通用选项 */
+interface PollWaitOptions extends AbortOptions {
+    /** 截图区域 `[x, y, w, h]`（相对客户区），省略表示整个窗口；返回坐标仍相对客户区 */
+    roi?: [number, number, number, number]
+    /** 轮询间隔（毫秒），默认 50 */
+    interval?: number
+    /** 超时时间（毫秒），默认 20000 */
+    timeout?: number
+}
+
+/** 轮询等待命中时的公共字段 */
+interface PollWaitResult {
+    /** 截图并检查的次数 */
+    polls: number
+    /** 等待耗时（毫秒） */
+    elapsed: number
+}
+
+/**
+ * 等待模板出现（异步，整个等待过程复用同一块截图缓冲）
+ * @param hwnd 窗口句柄
+ * @param templateMat 模板 Mat（BGR 或 BGRA）
+ * @param threshold 匹配得分阈值，默认 0.8
+ * @param options 可选 `{ roi, interval, timeout, method, preprocess, signal }`
+ * @returns 命中返回位置与得分（相对客户区），超时返回 null
+ */
+declare function waitTemplate(
+    hwnd: number,
+    templateMat: Mat,
+    threshold?: number,
+    options?: PollWaitOptions & MatchOptions
+): Promise<(TemplateMatchResult & PollWaitResult) | null>
+
+/**
+ * 等待多个取色点同时满足颜色条件（异步）
+ * @param hwnd 窗口句柄
+ * @param points 取色点 `[x, y, color]` 列表，坐标相对客户区，color 为 0xRRGGBB
+ * @param tolerance 逐通道容差（0-255），默认 0；ΔE 模式下为最大色差
+ * @param options 可选 `{ roi, interval, timeout, distance, signal }`
+ * @returns 全部命中返回结果对象，超时返回 null
+ */
+declare function waitColors(
+    hwnd: number,
+    points: [number, number, number][],
+    tolerance?: number,
+    options?: PollWaitOptions & ColorDistanceOptions
+): Promise<PollWaitResult | null>
+
+/**
+ * 等待画面感知哈希接近目标哈希（异步）
+ * @param hwnd 窗口句柄
+ * @param hash 目标哈希（须与 `perceptualHash(roi截图, options.color, options.kind)` 同长度）
+ * @param maxDistance 最大汉明距离，默认 10
+ * @param options 可选 `{ roi, interval, timeout, kind, color, signal }`
+ * @returns 命中返回当前哈希与距离，超时返回 null
+ */
+declare function waitHash(
+    hwnd: number,
+    hash: string,
+    maxDistance?: number,
+    options?: PollWaitOptions & { kind?: ImageHashKind; color?: boolean }
+): Promise<({ hash: string; distance: number } & PollWaitResult) | null>
+
+/**
+ * 等待 OCR 识别文本匹配正则（异步，需先调用 initOcr；OCR 较慢，建议配合 roi 使用）
+ * @param hwnd 窗口句柄
+ * @param pattern 正则字符串或 RegExp（支持 i/m/s 标志）
+ * @param options 可选 `{ roi, interval, timeout, signal, charset, customChars }`
+ * @returns 命中返回识别全文、命中文本与捕获组（未参与匹配的组为 null），超时返回 null
+ */
+declare function waitText(
+    hwnd: number,
+    pattern: string | RegExp,
+    options?: PollWaitOptions & Pick<OcrDecodeOptions, "charset" | "customChars">
+): Promise<({ text: string; matched: string; groups: (string | null)[] } & PollWaitResult) | null>
+
+/** 帧源选项 */
+interface FrameSourceOptions {
+    /** 帧推进方式：`capture` 每次截图后前进一帧（默认），`manual` 仅由 stepFrameSource / seekFrameSource 切帧 */
+    step?: "capture" | "manual"
+    /** 播放到末尾后是否回到第一帧，默认 false（停留在最后一帧） */
+    loop?: boolean
+}
+
+/** 帧源状态 */
+interface FrameSourceInfo {
+    /** 来源类型 */
+    kind: "images" | "video" | "mat"
+    /** 当前帧序号 */
+    index: number
+    /** 总帧数（视频未提供总帧数时为 0） */
+    length: number
+}
+
+/**
+ * 创建帧源虚拟窗口，返回的 id 可直接传给 captureWindow / waitColor / waitTemplate 等截图函数
+ * @param source 图片目录（按文件名自然序）、视频文件路径（相对路径按脚本目录解析）或固定 Mat
+ * @param options 可选 `{ step, loop }`
+ * @returns 虚拟窗口 id（负数）
+ * @throws 目录无图片或视频无法打开时抛出错误
+ */
+declare function createFrameSource(source: string | Mat, options?: FrameSourceOptions): number
+
+/**
+ * 将帧源前进若干帧
+ * @param hwnd 帧源虚拟窗口 id
+ * @param count 前进帧数，默认 1，可为负数
+ * @returns 切换后的帧序号
+ */
+declare function stepFrameSource(hwnd: number, count?: number): number
+
+/**
+ * 将帧源跳转到指定帧（循环模式下取模，否则夹到最后一帧）
+ * @param hwnd 帧源虚拟窗口 id
+ * @param index 目标帧序号
+ * @returns 实际帧序号
+ */
+declare function seekFrameSource(hwnd: number, index: number): number
+
+/**
+ * 查询帧源状态
+ * @param hwnd 帧源虚拟窗口 id
+ * @returns 帧源状态，不存在时返回 null
+ */
+declare function frameSourceInfo(hwnd: number): FrameSourceInfo | null
+
+/**
+ * 关闭帧源并释放资源（脚本启动时会自动清理上一次运行遗留的帧源）
+ * @param hwnd 帧源虚拟窗口 id
+ * @returns 帧源是否存在
+ */
+declare function closeFrameSource(hwnd: number): boolean
 
 type ScriptConfigBaseType = "number" | "string" | "boolean" | "bool" | "select" | "multi-select"
 type ScriptConfigStringFormat = ScriptConfigBaseType | `select:${string}` | `multi-select:${string}`
@@ -8,6 +8,7 @@ struct CliArgs {
     script_path: String,
     script_config: Option<serde_json::Value>,
     script_config_file_path: Option<String>,
+    profile_output_path: Option<String>,
 }
 
 /// `--config` 参数解析结果（值 + 可选来源文件路径）。
@@ -31,14 +32,18 @@ fn normalize_command_name(program_name: &str) -> String {
 /// 打印命令行帮助信息。
 fn print_help(command_name: &str) {
     eprintln!("用法:");
-    eprintln!("  {command_name} <script.js> [--config <json|config.json>]");
+    eprintln!("  {command_name} <script.js> [--config <json|config.json>] [--profile[=<file>]]");
     eprintln!();
     eprintln!("选项:");
     eprintln!("  -h, --help          显示帮助信息");
     eprintln!("  --config <value>    传入 readConfig 使用的配置（JSON 字符串或 JSON 文件路径）");
+    eprintln!(
+        "  --profile[=<file>]  启用性能分析，输出内置函数耗时汇总与折叠栈文件（默认 <脚本名>.profile.folded）"
+    );
     eprintln!();
     eprintln!("示例:");
     eprintln!("  {command_name} ./demo.js");
+    eprintln!("  {command_name} ./demo.js --profile");
     eprintln!("  {command_name} ./demo.js --config '{{\"speed\": 2}}'");
     eprintln!("  {command_name} ./demo.js --config ./config.json");
 }
@@ -73,6 +78,14 @@ fn parse_script_config_arg(raw_config: &str) -> Result<ParsedScriptConfigArg, St
     })
 }
 
+/// 默认折叠栈输出路径：与脚本同目录的 `<脚本名>.profile.folded`。
+fn default_profile_output_path(script_path: &str) -> String {
+    Path::new(script_path)
+        .with_extension("profile.folded")
+        .to_string_lossy()
+        .into_owned()
+}
+
 /// 解析命令行参数并返回脚本路径与可选配置。
 fn parse_cli_args() -> Result<CliArgs, ExitCode> {
     let mut args = env::args();
@@ -81,6 +94,8 @@ fn parse_cli_args() -> Result<CliArgs, ExitCode> {
     let mut script_path: Option<String> = None;
     let mut script_config: Option<serde_json::Value> = None;
     let mut script_config_file_path: Option<String> = None;
+    let mut profile = false;
+    let mut profile_output_path: Option<String> = None;
 
     while let Some(arg) = args.next() {
         if arg == "-h" || arg == "--help" {
@@ -88,6 +103,23 @@ fn parse_cli_args() -> Result<CliArgs, ExitCode> {
             return Err(ExitCode::SUCCESS);
         }
 
+        if arg == "--profile" {
+            profile = true;
+            continue;
+        }
+
+        if let Some(raw_path) = arg.strip_prefix("--profile=") {
+            let raw_path = raw_path.trim();
+            if raw_path.is_empty() {
+                eprintln!("--profile= 需要传入输出文件路径。");
+                print_help(command_name.as_str());
+                return Err(ExitCode::from(2));
+            }
+            profile = true;
+            profile_output_path = Some(raw_path.to_string());
+            continue;
+        }
+
         if arg == "--config" {
             let Some(raw_config) = args.next() else {
                 eprintln!("--config 需要传入 JSON 字符串或 JSON 文件路径。");
@@ -152,10 +184,15 @@ fn parse_cli_args() -> Result<CliArgs, ExitCode> {
         return Err(ExitCode::from(2));
     };
 
+    let profile_output_path = profile.then(|| {
+        profile_output_path.unwrap_or_else(|| default_profile_output_path(script_path.as_str()))
+    });
+
     Ok(CliArgs {
         script_path,
         script_config,
         script_config_file_path,
+        profile_output_path,
     })
 }
 
@@ -171,6 +208,7 @@ async fn main() -> ExitCode {
         cli_args.script_path,
         cli_args.script_config,
         cli_args.script_config_file_path,
+        cli_args.profile_output_path,
     )
     .await
     {
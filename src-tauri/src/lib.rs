@@ -2898,9 +2898,13 @@ fn path_exists(path: String) -> bool {
 mod submodules;
 
 #[tauri::command]
-async fn run_script(script_path: String, app_handle: tauri::AppHandle) -> Result<String, String> {
-    use submodules::script::run_script_file;
-    match run_script_file(script_path, app_handle).await {
+async fn run_script(
+    script_path: String,
+    profile: Option<bool>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    use submodules::script::run_script_file_with_profile;
+    match run_script_file_with_profile(script_path, app_handle, profile.unwrap_or(false)).await {
         Ok(result) => Ok(result),
         Err(e) => Err(format!("脚本执行失败: {}", e)),
     }
@@ -2927,6 +2931,7 @@ async fn exec_script(
 /// - `script_path`: 脚本路径（可相对或绝对）
 /// - `script_config`: 可选脚本配置（用于 CLI 模式 readConfig）
 /// - `script_config_file_path`: 可选配置文件路径（用于 CLI 模式 setConfig 写回）
+/// - `profile_output_path`: 可选折叠栈输出路径；提供时启用性能分析
 ///
 /// # 返回
 /// 返回脚本执行结果字符串；失败时返回错误信息
@@ -2935,9 +2940,16 @@ pub async fn run_script_cli(
     script_path: String,
     script_config: Option<serde_json::Value>,
     script_config_file_path: Option<String>,
+    profile_output_path: Option<String>,
 ) -> Result<String, String> {
     use submodules::script::run_script_file_cli;
-    run_script_file_cli(script_path, script_config, script_config_file_path).await
+    run_script_file_cli(
+        script_path,
+        script_config,
+        script_config_file_path,
+        profile_output_path.map(std::path::PathBuf::from),
+    )
+    .await
 }
 
 /// 响应脚本 readConfig 请求，将前端当前值回传给脚本运行时。
@@ -2971,6 +2983,95 @@ fn stop_script() -> Result<String, String> {
     }
 }
 
+/// 读取脚本网络访问权限（fetch/WebSocket）。
+#[tauri::command]
+fn get_script_network_permission() -> serde_json::Value {
+    use submodules::script_net::get_script_network_policy;
+    let policy = get_script_network_policy();
+    serde_json::json!({
+        "enabled": policy.enabled,
+        "allowedHosts": policy.allowed_hosts,
+    })
+}
+
+/// 设置脚本网络访问权限（fetch/WebSocket）。
+///
+/// # 参数
+/// - `enabled`: 是否允许脚本访问网络
+/// - `allowed_hosts`: 允许访问的主机列表，为空表示不限制
+#[tauri::command]
+fn set_script_network_permission(
+    enabled: bool,
+    allowed_hosts: Option<Vec<String>>,
+) -> Result<(), String> {
+    use submodules::script_net::{ScriptNetworkPolicy, set_script_network_policy};
+    set_script_network_policy(ScriptNetworkPolicy {
+        enabled,
+        allowed_hosts: allowed_hosts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|host| host.trim().to_string())
+            .filter(|host| !host.is_empty())
+            .collect(),
+    })
+}
+
+/// 列出脚本的文件日志运行记录（按时间从新到旧）。
+#[tauri::command]
+fn list_script_log_runs(
+    script_path: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<submodules::script_log::ScriptLogRunInfo>, String> {
+    use submodules::script::{normalize_script_path, script_log_root};
+    use submodules::script_log::{list_script_log_runs, script_log_dir};
+    let script_path = normalize_script_path(script_path)?;
+    let script_dir = script_log_dir(&script_log_root(&app_handle)?, &script_path);
+    Ok(list_script_log_runs(&script_dir))
+}
+
+/// 导出脚本某次运行的日志包（JSONL 日志与状态截图）。
+#[tauri::command]
+async fn export_script_log_bundle(
+    script_path: String,
+    run_id: Option<String>,
+    output_path: String,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    use submodules::script::{normalize_script_path, script_log_root};
+    use submodules::script_log::{export_script_log_bundle, script_log_dir};
+    let script_path = normalize_script_path(script_path)?;
+    let script_dir = script_log_dir(&script_log_root(&app_handle)?, &script_path);
+    tokio::task::spawn_blocking(move || {
+        export_script_log_bundle(
+            &script_dir,
+            run_id.as_deref(),
+            std::path::Path::new(&output_path),
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+}
+
+/// 将目录下的 PNG 模板打包为模板图集（图片 + JSON/TOML 清单）。
+///
+/// # 参数
+/// - `source_dir`: 模板目录（仅当前层级的 `.png`）
+/// - `manifest_path`: 输出清单路径，图集图片写到同名 `.png`
+#[tauri::command]
+async fn pack_template_atlas(
+    source_dir: String,
+    manifest_path: String,
+) -> Result<submodules::tpl::AtlasPackResult, String> {
+    tokio::task::spawn_blocking(move || {
+        submodules::tpl::pack_template_atlas(
+            std::path::Path::new(&source_dir),
+            std::path::Path::new(&manifest_path),
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+}
+
 /// 停止指定脚本路径对应的运行实例。
 #[tauri::command]
 fn stop_script_by_path(script_path: String) -> Result<String, String> {
@@ -3766,6 +3867,11 @@ pub fn run() {
         resolve_script_help_request,
         stop_script,
         stop_script_by_path,
+        get_script_network_permission,
+        set_script_network_permission,
+        list_script_log_runs,
+        export_script_log_bundle,
+        pack_template_atlas,
         get_script_running_state,
         get_script_runtime_info,
         get_script_mcp_server_state,
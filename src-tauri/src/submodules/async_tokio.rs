@@ -1,3 +1,4 @@
+use crate::submodules::script_profile::{IDLE_FRAME, JOBS_FRAME, sample_event_loop};
 use boa_engine::context::time::JsInstant;
 use boa_engine::job::{GenericJob, TimeoutJob};
 use boa_engine::{
@@ -121,9 +122,11 @@ impl JobExecutor for TokioJobExecutor {
             if let Some(Err(err)) = future::poll_once(group.next()).await.flatten() {
                 eprintln!("Uncaught {err}");
             };
+            sample_event_loop(IDLE_FRAME);
 
             // Only one macrotask can be executed before the next drain of the microtask queue.
             self.drain_jobs(&mut context.borrow_mut());
+            sample_event_loop(JOBS_FRAME);
             task::yield_now().await
         }
     }
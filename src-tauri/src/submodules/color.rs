@@ -1,3 +1,5 @@
+use std::sync::OnceLock;
+
 /// 将 RGB 颜色值转换为 HSL 格式
 ///
 /// # 参数
@@ -54,6 +56,198 @@ pub fn rgb_to_hsl(rgb: u32) -> (f64, f64, f64) {
     (hue, saturation, luminance)
 }
 
+/// 将 RGB 颜色值转换为 HSV 格式
+///
+/// # 参数
+/// * `rgb` - RGB 颜色值，格式为 0xRRGGBB（整数）
+///
+/// # 返回值
+/// * `(f64, f64, f64)` - HSV 值的元组 (hue, saturation, value)
+///   - hue: 色相，范围 0-360
+///   - saturation: 饱和度，范围 0-1
+///   - value: 明度，范围 0-1
+pub fn rgb_to_hsv(rgb: u32) -> (f64, f64, f64) {
+    let r = ((rgb >> 16) & 0xFF) as f64 / 255.0;
+    let g = ((rgb >> 8) & 0xFF) as f64 / 255.0;
+    let b = (rgb & 0xFF) as f64 / 255.0;
+
+    let vmax = r.max(g).max(b);
+    let vmin = r.min(g).min(b);
+    let delta = vmax - vmin;
+
+    let saturation = if vmax == 0.0 { 0.0 } else { delta / vmax };
+    // 色相与 HSL 定义一致，直接复用
+    let (hue, _, _) = rgb_to_hsl(rgb);
+
+    (hue, saturation, vmax)
+}
+
+/// CIELAB 颜色 (L*, a*, b*)，L* 范围 0-100，a*/b* 约在 -128..128
+pub type Lab = (f64, f64, f64);
+
+/// D65 白点（与 OpenCV `COLOR_BGR2Lab` 一致）
+const D65_WHITE: (f64, f64, f64) = (0.950456, 1.0, 1.088754);
+
+/// sRGB 8 位分量到线性分量的查找表，首次使用时预计算
+fn srgb_linear_lut() -> &'static [f64; 256] {
+    static LUT: OnceLock<[f64; 256]> = OnceLock::new();
+    LUT.get_or_init(|| {
+        let mut lut = [0.0; 256];
+        for (i, value) in lut.iter_mut().enumerate() {
+            let c = i as f64 / 255.0;
+            *value = if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            };
+        }
+        lut
+    })
+}
+
+/// 将 8 位 sRGB 分量转换为 CIELAB（D65）
+pub fn srgb_to_lab(r: u8, g: u8, b: u8) -> Lab {
+    let lut = srgb_linear_lut();
+    let (r, g, b) = (lut[r as usize], lut[g as usize], lut[b as usize]);
+
+    let x = (0.412453 * r + 0.357580 * g + 0.180423 * b) / D65_WHITE.0;
+    let y = (0.212671 * r + 0.715160 * g + 0.072169 * b) / D65_WHITE.1;
+    let z = (0.019334 * r + 0.119193 * g + 0.950227 * b) / D65_WHITE.2;
+
+    let f = |t: f64| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    let l = if y > 0.008856 {
+        116.0 * fy - 16.0
+    } else {
+        903.3 * y
+    };
+
+    (l, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+/// 将 RGB 颜色值（0xRRGGBB）转换为 CIELAB（D65）
+///
+/// 说明：转换公式与 OpenCV 浮点 `COLOR_BGR2Lab` 一致，便于与整图转换结果直接比较。
+pub fn rgb_to_lab(rgb: u32) -> Lab {
+    srgb_to_lab(
+        ((rgb >> 16) & 0xFF) as u8,
+        ((rgb >> 8) & 0xFF) as u8,
+        (rgb & 0xFF) as u8,
+    )
+}
+
+/// CIE76 色差：Lab 空间欧氏距离
+pub fn delta_e76(a: Lab, b: Lab) -> f64 {
+    let (dl, da, db) = (a.0 - b.0, a.1 - b.1, a.2 - b.2);
+    (dl * dl + da * da + db * db).sqrt()
+}
+
+/// CIEDE2000 色差（kL = kC = kH = 1）
+pub fn delta_e2000(a: Lab, b: Lab) -> f64 {
+    let (l1, a1, b1) = a;
+    let (l2, a2, b2) = b;
+
+    let c1 = a1.hypot(b1);
+    let c2 = a2.hypot(b2);
+    let c_mean = (c1 + c2) / 2.0;
+    let c_mean7 = c_mean.powi(7);
+    let g = 0.5 * (1.0 - (c_mean7 / (c_mean7 + 25f64.powi(7))).sqrt());
+
+    let a1p = a1 * (1.0 + g);
+    let a2p = a2 * (1.0 + g);
+    let c1p = a1p.hypot(b1);
+    let c2p = a2p.hypot(b2);
+
+    let hue = |bp: f64, ap: f64| {
+        if bp == 0.0 && ap == 0.0 {
+            0.0
+        } else {
+            bp.atan2(ap).to_degrees().rem_euclid(360.0)
+        }
+    };
+    let h1p = hue(b1, a1p);
+    let h2p = hue(b2, a2p);
+
+    let dlp = l2 - l1;
+    let dcp = c2p - c1p;
+    let dhp = if c1p * c2p == 0.0 {
+        0.0
+    } else if (h2p - h1p).abs() <= 180.0 {
+        h2p - h1p
+    } else if h2p - h1p > 180.0 {
+        h2p - h1p - 360.0
+    } else {
+        h2p - h1p + 360.0
+    };
+    let dhp_big = 2.0 * (c1p * c2p).sqrt() * (dhp.to_radians() / 2.0).sin();
+
+    let lp_mean = (l1 + l2) / 2.0;
+    let cp_mean = (c1p + c2p) / 2.0;
+    let hp_mean = if c1p * c2p == 0.0 {
+        h1p + h2p
+    } else if (h1p - h2p).abs() <= 180.0 {
+        (h1p + h2p) / 2.0
+    } else if h1p + h2p < 360.0 {
+        (h1p + h2p + 360.0) / 2.0
+    } else {
+        (h1p + h2p - 360.0) / 2.0
+    };
+
+    let t = 1.0 - 0.17 * (hp_mean - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * hp_mean).to_radians().cos()
+        + 0.32 * (3.0 * hp_mean + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * hp_mean - 63.0).to_radians().cos();
+    let d_theta = 30.0 * (-((hp_mean - 275.0) / 25.0).powi(2)).exp();
+    let cp_mean7 = cp_mean.powi(7);
+    let rc = 2.0 * (cp_mean7 / (cp_mean7 + 25f64.powi(7))).sqrt();
+    let lp_offset = (lp_mean - 50.0).powi(2);
+    let sl = 1.0 + 0.015 * lp_offset / (20.0 + lp_offset).sqrt();
+    let sc = 1.0 + 0.045 * cp_mean;
+    let sh = 1.0 + 0.015 * cp_mean * t;
+    let rt = -(2.0 * d_theta).to_radians().sin() * rc;
+
+    let (tl, tc, th) = (dlp / sl, dcp / sc, dhp_big / sh);
+    (tl * tl + tc * tc + th * th + rt * tc * th).sqrt()
+}
+
+/// 颜色距离模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorDistance {
+    /// 逐通道 RGB 容差（历史行为）
+    #[default]
+    Rgb,
+    /// CIE76 色差
+    DeltaE76,
+    /// CIEDE2000 色差
+    DeltaE2000,
+}
+
+impl ColorDistance {
+    /// 解析模式名称：`rgb` / `deltaE76`（`de76` / `cie76`）/ `deltaE2000`（`de2000` / `ciede2000`），不区分大小写
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "rgb" => Some(Self::Rgb),
+            "deltae76" | "de76" | "cie76" => Some(Self::DeltaE76),
+            "deltae2000" | "de2000" | "ciede2000" => Some(Self::DeltaE2000),
+            _ => None,
+        }
+    }
+
+    /// 计算两个 Lab 颜色的色差；`Rgb` 模式下退化为 CIE76
+    pub fn delta(self, a: Lab, b: Lab) -> f64 {
+        match self {
+            Self::DeltaE2000 => delta_e2000(a, b),
+            Self::Rgb | Self::DeltaE76 => delta_e76(a, b),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,4 +333,61 @@ mod tests {
         assert_eq!(s, 1.0);
         assert_eq!(l, 0.5);
     }
+
+    #[test]
+    fn test_rgb_to_hsv() {
+        assert_eq!(rgb_to_hsv(0xFF0000), (0.0, 1.0, 1.0));
+        assert_eq!(rgb_to_hsv(0x000000), (0.0, 0.0, 0.0));
+        let (h, s, v) = rgb_to_hsv(0x008080);
+        assert_eq!(h, 180.0);
+        assert_eq!(s, 1.0);
+        assert!((v - 0.502).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_rgb_to_lab_reference_points() {
+        let (l, a, b) = rgb_to_lab(0xFFFFFF);
+        assert!((l - 100.0).abs() < 0.01 && a.abs() < 0.01 && b.abs() < 0.01);
+        assert_eq!(rgb_to_lab(0x000000), (0.0, 0.0, 0.0));
+        // sRGB 红色约为 (53.24, 80.09, 67.20)
+        let (l, a, b) = rgb_to_lab(0xFF0000);
+        assert!((l - 53.24).abs() < 0.05);
+        assert!((a - 80.09).abs() < 0.1);
+        assert!((b - 67.20).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_delta_e2000_sharma_pairs() {
+        // Sharma 等人 CIEDE2000 测试数据
+        let pairs = [
+            ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+            ((50.0, 0.0, 0.0), (50.0, -1.0, 2.0), 2.3669),
+            ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+            (
+                (60.2574, -34.0099, 36.2677),
+                (60.4626, -34.1751, 39.4387),
+                1.2644,
+            ),
+        ];
+        for (a, b, expected) in pairs {
+            assert!((delta_e2000(a, b) - expected).abs() < 1e-4);
+            assert!((delta_e2000(b, a) - expected).abs() < 1e-4);
+        }
+        assert_eq!(delta_e2000((40.0, 10.0, -5.0), (40.0, 10.0, -5.0)), 0.0);
+    }
+
+    #[test]
+    fn test_delta_e76_and_mode_names() {
+        assert_eq!(delta_e76((50.0, 0.0, 0.0), (53.0, 4.0, 0.0)), 5.0);
+        assert_eq!(ColorDistance::from_name("RGB"), Some(ColorDistance::Rgb));
+        assert_eq!(
+            ColorDistance::from_name("deltaE2000"),
+            Some(ColorDistance::DeltaE2000)
+        );
+        assert_eq!(
+            ColorDistance::from_name("de76"),
+            Some(ColorDistance::DeltaE76)
+        );
+        assert_eq!(ColorDistance::from_name("hsv"), None);
+    }
 }
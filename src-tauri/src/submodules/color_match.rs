@@ -1,9 +1,14 @@
 use opencv::{
-    core::{self, BORDER_CONSTANT, CV_8UC1, CV_32F, Mat, Point, Scalar, Size},
+    core::{self, BORDER_CONSTANT, Mat, Point, Scalar, Size},
     imgproc,
     prelude::*,
 };
 use thiserror::Error;
+
+use crate::submodules::{
+    color::{ColorDistance, Lab, delta_e2000, srgb_to_lab},
+    tpl_match::{MatchConfig, compute_match_scores},
+};
 #[derive(Error, Debug)]
 pub enum ColorMatchError {
     #[error("图像处理错误: {0}")]
@@ -20,24 +25,193 @@ pub(crate) fn rgb_to_bgr(color: u32) -> (u8, u8, u8) {
     (b, g, r)
 }
 
+/// 单个像素是否在目标颜色的容差范围内（三个通道差值都不超过 `tolerance`）
+#[inline]
+pub(crate) fn pixel_within_tolerance(
+    pixel: [u8; 3],       // B, G, R
+    target: (u8, u8, u8), // B, G, R
+    tolerance: u8,
+) -> bool {
+    pixel[0].abs_diff(target.0) <= tolerance
+        && pixel[1].abs_diff(target.1) <= tolerance
+        && pixel[2].abs_diff(target.2) <= tolerance
+}
+
 pub(crate) fn check_color_mat(img_bgr: &Mat, x: i32, y: i32, color: u32, tolerance: u8) -> bool {
-    return img_bgr
+    img_bgr
+        .at_2d::<core::Vec3b>(y, x)
+        .map(|pixel| pixel_within_tolerance(pixel.0, rgb_to_bgr(color), tolerance))
+        .unwrap_or(false)
+}
+
+/// 生成颜色容差掩码：三个通道与目标颜色的差值都不超过 `tolerance` 的像素为 255，其余为 0
+///
+/// 等价于逐像素判断 `|b - tb| <= tol && |g - tg| <= tol && |r - tr| <= tol`，
+/// 但使用 `inRange` 按饱和区间 `[t - tol, t + tol]` 一次处理整张图（OpenCV 内部 SIMD）。
+///
+/// # 参数
+/// * `img_bgr` - 源图像（必须为 3 通道 BGR）
+/// * `target_color` - 目标颜色（B, G, R）
+/// * `tolerance` - 颜色容差
+pub(crate) fn color_tolerance_mask(
+    img_bgr: &Mat,
+    target_color: (u8, u8, u8), // B, G, R
+    tolerance: u8,
+) -> Result<Mat, ColorMatchError> {
+    if img_bgr.channels() != 3 {
+        return Err(ColorMatchError::ImageProcessing(format!(
+            "颜色掩码需要 3 通道 BGR 图像，实际通道数: {}",
+            img_bgr.channels()
+        )));
+    }
+    let (b, g, r) = target_color;
+    let lower = Scalar::new(
+        b.saturating_sub(tolerance) as f64,
+        g.saturating_sub(tolerance) as f64,
+        r.saturating_sub(tolerance) as f64,
+        0.0,
+    );
+    let upper = Scalar::new(
+        b.saturating_add(tolerance) as f64,
+        g.saturating_add(tolerance) as f64,
+        r.saturating_add(tolerance) as f64,
+        255.0,
+    );
+    let mut mask = Mat::default();
+    core::in_range(img_bgr, &lower, &upper, &mut mask)
+        .map_err(|e| ColorMatchError::OpenCV(format!("in_range: {e}")))?;
+    Ok(mask)
+}
+
+/// 将 BGR 图像整体转换为浮点 CIELAB 图像（`CV_32FC3`，L* 0-100）
+///
+/// 说明：ΔE 系列接口应对同一张图只转换一次，再对多个目标颜色复用结果。
+pub(crate) fn bgr_to_lab_image(img_bgr: &Mat) -> Result<Mat, ColorMatchError> {
+    if img_bgr.channels() != 3 {
+        return Err(ColorMatchError::ImageProcessing(format!(
+            "Lab 转换需要 3 通道 BGR 图像，实际通道数: {}",
+            img_bgr.channels()
+        )));
+    }
+    let mut float_mat = Mat::default();
+    img_bgr
+        .convert_to(&mut float_mat, core::CV_32FC3, 1.0 / 255.0, 0.0)
+        .map_err(|e| ColorMatchError::OpenCV(format!("convert_to: {e}")))?;
+    let mut lab = Mat::default();
+    imgproc::cvt_color(&float_mat, &mut lab, imgproc::COLOR_BGR2Lab, 0)
+        .map_err(|e| ColorMatchError::OpenCV(format!("cvt_color: {e}")))?;
+    Ok(lab)
+}
+
+/// 生成色差掩码：与目标颜色色差不超过 `max_delta` 的像素为 255，其余为 0
+///
+/// # 参数
+/// * `lab` - `bgr_to_lab_image` 的输出
+/// * `target` - 目标颜色（Lab）
+/// * `max_delta` - 最大色差
+/// * `distance` - 色差公式；`Rgb` 按 CIE76 处理
+pub(crate) fn color_distance_mask(
+    lab: &Mat,
+    target: Lab,
+    max_delta: f64,
+    distance: ColorDistance,
+) -> Result<Mat, ColorMatchError> {
+    let cv_err = |e: opencv::Error| ColorMatchError::OpenCV(e.to_string());
+    if distance != ColorDistance::DeltaE2000 {
+        // CIE76 可完全用整图运算完成：平方差按通道求和后与 max_delta² 比较
+        let mut diff = Mat::default();
+        core::subtract(
+            lab,
+            &Scalar::new(target.0, target.1, target.2, 0.0),
+            &mut diff,
+            &Mat::default(),
+            -1,
+        )
+        .map_err(cv_err)?;
+        let mut squared = Mat::default();
+        core::multiply(&diff, &diff, &mut squared, 1.0, -1).map_err(cv_err)?;
+        let mut summed = Mat::default();
+        let ones = Mat::from_slice_2d(&[[1.0f32, 1.0, 1.0]]).map_err(cv_err)?;
+        core::transform(&squared, &mut summed, &ones).map_err(cv_err)?;
+        let mut mask = Mat::default();
+        core::compare(
+            &summed,
+            &Scalar::all(max_delta * max_delta),
+            &mut mask,
+            core::CMP_LE,
+        )
+        .map_err(cv_err)?;
+        return Ok(mask);
+    }
+
+    let continuous;
+    let lab = if lab.is_continuous() {
+        lab
+    } else {
+        continuous = lab.try_clone().map_err(cv_err)?;
+        &continuous
+    };
+    let mut mask =
+        Mat::new_rows_cols_with_default(lab.rows(), lab.cols(), core::CV_8UC1, Scalar::all(0.0))
+            .map_err(cv_err)?;
+    let pixels = lab.data_typed::<core::Vec3f>().map_err(cv_err)?;
+    let out = mask.data_typed_mut::<u8>().map_err(cv_err)?;
+    for (dst, px) in out.iter_mut().zip(pixels) {
+        let lab_px = (px[0] as f64, px[1] as f64, px[2] as f64);
+        if delta_e2000(lab_px, target) <= max_delta {
+            *dst = 255;
+        }
+    }
+    Ok(mask)
+}
+
+/// 按色差判断单个像素是否与目标颜色接近，越界或非 3 通道图像返回 false
+pub(crate) fn check_color_distance(
+    img_bgr: &Mat,
+    x: i32,
+    y: i32,
+    target: Lab,
+    max_delta: f64,
+    distance: ColorDistance,
+) -> bool {
+    img_bgr
         .at_2d::<core::Vec3b>(y, x)
         .map(|pixel| {
-            let b = pixel[0];
-            let g = pixel[1];
-            let r = pixel[2];
-            let (b_target, g_target, r_target) = rgb_to_bgr(color);
-            let db = (b as i32 - b_target as i32).abs() as u8;
-            let dg = (g as i32 - g_target as i32).abs() as u8;
-            let dr = (r as i32 - r_target as i32).abs() as u8;
-            if db <= tolerance && dg <= tolerance && dr <= tolerance {
-                return true;
-            }
-            false
+            let [b, g, r] = pixel.0;
+            distance.delta(srgb_to_lab(r, g, b), target) <= max_delta
         })
-        .unwrap_or(false);
+        .unwrap_or(false)
 }
+
+/// 计算两张同尺寸 Lab 图像的平均色差
+pub(crate) fn mean_color_distance(
+    lab_a: &Mat,
+    lab_b: &Mat,
+    distance: ColorDistance,
+) -> Result<f64, ColorMatchError> {
+    if lab_a.size().ok() != lab_b.size().ok() {
+        return Err(ColorMatchError::ImageProcessing(
+            "平均色差需要两张尺寸一致的图像".to_string(),
+        ));
+    }
+    let cv_err = |e: opencv::Error| ColorMatchError::OpenCV(e.to_string());
+    // ROI 视图不连续，先拷贝为连续内存再按切片遍历
+    let lab_a = lab_a.try_clone().map_err(cv_err)?;
+    let lab_b = lab_b.try_clone().map_err(cv_err)?;
+    let a = lab_a.data_typed::<core::Vec3f>().map_err(cv_err)?;
+    let b = lab_b.data_typed::<core::Vec3f>().map_err(cv_err)?;
+    if a.is_empty() {
+        return Ok(0.0);
+    }
+    let to_lab = |px: &core::Vec3f| (px[0] as f64, px[1] as f64, px[2] as f64);
+    let total: f64 = a
+        .iter()
+        .zip(b)
+        .map(|(pa, pb)| distance.delta(to_lab(pa), to_lab(pb)))
+        .sum();
+    Ok(total / a.len() as f64)
+}
+
 /// 颜色匹配与模板匹配函数
 ///
 /// # 参数
@@ -53,6 +227,35 @@ pub(crate) fn find_color_and_match_template(
     template: &Mat,
     target_color: (u8, u8, u8), // B, G, R
     tolerance: u8,
+) -> Result<Option<(i32, i32)>, ColorMatchError> {
+    find_color_and_match_template_with(
+        img_bgr,
+        template,
+        target_color,
+        tolerance,
+        &MatchConfig::default(),
+    )
+}
+
+/// 颜色匹配与模板匹配函数（可选匹配算法与预处理）
+///
+/// 预处理作用于颜色二值化后的掩码图，因此只有 `Canny` 会改变结果（按轮廓匹配）。
+///
+/// # 参数
+/// * `img_bgr` - 源图像（BGR格式）
+/// * `template` - 模板图像（BGR格式）
+/// * `target_color` - 目标颜色（B, G, R）
+/// * `tolerance` - 颜色容差
+/// * `config` - 匹配算法与预处理
+///
+/// # 返回值
+/// * `Result<Option<(i32, i32)>>` - 匹配位置（x, y）或错误
+pub(crate) fn find_color_and_match_template_with(
+    img_bgr: &Mat,
+    template: &Mat,
+    target_color: (u8, u8, u8), // B, G, R
+    tolerance: u8,
+    config: &MatchConfig,
 ) -> Result<Option<(i32, i32)>, ColorMatchError> {
     if img_bgr.rows() <= 0 || img_bgr.cols() <= 0 {
         return Err(ColorMatchError::ImageProcessing(
@@ -78,35 +281,8 @@ pub(crate) fn find_color_and_match_template(
         target_color: (u8, u8, u8),
         tolerance: u8,
     ) -> Result<Mat, ColorMatchError> {
-        // 创建掩码：查找与目标颜色近似的像素
-        let mask_expr = Mat::zeros(img.rows(), img.cols(), CV_8UC1)
-            .map_err(|e| ColorMatchError::OpenCV(format!("Mat::zeros: {e}")))?;
-        let mut mask = mask_expr
-            .to_mat()
-            .map_err(|e| ColorMatchError::OpenCV(format!("to_mat: {e}")))?;
-
-        for y in 0..img.rows() {
-            for x in 0..img.cols() {
-                let pixel = img
-                    .at_2d::<core::Vec3b>(y, x)
-                    .map_err(|e| ColorMatchError::OpenCV(format!("at_2d: {e}")))?;
-                let b = pixel[0];
-                let g = pixel[1];
-                let r = pixel[2];
-
-                // 计算与目标颜色的差值
-                let db = (b as i32 - target_color.0 as i32).abs() as u8;
-                let dg = (g as i32 - target_color.1 as i32).abs() as u8;
-                let dr = (r as i32 - target_color.2 as i32).abs() as u8;
-
-                // 如果差值在容差范围内，标记为白色（255）
-                if db <= tolerance && dg <= tolerance && dr <= tolerance {
-                    *mask
-                        .at_2d_mut::<u8>(y, x)
-                        .map_err(|e| ColorMatchError::OpenCV(format!("at_2d_mut: {e}")))? = 255;
-                }
-            }
-        }
+        // 创建掩码：查找与目标颜色近似的像素（命中为 255）
+        let mask = color_tolerance_mask(img, target_color, tolerance)?;
 
         // 对二值图像进行形态学操作，去除噪声
         let kernel = imgproc::get_structuring_element(
@@ -149,23 +325,9 @@ pub(crate) fn find_color_and_match_template(
         ));
     }
 
-    // 创建结果矩阵
-    let result_cols = processed.cols() - processed_template.cols() + 1;
-    let result_rows = processed.rows() - processed_template.rows() + 1;
-    let mut result = unsafe {
-        Mat::new_rows_cols(result_rows, result_cols, CV_32F)
-            .map_err(|e| ColorMatchError::OpenCV(format!("Mat::new_rows_cols: {e}")))?
-    };
-
-    // 执行模板匹配
-    imgproc::match_template(
-        &processed,
-        &processed_template,
-        &mut result,
-        imgproc::TM_CCOEFF_NORMED,
-        &Mat::default(),
-    )
-    .map_err(|e| ColorMatchError::OpenCV(format!("match_template: {e}")))?;
+    // 执行模板匹配（得分统一为越高越好）
+    let result = compute_match_scores(&processed, &processed_template, config)
+        .map_err(|e| ColorMatchError::OpenCV(e.to_string()))?;
 
     // 找到最佳匹配位置
     let mut min_val = 0.0;
@@ -189,3 +351,159 @@ pub(crate) fn find_color_and_match_template(
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submodules::test_support::random_bgr;
+    use opencv::core::Vec3b;
+    use std::time::Instant;
+
+    /// 逐像素参考实现（向量化之前的写法）
+    fn reference_mask(img: &Mat, target: (u8, u8, u8), tolerance: u8) -> Mat {
+        let mut mask = Mat::new_rows_cols_with_default(
+            img.rows(),
+            img.cols(),
+            core::CV_8UC1,
+            Scalar::all(0.0),
+        )
+        .unwrap();
+        for y in 0..img.rows() {
+            for x in 0..img.cols() {
+                let pixel = img.at_2d::<Vec3b>(y, x).unwrap();
+                if pixel_within_tolerance(pixel.0, target, tolerance) {
+                    *mask.at_2d_mut::<u8>(y, x).unwrap() = 255;
+                }
+            }
+        }
+        mask
+    }
+
+    fn assert_same_mask(a: &Mat, b: &Mat) {
+        assert_eq!(a.size().unwrap(), b.size().unwrap());
+        let mut diff = Mat::default();
+        core::compare(a, b, &mut diff, core::CMP_NE).unwrap();
+        assert_eq!(core::count_non_zero(&diff).unwrap(), 0);
+    }
+
+    #[test]
+    fn tolerance_mask_matches_per_pixel_reference() {
+        let img = random_bgr(97, 61, 7);
+        let targets = [(0, 0, 0), (255, 255, 255), (3, 128, 252), (250, 1, 77)];
+        for &target in &targets {
+            for tolerance in [0u8, 1, 40, 128, 254, 255] {
+                let fast = color_tolerance_mask(&img, target, tolerance).unwrap();
+                let slow = reference_mask(&img, target, tolerance);
+                assert_same_mask(&fast, &slow);
+            }
+        }
+    }
+
+    #[test]
+    fn tolerance_mask_zero_tolerance_hits_exact_pixels() {
+        let img = random_bgr(32, 32, 11);
+        let pixel = img.at_2d::<Vec3b>(5, 9).unwrap().0;
+        let target = (pixel[0], pixel[1], pixel[2]);
+        let mask = color_tolerance_mask(&img, target, 0).unwrap();
+        assert_eq!(*mask.at_2d::<u8>(5, 9).unwrap(), 255);
+        assert_same_mask(&mask, &reference_mask(&img, target, 0));
+    }
+
+    #[test]
+    fn tolerance_mask_rejects_non_bgr_input() {
+        let gray = Mat::new_rows_cols_with_default(4, 4, core::CV_8UC1, Scalar::all(0.0)).unwrap();
+        assert!(color_tolerance_mask(&gray, (0, 0, 0), 10).is_err());
+        assert!(!check_color_mat(&gray, 0, 0, 0, 10));
+    }
+
+    #[test]
+    fn check_color_mat_agrees_with_mask() {
+        let img = random_bgr(24, 16, 3);
+        let color = 0x80_40_C0;
+        let mask = color_tolerance_mask(&img, rgb_to_bgr(color), 60).unwrap();
+        for y in 0..img.rows() {
+            for x in 0..img.cols() {
+                let expected = *mask.at_2d::<u8>(y, x).unwrap() == 255;
+                assert_eq!(check_color_mat(&img, x, y, color, 60), expected);
+            }
+        }
+        assert!(!check_color_mat(&img, img.cols(), 0, color, 60));
+    }
+
+    #[test]
+    fn lab_image_matches_scalar_conversion() {
+        let img = random_bgr(16, 16, 5);
+        let lab = bgr_to_lab_image(&img).unwrap();
+        for y in 0..img.rows() {
+            for x in 0..img.cols() {
+                let [b, g, r] = img.at_2d::<Vec3b>(y, x).unwrap().0;
+                let expected = srgb_to_lab(r, g, b);
+                let actual = lab.at_2d::<core::Vec3f>(y, x).unwrap();
+                assert!((actual[0] as f64 - expected.0).abs() < 0.05);
+                assert!((actual[1] as f64 - expected.1).abs() < 0.05);
+                assert!((actual[2] as f64 - expected.2).abs() < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn distance_mask_matches_per_pixel_reference() {
+        let img = random_bgr(40, 30, 9);
+        let lab = bgr_to_lab_image(&img).unwrap();
+        let target = crate::submodules::color::rgb_to_lab(0x80_40_C0);
+        for distance in [ColorDistance::DeltaE76, ColorDistance::DeltaE2000] {
+            let max_delta = 37.5;
+            let mask = color_distance_mask(&lab, target, max_delta, distance).unwrap();
+            let mut hits = 0;
+            for y in 0..lab.rows() {
+                for x in 0..lab.cols() {
+                    let px = lab.at_2d::<core::Vec3f>(y, x).unwrap();
+                    let delta = distance.delta((px[0] as f64, px[1] as f64, px[2] as f64), target);
+                    let expected = if delta <= max_delta { 255 } else { 0 };
+                    assert_eq!(*mask.at_2d::<u8>(y, x).unwrap(), expected);
+                    hits += (expected == 255) as i32;
+                }
+            }
+            assert!(hits > 0);
+            assert!(check_color_distance(&img, 0, 0, target, 1000.0, distance));
+            assert!(!check_color_distance(&img, -1, 0, target, 1000.0, distance));
+        }
+    }
+
+    #[test]
+    fn mean_color_distance_is_zero_for_identical_images() {
+        let lab = bgr_to_lab_image(&random_bgr(8, 8, 1)).unwrap();
+        let other = bgr_to_lab_image(&random_bgr(8, 8, 2)).unwrap();
+        for distance in [ColorDistance::DeltaE76, ColorDistance::DeltaE2000] {
+            assert_eq!(mean_color_distance(&lab, &lab, distance).unwrap(), 0.0);
+            assert!(mean_color_distance(&lab, &other, distance).unwrap() > 1.0);
+        }
+    }
+
+    /// 性能对比：`cargo test color_mask_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn color_mask_benchmark() {
+        let img = random_bgr(1920, 1080, 42);
+        let target = (120, 80, 200);
+        let rounds = 5;
+
+        let start = Instant::now();
+        for _ in 0..rounds {
+            reference_mask(&img, target, 30);
+        }
+        let slow = start.elapsed() / rounds;
+
+        let start = Instant::now();
+        for _ in 0..rounds {
+            color_tolerance_mask(&img, target, 30).unwrap();
+        }
+        let fast = start.elapsed() / rounds;
+
+        println!("1920x1080 颜色掩码: 逐像素 {slow:?}, inRange {fast:?}");
+        assert_same_mask(
+            &color_tolerance_mask(&img, target, 30).unwrap(),
+            &reference_mask(&img, target, 30),
+        );
+    }
+}
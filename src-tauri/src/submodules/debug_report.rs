@@ -0,0 +1,458 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use opencv::{core::Mat, imgproc};
+
+use crate::submodules::{
+    script_builtin::_mat_to_png_data_url,
+    script_vision::{draw_bboxes_impl, draw_contours_impl},
+};
+
+/// 报告内联样式（保证生成的 HTML 单文件可离线打开）。
+const REPORT_STYLE: &str = "body{font-family:system-ui,-apple-system,'Segoe UI',sans-serif;margin:24px;color:#222;background:#fafafa}\
+h1{margin:0 0 4px}h2{margin:0 0 8px;font-size:18px}\
+.meta{color:#666;margin:0 0 16px}\
+table{border-collapse:collapse;margin:8px 0;background:#fff}\
+th,td{border:1px solid #ddd;padding:4px 10px;text-align:left;font-size:13px}\
+th{background:#f0f0f0}\
+section{background:#fff;border:1px solid #e3e3e3;border-radius:6px;padding:12px 16px;margin:16px 0}\
+section.fail{border-left:4px solid #d33}section.pass{border-left:4px solid #2a2}\
+.pass-text{color:#2a2}.fail-text{color:#d33}\
+.note{white-space:pre-wrap;margin:4px 0}\
+.images{display:flex;flex-wrap:wrap;gap:12px}\
+figure{margin:0}figure img{max-width:640px;border:1px solid #ccc;image-rendering:pixelated}\
+figcaption{font-size:12px;color:#666}";
+
+/// 帧标注参数：在报告截图上叠加 bbox 与轮廓。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FrameAnnotation {
+    /// 需要框出的区域 `(x, y, w, h)`
+    pub(crate) bboxes: Vec<(i32, i32, i32, i32)>,
+    /// 轮廓最小面积；`None` 表示不绘制轮廓
+    pub(crate) contour_min_area: Option<f64>,
+    /// 标注颜色（0xRRGGBB）
+    pub(crate) color: u32,
+}
+
+impl FrameAnnotation {
+    /// 按标注参数生成新图像（原图不变）。
+    ///
+    /// 说明：先绘制轮廓再绘制 bbox，均复用 `drawContours` 的实现；无任何标注时仅做 BGR 规整。
+    pub(crate) fn apply(&self, mat: &Mat) -> Result<Mat, String> {
+        let base = match self.contour_min_area {
+            Some(min_area) => draw_contours_impl(
+                mat,
+                imgproc::RETR_EXTERNAL,
+                imgproc::CHAIN_APPROX_SIMPLE,
+                min_area,
+                self.color,
+                1,
+            )?,
+            None => mat.clone(),
+        };
+        draw_bboxes_impl(&base, &self.bboxes, self.color, 2)
+    }
+}
+
+/// 报告中的单张图片（PNG data URL）。
+#[derive(Debug, Clone)]
+pub(crate) struct ReportImage {
+    pub(crate) label: String,
+    pub(crate) data_url: String,
+}
+
+impl ReportImage {
+    /// 对帧做标注并编码为 PNG data URL。
+    pub(crate) fn from_mat(
+        label: impl Into<String>,
+        mat: &Mat,
+        annotation: &FrameAnnotation,
+    ) -> Result<Self, String> {
+        let annotated = annotation.apply(mat)?;
+        Ok(Self {
+            label: label.into(),
+            data_url: _mat_to_png_data_url(&annotated)?,
+        })
+    }
+}
+
+/// 报告中的一个步骤：图片 + 得分 / 阈值 / 耗时 + 自定义字段。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReportStep {
+    pub(crate) name: String,
+    pub(crate) note: Option<String>,
+    pub(crate) score: Option<f64>,
+    pub(crate) threshold: Option<f64>,
+    pub(crate) elapsed_ms: Option<f64>,
+    pub(crate) fields: Vec<(String, String)>,
+    pub(crate) images: Vec<ReportImage>,
+    /// 相对报告创建时刻的偏移（毫秒），由 `DebugReport::push_step` 填写
+    pub(crate) at_ms: u64,
+}
+
+impl ReportStep {
+    /// 是否通过：仅当得分与阈值都存在时才有结论（`score >= threshold`）。
+    pub(crate) fn passed(&self) -> Option<bool> {
+        Some(self.score? >= self.threshold?)
+    }
+}
+
+/// 视觉调试报告构建器。
+///
+/// 说明：
+/// - 脚本按步骤追加标注帧、得分、阈值与耗时，最后生成单文件 HTML（图片以 data URL 内嵌）；
+/// - `summary_text` / `status_images` 用于挂到脚本状态面板，供 MCP `read_status` 读取。
+#[derive(Debug)]
+pub(crate) struct DebugReport {
+    title: String,
+    created_at: u64,
+    started: Instant,
+    steps: Vec<ReportStep>,
+    saved_path: Option<PathBuf>,
+}
+
+impl DebugReport {
+    pub(crate) fn new(title: impl Into<String>) -> Self {
+        let title = title.into();
+        Self {
+            title: if title.trim().is_empty() {
+                "调试报告".to_string()
+            } else {
+                title.trim().to_string()
+            },
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            started: Instant::now(),
+            steps: Vec::new(),
+            saved_path: None,
+        }
+    }
+
+    pub(crate) fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// 追加步骤并返回其序号（从 0 开始）。
+    pub(crate) fn push_step(&mut self, mut step: ReportStep) -> usize {
+        step.at_ms = self.started.elapsed().as_millis() as u64;
+        self.steps.push(step);
+        self.steps.len() - 1
+    }
+
+    /// 清空全部步骤（保留标题与创建时间）。
+    pub(crate) fn clear(&mut self) {
+        self.steps.clear();
+    }
+
+    /// 生成逐步骤的文本摘要，每步一行。
+    pub(crate) fn summary_text(&self) -> String {
+        let failed = self
+            .steps
+            .iter()
+            .filter(|step| step.passed() == Some(false))
+            .count();
+        let mut text = format!("{}：{} 步，未通过 {}", self.title, self.steps.len(), failed);
+        if let Some(path) = &self.saved_path {
+            let _ = write!(text, "\n报告: {}", path.display());
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            let _ = write!(text, "\n#{} {}", index + 1, step.name);
+            if let Some(passed) = step.passed() {
+                text.push_str(if passed { " [通过]" } else { " [未通过]" });
+            }
+            if let Some(score) = step.score {
+                let _ = write!(text, " score={score:.4}");
+            }
+            if let Some(threshold) = step.threshold {
+                let _ = write!(text, " threshold={threshold:.4}");
+            }
+            if let Some(elapsed) = step.elapsed_ms {
+                let _ = write!(text, " {elapsed:.1}ms");
+            }
+            for (key, value) in &step.fields {
+                let _ = write!(text, " {key}={value}");
+            }
+            if let Some(note) = &step.note {
+                let _ = write!(text, " ({note})");
+            }
+        }
+        text
+    }
+
+    /// 取最近步骤中的至多 `max_images` 张图片（按步骤顺序），用于状态面板展示。
+    pub(crate) fn status_images(&self, max_images: usize) -> Vec<String> {
+        let mut images: Vec<String> = self
+            .steps
+            .iter()
+            .rev()
+            .flat_map(|step| step.images.iter().rev())
+            .take(max_images)
+            .map(|image| image.data_url.clone())
+            .collect();
+        images.reverse();
+        images
+    }
+
+    /// 渲染为单文件 HTML。
+    pub(crate) fn to_html(&self) -> String {
+        let mut html = String::with_capacity(
+            4096 + self
+                .steps
+                .iter()
+                .flat_map(|step| step.images.iter())
+                .map(|image| image.data_url.len() + 128)
+                .sum::<usize>(),
+        );
+        let title = _escape_html(&self.title);
+        let failed = self
+            .steps
+            .iter()
+            .filter(|step| step.passed() == Some(false))
+            .count();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{REPORT_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<p class=\"meta\">生成时间 <time data-ms=\"{created}\">{created}</time> · 共 {total} 步 · 未通过 {failed}</p>\n",
+            created = self.created_at,
+            total = self.steps.len(),
+        );
+
+        html.push_str(
+            "<table>\n<tr><th>#</th><th>步骤</th><th>结果</th><th>得分</th><th>阈值</th><th>耗时 (ms)</th><th>时刻 (ms)</th></tr>\n",
+        );
+        for (index, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(
+                html,
+                "<tr><td><a href=\"#step-{n}\">{n}</a></td><td>{name}</td><td>{result}</td><td>{score}</td><td>{threshold}</td><td>{elapsed}</td><td>{at}</td></tr>",
+                n = index + 1,
+                name = _escape_html(&step.name),
+                result = _result_cell(step.passed()),
+                score = _format_number(step.score, 4),
+                threshold = _format_number(step.threshold, 4),
+                elapsed = _format_number(step.elapsed_ms, 1),
+                at = step.at_ms,
+            );
+        }
+        html.push_str("</table>\n");
+
+        for (index, step) in self.steps.iter().enumerate() {
+            let class = match step.passed() {
+                Some(true) => " class=\"pass\"",
+                Some(false) => " class=\"fail\"",
+                None => "",
+            };
+            let _ = writeln!(
+                html,
+                "<section id=\"step-{n}\"{class}>\n<h2>#{n} {name}</h2>",
+                n = index + 1,
+                name = _escape_html(&step.name),
+            );
+            if let Some(note) = &step.note {
+                let _ = writeln!(html, "<p class=\"note\">{}</p>", _escape_html(note));
+            }
+
+            let mut rows = Vec::new();
+            if let Some(passed) = step.passed() {
+                rows.push(("结果".to_string(), _result_cell(Some(passed))));
+            }
+            if step.score.is_some() {
+                rows.push(("得分".to_string(), _format_number(step.score, 4)));
+            }
+            if step.threshold.is_some() {
+                rows.push(("阈值".to_string(), _format_number(step.threshold, 4)));
+            }
+            if step.elapsed_ms.is_some() {
+                rows.push(("耗时 (ms)".to_string(), _format_number(step.elapsed_ms, 1)));
+            }
+            for (key, value) in &step.fields {
+                rows.push((_escape_html(key), _escape_html(value)));
+            }
+            if !rows.is_empty() {
+                html.push_str("<table>\n");
+                for (key, value) in rows {
+                    let _ = writeln!(html, "<tr><th>{key}</th><td>{value}</td></tr>");
+                }
+                html.push_str("</table>\n");
+            }
+
+            if !step.images.is_empty() {
+                html.push_str("<div class=\"images\">\n");
+                for image in &step.images {
+                    let label = _escape_html(&image.label);
+                    let _ = writeln!(
+                        html,
+                        "<figure><img src=\"{}\" alt=\"{label}\"><figcaption>{label}</figcaption></figure>",
+                        image.data_url
+                    );
+                }
+                html.push_str("</div>\n");
+            }
+            html.push_str("</section>\n");
+        }
+
+        html.push_str(
+            "<script>document.querySelectorAll('time[data-ms]').forEach(function(t){t.textContent=new Date(Number(t.dataset.ms)).toLocaleString()})</script>\n</body>\n</html>\n",
+        );
+        html
+    }
+
+    /// 写出 HTML 报告（自动创建父目录），并记录路径供摘要引用。
+    pub(crate) fn save_html(&mut self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| format!("创建报告目录失败: {e}"))?;
+        }
+        fs::write(path, self.to_html()).map_err(|e| format!("写入调试报告失败: {e}"))?;
+        self.saved_path = Some(path.to_path_buf());
+        Ok(())
+    }
+}
+
+/// 转义 HTML 特殊字符。
+fn _escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// 格式化可选数值，缺省时输出 `-`。
+fn _format_number(value: Option<f64>, precision: usize) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v:.precision$}"))
+}
+
+/// 生成结果单元格内容。
+fn _result_cell(passed: Option<bool>) -> String {
+    match passed {
+        Some(true) => "<span class=\"pass-text\">通过</span>".to_string(),
+        Some(false) => "<span class=\"fail-text\">未通过</span>".to_string(),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{_escape_html, DebugReport, FrameAnnotation, ReportImage, ReportStep};
+    use opencv::{
+        core::{CV_8UC3, Mat, Scalar},
+        prelude::*,
+    };
+
+    fn scored_step(name: &str, score: f64, threshold: f64) -> ReportStep {
+        ReportStep {
+            name: name.to_string(),
+            score: Some(score),
+            threshold: Some(threshold),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn escape_html_special_chars() {
+        assert_eq!(
+            _escape_html("<a href=\"x\">'&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn step_pass_requires_score_and_threshold() {
+        assert_eq!(scored_step("a", 0.9, 0.8).passed(), Some(true));
+        assert_eq!(scored_step("b", 0.5, 0.8).passed(), Some(false));
+        let step = ReportStep {
+            score: Some(0.9),
+            ..Default::default()
+        };
+        assert_eq!(step.passed(), None);
+    }
+
+    #[test]
+    fn html_contains_steps_and_escaped_fields() {
+        let mut report = DebugReport::new("登录<检测>");
+        report.push_step(scored_step("找按钮", 0.92, 0.8));
+        let mut failed = scored_step("找图标", 0.41, 0.8);
+        failed.elapsed_ms = Some(12.5);
+        failed
+            .fields
+            .push(("roi".to_string(), "[0,0,<10>,10]".to_string()));
+        report.push_step(failed);
+
+        let html = report.to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>登录&lt;检测&gt;</title>"));
+        assert!(html.contains("id=\"step-1\" class=\"pass\""));
+        assert!(html.contains("id=\"step-2\" class=\"fail\""));
+        assert!(html.contains("[0,0,&lt;10&gt;,10]"));
+        assert!(html.contains("0.4100"));
+        assert!(html.contains("12.5"));
+        assert!(html.contains("未通过 1"));
+    }
+
+    #[test]
+    fn annotated_image_is_embedded_and_attached_to_status() {
+        let frame = Mat::new_rows_cols_with_default(16, 16, CV_8UC3, Scalar::all(0.0)).unwrap();
+        let annotation = FrameAnnotation {
+            bboxes: vec![(2, 2, 8, 8)],
+            contour_min_area: None,
+            color: 0xFF0000,
+        };
+        assert_ne!(
+            annotation
+                .apply(&frame)
+                .unwrap()
+                .at_2d::<opencv::core::Vec3b>(2, 2)
+                .unwrap()[2],
+            0
+        );
+
+        let mut report = DebugReport::new("");
+        assert_eq!(report.title(), "调试报告");
+        for index in 0..3 {
+            let mut step = scored_step(&format!("s{index}"), 1.0, 0.5);
+            step.images
+                .push(ReportImage::from_mat(format!("frame{index}"), &frame, &annotation).unwrap());
+            report.push_step(step);
+        }
+        assert_eq!(report.len(), 3);
+        assert!(report.to_html().contains("src=\"data:image/png;base64,"));
+
+        let images = report.status_images(2);
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[1], report.steps[2].images[0].data_url);
+
+        let summary = report.summary_text();
+        assert!(summary.starts_with("调试报告：3 步，未通过 0"));
+        assert!(summary.contains("#3 s2 [通过] score=1.0000"));
+
+        report.clear();
+        assert_eq!(report.len(), 0);
+    }
+
+    #[test]
+    fn save_html_records_path_in_summary() {
+        let dir = std::env::temp_dir().join(format!("dob-debug-report-{}", std::process::id()));
+        let path = dir.join("nested").join("report.html");
+        let mut report = DebugReport::new("save");
+        report.push_step(scored_step("a", 0.1, 0.2));
+        report.save_html(&path).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("#1 a"));
+        assert!(report.summary_text().contains("report.html"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,845 @@
+use opencv::{
+    calib3d,
+    core::{self, CV_8UC1, CV_32FC1, DMatch, KeyPoint, Mat, Point2f, Ptr, Size, Vector},
+    features2d, flann, imgcodecs, imgproc,
+    prelude::{
+        DescriptorMatcherTrait, DescriptorMatcherTraitConst, Feature2DTrait, KeyPointTraitConst,
+        MatTraitConst, MatTraitConstManual, MatTraitManual,
+    },
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::submodules::script_vision::{
+    _compress_bytes_zip_deflate, _decompress_bytes_zip_deflate, _to_gray_mat,
+};
+
+/// 特征库文件魔数
+const LIBRARY_MAGIC: &[u8; 4] = b"DFL1";
+/// 可入库的图片扩展名
+pub(crate) const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "bmp", "webp"];
+/// 短边低于该值时先放大再提取特征
+const SMALL_IMAGE_SIDE: i32 = 96;
+/// 小图放大后的目标短边
+const SMALL_IMAGE_TARGET_SIDE: f64 = 128.0;
+
+/// 特征算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FeatureKind {
+    /// ORB 二进制描述子（32 字节，汉明距离）
+    #[default]
+    Orb,
+    /// SIFT 浮点描述子（128 维，L2 距离）
+    Sift,
+}
+
+impl FeatureKind {
+    /// 从名称解析特征算法（大小写不敏感）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "orb" => Some(Self::Orb),
+            "sift" => Some(Self::Sift),
+            _ => None,
+        }
+    }
+
+    /// 算法名称
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Orb => "orb",
+            Self::Sift => "sift",
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            Self::Orb => 0,
+            Self::Sift => 1,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::Orb),
+            1 => Some(Self::Sift),
+            _ => None,
+        }
+    }
+
+    /// 描述子列数
+    pub(crate) fn descriptor_cols(self) -> i32 {
+        match self {
+            Self::Orb => 32,
+            Self::Sift => 128,
+        }
+    }
+
+    /// 描述子 Mat 类型
+    pub(crate) fn descriptor_type(self) -> i32 {
+        match self {
+            Self::Orb => CV_8UC1,
+            Self::Sift => CV_32FC1,
+        }
+    }
+
+    /// 单行描述子字节数
+    pub(crate) fn row_bytes(self) -> usize {
+        match self {
+            Self::Orb => 32,
+            Self::Sift => 128 * 4,
+        }
+    }
+
+    /// 描述子距离范数
+    pub(crate) fn norm_type(self) -> i32 {
+        match self {
+            Self::Orb => core::NORM_HAMMING,
+            Self::Sift => core::NORM_L2,
+        }
+    }
+}
+
+/// 识别参数
+#[derive(Debug, Clone, Copy)]
+pub struct IdentifyOptions {
+    /// 最多返回的标注数量
+    pub top_k: usize,
+    /// 进入几何校验的候选条目数量
+    pub candidates: usize,
+    /// 最少 RANSAC 内点数，低于该值的候选不返回
+    pub min_inliers: i32,
+    /// Lowe Ratio Test 阈值
+    pub ratio: f32,
+}
+
+impl Default for IdentifyOptions {
+    fn default() -> Self {
+        Self {
+            top_k: 5,
+            candidates: 8,
+            min_inliers: 8,
+            ratio: 0.75,
+        }
+    }
+}
+
+/// 识别结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureMatch {
+    /// 标注名称
+    pub label: String,
+    /// 单应性 RANSAC 内点数
+    pub inliers: i32,
+    /// 通过 Ratio Test 的匹配数
+    pub good_matches: i32,
+    /// 内点数占查询特征点数的比例（0-1）
+    pub score: f64,
+}
+
+/// 单个入库条目：关键点坐标 + 描述子
+struct FeatureEntry {
+    label: String,
+    points: Vec<Point2f>,
+    descriptors: Mat,
+}
+
+/// 持久化的 ORB/SIFT 特征库
+///
+/// 说明：
+/// - 同一标注可以有多个条目（例如子目录中的多张视角图），识别结果按标注去重；
+/// - 识别分两步：FLANN（ORB 使用 LSH 索引）在全库上投票筛出候选条目，
+///   再对候选逐一做 Ratio Test + 单应性 RANSAC，按内点数排序；
+/// - FLANN 匹配器在首次识别时训练，增删条目后自动失效重建。
+#[derive(Default)]
+pub struct FeatureLibrary {
+    kind: FeatureKind,
+    entries: Vec<FeatureEntry>,
+    matcher: Option<features2d::FlannBasedMatcher>,
+}
+
+impl fmt::Debug for FeatureLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeatureLibrary")
+            .field("kind", &self.kind)
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+/// 提取图像特征，返回原图坐标系下的关键点坐标与描述子
+///
+/// 说明：短边小于 96 的图像先放大到 128 再检测，坐标会换算回原图。
+pub fn detect_features(mat: &Mat, kind: FeatureKind) -> Result<(Vec<Point2f>, Mat), String> {
+    if mat.rows() <= 0 || mat.cols() <= 0 {
+        return Err("源图像尺寸无效".to_string());
+    }
+    let gray = _to_gray_mat(mat)?;
+    let short_side = gray.rows().min(gray.cols());
+    let (input, scale) = if short_side < SMALL_IMAGE_SIDE {
+        let scale = SMALL_IMAGE_TARGET_SIDE / short_side as f64;
+        let mut resized = Mat::default();
+        imgproc::resize(
+            &gray,
+            &mut resized,
+            Size::new(
+                ((gray.cols() as f64) * scale).round().max(1.0) as i32,
+                ((gray.rows() as f64) * scale).round().max(1.0) as i32,
+            ),
+            0.0,
+            0.0,
+            imgproc::INTER_LINEAR,
+        )
+        .map_err(|e| format!("特征小图放大失败: {e}"))?;
+        (resized, scale)
+    } else {
+        (gray, 1.0)
+    };
+
+    let mut keypoints = Vector::<KeyPoint>::new();
+    let mut descriptors = Mat::default();
+    match kind {
+        FeatureKind::Orb => features2d::ORB::create_def()
+            .map_err(|e| format!("创建 ORB 检测器失败: {e}"))?
+            .detect_and_compute(
+                &input,
+                &Mat::default(),
+                &mut keypoints,
+                &mut descriptors,
+                false,
+            )
+            .map_err(|e| format!("计算 ORB 特征失败: {e}"))?,
+        FeatureKind::Sift => features2d::SIFT::create_def()
+            .map_err(|e| format!("创建 SIFT 检测器失败: {e}"))?
+            .detect_and_compute(
+                &input,
+                &Mat::default(),
+                &mut keypoints,
+                &mut descriptors,
+                false,
+            )
+            .map_err(|e| format!("计算 SIFT 特征失败: {e}"))?,
+    }
+
+    if descriptors.empty() {
+        return Ok((Vec::new(), Mat::default()));
+    }
+    let inv = (1.0 / scale) as f32;
+    let points = keypoints
+        .iter()
+        .map(|kp| {
+            let pt = kp.pt();
+            Point2f::new(pt.x * inv, pt.y * inv)
+        })
+        .collect();
+    Ok((points, descriptors))
+}
+
+/// 收集目录中的图片并生成标注：根目录图片取文件名，子目录图片取子目录相对路径
+pub(crate) fn collect_labelled_images(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut images = Vec::new();
+    let mut pending = vec![(dir.to_path_buf(), None::<String>)];
+    while let Some((current, label)) = pending.pop() {
+        let entries = std::fs::read_dir(&current)
+            .map_err(|e| format!("读取目录失败 {}: {e}", current.display()))?;
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if path.is_dir() {
+                let sub_label = match &label {
+                    Some(parent) => format!("{parent}/{name}"),
+                    None => name,
+                };
+                pending.push((path, Some(sub_label)));
+                continue;
+            }
+            let is_image = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    IMAGE_EXTENSIONS
+                        .iter()
+                        .any(|allowed| ext.eq_ignore_ascii_case(allowed))
+                });
+            if !is_image {
+                continue;
+            }
+            let image_label = label.clone().unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+            images.push((image_label, path));
+        }
+    }
+    images.sort();
+    Ok(images)
+}
+
+/// 小端读取游标
+struct ByteReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| "特征库数据被截断".to_string())?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+}
+
+impl FeatureLibrary {
+    /// 创建空特征库
+    pub fn new(kind: FeatureKind) -> Self {
+        Self {
+            kind,
+            ..Self::default()
+        }
+    }
+
+    /// 特征算法
+    pub fn kind(&self) -> FeatureKind {
+        self.kind
+    }
+
+    /// 条目数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 去重后的标注列表（按入库顺序）
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = Vec::new();
+        for entry in &self.entries {
+            if !labels.contains(&entry.label) {
+                labels.push(entry.label.clone());
+            }
+        }
+        labels
+    }
+
+    /// 提取图像特征并加入特征库，返回关键点数量
+    ///
+    /// 说明：检测不到任何特征点时返回错误，避免入库无法识别的空条目。
+    pub fn add(&mut self, label: &str, mat: &Mat) -> Result<usize, String> {
+        let (points, descriptors) = detect_features(mat, self.kind)?;
+        if points.is_empty() {
+            return Err(format!("图像未检测到 {} 特征点: {label}", self.kind.name()));
+        }
+        let count = points.len();
+        self.entries.push(FeatureEntry {
+            label: label.to_string(),
+            points,
+            descriptors,
+        });
+        self.matcher = None;
+        Ok(count)
+    }
+
+    /// 从图片目录构建特征库
+    ///
+    /// # 参数
+    /// - `dir`: 图片目录；根目录图片以文件名为标注，子目录内图片以子目录相对路径（`/` 分隔）为标注
+    /// - `kind`: 特征算法
+    ///
+    /// 说明：检测不到特征点的图片会被跳过，返回值第二项为跳过的文件列表。
+    pub fn build_from_dir(dir: &Path, kind: FeatureKind) -> Result<(Self, Vec<String>), String> {
+        if !dir.is_dir() {
+            return Err(format!("不是目录: {}", dir.display()));
+        }
+        let images = collect_labelled_images(dir)?;
+        if images.is_empty() {
+            return Err(format!("目录中没有图片: {}", dir.display()));
+        }
+
+        let mut library = Self::new(kind);
+        let mut skipped = Vec::new();
+        for (label, path) in images {
+            let mat = imgcodecs::imread(&path.to_string_lossy(), imgcodecs::IMREAD_COLOR)
+                .map_err(|e| format!("读取图片失败 {}: {e}", path.display()))?;
+            if mat.empty() {
+                skipped.push(path.to_string_lossy().into_owned());
+                continue;
+            }
+            if library.add(&label, &mat).is_err() {
+                skipped.push(path.to_string_lossy().into_owned());
+            }
+        }
+        Ok((library, skipped))
+    }
+
+    /// 序列化为压缩字节流
+    ///
+    /// 说明：
+    /// - 压缩前格式：`"DFL1"[kind:u8][count:u32]`，随后每个条目为
+    ///   `[label_len:u16][label utf8][points:u32][x:f32,y:f32]*points[descriptor bytes]`；
+    /// - 整段字节流使用与 ORB 特征字符串相同的 ZIP(Deflate) 压缩。
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let row_bytes = self.kind.row_bytes();
+        let capacity: usize = self
+            .entries
+            .iter()
+            .map(|entry| 6 + entry.label.len() + entry.points.len() * (8 + row_bytes))
+            .sum();
+        let mut raw = Vec::with_capacity(9 + capacity);
+        raw.extend_from_slice(LIBRARY_MAGIC);
+        raw.push(self.kind.code());
+        raw.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for entry in &self.entries {
+            let label = entry.label.as_bytes();
+            if label.len() > u16::MAX as usize {
+                return Err(format!("标注过长: {}", entry.label));
+            }
+            raw.extend_from_slice(&(label.len() as u16).to_le_bytes());
+            raw.extend_from_slice(label);
+            raw.extend_from_slice(&(entry.points.len() as u32).to_le_bytes());
+            for point in &entry.points {
+                raw.extend_from_slice(&point.x.to_le_bytes());
+                raw.extend_from_slice(&point.y.to_le_bytes());
+            }
+            let bytes = entry
+                .descriptors
+                .data_bytes()
+                .map_err(|e| format!("读取描述子失败: {e}"))?;
+            if bytes.len() != entry.points.len() * row_bytes {
+                return Err(format!("描述子与关键点数量不一致: {}", entry.label));
+            }
+            raw.extend_from_slice(bytes);
+        }
+        _compress_bytes_zip_deflate(&raw)
+    }
+
+    /// 从压缩字节流反序列化
+    pub fn from_bytes(compressed: &[u8]) -> Result<Self, String> {
+        let raw = _decompress_bytes_zip_deflate(compressed)?;
+        let mut reader = ByteReader {
+            data: &raw,
+            offset: 0,
+        };
+        if reader.take(4)? != LIBRARY_MAGIC {
+            return Err("不是有效的特征库文件".to_string());
+        }
+        let code = reader.take(1)?[0];
+        let kind = FeatureKind::from_code(code).ok_or_else(|| format!("未知的特征算法: {code}"))?;
+        let count = reader.u32()? as usize;
+
+        let row_bytes = kind.row_bytes();
+        let mut library = Self::new(kind);
+        for _ in 0..count {
+            let label_len = reader.u16()? as usize;
+            let label = String::from_utf8(reader.take(label_len)?.to_vec())
+                .map_err(|e| format!("标注不是有效 UTF-8: {e}"))?;
+            let point_count = reader.u32()? as usize;
+            // 数量来自文件内容，分配前先确认剩余字节足以容纳全部坐标与描述子。
+            let rows = i32::try_from(point_count)
+                .ok()
+                .filter(|_| {
+                    point_count
+                        .checked_mul(8 + row_bytes)
+                        .is_some_and(|len| len <= reader.remaining())
+                })
+                .ok_or_else(|| format!("特征库条目关键点数量无效: {label} ({point_count})"))?;
+            let mut points = Vec::with_capacity(point_count);
+            for _ in 0..point_count {
+                points.push(Point2f::new(reader.f32()?, reader.f32()?));
+            }
+            let bytes = reader.take(point_count * row_bytes)?;
+            let mut descriptors = Mat::new_rows_cols_with_default(
+                rows,
+                kind.descriptor_cols(),
+                kind.descriptor_type(),
+                core::Scalar::all(0.0),
+            )
+            .map_err(|e| format!("创建描述子 Mat 失败: {e}"))?;
+            descriptors
+                .data_bytes_mut()
+                .map_err(|e| format!("写入描述子失败: {e}"))?
+                .copy_from_slice(bytes);
+            library.entries.push(FeatureEntry {
+                label,
+                points,
+                descriptors,
+            });
+        }
+        if reader.offset != raw.len() {
+            return Err("特征库数据末尾存在多余字节".to_string());
+        }
+        Ok(library)
+    }
+
+    /// 保存到文件
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {e}"))?;
+        }
+        std::fs::write(path, self.to_bytes()?)
+            .map_err(|e| format!("写入特征库失败 {}: {e}", path.display()))
+    }
+
+    /// 从文件加载
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes =
+            std::fs::read(path).map_err(|e| format!("读取特征库失败 {}: {e}", path.display()))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("解析特征库失败 {}: {e}", path.display()))
+    }
+
+    /// 按特征算法创建 FLANN 匹配器：ORB 使用 LSH 索引，SIFT 使用默认 KD 树
+    fn create_flann_matcher(kind: FeatureKind) -> Result<features2d::FlannBasedMatcher, String> {
+        match kind {
+            FeatureKind::Orb => {
+                let index_params = flann::LshIndexParams::new(6, 12, 1)
+                    .map_err(|e| format!("创建 LSH 索引参数失败: {e}"))?;
+                let search_params = flann::SearchParams::new_1(50, 0.0, true)
+                    .map_err(|e| format!("创建 FLANN 搜索参数失败: {e}"))?;
+                features2d::FlannBasedMatcher::new(
+                    &Ptr::new(flann::IndexParams::from(index_params)),
+                    &Ptr::new(search_params),
+                )
+                .map_err(|e| format!("创建 FLANN 匹配器失败: {e}"))
+            }
+            FeatureKind::Sift => features2d::FlannBasedMatcher::new_def()
+                .map_err(|e| format!("创建 FLANN 匹配器失败: {e}")),
+        }
+    }
+
+    /// 取得已训练的 FLANN 匹配器（条目变化后重建）
+    fn trained_matcher(&mut self) -> Result<&mut features2d::FlannBasedMatcher, String> {
+        if self.matcher.is_none() {
+            let mut matcher = Self::create_flann_matcher(self.kind)?;
+            let collection: Vector<Mat> = self
+                .entries
+                .iter()
+                .map(|entry| entry.descriptors.clone())
+                .collect();
+            matcher
+                .add(&collection)
+                .map_err(|e| format!("FLANN 添加描述子失败: {e}"))?;
+            matcher
+                .train()
+                .map_err(|e| format!("FLANN 训练失败: {e}"))?;
+            self.matcher = Some(matcher);
+        }
+        self.matcher
+            .as_mut()
+            .ok_or_else(|| "FLANN 匹配器未初始化".to_string())
+    }
+
+    /// 全库投票：统计每个条目获得的最近邻票数
+    ///
+    /// 说明：两个近邻来自同一条目时做 Ratio Test；来自不同条目时最近邻直接计票。
+    fn vote(&mut self, query: &Mat, ratio: f32) -> Result<Vec<u32>, String> {
+        let mut votes = vec![0u32; self.entries.len()];
+        let mut knn_matches = Vector::<Vector<DMatch>>::new();
+        self.trained_matcher()?
+            .knn_match_def(query, &mut knn_matches, 2)
+            .map_err(|e| format!("FLANN knn 匹配失败: {e}"))?;
+        for pair in knn_matches.iter() {
+            let Ok(first) = pair.get(0) else {
+                continue;
+            };
+            if first.img_idx < 0 || first.img_idx as usize >= votes.len() {
+                continue;
+            }
+            if let Ok(second) = pair.get(1)
+                && second.img_idx == first.img_idx
+                && first.distance >= second.distance * ratio
+            {
+                continue;
+            }
+            votes[first.img_idx as usize] += 1;
+        }
+        Ok(votes)
+    }
+
+    /// 对单个条目做 Ratio Test + 单应性 RANSAC，返回 (优质匹配数, 内点数)
+    fn verify(
+        &self,
+        entry: &FeatureEntry,
+        query_points: &[Point2f],
+        query_descriptors: &Mat,
+        ratio: f32,
+    ) -> Result<(i32, i32), String> {
+        let matcher = features2d::BFMatcher::create(self.kind.norm_type(), false)
+            .map_err(|e| format!("创建特征匹配器失败: {e}"))?;
+        let mut knn_matches = Vector::<Vector<DMatch>>::new();
+        matcher
+            .knn_train_match_def(query_descriptors, &entry.descriptors, &mut knn_matches, 2)
+            .map_err(|e| format!("特征 knn 匹配失败: {e}"))?;
+
+        let mut src_points = Vector::<Point2f>::new();
+        let mut dst_points = Vector::<Point2f>::new();
+        for pair in knn_matches.iter() {
+            let (Ok(first), Ok(second)) = (pair.get(0), pair.get(1)) else {
+                continue;
+            };
+            if first.distance >= second.distance * ratio {
+                continue;
+            }
+            let (Some(src), Some(dst)) = (
+                query_points.get(first.query_idx as usize),
+                entry.points.get(first.train_idx as usize),
+            ) else {
+                continue;
+            };
+            src_points.push(*src);
+            dst_points.push(*dst);
+        }
+
+        let good_matches = src_points.len() as i32;
+        if good_matches < 4 {
+            return Ok((good_matches, 0));
+        }
+        let mut inlier_mask = Mat::default();
+        let homography = calib3d::find_homography(
+            &src_points,
+            &dst_points,
+            &mut inlier_mask,
+            calib3d::RANSAC,
+            3.0,
+        )
+        .map_err(|e| format!("估计单应矩阵失败: {e}"))?;
+        if homography.empty() {
+            return Ok((good_matches, 0));
+        }
+        let inliers =
+            core::count_non_zero(&inlier_mask).map_err(|e| format!("统计内点失败: {e}"))?;
+        Ok((good_matches, inliers))
+    }
+
+    /// 识别查询图像，返回按内点数降序排列的标注
+    ///
+    /// # 参数
+    /// - `mat`: 查询图像（支持灰度/BGR/BGRA）
+    /// - `options`: 识别参数
+    ///
+    /// # 返回值
+    /// 每个标注只保留内点数最多的条目；内点数相同按优质匹配数、标注名排序。
+    pub fn identify(
+        &mut self,
+        mat: &Mat,
+        options: &IdentifyOptions,
+    ) -> Result<Vec<FeatureMatch>, String> {
+        if self.is_empty() || options.top_k == 0 {
+            return Ok(Vec::new());
+        }
+        let (query_points, query_descriptors) = detect_features(mat, self.kind)?;
+        if query_points.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let votes = self.vote(&query_descriptors, options.ratio)?;
+        let mut candidates: Vec<usize> = (0..self.entries.len())
+            .filter(|idx| votes[*idx] > 0)
+            .collect();
+        candidates.sort_by(|a, b| votes[*b].cmp(&votes[*a]).then(a.cmp(b)));
+        candidates.truncate(options.candidates.max(1));
+
+        let mut best: HashMap<&str, FeatureMatch> = HashMap::new();
+        for idx in candidates {
+            let entry = &self.entries[idx];
+            let (good_matches, inliers) =
+                self.verify(entry, &query_points, &query_descriptors, options.ratio)?;
+            if inliers < options.min_inliers.max(4) {
+                continue;
+            }
+            let candidate = FeatureMatch {
+                label: entry.label.clone(),
+                inliers,
+                good_matches,
+                score: (inliers as f64 / query_points.len() as f64).clamp(0.0, 1.0),
+            };
+            let replace = best.get(entry.label.as_str()).is_none_or(|current| {
+                (inliers, good_matches) > (current.inliers, current.good_matches)
+            });
+            if replace {
+                best.insert(entry.label.as_str(), candidate);
+            }
+        }
+
+        let mut results: Vec<FeatureMatch> = best.into_values().collect();
+        results.sort_by(|a, b| {
+            (b.inliers, b.good_matches)
+                .cmp(&(a.inliers, a.good_matches))
+                .then_with(|| a.label.cmp(&b.label))
+        });
+        results.truncate(options.top_k);
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submodules::test_support::ShapeTexture;
+    use opencv::core::Rect;
+
+    /// 240×180 测试图的纹理参数
+    const TEXTURE: ShapeTexture = ShapeTexture {
+        background: 40.0,
+        area_per_shape: 720,
+        rect_bound: (60, 45),
+        radius_bound: 30,
+        min_channel: 0,
+    };
+
+    fn sample_library(kind: FeatureKind) -> FeatureLibrary {
+        let mut library = FeatureLibrary::new(kind);
+        for (label, seed) in [("alpha", 1), ("beta", 2), ("gamma", 3)] {
+            library.add(label, &TEXTURE.render(seed, 240, 180)).unwrap();
+        }
+        library
+    }
+
+    /// 从测试图中裁出一块并放大，模拟不同尺度的查询
+    fn scaled_crop(mat: &Mat) -> Mat {
+        let roi = Mat::roi(mat, Rect::new(30, 20, 160, 120)).unwrap();
+        let mut scaled = Mat::default();
+        imgproc::resize(
+            &roi,
+            &mut scaled,
+            Size::new(200, 150),
+            0.0,
+            0.0,
+            imgproc::INTER_LINEAR,
+        )
+        .unwrap();
+        scaled
+    }
+
+    #[test]
+    fn identify_ranks_the_source_label_first() {
+        for kind in [FeatureKind::Orb, FeatureKind::Sift] {
+            let mut library = sample_library(kind);
+            let query = scaled_crop(&TEXTURE.render(2, 240, 180));
+            let results = library
+                .identify(&query, &IdentifyOptions::default())
+                .unwrap();
+            assert_eq!(
+                results.first().map(|hit| hit.label.as_str()),
+                Some("beta"),
+                "{kind:?}"
+            );
+            assert!(results[0].inliers >= 8);
+        }
+    }
+
+    #[test]
+    fn unrelated_query_has_no_confident_match() {
+        let mut library = sample_library(FeatureKind::Orb);
+        let query = TEXTURE.render(99, 240, 180);
+        let options = IdentifyOptions {
+            min_inliers: 25,
+            ..IdentifyOptions::default()
+        };
+        assert!(library.identify(&query, &options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bytes_round_trip_preserves_entries() {
+        for kind in [FeatureKind::Orb, FeatureKind::Sift] {
+            let library = sample_library(kind);
+            let mut loaded = FeatureLibrary::from_bytes(&library.to_bytes().unwrap()).unwrap();
+            assert_eq!(loaded.kind(), kind);
+            assert_eq!(loaded.labels(), library.labels());
+            for (a, b) in library.entries.iter().zip(&loaded.entries) {
+                assert_eq!(a.points, b.points);
+                assert_eq!(
+                    a.descriptors.data_bytes().unwrap(),
+                    b.descriptors.data_bytes().unwrap()
+                );
+            }
+            let query = scaled_crop(&TEXTURE.render(3, 240, 180));
+            let results = loaded
+                .identify(&query, &IdentifyOptions::default())
+                .unwrap();
+            assert_eq!(results[0].label, "gamma");
+        }
+        assert!(FeatureLibrary::from_bytes(b"not a library").is_err());
+    }
+
+    #[test]
+    fn from_bytes_rejects_oversized_point_counts() {
+        for point_count in [u32::MAX, 1_000_000] {
+            let mut raw = LIBRARY_MAGIC.to_vec();
+            raw.push(FeatureKind::Sift.code());
+            raw.extend_from_slice(&1u32.to_le_bytes());
+            raw.extend_from_slice(&1u16.to_le_bytes());
+            raw.push(b'x');
+            raw.extend_from_slice(&point_count.to_le_bytes());
+            raw.extend_from_slice(&[0u8; 64]);
+            let compressed = _compress_bytes_zip_deflate(&raw).unwrap();
+            let error = FeatureLibrary::from_bytes(&compressed).unwrap_err();
+            assert!(error.contains("关键点数量无效"), "{error}");
+        }
+    }
+
+    #[test]
+    fn build_from_dir_labels_files_and_subfolders() {
+        let dir = std::env::temp_dir().join(format!("dob-feature-lib-{}", std::process::id()));
+        let views = dir.join("views").join("boss");
+        std::fs::create_dir_all(&views).unwrap();
+        let write = |path: PathBuf, seed: u64| {
+            imgcodecs::imwrite(
+                &path.to_string_lossy(),
+                &TEXTURE.render(seed, 240, 180),
+                &Vector::new(),
+            )
+            .unwrap();
+        };
+        write(dir.join("door.png"), 5);
+        write(views.join("front.png"), 6);
+        write(views.join("side.jpg"), 7);
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let (library, skipped) = FeatureLibrary::build_from_dir(&dir, FeatureKind::Orb).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(library.len(), 3);
+        let mut labels = library.labels();
+        labels.sort();
+        assert_eq!(labels, vec!["door".to_string(), "views/boss".to_string()]);
+
+        let file = dir.join("out").join("lib.dfl");
+        library.save(&file).unwrap();
+        let mut loaded = FeatureLibrary::load(&file).unwrap();
+        let results = loaded
+            .identify(
+                &scaled_crop(&TEXTURE.render(7, 240, 180)),
+                &IdentifyOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(results[0].label, "views/boss");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
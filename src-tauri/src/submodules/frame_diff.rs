@@ -0,0 +1,315 @@
+use opencv::{
+    core::{self, BORDER_DEFAULT, Mat, Point, Rect, Size},
+    imgproc,
+    prelude::MatTraitConst,
+};
+
+use crate::submodules::script_vision::{_to_bgr_mat, BBoxResult};
+
+/// 帧差分参数
+#[derive(Debug, Clone, Copy)]
+pub struct FrameDiffOptions {
+    /// 像素变化阈值（0-255，按 BGR 各通道差值的最大值判断）
+    pub threshold: f64,
+    /// 比较前的高斯模糊核尺寸（<=1 表示不模糊，偶数自动加 1）
+    pub blur: i32,
+    /// 是否用 3x3 开运算去除孤立噪点
+    pub denoise: bool,
+    /// 合并相邻变化区域的距离（像素）
+    pub merge: i32,
+    /// 变化区域的最小像素数，小于该值的区域视为噪声
+    pub min_area: i32,
+}
+
+impl Default for FrameDiffOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 24.0,
+            blur: 3,
+            denoise: true,
+            merge: 4,
+            min_area: 16,
+        }
+    }
+}
+
+/// 帧变化统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameChange {
+    /// 变化区域外接框（按面积降序）
+    pub regions: Vec<BBoxResult>,
+    /// 变化区域内的变化像素数之和
+    pub changed_pixels: i32,
+    /// 变化像素占整帧的比例
+    pub ratio: f64,
+}
+
+/// 对图像做可选高斯模糊，用于抑制压缩噪声与抗锯齿抖动。
+fn _blur(mat: &Mat, ksize: i32) -> Result<Mat, String> {
+    if ksize <= 1 {
+        return Ok(mat.clone());
+    }
+    let ksize = ksize | 1;
+    let mut blurred = Mat::default();
+    imgproc::gaussian_blur(
+        mat,
+        &mut blurred,
+        Size::new(ksize, ksize),
+        0.0,
+        0.0,
+        BORDER_DEFAULT,
+    )
+    .map_err(|e| format!("高斯模糊失败: {e}"))?;
+    Ok(blurred)
+}
+
+/// 计算两帧之间的稳定差分掩码。
+///
+/// # 参数
+/// - `prev` / `curr`: 前后两帧（尺寸须一致，支持灰度/BGR/BGRA）
+/// - `options`: 阈值、模糊与去噪参数
+///
+/// # 返回值
+/// 与输入同尺寸的单通道掩码，变化像素为 255。
+///
+/// 说明：两帧先统一为 BGR 并模糊，再取各通道绝对差的最大值做阈值化，
+/// 避免仅色相变化的区域在灰度下被漏检。
+pub fn frame_diff_mask(prev: &Mat, curr: &Mat, options: &FrameDiffOptions) -> Result<Mat, String> {
+    if prev.rows() <= 0 || prev.cols() <= 0 {
+        return Err("前一帧图像尺寸无效".to_string());
+    }
+    if prev.rows() != curr.rows() || prev.cols() != curr.cols() {
+        return Err(format!(
+            "两帧尺寸不一致: {}x{} vs {}x{}",
+            prev.cols(),
+            prev.rows(),
+            curr.cols(),
+            curr.rows()
+        ));
+    }
+
+    let prev = _blur(&_to_bgr_mat(prev)?, options.blur)?;
+    let curr = _blur(&_to_bgr_mat(curr)?, options.blur)?;
+    let mut diff = Mat::default();
+    core::absdiff(&prev, &curr, &mut diff).map_err(|e| format!("帧差分失败: {e}"))?;
+
+    let mut channels = core::Vector::<Mat>::new();
+    core::split(&diff, &mut channels).map_err(|e| format!("拆分差分通道失败: {e}"))?;
+    let mut max_diff = channels
+        .get(0)
+        .map_err(|e| format!("读取差分通道失败: {e}"))?;
+    for idx in 1..channels.len() {
+        let channel = channels
+            .get(idx)
+            .map_err(|e| format!("读取差分通道失败: {e}"))?;
+        let mut merged = Mat::default();
+        core::max(&max_diff, &channel, &mut merged)
+            .map_err(|e| format!("合并差分通道失败: {e}"))?;
+        max_diff = merged;
+    }
+
+    let mut mask = Mat::default();
+    imgproc::threshold(
+        &max_diff,
+        &mut mask,
+        options.threshold.clamp(0.0, 254.0),
+        255.0,
+        imgproc::THRESH_BINARY,
+    )
+    .map_err(|e| format!("差分阈值化失败: {e}"))?;
+
+    if options.denoise {
+        let kernel = imgproc::get_structuring_element_def(imgproc::MORPH_RECT, Size::new(3, 3))
+            .map_err(|e| format!("创建去噪核失败: {e}"))?;
+        let mut opened = Mat::default();
+        imgproc::morphology_ex_def(&mask, &mut opened, imgproc::MORPH_OPEN, &kernel)
+            .map_err(|e| format!("差分去噪失败: {e}"))?;
+        mask = opened;
+    }
+    Ok(mask)
+}
+
+/// 从差分掩码中提取变化区域。
+///
+/// # 参数
+/// - `mask`: `frame_diff_mask` 输出的单通道掩码
+/// - `merge`: 合并相邻区域的距离（像素）
+/// - `min_area`: 区域最小变化像素数
+///
+/// # 返回值
+/// 变化统计；外接框紧贴实际变化像素（不含合并时的膨胀边距），按面积降序排列。
+pub fn changed_regions(mask: &Mat, merge: i32, min_area: i32) -> Result<FrameChange, String> {
+    if mask.rows() <= 0 || mask.cols() <= 0 {
+        return Err("差分掩码尺寸无效".to_string());
+    }
+
+    let grouped = if merge > 0 {
+        let size = merge * 2 + 1;
+        let kernel =
+            imgproc::get_structuring_element_def(imgproc::MORPH_RECT, Size::new(size, size))
+                .map_err(|e| format!("创建合并核失败: {e}"))?;
+        let mut dilated = Mat::default();
+        imgproc::dilate_def(mask, &mut dilated, &kernel)
+            .map_err(|e| format!("合并变化区域失败: {e}"))?;
+        dilated
+    } else {
+        mask.clone()
+    };
+
+    let mut contours = core::Vector::<core::Vector<Point>>::new();
+    imgproc::find_contours_def(
+        &grouped,
+        &mut contours,
+        imgproc::RETR_EXTERNAL,
+        imgproc::CHAIN_APPROX_SIMPLE,
+    )
+    .map_err(|e| format!("find_contours 失败: {e}"))?;
+
+    let mut regions: Vec<(i32, BBoxResult)> = Vec::with_capacity(contours.len());
+    for idx in 0..contours.len() {
+        let contour = contours
+            .get(idx)
+            .map_err(|e| format!("读取轮廓[{idx}] 失败: {e}"))?;
+        let outer = imgproc::bounding_rect(&contour)
+            .map_err(|e| format!("计算轮廓[{idx}] 外接矩形失败: {e}"))?;
+        let roi = Mat::roi(mask, outer).map_err(|e| format!("裁剪变化区域失败: {e}"))?;
+        let area = core::count_non_zero(&roi).map_err(|e| format!("统计变化像素失败: {e}"))?;
+        if area <= 0 || area < min_area {
+            continue;
+        }
+
+        let mut points = core::Vector::<Point>::new();
+        core::find_non_zero(&roi, &mut points).map_err(|e| format!("读取变化像素失败: {e}"))?;
+        let inner: Rect =
+            imgproc::bounding_rect(&points).map_err(|e| format!("计算变化外接框失败: {e}"))?;
+        regions.push((
+            area,
+            (
+                outer.x + inner.x,
+                outer.y + inner.y,
+                inner.width,
+                inner.height,
+            ),
+        ));
+    }
+    regions.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    let changed_pixels = regions.iter().map(|(area, _)| *area).sum::<i32>();
+    let total = mask.total() as f64;
+    Ok(FrameChange {
+        regions: regions.into_iter().map(|(_, bbox)| bbox).collect(),
+        changed_pixels,
+        ratio: changed_pixels as f64 / total,
+    })
+}
+
+/// 比较两帧并返回变化统计。
+pub fn frame_change(
+    prev: &Mat,
+    curr: &Mat,
+    options: &FrameDiffOptions,
+) -> Result<FrameChange, String> {
+    let mask = frame_diff_mask(prev, curr, options)?;
+    changed_regions(&mask, options.merge, options.min_area)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameDiffOptions, frame_change, frame_diff_mask};
+    use opencv::{
+        core::{self, CV_8UC3, Mat, Rect, Scalar},
+        imgproc,
+        prelude::MatTrait,
+    };
+
+    /// 构造带有固定纹理的测试帧。
+    fn textured_frame() -> Mat {
+        let mut mat = Mat::new_rows_cols_with_default(120, 160, CV_8UC3, Scalar::all(40.0))
+            .expect("创建测试图像失败");
+        for i in 0..8 {
+            imgproc::rectangle(
+                &mut mat,
+                Rect::new(10 + i * 18, 10 + (i % 3) * 30, 12, 12),
+                Scalar::new(200.0, 120.0, 60.0, 0.0),
+                -1,
+                imgproc::LINE_8,
+                0,
+            )
+            .expect("绘制纹理失败");
+        }
+        mat
+    }
+
+    #[test]
+    fn identical_frames_have_no_change() {
+        let frame = textured_frame();
+        let change = frame_change(&frame, &frame, &FrameDiffOptions::default()).expect("差分失败");
+        assert!(change.regions.is_empty());
+        assert_eq!(change.changed_pixels, 0);
+    }
+
+    #[test]
+    fn small_noise_is_ignored() {
+        let prev = textured_frame();
+        let mut curr = prev.clone();
+        // 全图轻微亮度抖动 + 若干孤立噪点
+        let mut shifted = Mat::default();
+        core::add(
+            &curr,
+            &Scalar::all(6.0),
+            &mut shifted,
+            &core::no_array(),
+            -1,
+        )
+        .expect("叠加亮度失败");
+        curr = shifted;
+        for (x, y) in [(5, 100), (80, 70), (150, 5)] {
+            *curr.at_2d_mut::<core::Vec3b>(y, x).expect("写入像素失败") =
+                core::Vec3b::from([255, 255, 255]);
+        }
+
+        let change = frame_change(&prev, &curr, &FrameDiffOptions::default()).expect("差分失败");
+        assert!(change.regions.is_empty(), "{:?}", change.regions);
+    }
+
+    #[test]
+    fn reports_changed_regions_with_tight_boxes() {
+        let prev = textured_frame();
+        let mut curr = prev.clone();
+        imgproc::rectangle(
+            &mut curr,
+            Rect::new(100, 80, 30, 20),
+            Scalar::new(0.0, 0.0, 255.0, 0.0),
+            -1,
+            imgproc::LINE_8,
+            0,
+        )
+        .expect("绘制变化区域失败");
+        imgproc::rectangle(
+            &mut curr,
+            Rect::new(20, 95, 8, 8),
+            Scalar::new(0.0, 255.0, 0.0, 0.0),
+            -1,
+            imgproc::LINE_8,
+            0,
+        )
+        .expect("绘制变化区域失败");
+
+        let options = FrameDiffOptions {
+            blur: 0,
+            ..FrameDiffOptions::default()
+        };
+        let change = frame_change(&prev, &curr, &options).expect("差分失败");
+        assert_eq!(change.regions, vec![(100, 80, 30, 20), (20, 95, 8, 8)]);
+        assert_eq!(change.changed_pixels, 30 * 20 + 8 * 8);
+        assert!((change.ratio - 664.0 / (160.0 * 120.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_mismatched_sizes() {
+        let prev = textured_frame();
+        let curr = Mat::new_rows_cols_with_default(60, 80, CV_8UC3, Scalar::all(0.0))
+            .expect("创建测试图像失败");
+        assert!(frame_diff_mask(&prev, &curr, &FrameDiffOptions::default()).is_err());
+    }
+}
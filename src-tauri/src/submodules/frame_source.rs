@@ -0,0 +1,521 @@
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicIsize, Ordering as AtomicOrdering},
+    },
+};
+
+use opencv::{
+    core::Mat,
+    imgcodecs,
+    prelude::{MatTraitConst, VideoCaptureTrait, VideoCaptureTraitConst},
+    videoio,
+};
+
+use crate::submodules::{feature_library::IMAGE_EXTENSIONS, script_vision::_to_bgr_mat};
+
+/// 虚拟窗口 id 的起点（负数，避免与真实 HWND 冲突），依次递减分配。
+const VIRTUAL_WINDOW_BASE: isize = -0x10000;
+
+/// 帧推进方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum FrameStep {
+    /// 每次截图返回当前帧后自动前进一帧
+    #[default]
+    PerCapture,
+    /// 仅在显式调用 `step` / `seek` 时切换帧，适合测试中逐帧断言
+    Manual,
+}
+
+impl FrameStep {
+    /// 解析推进方式名称：`capture`（默认）/ `manual`，不区分大小写
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "capture" | "percapture" | "per_capture" => Some(Self::PerCapture),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+}
+
+/// 帧来源
+enum SourceKind {
+    /// 图片序列（按文件名自然序）
+    Images(Vec<PathBuf>),
+    /// 视频文件；`decoded` 为最近一次解码的帧序号，顺序读取时无需重新定位
+    Video {
+        capture: videoio::VideoCapture,
+        decoded: Option<usize>,
+    },
+    /// 固定图像
+    Fixed,
+}
+
+/// 可替代真实窗口截图的帧源。
+///
+/// 说明：当前帧解码后缓存在 `current` 中，同一帧的重复截图不会重复读取文件。
+pub(crate) struct FrameSource {
+    kind: SourceKind,
+    len: usize,
+    position: usize,
+    step: FrameStep,
+    looping: bool,
+    current: Option<Mat>,
+}
+
+/// 帧源状态快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FrameSourceInfo {
+    /// 来源类型：`images` / `video` / `mat`
+    pub kind: &'static str,
+    /// 当前帧序号（从 0 开始）
+    pub index: usize,
+    /// 总帧数（视频无法获取帧数时为 0）
+    pub len: usize,
+}
+
+/// 按“自然序”比较文件名（`frame2` 排在 `frame10` 之前）。
+fn _natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let digits = |s: &[u8]| s.iter().take_while(|c| c.is_ascii_digit()).count();
+                let (da, db) = (digits(a), digits(b));
+                let trim = |s: &[u8]| -> usize { s.iter().take_while(|&&c| c == b'0').count() };
+                let (na, nb) = (
+                    &a[trim(&a[..da]).min(da)..da],
+                    &b[trim(&b[..db]).min(db)..db],
+                );
+                let ordering = na.len().cmp(&nb.len()).then_with(|| na.cmp(nb));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[da..];
+                b = &b[db..];
+            }
+            (Some(ca), Some(cb)) => {
+                let ordering = ca.to_ascii_lowercase().cmp(&cb.to_ascii_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[1..];
+                b = &b[1..];
+            }
+        }
+    }
+}
+
+impl FrameSource {
+    fn new(kind: SourceKind, len: usize, current: Option<Mat>) -> Self {
+        Self {
+            kind,
+            len,
+            position: 0,
+            step: FrameStep::default(),
+            looping: false,
+            current,
+        }
+    }
+
+    /// 从目录中的图片序列创建帧源（不递归，按文件名自然序）。
+    pub(crate) fn from_image_dir(dir: &Path) -> Result<Self, String> {
+        let entries =
+            std::fs::read_dir(dir).map_err(|e| format!("读取目录失败 {}: {e}", dir.display()))?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| {
+                            IMAGE_EXTENSIONS
+                                .iter()
+                                .any(|allowed| ext.eq_ignore_ascii_case(allowed))
+                        })
+            })
+            .collect();
+        if paths.is_empty() {
+            return Err(format!("目录中没有图片: {}", dir.display()));
+        }
+        paths.sort_by(|a, b| {
+            let name = |p: &PathBuf| {
+                p.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            _natural_cmp(&name(a), &name(b))
+        });
+        let len = paths.len();
+        Ok(Self::new(SourceKind::Images(paths), len, None))
+    }
+
+    /// 从视频文件创建帧源。
+    pub(crate) fn from_video(path: &Path) -> Result<Self, String> {
+        let path_str = path.to_string_lossy();
+        let capture = videoio::VideoCapture::from_file(&path_str, videoio::CAP_ANY)
+            .map_err(|e| format!("打开视频失败 {path_str}: {e}"))?;
+        if !capture
+            .is_opened()
+            .map_err(|e| format!("打开视频失败 {path_str}: {e}"))?
+        {
+            return Err(format!("无法打开视频: {path_str}"));
+        }
+        let len = capture
+            .get(videoio::CAP_PROP_FRAME_COUNT)
+            .map(|count| {
+                if count.is_finite() {
+                    count.max(0.0) as usize
+                } else {
+                    0
+                }
+            })
+            .unwrap_or(0);
+        Ok(Self::new(
+            SourceKind::Video {
+                capture,
+                decoded: None,
+            },
+            len,
+            None,
+        ))
+    }
+
+    /// 以固定图像创建帧源（灰度/BGRA 会转为 BGR，与窗口截图保持一致）。
+    pub(crate) fn from_mat(mat: &Mat) -> Result<Self, String> {
+        if mat.rows() <= 0 || mat.cols() <= 0 {
+            return Err("帧源图像尺寸无效".to_string());
+        }
+        Ok(Self::new(SourceKind::Fixed, 1, Some(_to_bgr_mat(mat)?)))
+    }
+
+    /// 设置推进方式与是否循环播放（到达末尾后回到第一帧；否则停留在最后一帧）。
+    pub(crate) fn with_playback(mut self, step: FrameStep, looping: bool) -> Self {
+        self.step = step;
+        self.looping = looping;
+        self
+    }
+
+    /// 当前状态快照。
+    pub(crate) fn info(&self) -> FrameSourceInfo {
+        let kind = match self.kind {
+            SourceKind::Images(_) => "images",
+            SourceKind::Video { .. } => "video",
+            SourceKind::Fixed => "mat",
+        };
+        FrameSourceInfo {
+            kind,
+            index: self.position,
+            len: self.len,
+        }
+    }
+
+    /// 跳转到指定帧；越界时循环模式取模，否则夹到最后一帧。返回实际帧序号。
+    pub(crate) fn seek(&mut self, index: usize) -> usize {
+        let target = match self.len {
+            // 帧数未知的视频不做夹取，是否越界由解码结果决定
+            0 => index,
+            len if self.looping => index % len,
+            len => index.min(len - 1),
+        };
+        if target != self.position && !matches!(self.kind, SourceKind::Fixed) {
+            self.position = target;
+            self.current = None;
+        }
+        self.position
+    }
+
+    /// 前进（或后退）`delta` 帧，返回实际帧序号。
+    pub(crate) fn step(&mut self, delta: i64) -> usize {
+        let target = if self.looping && self.len > 0 {
+            (self.position as i64 + delta).rem_euclid(self.len as i64) as usize
+        } else {
+            (self.position as i64 + delta).max(0) as usize
+        };
+        self.seek(target)
+    }
+
+    /// 解码当前帧（已缓存时直接返回）。
+    fn frame(&mut self) -> Result<&Mat, String> {
+        if self.current.is_none() {
+            let decoded = match &mut self.kind {
+                SourceKind::Images(paths) => {
+                    let path = &paths[self.position];
+                    let mat = imgcodecs::imread(&path.to_string_lossy(), imgcodecs::IMREAD_COLOR)
+                        .map_err(|e| format!("读取帧图片失败 {}: {e}", path.display()))?;
+                    if mat.rows() <= 0 || mat.cols() <= 0 {
+                        return Err(format!("无法解码帧图片: {}", path.display()));
+                    }
+                    mat
+                }
+                SourceKind::Video { capture, decoded } => {
+                    // 非顺序访问时按帧号重新定位
+                    if decoded.map_or(self.position != 0, |last| last + 1 != self.position) {
+                        capture
+                            .set(videoio::CAP_PROP_POS_FRAMES, self.position as f64)
+                            .map_err(|e| format!("视频定位失败: {e}"))?;
+                    }
+                    let mut mat = Mat::default();
+                    let ok = capture
+                        .read(&mut mat)
+                        .map_err(|e| format!("读取视频帧失败: {e}"))?;
+                    if !ok || mat.rows() <= 0 || mat.cols() <= 0 {
+                        *decoded = None;
+                        return Err(format!("视频没有第 {} 帧", self.position));
+                    }
+                    *decoded = Some(self.position);
+                    mat
+                }
+                SourceKind::Fixed => return Err("固定帧源缺少图像".to_string()),
+            };
+            self.current = Some(decoded);
+        }
+        self.current
+            .as_ref()
+            .ok_or_else(|| "帧源没有可用帧".to_string())
+    }
+
+    /// 取当前帧交给 `f` 处理，并按推进方式前进。
+    ///
+    /// 说明：帧数未知的视频读到末尾后，循环模式回到第一帧，否则停留在最后一次成功解码的帧。
+    pub(crate) fn capture<R>(&mut self, f: impl FnOnce(&Mat) -> R) -> Result<R, String> {
+        let result = match self.frame() {
+            Ok(frame) => f(frame),
+            Err(e) if self.len == 0 && self.position > 0 => {
+                if self.looping {
+                    self.seek(0);
+                } else {
+                    self.step(-1);
+                }
+                let frame = self.frame().map_err(|_| e)?;
+                f(frame)
+            }
+            Err(e) => return Err(e),
+        };
+        if self.step == FrameStep::PerCapture {
+            self.step(1);
+        }
+        Ok(result)
+    }
+}
+
+/// 已注册的虚拟窗口；每个帧源单独加锁，解码时不占用注册表锁
+fn _sources() -> &'static Mutex<HashMap<isize, Arc<Mutex<FrameSource>>>> {
+    static SOURCES: OnceLock<Mutex<HashMap<isize, Arc<Mutex<FrameSource>>>>> = OnceLock::new();
+    SOURCES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+static NEXT_VIRTUAL_WINDOW: AtomicIsize = AtomicIsize::new(VIRTUAL_WINDOW_BASE);
+
+thread_local! {
+    /// 当前线程脚本运行期间注册的虚拟窗口 id
+    static RUN_FRAME_SOURCES: RefCell<Vec<isize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 绑定当前线程虚拟窗口登记表的守卫。
+///
+/// 说明：离开作用域（脚本运行结束）时注销本次运行注册的全部虚拟窗口，不影响并行运行的其它脚本。
+pub(crate) struct FrameSourceRunGuard {
+    previous: Vec<isize>,
+}
+
+impl FrameSourceRunGuard {
+    /// 为当前线程启用新的虚拟窗口登记表。
+    pub(crate) fn enter() -> Self {
+        let previous = RUN_FRAME_SOURCES.with(|slot| std::mem::take(&mut *slot.borrow_mut()));
+        Self { previous }
+    }
+}
+
+impl Drop for FrameSourceRunGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.previous);
+        let ids = RUN_FRAME_SOURCES.with(|slot| slot.replace(previous));
+        if let Ok(mut sources) = _sources().lock() {
+            for id in ids {
+                sources.remove(&id);
+            }
+        }
+    }
+}
+
+/// 注册帧源并返回其虚拟窗口 id（可直接作为截图类函数的窗口句柄使用）。
+///
+/// 说明：脚本运行期间注册的帧源记入本次运行，运行结束时自动注销。
+pub(crate) fn register_frame_source(source: FrameSource) -> Result<isize, String> {
+    let id = NEXT_VIRTUAL_WINDOW.fetch_sub(1, AtomicOrdering::Relaxed);
+    _sources()
+        .lock()
+        .map_err(|e| format!("获取帧源锁失败: {e}"))?
+        .insert(id, Arc::new(Mutex::new(source)));
+    RUN_FRAME_SOURCES.with(|slot| slot.borrow_mut().push(id));
+    Ok(id)
+}
+
+/// 注销虚拟窗口，返回是否存在。
+pub(crate) fn close_frame_source(id: isize) -> bool {
+    RUN_FRAME_SOURCES.with(|slot| slot.borrow_mut().retain(|run_id| *run_id != id));
+    _sources()
+        .lock()
+        .is_ok_and(|mut sources| sources.remove(&id).is_some())
+}
+
+/// 对指定虚拟窗口执行操作；id 不是虚拟窗口时返回 `None`。
+///
+/// 说明：只在查找时持有注册表锁，`f`（解码、截图）仅锁定该帧源本身，不阻塞其它虚拟窗口。
+pub(crate) fn with_frame_source<R>(id: isize, f: impl FnOnce(&mut FrameSource) -> R) -> Option<R> {
+    if id > VIRTUAL_WINDOW_BASE {
+        return None;
+    }
+    let source = _sources().lock().ok()?.get(&id).cloned()?;
+    let mut source = source.lock().ok()?;
+    Some(f(&mut source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        _natural_cmp, FrameSource, FrameSourceRunGuard, FrameStep, close_frame_source,
+        register_frame_source, with_frame_source,
+    };
+    use opencv::{
+        core::{self, CV_8UC1, CV_8UC3, Mat, Scalar, Vector},
+        imgcodecs,
+        prelude::MatTraitConst,
+    };
+    use std::{cmp::Ordering, path::PathBuf};
+
+    /// 创建临时目录并写入亮度依次为 10/20/... 的帧图片（文件名刻意打乱自然序与字典序）。
+    fn write_frames(name: &str, count: usize) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("dob_frame_source_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("创建临时目录失败");
+        for idx in 0..count {
+            let mat = Mat::new_rows_cols_with_default(
+                6,
+                8,
+                CV_8UC3,
+                Scalar::all((idx + 1) as f64 * 10.0),
+            )
+            .expect("创建帧失败");
+            let path = dir.join(format!("frame{}.png", idx + 1));
+            imgcodecs::imwrite(&path.to_string_lossy(), &mat, &Vector::new()).expect("写入帧失败");
+        }
+        std::fs::write(dir.join("notes.txt"), "ignored").expect("写入文件失败");
+        dir
+    }
+
+    fn brightness(mat: &Mat) -> f64 {
+        core::mean_def(mat).expect("计算均值失败")[0].round()
+    }
+
+    #[test]
+    fn natural_order_sorts_numbers_by_value() {
+        assert_eq!(_natural_cmp("frame2.png", "frame10.png"), Ordering::Less);
+        assert_eq!(
+            _natural_cmp("frame010.png", "frame9.png"),
+            Ordering::Greater
+        );
+        assert_eq!(_natural_cmp("Frame1", "frame1"), Ordering::Equal);
+        assert_eq!(_natural_cmp("a", "a1"), Ordering::Less);
+    }
+
+    #[test]
+    fn image_sequence_steps_per_capture_and_holds_last_frame() {
+        let dir = write_frames("per_capture", 12);
+        let mut source = FrameSource::from_image_dir(&dir).expect("创建帧源失败");
+        assert_eq!(source.info().len, 12);
+
+        let seen: Vec<f64> = (0..14)
+            .map(|_| source.capture(brightness).expect("取帧失败"))
+            .collect();
+        let mut expected: Vec<f64> = (1..=12).map(|v| v as f64 * 10.0).collect();
+        expected.extend([120.0, 120.0]);
+        assert_eq!(seen, expected);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn manual_stepping_is_deterministic() {
+        let dir = write_frames("manual", 3);
+        let mut source = FrameSource::from_image_dir(&dir)
+            .expect("创建帧源失败")
+            .with_playback(FrameStep::Manual, true);
+
+        assert_eq!(source.capture(brightness).unwrap(), 10.0);
+        assert_eq!(source.capture(brightness).unwrap(), 10.0);
+        assert_eq!(source.step(2), 2);
+        assert_eq!(source.capture(brightness).unwrap(), 30.0);
+        // 循环模式下前进/后退均取模
+        assert_eq!(source.step(1), 0);
+        assert_eq!(source.step(-1), 2);
+        assert_eq!(source.seek(7), 1);
+        assert_eq!(source.capture(brightness).unwrap(), 20.0);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn fixed_mat_is_converted_to_bgr() {
+        let gray = Mat::new_rows_cols_with_default(4, 5, CV_8UC1, Scalar::all(77.0)).unwrap();
+        let mut source = FrameSource::from_mat(&gray).expect("创建帧源失败");
+        for _ in 0..3 {
+            let (channels, value) = source
+                .capture(|frame| (frame.channels(), brightness(frame)))
+                .unwrap();
+            assert_eq!((channels, value), (3, 77.0));
+        }
+        assert_eq!(source.info().index, 0);
+        assert!(FrameSource::from_mat(&Mat::default()).is_err());
+    }
+
+    #[test]
+    fn registry_assigns_virtual_ids() {
+        let mat = Mat::new_rows_cols_with_default(2, 2, CV_8UC3, Scalar::all(5.0)).unwrap();
+        let a = register_frame_source(FrameSource::from_mat(&mat).unwrap()).unwrap();
+        let b = register_frame_source(FrameSource::from_mat(&mat).unwrap()).unwrap();
+        assert!(a < 0 && b < a);
+        assert!(with_frame_source(a, |_| ()).is_some());
+        assert!(with_frame_source(0, |_| ()).is_none());
+        assert!(with_frame_source(0x1234, |_| ()).is_none());
+
+        let value = with_frame_source(b, |source| source.capture(brightness).unwrap());
+        assert_eq!(value, Some(5.0));
+        assert!(close_frame_source(b));
+        assert!(!close_frame_source(b));
+        assert!(with_frame_source(b, |source| source.info()).is_none());
+        assert!(close_frame_source(a));
+    }
+
+    #[test]
+    fn run_guard_closes_only_its_own_sources() {
+        let mat = Mat::new_rows_cols_with_default(2, 2, CV_8UC3, Scalar::all(5.0)).unwrap();
+        let outer = register_frame_source(FrameSource::from_mat(&mat).unwrap()).unwrap();
+        let inner = {
+            let _guard = FrameSourceRunGuard::enter();
+            let inner = register_frame_source(FrameSource::from_mat(&mat).unwrap()).unwrap();
+            // 其它线程（并行脚本）注册的帧源不受本次运行影响
+            let other = std::thread::spawn(move || {
+                register_frame_source(FrameSource::from_mat(&mat).unwrap()).unwrap()
+            })
+            .join()
+            .unwrap();
+            assert!(with_frame_source(inner, |_| ()).is_some());
+            assert!(close_frame_source(other));
+            inner
+        };
+        assert!(with_frame_source(inner, |_| ()).is_none());
+        assert!(with_frame_source(outer, |_| ()).is_some());
+        assert!(close_frame_source(outer));
+    }
+}
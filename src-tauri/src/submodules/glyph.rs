@@ -0,0 +1,512 @@
+use opencv::{
+    core::{self, CV_8UC1, Mat, Point, Rect, Scalar, Size},
+    imgcodecs, imgproc,
+    prelude::{MatTraitConst, MatTraitConstManual},
+};
+use std::path::Path;
+
+use crate::submodules::{
+    feature_library::collect_labelled_images,
+    script_vision::{_phash_gray_64, _to_gray_mat, BBoxResult, segment_single_line_chars_impl},
+};
+
+/// 归一化字形宽度
+const GLYPH_WIDTH: i32 = 24;
+/// 归一化字形高度
+const GLYPH_HEIGHT: i32 = 32;
+/// 归一化字形四周留白
+const GLYPH_PADDING: i32 = 2;
+
+/// 文件名中无法直接使用的字符别名（目录/文件名 -> 字形标注）
+const LABEL_ALIASES: [(&str, &str); 7] = [
+    ("colon", ":"),
+    ("dot", "."),
+    ("slash", "/"),
+    ("minus", "-"),
+    ("plus", "+"),
+    ("percent", "%"),
+    ("comma", ","),
+];
+
+/// 字形比对方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GlyphMethod {
+    /// 归一化互相关（零均值），对笔画粗细变化更敏感，默认
+    #[default]
+    Ncc,
+    /// 64 位感知哈希的汉明距离，对轻微模糊/缩放更稳定
+    Phash,
+}
+
+impl GlyphMethod {
+    /// 从名称解析比对方式（大小写不敏感）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "ncc" => Some(Self::Ncc),
+            "phash" => Some(Self::Phash),
+            _ => None,
+        }
+    }
+}
+
+/// 字形识别参数
+#[derive(Debug, Clone)]
+pub struct GlyphReadOptions {
+    /// 比对方式
+    pub method: GlyphMethod,
+    /// 低于该置信度的字符输出为 `unknown`
+    pub min_confidence: f64,
+    /// 字符分割的最小空隙宽度（像素列）
+    pub min_gap_width: i32,
+    /// 字符分割的最小字符宽度（像素）
+    pub min_char_width: i32,
+    /// 无法识别时的占位文本
+    pub unknown: String,
+}
+
+impl Default for GlyphReadOptions {
+    fn default() -> Self {
+        Self {
+            method: GlyphMethod::Ncc,
+            min_confidence: 0.6,
+            min_gap_width: 1,
+            min_char_width: 1,
+            unknown: "?".to_string(),
+        }
+    }
+}
+
+/// 单个字符的识别结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphChar {
+    /// 字形标注；置信度不足时为 `None`
+    pub label: Option<String>,
+    /// 最佳候选的置信度（0-1）
+    pub confidence: f64,
+    /// 字符外接框（输入图像坐标）
+    pub bbox: BBoxResult,
+}
+
+/// 整行识别结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphReading {
+    /// 识别文本（无法识别的字符以 `unknown` 占位）
+    pub text: String,
+    /// 各字符置信度的最小值；没有字符时为 0
+    pub confidence: f64,
+    /// 逐字符结果（从左到右）
+    pub chars: Vec<GlyphChar>,
+}
+
+/// 单个字形样本的特征
+#[derive(Debug, Clone)]
+struct GlyphSample {
+    label: String,
+    /// 零均值、单位范数的归一化像素
+    pixels: Vec<f32>,
+    hash: u64,
+}
+
+/// 由标注样本构建的轻量字形集，用于识别 HUD 数字、计时器等固定字体文本
+///
+/// 说明：
+/// - 样本与待识别字符都先二值化（背景由边框像素多数决定），再裁剪到前景外接框，
+///   按原始宽高比缩放并居中到 24x32 画布；
+/// - 同一标注可以有多个样本，识别时取各样本中的最高分。
+#[derive(Debug, Clone, Default)]
+pub struct GlyphSet {
+    samples: Vec<GlyphSample>,
+}
+
+/// 二值化字形图像，前景为白色。
+///
+/// 说明：先用 OTSU 阈值化，若边框像素多数为白色则取反，保证背景为黑色。
+fn _glyph_binary(gray: &Mat) -> Result<Mat, String> {
+    let mut binary = Mat::default();
+    imgproc::threshold(
+        gray,
+        &mut binary,
+        0.0,
+        255.0,
+        imgproc::THRESH_BINARY | imgproc::THRESH_OTSU,
+    )
+    .map_err(|e| format!("字形二值化失败: {e}"))?;
+
+    let (rows, cols) = (binary.rows(), binary.cols());
+    let mut border_white = 0;
+    let mut border_total = 0;
+    let edges = [
+        Rect::new(0, 0, cols, 1),
+        Rect::new(0, rows - 1, cols, 1),
+        Rect::new(0, 0, 1, rows),
+        Rect::new(cols - 1, 0, 1, rows),
+    ];
+    for edge in edges {
+        let roi = Mat::roi(&binary, edge).map_err(|e| format!("读取字形边框失败: {e}"))?;
+        border_white += core::count_non_zero(&roi).map_err(|e| format!("统计字形边框失败: {e}"))?;
+        border_total += edge.width * edge.height;
+    }
+    if border_white * 2 > border_total {
+        let mut inverted = Mat::default();
+        core::bitwise_not_def(&binary, &mut inverted)
+            .map_err(|e| format!("字形二值图取反失败: {e}"))?;
+        binary = inverted;
+    }
+    Ok(binary)
+}
+
+/// 将二值图中指定区域的前景裁剪并缩放到归一化画布，区域内没有前景时返回 `None`。
+fn _normalize_glyph(binary: &Mat, rect: Rect) -> Result<Option<Mat>, String> {
+    let region = Mat::roi(binary, rect).map_err(|e| format!("裁剪字形区域失败: {e}"))?;
+    let mut points = core::Vector::<Point>::new();
+    core::find_non_zero(&region, &mut points).map_err(|e| format!("读取字形前景失败: {e}"))?;
+    if points.is_empty() {
+        return Ok(None);
+    }
+    let bounds = imgproc::bounding_rect(&points).map_err(|e| format!("计算字形外接框失败: {e}"))?;
+    let glyph = Mat::roi(&region, bounds).map_err(|e| format!("裁剪字形前景失败: {e}"))?;
+
+    let inner_w = (GLYPH_WIDTH - GLYPH_PADDING * 2) as f64;
+    let inner_h = (GLYPH_HEIGHT - GLYPH_PADDING * 2) as f64;
+    let scale = (inner_w / bounds.width as f64).min(inner_h / bounds.height as f64);
+    let width = ((bounds.width as f64 * scale).round() as i32).clamp(1, inner_w as i32);
+    let height = ((bounds.height as f64 * scale).round() as i32).clamp(1, inner_h as i32);
+    let mut resized = Mat::default();
+    imgproc::resize(
+        &glyph,
+        &mut resized,
+        Size::new(width, height),
+        0.0,
+        0.0,
+        imgproc::INTER_AREA,
+    )
+    .map_err(|e| format!("字形缩放失败: {e}"))?;
+
+    let mut canvas =
+        Mat::new_rows_cols_with_default(GLYPH_HEIGHT, GLYPH_WIDTH, CV_8UC1, Scalar::all(0.0))
+            .map_err(|e| format!("创建字形画布失败: {e}"))?;
+    let target = Rect::new(
+        (GLYPH_WIDTH - width) / 2,
+        (GLYPH_HEIGHT - height) / 2,
+        width,
+        height,
+    );
+    let mut dst =
+        Mat::roi_mut(&mut canvas, target).map_err(|e| format!("定位字形画布失败: {e}"))?;
+    resized
+        .copy_to(&mut dst)
+        .map_err(|e| format!("写入字形画布失败: {e}"))?;
+    Ok(Some(canvas))
+}
+
+/// 从归一化字形提取比对特征。
+fn _glyph_sample(label: &str, normalized: &Mat) -> Result<GlyphSample, String> {
+    let bytes = normalized
+        .data_bytes()
+        .map_err(|e| format!("读取字形像素失败: {e}"))?;
+    let mean = bytes.iter().map(|&v| v as f32).sum::<f32>() / bytes.len() as f32;
+    let mut pixels: Vec<f32> = bytes.iter().map(|&v| v as f32 - mean).collect();
+    let norm = pixels.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > f32::EPSILON {
+        pixels.iter_mut().for_each(|v| *v /= norm);
+    }
+    Ok(GlyphSample {
+        label: label.to_string(),
+        pixels,
+        hash: _phash_gray_64(normalized)?,
+    })
+}
+
+/// 将目录名/文件名转换为字形标注。
+fn _label_from_name(name: &str) -> String {
+    LABEL_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map_or_else(|| name.to_string(), |(_, label)| label.to_string())
+}
+
+impl GlyphSample {
+    /// 与另一样本的相似度（0-1）。
+    fn similarity(&self, other: &GlyphSample, method: GlyphMethod) -> f64 {
+        match method {
+            GlyphMethod::Ncc => {
+                let dot: f32 = self
+                    .pixels
+                    .iter()
+                    .zip(&other.pixels)
+                    .map(|(a, b)| a * b)
+                    .sum();
+                (dot as f64).clamp(0.0, 1.0)
+            }
+            GlyphMethod::Phash => 1.0 - (self.hash ^ other.hash).count_ones() as f64 / 64.0,
+        }
+    }
+}
+
+impl GlyphSet {
+    /// 创建空字形集
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 样本数量
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// 是否没有样本
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// 去重后的标注列表（按首次加入顺序）
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = Vec::new();
+        for sample in &self.samples {
+            if !labels.contains(&sample.label) {
+                labels.push(sample.label.clone());
+            }
+        }
+        labels
+    }
+
+    /// 加入单个字形样本（整张图视为一个字符，建议保留少量背景边距）
+    pub fn add(&mut self, label: &str, mat: &Mat) -> Result<(), String> {
+        if label.is_empty() {
+            return Err("字形标注不能为空".to_string());
+        }
+        if mat.rows() <= 0 || mat.cols() <= 0 {
+            return Err("字形样本尺寸无效".to_string());
+        }
+        let binary = _glyph_binary(&_to_gray_mat(mat)?)?;
+        let normalized = _normalize_glyph(&binary, Rect::new(0, 0, mat.cols(), mat.rows()))?
+            .ok_or_else(|| format!("字形样本没有前景像素: {label}"))?;
+        self.samples.push(_glyph_sample(label, &normalized)?);
+        Ok(())
+    }
+
+    /// 从一行文本样本批量加入字形
+    ///
+    /// # 参数
+    /// - `mat`: 单行文本图像
+    /// - `text`: 图像中的文字（空白字符会被忽略），数量须与分割结果一致
+    /// - `min_gap_width` / `min_char_width`: 字符分割参数，同 `segmentChars`
+    ///
+    /// # 返回值
+    /// 加入的样本数量。
+    pub fn add_line(
+        &mut self,
+        mat: &Mat,
+        text: &str,
+        min_gap_width: i32,
+        min_char_width: i32,
+    ) -> Result<usize, String> {
+        let labels: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+        let bboxes = segment_single_line_chars_impl(mat, min_gap_width, min_char_width)?;
+        if bboxes.len() != labels.len() {
+            return Err(format!(
+                "分割得到 {} 个字符，与标注的 {} 个字符不一致",
+                bboxes.len(),
+                labels.len()
+            ));
+        }
+        let binary = _glyph_binary(&_to_gray_mat(mat)?)?;
+        let mut samples = Vec::with_capacity(labels.len());
+        for (label, (x, y, w, h)) in labels.iter().zip(bboxes) {
+            let label = label.to_string();
+            let normalized = _normalize_glyph(&binary, Rect::new(x, y, w, h))?
+                .ok_or_else(|| format!("字形样本没有前景像素: {label}"))?;
+            samples.push(_glyph_sample(&label, &normalized)?);
+        }
+        let added = samples.len();
+        self.samples.extend(samples);
+        Ok(added)
+    }
+
+    /// 从目录加载字形样本
+    ///
+    /// 说明：子目录名即标注（目录内每张图片为一个样本），根目录图片以文件名为标注；
+    /// 无法作为文件名的字符可用别名：`colon` `dot` `slash` `minus` `plus` `percent` `comma`。
+    ///
+    /// # 返回值
+    /// `(字形集, 无法加载的文件列表)`
+    pub fn load_dir(dir: &Path) -> Result<(Self, Vec<String>), String> {
+        if !dir.is_dir() {
+            return Err(format!("不是目录: {}", dir.display()));
+        }
+        let images = collect_labelled_images(dir)?;
+        if images.is_empty() {
+            return Err(format!("目录中没有图片: {}", dir.display()));
+        }
+
+        let mut set = Self::new();
+        let mut skipped = Vec::new();
+        for (label, path) in images {
+            let mat = imgcodecs::imread(&path.to_string_lossy(), imgcodecs::IMREAD_COLOR)
+                .map_err(|e| format!("读取图片失败 {}: {e}", path.display()))?;
+            if mat.empty() || set.add(&_label_from_name(&label), &mat).is_err() {
+                skipped.push(path.to_string_lossy().into_owned());
+            }
+        }
+        Ok((set, skipped))
+    }
+
+    /// 分割并识别单行文本
+    ///
+    /// # 参数
+    /// - `mat`: 单行文本图像（如 HUD 数字区域截图）
+    /// - `options`: 比对方式、置信度阈值与分割参数
+    /// - `accept`: 候选标注过滤器，返回 `false` 的样本不参与比对
+    pub fn read(
+        &self,
+        mat: &Mat,
+        options: &GlyphReadOptions,
+        accept: impl Fn(&str) -> bool,
+    ) -> Result<GlyphReading, String> {
+        if self.is_empty() {
+            return Err("字形集为空".to_string());
+        }
+        let candidates: Vec<&GlyphSample> = self
+            .samples
+            .iter()
+            .filter(|sample| accept(&sample.label))
+            .collect();
+        if candidates.is_empty() {
+            return Err("字形集中没有可用的候选字形".to_string());
+        }
+
+        let bboxes =
+            segment_single_line_chars_impl(mat, options.min_gap_width, options.min_char_width)?;
+        let binary = _glyph_binary(&_to_gray_mat(mat)?)?;
+        let mut text = String::new();
+        let mut chars = Vec::with_capacity(bboxes.len());
+        for (x, y, w, h) in bboxes {
+            let Some(normalized) = _normalize_glyph(&binary, Rect::new(x, y, w, h))? else {
+                continue;
+            };
+            let query = _glyph_sample("", &normalized)?;
+            let (best, confidence) = candidates
+                .iter()
+                .map(|sample| (*sample, query.similarity(sample, options.method)))
+                .fold((candidates[0], f64::MIN), |acc, item| {
+                    if item.1 > acc.1 { item } else { acc }
+                });
+            let label = (confidence >= options.min_confidence).then(|| best.label.clone());
+            text.push_str(label.as_deref().unwrap_or(&options.unknown));
+            chars.push(GlyphChar {
+                label,
+                confidence,
+                bbox: (x, y, w, h),
+            });
+        }
+
+        let confidence = chars
+            .iter()
+            .map(|c| c.confidence)
+            .reduce(f64::min)
+            .unwrap_or(0.0);
+        Ok(GlyphReading {
+            text,
+            confidence,
+            chars,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::CV_8UC3;
+
+    /// 按固定间距逐字绘制一行文本（深色背景、亮色文字）。
+    fn render_line(text: &str) -> Mat {
+        let width = 16 + text.chars().count() as i32 * 30;
+        let mut mat = Mat::new_rows_cols_with_default(48, width, CV_8UC3, Scalar::all(20.0))
+            .expect("创建测试图像失败");
+        for (i, ch) in text.chars().enumerate() {
+            imgproc::put_text(
+                &mut mat,
+                &ch.to_string(),
+                Point::new(10 + i as i32 * 30, 36),
+                imgproc::FONT_HERSHEY_SIMPLEX,
+                1.0,
+                Scalar::new(230.0, 230.0, 230.0, 0.0),
+                2,
+                imgproc::LINE_8,
+                false,
+            )
+            .expect("绘制文字失败");
+        }
+        mat
+    }
+
+    fn digit_set() -> GlyphSet {
+        let mut set = GlyphSet::new();
+        let added = set
+            .add_line(&render_line("0123456789"), "0123456789", 1, 1)
+            .expect("构建字形集失败");
+        assert_eq!(added, 10);
+        set
+    }
+
+    #[test]
+    fn reads_digits_with_both_methods() {
+        let set = digit_set();
+        let line = render_line("3071");
+        for method in [GlyphMethod::Ncc, GlyphMethod::Phash] {
+            let options = GlyphReadOptions {
+                method,
+                ..GlyphReadOptions::default()
+            };
+            let reading = set.read(&line, &options, |_| true).expect("识别失败");
+            assert_eq!(reading.text, "3071", "{method:?}");
+            assert_eq!(reading.chars.len(), 4);
+            assert!(
+                reading.confidence > 0.9,
+                "{method:?}: {}",
+                reading.confidence
+            );
+        }
+    }
+
+    #[test]
+    fn single_samples_match_line_samples() {
+        let mut set = GlyphSet::new();
+        for digit in ["4", "7"] {
+            set.add(digit, &render_line(digit)).expect("加入样本失败");
+        }
+        // 反色样本（浅底深字）应得到相同的归一化结果
+        let mut inverted = Mat::default();
+        core::bitwise_not_def(&render_line("47"), &mut inverted).expect("取反失败");
+        let reading = set
+            .read(&inverted, &GlyphReadOptions::default(), |_| true)
+            .expect("识别失败");
+        assert_eq!(reading.text, "47");
+        assert_eq!(set.labels(), vec!["4".to_string(), "7".to_string()]);
+    }
+
+    #[test]
+    fn filtered_candidates_and_unknown_placeholder() {
+        let set = digit_set();
+        let options = GlyphReadOptions {
+            min_confidence: 0.999,
+            unknown: "_".to_string(),
+            ..GlyphReadOptions::default()
+        };
+        // 只允许 "1"：其他数字置信度不足，输出占位符
+        let reading = set
+            .read(&render_line("18"), &options, |label| label == "1")
+            .expect("识别失败");
+        assert_eq!(reading.text, "1_");
+        assert_eq!(reading.chars[1].label, None);
+        assert!(set.read(&render_line("1"), &options, |_| false).is_err());
+    }
+
+    #[test]
+    fn add_line_rejects_label_count_mismatch() {
+        let mut set = GlyphSet::new();
+        assert!(set.add_line(&render_line("12"), "123", 1, 1).is_err());
+        assert!(set.is_empty());
+        assert_eq!(_label_from_name("Colon"), ":");
+    }
+}
@@ -0,0 +1,382 @@
+use serde_json::Value;
+use std::path::Path;
+
+use crate::submodules::script_vision::{ImageHashKind, normalize_hash_hex};
+
+/// 哈希索引查询结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashHit {
+    /// 标注名称
+    pub label: String,
+    /// 与查询哈希的汉明距离
+    pub distance: u32,
+}
+
+/// BK 树节点：相同哈希的多个标注合并到同一节点
+#[derive(Debug, Clone)]
+struct BkNode {
+    hash: Vec<u8>,
+    labels: Vec<String>,
+    /// (到子节点的距离, 子节点下标)
+    children: Vec<(u32, usize)>,
+}
+
+/// 基于 BK 树的感知哈希索引
+///
+/// 说明：
+/// - 汉明距离满足三角不等式，查询时只需访问距离落在 `[d - r, d + r]` 的子树；
+/// - 索引内所有哈希长度必须一致（同一种算法、同一种颜色模式）；
+/// - `kind` / `color` 记录哈希的计算方式，便于直接用图像查询。
+#[derive(Debug, Clone, Default)]
+pub struct HashIndex {
+    kind: ImageHashKind,
+    color: bool,
+    nodes: Vec<BkNode>,
+    label_count: usize,
+}
+
+/// 解析十六进制哈希为字节数组（支持 `0x` 前缀与大写）
+pub fn parse_hash_hex(hash: &str) -> Result<Vec<u8>, String> {
+    let normalized = normalize_hash_hex(hash)?;
+    (0..normalized.len())
+        .step_by(2)
+        .map(|idx| {
+            u8::from_str_radix(&normalized[idx..idx + 2], 16)
+                .map_err(|e| format!("解析哈希字节失败 {hash}: {e}"))
+        })
+        .collect()
+}
+
+/// 计算两个等长字节哈希的汉明距离
+fn hamming(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+impl HashIndex {
+    /// 创建空索引
+    pub fn new(kind: ImageHashKind, color: bool) -> Self {
+        Self {
+            kind,
+            color,
+            ..Default::default()
+        }
+    }
+
+    /// 哈希算法
+    pub fn kind(&self) -> ImageHashKind {
+        self.kind
+    }
+
+    /// 是否为彩色哈希
+    pub fn color(&self) -> bool {
+        self.color
+    }
+
+    /// 标注总数
+    pub fn len(&self) -> usize {
+        self.label_count
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.label_count == 0
+    }
+
+    /// 哈希字节长度（空索引返回 None）
+    pub fn hash_len(&self) -> Option<usize> {
+        self.nodes.first().map(|node| node.hash.len())
+    }
+
+    /// 插入一条标注
+    ///
+    /// # 参数
+    /// * `label` - 标注名称
+    /// * `hash` - 十六进制哈希
+    pub fn insert(&mut self, label: &str, hash: &str) -> Result<(), String> {
+        let hash = parse_hash_hex(hash)?;
+        self.insert_bytes(label, hash)
+    }
+
+    fn insert_bytes(&mut self, label: &str, hash: Vec<u8>) -> Result<(), String> {
+        if let Some(expected) = self.hash_len()
+            && expected != hash.len()
+        {
+            return Err(format!(
+                "哈希长度不一致: 索引为 {} 位，标注 {label} 为 {} 位",
+                expected * 8,
+                hash.len() * 8
+            ));
+        }
+        self.label_count += 1;
+        if self.nodes.is_empty() {
+            self.nodes.push(BkNode {
+                hash,
+                labels: vec![label.to_string()],
+                children: Vec::new(),
+            });
+            return Ok(());
+        }
+
+        let mut current = 0usize;
+        loop {
+            let distance = hamming(&self.nodes[current].hash, &hash);
+            if distance == 0 {
+                self.nodes[current].labels.push(label.to_string());
+                return Ok(());
+            }
+            match self.nodes[current]
+                .children
+                .iter()
+                .find(|(edge, _)| *edge == distance)
+            {
+                Some(&(_, child)) => current = child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(BkNode {
+                        hash,
+                        labels: vec![label.to_string()],
+                        children: Vec::new(),
+                    });
+                    self.nodes[current].children.push((distance, child));
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// 查询距离不超过 `max_distance` 的最近 `k` 个标注
+    ///
+    /// # 返回值
+    /// * 按距离升序（距离相同按标注名）排列的结果；长度不一致的哈希返回错误
+    pub fn query(&self, hash: &str, k: usize, max_distance: u32) -> Result<Vec<HashHit>, String> {
+        let hash = parse_hash_hex(hash)?;
+        if let Some(expected) = self.hash_len()
+            && expected != hash.len()
+        {
+            return Err(format!(
+                "哈希长度不一致: 索引为 {} 位，查询为 {} 位",
+                expected * 8,
+                hash.len() * 8
+            ));
+        }
+        Ok(self.query_bytes(&hash, k, max_distance))
+    }
+
+    /// 按字节哈希查询，参见 [`HashIndex::query`]
+    pub fn query_bytes(&self, hash: &[u8], k: usize, max_distance: u32) -> Vec<HashHit> {
+        let mut hits: Vec<HashHit> = Vec::new();
+        if k == 0 || self.is_empty() {
+            return hits;
+        }
+
+        let mut stack = vec![0usize];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let distance = hamming(&node.hash, hash);
+            // 结果已满时只需关注不比当前最差结果更远的节点
+            let radius = if hits.len() >= k {
+                max_distance.min(hits[k - 1].distance)
+            } else {
+                max_distance
+            };
+
+            if distance <= radius {
+                for label in &node.labels {
+                    let hit = HashHit {
+                        label: label.clone(),
+                        distance,
+                    };
+                    let pos = hits.partition_point(|h| {
+                        (h.distance, h.label.as_str()) <= (hit.distance, hit.label.as_str())
+                    });
+                    if pos < k {
+                        hits.insert(pos, hit);
+                        hits.truncate(k);
+                    }
+                }
+            }
+
+            let low = distance.saturating_sub(radius);
+            let high = distance.saturating_add(radius);
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|(edge, _)| *edge >= low && *edge <= high)
+                    .map(|&(_, child)| child),
+            );
+        }
+        hits
+    }
+
+    /// 从标注 JSON 文本构建索引
+    ///
+    /// 支持以下格式：
+    /// - `{ "label": "hash" }` 或 `{ "label": ["hash1", "hash2"] }`
+    /// - `[{ "label": "...", "hash": "..." }]`（`hash` 也可为数组）
+    /// - `{ "kind": "dhash", "color": false, "entries": <以上任一格式> }`
+    pub fn from_json_str(text: &str) -> Result<Self, String> {
+        let root: Value =
+            serde_json::from_str(text).map_err(|e| format!("解析哈希索引 JSON 失败: {e}"))?;
+
+        let (kind, color, entries) = match root.get("entries") {
+            Some(entries) => {
+                let kind = match root.get("kind").and_then(Value::as_str) {
+                    Some(name) => ImageHashKind::from_name(name)
+                        .ok_or_else(|| format!("未知的哈希算法: {name}"))?,
+                    None => ImageHashKind::default(),
+                };
+                let color = root.get("color").and_then(Value::as_bool).unwrap_or(false);
+                (kind, color, entries)
+            }
+            None => (ImageHashKind::default(), false, &root),
+        };
+
+        let mut index = Self::new(kind, color);
+        let mut add = |label: &str, hashes: &Value| -> Result<(), String> {
+            match hashes {
+                Value::String(hash) => index.insert(label, hash),
+                Value::Array(items) => items.iter().try_for_each(|item| {
+                    let hash = item
+                        .as_str()
+                        .ok_or_else(|| format!("标注 {label} 的哈希必须是字符串"))?;
+                    index.insert(label, hash)
+                }),
+                _ => Err(format!("标注 {label} 的哈希必须是字符串或字符串数组")),
+            }
+        };
+
+        match entries {
+            Value::Object(map) => {
+                for (label, hashes) in map {
+                    add(label, hashes)?;
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    let label = item
+                        .get("label")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| format!("entries[{idx}] 缺少 label"))?;
+                    let hashes = item
+                        .get("hash")
+                        .or_else(|| item.get("hashes"))
+                        .ok_or_else(|| format!("entries[{idx}] 缺少 hash"))?;
+                    add(label, hashes)?;
+                }
+            }
+            _ => return Err("哈希索引 entries 必须是对象或数组".to_string()),
+        }
+        Ok(index)
+    }
+
+    /// 从标注 JSON 文件构建索引，格式见 [`HashIndex::from_json_str`]
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("读取哈希索引文件失败 {}: {e}", path.display()))?;
+        Self::from_json_str(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submodules::test_support::Lcg64;
+
+    /// 线性同余生成的确定性 64 位哈希
+    fn random_hashes(count: usize, seed: u64) -> Vec<String> {
+        let mut rng = Lcg64::new(seed);
+        (0..count)
+            .map(|_| format!("{:016x}", rng.next_u64()))
+            .collect()
+    }
+
+    fn brute_force(entries: &[(String, String)], query: &str, k: usize, max: u32) -> Vec<HashHit> {
+        let query = parse_hash_hex(query).unwrap();
+        let mut hits: Vec<HashHit> = entries
+            .iter()
+            .map(|(label, hash)| HashHit {
+                label: label.clone(),
+                distance: hamming(&parse_hash_hex(hash).unwrap(), &query),
+            })
+            .filter(|hit| hit.distance <= max)
+            .collect();
+        hits.sort_by(|a, b| (a.distance, &a.label).cmp(&(b.distance, &b.label)));
+        hits.truncate(k);
+        hits
+    }
+
+    #[test]
+    fn knn_matches_brute_force() {
+        let hashes = random_hashes(500, 17);
+        let entries: Vec<(String, String)> = hashes
+            .iter()
+            .enumerate()
+            .map(|(idx, hash)| (format!("item{idx:03}"), hash.clone()))
+            .collect();
+        let mut index = HashIndex::new(ImageHashKind::PHash, false);
+        for (label, hash) in &entries {
+            index.insert(label, hash).unwrap();
+        }
+        assert_eq!(index.len(), 500);
+
+        for query in random_hashes(20, 99).iter().chain(hashes.iter().take(5)) {
+            for (k, max) in [(1, 64), (5, 28), (10, 24), (3, 0)] {
+                assert_eq!(
+                    index.query(query, k, max).unwrap(),
+                    brute_force(&entries, query, k, max)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_hashes_keep_every_label() {
+        let mut index = HashIndex::new(ImageHashKind::AHash, false);
+        index.insert("a", "ff00ff00ff00ff00").unwrap();
+        index.insert("b", "0xFF00FF00FF00FF00").unwrap();
+        index.insert("c", "ff00ff00ff00ff01").unwrap();
+        let hits = index.query("ff00ff00ff00ff00", 10, 1).unwrap();
+        let labels: Vec<_> = hits
+            .iter()
+            .map(|h| (h.label.as_str(), h.distance))
+            .collect();
+        assert_eq!(labels, vec![("a", 0), ("b", 0), ("c", 1)]);
+
+        assert!(index.insert("short", "ff00").is_err());
+        assert!(index.query("ff00", 1, 8).is_err());
+    }
+
+    #[test]
+    fn parses_all_json_layouts() {
+        let map = HashIndex::from_json_str(r#"{ "sword": "00000000000000ff", "shield": ["ffffffffffffffff", "fffffffffffffff0"] }"#)
+            .unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.kind(), ImageHashKind::PHash);
+
+        let list = HashIndex::from_json_str(
+            r#"[{ "label": "sword", "hash": "00000000000000ff" }, { "label": "bow", "hashes": ["0f0f0f0f0f0f0f0f"] }]"#,
+        )
+        .unwrap();
+        assert_eq!(list.len(), 2);
+
+        let wrapped = HashIndex::from_json_str(
+            r#"{ "kind": "dhash", "color": true, "entries": { "sword": "00000000000000ff00000000000000ff00000000000000ff" } }"#,
+        )
+        .unwrap();
+        assert_eq!(wrapped.kind(), ImageHashKind::DHash);
+        assert!(wrapped.color());
+        assert_eq!(wrapped.hash_len(), Some(24));
+
+        let hits = list.query("00000000000000fe", 2, 8).unwrap();
+        assert_eq!(
+            hits,
+            vec![HashHit {
+                label: "sword".to_string(),
+                distance: 1
+            }]
+        );
+
+        assert!(HashIndex::from_json_str(r#"{ "kind": "xhash", "entries": {} }"#).is_err());
+        assert!(HashIndex::from_json_str(r#"{ "sword": 1 }"#).is_err());
+    }
+}
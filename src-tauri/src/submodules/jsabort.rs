@@ -0,0 +1,741 @@
+use boa_engine::{
+    Context, Finalize, JsArgs, JsData, JsNativeError, JsObject, JsResult, JsValue, Trace,
+    class::{Class, ClassBuilder},
+    job::NativeAsyncJob,
+    js_string,
+    native_function::NativeFunction,
+    object::{ObjectInitializer, builtins::JsFunction},
+    property::Attribute,
+};
+use boa_gc::GcRefCell;
+use std::cell::Cell;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// 默认中止原因的错误消息。
+const ABORT_ERROR_MESSAGE: &str = "This operation was aborted";
+/// 超时中止原因的错误消息。
+const TIMEOUT_ERROR_MESSAGE: &str = "The operation timed out";
+
+/// 取消令牌内部状态。
+#[derive(Debug, Default)]
+struct AbortTokenInner {
+    aborted: AtomicBool,
+    deadline: Option<Instant>,
+    notify: Notify,
+    children: Mutex<Vec<Weak<AbortTokenInner>>>,
+}
+
+impl AbortTokenInner {
+    /// 锁定子令牌列表；锁中毒时沿用内部数据，避免级联中止因其它线程 panic 而失效。
+    fn children(&self) -> MutexGuard<'_, Vec<Weak<AbortTokenInner>>> {
+        self.children.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn abort(&self) {
+        if self.aborted.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.notify.notify_waiters();
+        let children = std::mem::take(&mut *self.children());
+        for child in children.iter().filter_map(Weak::upgrade) {
+            child.abort();
+        }
+    }
+}
+
+/// 跨线程共享的取消令牌（`AbortSignal` 在 Rust 侧的状态）。
+///
+/// 说明：
+/// - 可在阻塞线程中轮询 `is_aborted`，也可在异步任务中等待 `cancelled`；
+/// - 带截止时间的令牌到期即视为已中止，无需额外定时任务。
+#[derive(Debug, Clone, Default)]
+pub struct AbortToken {
+    inner: Arc<AbortTokenInner>,
+}
+
+impl AbortToken {
+    /// 创建未中止的令牌。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建在 `timeout` 后自动中止的令牌。
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            inner: Arc::new(AbortTokenInner {
+                deadline: Some(Instant::now() + timeout),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// 创建跟随任一父令牌中止的令牌。
+    ///
+    /// 说明：登记到父令牌时顺带清理已释放或已中止的子令牌，长期存活的父令牌不会无限累积。
+    pub fn linked(parents: &[AbortToken]) -> Self {
+        let token = Self {
+            inner: Arc::new(AbortTokenInner {
+                deadline: parents.iter().filter_map(|p| p.inner.deadline).min(),
+                ..Default::default()
+            }),
+        };
+        for parent in parents {
+            {
+                let mut children = parent.inner.children();
+                children.retain(|child| {
+                    child
+                        .upgrade()
+                        .is_some_and(|child| !child.aborted.load(Ordering::SeqCst))
+                });
+                children.push(Arc::downgrade(&token.inner));
+            }
+            if parent.inner.aborted.load(Ordering::SeqCst) {
+                token.abort();
+            }
+        }
+        token
+    }
+
+    /// 中止令牌（重复调用无副作用），并级联中止子令牌。
+    pub fn abort(&self) {
+        self.inner.abort();
+    }
+
+    /// 是否已超过截止时间。
+    pub fn timed_out(&self) -> bool {
+        self.inner
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// 是否已中止（显式中止或已超时）。
+    pub fn is_aborted(&self) -> bool {
+        self.inner.aborted.load(Ordering::SeqCst) || self.timed_out()
+    }
+
+    /// 等待令牌中止。
+    pub async fn cancelled(&self) {
+        let notified = self.inner.notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        if self.is_aborted() {
+            return;
+        }
+        match self.inner.deadline {
+            Some(deadline) => {
+                tokio::select! {
+                    _ = notified => {}
+                    _ = tokio::time::sleep_until(deadline.into()) => {}
+                }
+            }
+            None => notified.await,
+        }
+    }
+}
+
+/// 在令牌中止前等待 `future` 完成。
+///
+/// # 返回
+/// - `Some(output)`：任务先完成（未提供令牌时总是如此）
+/// - `None`：令牌先中止，`future` 被丢弃
+pub(crate) async fn run_abortable<F: Future>(
+    token: Option<&AbortToken>,
+    future: F,
+) -> Option<F::Output> {
+    match token {
+        None => Some(future.await),
+        Some(token) => {
+            tokio::select! {
+                biased;
+                _ = token.cancelled() => None,
+                output = future => Some(output),
+            }
+        }
+    }
+}
+
+/// 构造带 `name` 的 Error 对象（用于 `AbortError`/`TimeoutError`）。
+pub(crate) fn named_error(name: &str, message: &str, context: &mut Context) -> JsValue {
+    let error = JsNativeError::error()
+        .with_message(message.to_string())
+        .to_opaque(context);
+    // 新建错误对象上定义自有属性不会失败。
+    let _ = error.set(js_string!("name"), js_string!(name), false, context);
+    error.into()
+}
+
+/// 定义 AbortSignal 类。
+#[derive(Trace, Finalize, JsData)]
+pub struct JsAbortSignal {
+    #[unsafe_ignore_trace]
+    pub(crate) token: AbortToken,
+    /// 中止原因（未中止时为 `undefined`）。
+    reason: GcRefCell<JsValue>,
+    /// `abort` 事件监听器。
+    listeners: GcRefCell<Vec<JsObject>>,
+    /// 由 `AbortSignal.any` 派生、需跟随中止的信号（以 `WeakRef` 持有，不阻止派生信号被回收）。
+    dependents: GcRefCell<Vec<JsObject>>,
+    /// 是否已派发过 `abort` 事件。
+    #[unsafe_ignore_trace]
+    dispatched: Cell<bool>,
+    /// 是否已为超时信号安排事件派发任务。
+    #[unsafe_ignore_trace]
+    timer_scheduled: Cell<bool>,
+}
+
+impl JsAbortSignal {
+    fn new(token: AbortToken) -> Self {
+        Self {
+            token,
+            reason: GcRefCell::new(JsValue::undefined()),
+            listeners: GcRefCell::new(Vec::new()),
+            dependents: GcRefCell::new(Vec::new()),
+            dispatched: Cell::new(false),
+            timer_scheduled: Cell::new(false),
+        }
+    }
+
+    /// 创建绑定指定令牌的 AbortSignal 对象。
+    fn create(token: AbortToken, context: &mut Context) -> JsResult<JsObject> {
+        Self::from_data(Self::new(token), context)
+    }
+}
+
+/// 将 `this` 视为 AbortSignal 对象。
+fn this_signal(this: &JsValue) -> JsResult<JsObject> {
+    this.as_object()
+        .filter(|obj| obj.is::<JsAbortSignal>())
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Object is not an AbortSignal")
+                .into()
+        })
+}
+
+/// 创建指向 `target` 的内置 `WeakRef` 对象。
+fn weak_ref(target: &JsObject, context: &mut Context) -> JsResult<JsObject> {
+    context
+        .intrinsics()
+        .constructors()
+        .weak_ref()
+        .constructor()
+        .construct(&[target.clone().into()], None, context)
+}
+
+/// 取出 `WeakRef` 指向的对象，目标已被回收时返回 `None`。
+fn deref_weak(weak: &JsObject, context: &mut Context) -> JsResult<Option<JsObject>> {
+    let deref = weak.get(js_string!("deref"), context)?;
+    let Some(deref) = deref.as_callable() else {
+        return Ok(None);
+    };
+    Ok(deref.call(&weak.clone().into(), &[], context)?.as_object())
+}
+
+/// 中止信号：记录原因、中止令牌，并派发 `abort` 事件（含派生信号）。
+///
+/// 说明：已派发过事件的信号直接忽略；监听器抛出的首个异常在全部派发后返回。
+fn abort_signal(signal: &JsObject, reason: JsValue, context: &mut Context) -> JsResult<()> {
+    let (listeners, dependents) = {
+        let data = signal
+            .downcast_ref::<JsAbortSignal>()
+            .ok_or_else(|| JsNativeError::typ().with_message("Object is not an AbortSignal"))?;
+        if data.dispatched.replace(true) {
+            return Ok(());
+        }
+        *data.reason.borrow_mut() = reason.clone();
+        data.token.abort();
+        let listeners = std::mem::take(&mut *data.listeners.borrow_mut());
+        let dependents = std::mem::take(&mut *data.dependents.borrow_mut());
+        (listeners, dependents)
+    };
+
+    let event = ObjectInitializer::new(context)
+        .property(js_string!("type"), js_string!("abort"), Attribute::all())
+        .property(js_string!("target"), signal.clone(), Attribute::all())
+        .build();
+    let this = JsValue::from(signal.clone());
+    let mut first_error = None;
+    let handler = signal.get(js_string!("onabort"), context)?;
+    if let Some(handler) = handler.as_callable()
+        && let Err(e) = handler.call(&this, &[event.clone().into()], context)
+    {
+        first_error.get_or_insert(e);
+    }
+    for listener in listeners {
+        if let Err(e) = listener.call(&this, &[event.clone().into()], context) {
+            first_error.get_or_insert(e);
+        }
+    }
+    for dependent in dependents {
+        let dependent = match deref_weak(&dependent, context) {
+            Ok(Some(dependent)) => dependent,
+            Ok(None) => continue,
+            Err(e) => {
+                first_error.get_or_insert(e);
+                continue;
+            }
+        };
+        if let Err(e) = abort_signal(&dependent, reason.clone(), context) {
+            first_error.get_or_insert(e);
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+/// 同步信号状态：令牌已在 Rust 侧中止（如超时）但尚未派发事件时补发。
+///
+/// # 返回
+/// 信号是否已中止
+fn sync_signal(signal: &JsObject, context: &mut Context) -> JsResult<bool> {
+    let (dispatched, aborted, timed_out) = {
+        let data = signal
+            .downcast_ref::<JsAbortSignal>()
+            .ok_or_else(|| JsNativeError::typ().with_message("Object is not an AbortSignal"))?;
+        (
+            data.dispatched.get(),
+            data.token.is_aborted(),
+            data.token.timed_out(),
+        )
+    };
+    if dispatched {
+        return Ok(true);
+    }
+    if !aborted {
+        return Ok(false);
+    }
+    let reason = if timed_out {
+        named_error("TimeoutError", TIMEOUT_ERROR_MESSAGE, context)
+    } else {
+        named_error("AbortError", ABORT_ERROR_MESSAGE, context)
+    };
+    abort_signal(signal, reason, context)?;
+    Ok(true)
+}
+
+/// 读取信号的中止原因（会先同步超时状态）。
+fn signal_reason(signal: &JsObject, context: &mut Context) -> JsResult<JsValue> {
+    sync_signal(signal, context)?;
+    let data = signal
+        .downcast_ref::<JsAbortSignal>()
+        .ok_or_else(|| JsNativeError::typ().with_message("Object is not an AbortSignal"))?;
+    Ok(data.reason.borrow().clone())
+}
+
+/// 为带截止时间的信号安排一次事件派发，使监听器在超时时得到通知。
+///
+/// 说明：仅在注册监听器时安排，避免未被监听的 `AbortSignal.timeout` 拖住脚本退出。
+fn schedule_timeout_dispatch(signal: &JsObject, context: &mut Context) -> JsResult<()> {
+    let token = {
+        let data = signal
+            .downcast_ref::<JsAbortSignal>()
+            .ok_or_else(|| JsNativeError::typ().with_message("Object is not an AbortSignal"))?;
+        if data.token.inner.deadline.is_none() || data.timer_scheduled.replace(true) {
+            return Ok(());
+        }
+        data.token.clone()
+    };
+    let signal = signal.clone();
+    context.enqueue_job(
+        NativeAsyncJob::new(async move |context| {
+            token.cancelled().await;
+            let context = &mut context.borrow_mut();
+            sync_signal(&signal, context)?;
+            Ok(JsValue::undefined())
+        })
+        .into(),
+    );
+    Ok(())
+}
+
+/// 将 JS 值转换为 `AbortSignal.timeout` 的毫秒数。
+fn to_timeout_ms(value: &JsValue, context: &mut Context) -> JsResult<u64> {
+    let ms = value.to_number(context)?;
+    if !ms.is_finite() || ms < 0.0 {
+        return Err(JsNativeError::typ()
+            .with_message("AbortSignal.timeout 的毫秒数必须是非负有限数")
+            .into());
+    }
+    Ok(ms as u64)
+}
+
+/// 构造访问器使用的 JsFunction。
+fn accessor_fn(class: &mut ClassBuilder<'_>, function: NativeFunction) -> JsFunction {
+    let realm = class.context().realm().clone();
+    function.to_js_function(&realm)
+}
+
+impl Class for JsAbortSignal {
+    /// 绑定到 JS 中的类名
+    const NAME: &'static str = "AbortSignal";
+    /// 绑定的长度 (构造函数参数个数)
+    const LENGTH: usize = 0;
+
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        let aborted = accessor_fn(
+            class,
+            NativeFunction::from_fn_ptr(|this, _args, ctx| {
+                let signal = this_signal(this)?;
+                Ok(JsValue::new(sync_signal(&signal, ctx)?))
+            }),
+        );
+        class.accessor(
+            js_string!("aborted"),
+            Some(aborted),
+            None,
+            Attribute::CONFIGURABLE,
+        );
+        let reason = accessor_fn(
+            class,
+            NativeFunction::from_fn_ptr(|this, _args, ctx| {
+                let signal = this_signal(this)?;
+                signal_reason(&signal, ctx)
+            }),
+        );
+        class.accessor(
+            js_string!("reason"),
+            Some(reason),
+            None,
+            Attribute::CONFIGURABLE,
+        );
+        class.method(
+            js_string!("throwIfAborted"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, ctx| {
+                let signal = this_signal(this)?;
+                if sync_signal(&signal, ctx)? {
+                    return Err(boa_engine::JsError::from_opaque(signal_reason(
+                        &signal, ctx,
+                    )?));
+                }
+                Ok(JsValue::undefined())
+            }),
+        );
+        class.method(
+            js_string!("addEventListener"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let signal = this_signal(this)?;
+                let event_type = args.get_or_undefined(0).to_string(ctx)?;
+                let Some(listener) = args.get_or_undefined(1).as_callable() else {
+                    return Ok(JsValue::undefined());
+                };
+                if event_type != js_string!("abort") || sync_signal(&signal, ctx)? {
+                    return Ok(JsValue::undefined());
+                }
+                {
+                    let data = signal.downcast_ref::<JsAbortSignal>().ok_or_else(|| {
+                        JsNativeError::typ().with_message("Object is not an AbortSignal")
+                    })?;
+                    let mut listeners = data.listeners.borrow_mut();
+                    if !listeners
+                        .iter()
+                        .any(|item| JsObject::equals(item, &listener))
+                    {
+                        listeners.push(listener.clone());
+                    }
+                }
+                schedule_timeout_dispatch(&signal, ctx)?;
+                Ok(JsValue::undefined())
+            }),
+        );
+        class.method(
+            js_string!("removeEventListener"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let signal = this_signal(this)?;
+                let event_type = args.get_or_undefined(0).to_string(ctx)?;
+                let Some(listener) = args.get_or_undefined(1).as_object() else {
+                    return Ok(JsValue::undefined());
+                };
+                if event_type == js_string!("abort") {
+                    let data = signal.downcast_ref::<JsAbortSignal>().ok_or_else(|| {
+                        JsNativeError::typ().with_message("Object is not an AbortSignal")
+                    })?;
+                    data.listeners
+                        .borrow_mut()
+                        .retain(|item| !JsObject::equals(item, &listener));
+                }
+                Ok(JsValue::undefined())
+            }),
+        );
+        class.static_method(
+            js_string!("abort"),
+            1,
+            NativeFunction::from_fn_ptr(|_this, args, ctx| {
+                let reason = match args.get_or_undefined(0) {
+                    reason if reason.is_undefined() => {
+                        named_error("AbortError", ABORT_ERROR_MESSAGE, ctx)
+                    }
+                    reason => reason.clone(),
+                };
+                let signal = JsAbortSignal::create(AbortToken::new(), ctx)?;
+                abort_signal(&signal, reason, ctx)?;
+                Ok(signal.into())
+            }),
+        );
+        class.static_method(
+            js_string!("timeout"),
+            1,
+            NativeFunction::from_fn_ptr(|_this, args, ctx| {
+                let ms = to_timeout_ms(args.get_or_undefined(0), ctx)?;
+                let token = AbortToken::with_timeout(Duration::from_millis(ms));
+                Ok(JsAbortSignal::create(token, ctx)?.into())
+            }),
+        );
+        class.static_method(
+            js_string!("any"),
+            1,
+            NativeFunction::from_fn_ptr(|_this, args, ctx| {
+                let list = args.get_or_undefined(0).as_object().ok_or_else(|| {
+                    JsNativeError::typ().with_message("AbortSignal.any 需要 AbortSignal 数组")
+                })?;
+                let len = list.get(js_string!("length"), ctx)?.to_length(ctx)?;
+                let mut parents = Vec::with_capacity(len as usize);
+                for i in 0..len as u32 {
+                    let parent = this_signal(&list.get(i, ctx)?)?;
+                    // 任一来源已中止时，直接返回携带相同原因的已中止信号。
+                    if sync_signal(&parent, ctx)? {
+                        let reason = signal_reason(&parent, ctx)?;
+                        let signal = JsAbortSignal::create(AbortToken::new(), ctx)?;
+                        abort_signal(&signal, reason, ctx)?;
+                        return Ok(signal.into());
+                    }
+                    parents.push(parent);
+                }
+
+                let tokens: Vec<AbortToken> = parents
+                    .iter()
+                    .filter_map(|parent| {
+                        parent
+                            .downcast_ref::<JsAbortSignal>()
+                            .map(|data| data.token.clone())
+                    })
+                    .collect();
+                let signal = JsAbortSignal::create(AbortToken::linked(&tokens), ctx)?;
+                for parent in &parents {
+                    let Some(previous) = parent
+                        .downcast_ref::<JsAbortSignal>()
+                        .map(|data| std::mem::take(&mut *data.dependents.borrow_mut()))
+                    else {
+                        continue;
+                    };
+                    // 清理已被回收或已中止的派生信号，长期存活的父信号不会无限累积。
+                    let mut dependents = Vec::with_capacity(previous.len() + 1);
+                    for weak in previous {
+                        let alive = deref_weak(&weak, ctx)?.is_some_and(|dependent| {
+                            dependent
+                                .downcast_ref::<JsAbortSignal>()
+                                .is_some_and(|data| !data.token.is_aborted())
+                        });
+                        if alive {
+                            dependents.push(weak);
+                        }
+                    }
+                    dependents.push(weak_ref(&signal, ctx)?);
+                    if let Some(data) = parent.downcast_ref::<JsAbortSignal>() {
+                        data.dependents.borrow_mut().extend(dependents);
+                    }
+                }
+                Ok(signal.into())
+            }),
+        );
+        Ok(())
+    }
+
+    fn data_constructor(
+        _new_target: &JsValue,
+        _args: &[JsValue],
+        _context: &mut Context,
+    ) -> JsResult<Self> {
+        Err(JsNativeError::typ()
+            .with_message("Illegal constructor: 请使用 AbortController 或 AbortSignal 静态方法")
+            .into())
+    }
+}
+
+/// 定义 AbortController 类。
+#[derive(Trace, Finalize, JsData)]
+pub struct JsAbortController {
+    signal: JsObject,
+}
+
+/// 将 `this` 视为 AbortController，返回其信号对象。
+fn controller_signal(this: &JsValue) -> JsResult<JsObject> {
+    let binding = this
+        .as_object()
+        .ok_or_else(|| JsNativeError::typ().with_message("Object is not an AbortController"))?;
+    let controller = binding
+        .downcast_ref::<JsAbortController>()
+        .ok_or_else(|| JsNativeError::typ().with_message("Object is not an AbortController"))?;
+    Ok(controller.signal.clone())
+}
+
+impl Class for JsAbortController {
+    /// 绑定到 JS 中的类名
+    const NAME: &'static str = "AbortController";
+    /// 绑定的长度 (构造函数参数个数)
+    const LENGTH: usize = 0;
+
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        let signal = accessor_fn(
+            class,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| Ok(controller_signal(this)?.into())),
+        );
+        class.accessor(
+            js_string!("signal"),
+            Some(signal),
+            None,
+            Attribute::CONFIGURABLE,
+        );
+        class.method(
+            js_string!("abort"),
+            1,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let signal = controller_signal(this)?;
+                let reason = match args.get_or_undefined(0) {
+                    reason if reason.is_undefined() => {
+                        named_error("AbortError", ABORT_ERROR_MESSAGE, ctx)
+                    }
+                    reason => reason.clone(),
+                };
+                abort_signal(&signal, reason, ctx)?;
+                Ok(JsValue::undefined())
+            }),
+        );
+        Ok(())
+    }
+
+    fn data_constructor(
+        _new_target: &JsValue,
+        _args: &[JsValue],
+        context: &mut Context,
+    ) -> JsResult<Self> {
+        Ok(JsAbortController {
+            signal: JsAbortSignal::create(AbortToken::new(), context)?,
+        })
+    }
+}
+
+/// 异步内置函数的取消句柄：JS 侧信号与 Rust 侧令牌。
+pub(crate) struct ScriptAbort {
+    signal: JsObject,
+    token: AbortToken,
+}
+
+impl ScriptAbort {
+    /// 从 AbortSignal 值创建取消句柄（`undefined`/`null` 返回 `None`）。
+    pub(crate) fn from_signal(value: &JsValue) -> JsResult<Option<Self>> {
+        if value.is_undefined() || value.is_null() {
+            return Ok(None);
+        }
+        let signal = value
+            .as_object()
+            .filter(|obj| obj.is::<JsAbortSignal>())
+            .ok_or_else(|| JsNativeError::typ().with_message("signal 必须是 AbortSignal"))?;
+        let token = signal
+            .downcast_ref::<JsAbortSignal>()
+            .map(|data| data.token.clone())
+            .unwrap_or_default();
+        Ok(Some(Self { signal, token }))
+    }
+
+    /// 从 `{ signal }` 选项对象创建取消句柄。
+    pub(crate) fn from_options(
+        options: Option<&JsValue>,
+        context: &mut Context,
+    ) -> JsResult<Option<Self>> {
+        let Some(options) = options.and_then(JsValue::as_object) else {
+            return Ok(None);
+        };
+        let signal = options.get(js_string!("signal"), context)?;
+        Self::from_signal(&signal)
+    }
+
+    /// Rust 侧取消令牌（可移入阻塞线程或异步任务）。
+    pub(crate) fn token(&self) -> AbortToken {
+        self.token.clone()
+    }
+
+    /// 使用信号的中止原因拒绝 Promise（未提供信号时使用默认 `AbortError`）。
+    pub(crate) fn reject_aborted(
+        abort: Option<&Self>,
+        reject: &JsFunction,
+        context: &mut Context,
+    ) -> JsResult<JsValue> {
+        let reason = match abort {
+            Some(abort) => signal_reason(&abort.signal, context)?,
+            None => named_error("AbortError", ABORT_ERROR_MESSAGE, context),
+        };
+        reject.call(&JsValue::undefined(), &[reason], context)
+    }
+
+    /// 若信号在调用时已中止，立即拒绝 Promise。
+    ///
+    /// # 返回
+    /// 是否已拒绝
+    pub(crate) fn reject_if_aborted(
+        abort: Option<&Self>,
+        reject: &JsFunction,
+        context: &mut Context,
+    ) -> JsResult<bool> {
+        let Some(signal) = abort.map(|abort| &abort.signal) else {
+            return Ok(false);
+        };
+        if !sync_signal(signal, context)? {
+            return Ok(false);
+        }
+        Self::reject_aborted(abort, reject, context)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AbortToken;
+    use std::time::Duration;
+
+    #[test]
+    fn linked_token_follows_any_parent() {
+        let first = AbortToken::new();
+        let second = AbortToken::new();
+        let linked = AbortToken::linked(&[first.clone(), second.clone()]);
+        assert!(!linked.is_aborted());
+
+        second.abort();
+        assert!(linked.is_aborted());
+        assert!(!first.is_aborted());
+        assert!(!linked.timed_out());
+    }
+
+    #[test]
+    fn timeout_token_aborts_after_deadline() {
+        let token = AbortToken::with_timeout(Duration::ZERO);
+        assert!(token.is_aborted());
+        assert!(token.timed_out());
+
+        let pending = AbortToken::with_timeout(Duration::from_secs(3600));
+        let linked = AbortToken::linked(&[pending]);
+        assert!(!linked.is_aborted());
+    }
+
+    #[test]
+    fn linking_prunes_dropped_and_aborted_children() {
+        let parent = AbortToken::new();
+        for _ in 0..100 {
+            drop(AbortToken::linked(&[parent.clone()]));
+        }
+        assert_eq!(parent.inner.children().len(), 1);
+
+        let aborted = AbortToken::linked(&[parent.clone()]);
+        aborted.abort();
+        let live = AbortToken::linked(&[parent.clone()]);
+        assert_eq!(parent.inner.children().len(), 1);
+
+        parent.abort();
+        assert!(live.is_aborted());
+    }
+}
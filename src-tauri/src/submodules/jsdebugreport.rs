@@ -0,0 +1,269 @@
+use crate::submodules::{
+    debug_report::{DebugReport, FrameAnnotation, ReportImage, ReportStep},
+    jsmat::JsMat,
+    script_builtin::{
+        _emit_script_status, _get_option_number, _get_option_value, _parse_bbox_array,
+        _resolve_script_resource_path,
+    },
+};
+use boa_engine::{
+    Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsValue, Trace,
+    class::{Class, ClassBuilder},
+    js_string,
+    native_function::NativeFunction,
+    object::builtins::JsArray,
+    property::PropertyKey,
+};
+use opencv::core::Mat;
+use std::{cell::RefCell, path::PathBuf};
+
+/// 挂到状态面板时默认携带的图片数量上限。
+const DEFAULT_STATUS_IMAGES: usize = 8;
+
+/// JS 侧视觉调试报告对象封装。
+#[derive(Debug, Trace, Finalize, JsData)]
+pub struct JsDebugReport {
+    #[unsafe_ignore_trace]
+    pub(crate) inner: RefCell<DebugReport>,
+}
+
+/// 取出 `this` 对应的 `DebugReport` 对象。
+fn _this_debug_report(this: &JsValue) -> JsResult<JsObject<JsDebugReport>> {
+    this.as_object()
+        .and_then(|obj| obj.downcast::<JsDebugReport>().ok())
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Object is not a DebugReport")
+                .into()
+        })
+}
+
+/// 解析 `image` 字段：支持单个 Mat 或 Mat 数组。
+fn _option_mats(options: &JsObject, ctx: &mut Context) -> JsResult<Vec<Mat>> {
+    let Some(value) = _get_option_value(options, "image", ctx)? else {
+        return Ok(Vec::new());
+    };
+    if let Ok(js_mat) = value.get_native::<JsMat>() {
+        return Ok(vec![(*js_mat.borrow().data().inner).clone()]);
+    }
+    let array = value
+        .as_object()
+        .and_then(|obj| JsArray::from_object(obj.clone()).ok())
+        .ok_or_else(|| JsNativeError::typ().with_message("image 必须是 Mat 或 Mat[]"))?;
+    let length = array.length(ctx)? as usize;
+    let mut mats = Vec::with_capacity(length);
+    for idx in 0..length {
+        let js_mat = array
+            .get(idx as u32, ctx)?
+            .get_native::<JsMat>()
+            .map_err(|_| JsNativeError::typ().with_message(format!("image[{idx}] 必须是 Mat")))?;
+        mats.push((*js_mat.borrow().data().inner).clone());
+    }
+    Ok(mats)
+}
+
+/// 解析标注参数（`bboxes` / `contours` / `color`）。
+///
+/// 说明：`contours` 为 true 时绘制全部外轮廓，为数字时作为最小面积过滤。
+fn _option_annotation(options: &JsObject, ctx: &mut Context) -> JsResult<FrameAnnotation> {
+    let mut annotation = FrameAnnotation {
+        color: 0x00FF00,
+        ..Default::default()
+    };
+    if let Some(value) = _get_option_value(options, "bboxes", ctx)? {
+        annotation.bboxes = _parse_bbox_array(value, ctx)?;
+    }
+    if let Some(value) = _get_option_value(options, "contours", ctx)? {
+        annotation.contour_min_area = if value.is_boolean() {
+            value.to_boolean().then_some(0.0)
+        } else {
+            Some(value.to_number(ctx)?.max(0.0))
+        };
+    }
+    if let Some(value) = _get_option_value(options, "color", ctx)? {
+        annotation.color = value.to_number(ctx)? as u32 & 0x00FF_FFFF;
+    }
+    Ok(annotation)
+}
+
+/// 解析 `data` 字段为有序键值对（按对象自身属性顺序）。
+fn _option_fields(options: &JsObject, ctx: &mut Context) -> JsResult<Vec<(String, String)>> {
+    let Some(data) = _get_option_value(options, "data", ctx)? else {
+        return Ok(Vec::new());
+    };
+    let data = data
+        .as_object()
+        .ok_or_else(|| JsNativeError::typ().with_message("data 必须是对象"))?;
+    let mut fields = Vec::new();
+    for key in data.own_property_keys(ctx)? {
+        let name = match &key {
+            PropertyKey::String(s) => s.to_std_string_lossy(),
+            PropertyKey::Index(i) => i.get().to_string(),
+            PropertyKey::Symbol(_) => continue,
+        };
+        let value = data.get(key, ctx)?;
+        fields.push((name, value.to_string(ctx)?.to_std_string_lossy()));
+    }
+    Ok(fields)
+}
+
+/// 按 `step(name, options)` 参数构造报告步骤（图片在此完成标注与编码）。
+fn _parse_step(name: String, options: Option<&JsValue>, ctx: &mut Context) -> JsResult<ReportStep> {
+    let mut step = ReportStep {
+        name,
+        ..Default::default()
+    };
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(step);
+    };
+    step.score = _get_option_number(&options, "score", ctx)?;
+    step.threshold = _get_option_number(&options, "threshold", ctx)?;
+    step.elapsed_ms = _get_option_number(&options, "elapsed", ctx)?;
+    if let Some(note) = _get_option_value(&options, "note", ctx)? {
+        step.note = Some(note.to_string(ctx)?.to_std_string_lossy());
+    }
+    step.fields = _option_fields(&options, ctx)?;
+
+    let mats = _option_mats(&options, ctx)?;
+    if !mats.is_empty() {
+        let annotation = _option_annotation(&options, ctx)?;
+        let single = mats.len() == 1;
+        for (idx, mat) in mats.iter().enumerate() {
+            let label = if single {
+                step.name.clone()
+            } else {
+                format!("{} #{}", step.name, idx + 1)
+            };
+            let image = ReportImage::from_mat(label, mat, &annotation)
+                .map_err(|msg| JsNativeError::error().with_message(msg))?;
+            step.images.push(image);
+        }
+    }
+    Ok(step)
+}
+
+impl Class for JsDebugReport {
+    /// 绑定到 JS 的类型名。
+    const NAME: &'static str = "DebugReport";
+    /// 构造函数参数个数（`new DebugReport(title?)`）。
+    const LENGTH: usize = 1;
+
+    /// 注册 `DebugReport` 原型方法。
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        class.method(
+            js_string!("step"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let name = args
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| JsValue::undefined())
+                    .to_string(ctx)?
+                    .to_std_string_lossy();
+                let step = _parse_step(name, args.get(1), ctx)?;
+                let report = _this_debug_report(this)?;
+                let index = report.borrow().data().inner.borrow_mut().push_step(step);
+                Ok(JsValue::new(index as u32))
+            }),
+        );
+
+        class.method(
+            js_string!("html"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let report = _this_debug_report(this)?;
+                let html = report.borrow().data().inner.borrow().to_html();
+                Ok(JsValue::from(js_string!(html)))
+            }),
+        );
+
+        class.method(
+            js_string!("save"),
+            1,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let raw_path = args
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| JsValue::undefined())
+                    .to_string(ctx)?
+                    .to_std_string_lossy();
+                if raw_path.trim().is_empty() {
+                    return Err(JsNativeError::typ().with_message("path 不能为空").into());
+                }
+                let path = PathBuf::from(_resolve_script_resource_path(&raw_path).as_ref());
+                let report = _this_debug_report(this)?;
+                report
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .save_html(&path)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Ok(JsValue::from(js_string!(
+                    path.to_string_lossy().into_owned()
+                )))
+            }),
+        );
+
+        class.method(
+            js_string!("attachStatus"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let report = _this_debug_report(this)?;
+                let title = match args.first().filter(|v| !v.is_undefined() && !v.is_null()) {
+                    Some(value) => value.to_string(ctx)?.to_std_string_lossy(),
+                    None => report.borrow().data().inner.borrow().title().to_string(),
+                };
+                let max_images = match args.get(1).filter(|v| !v.is_undefined() && !v.is_null()) {
+                    Some(value) => value.to_number(ctx)?.max(0.0) as usize,
+                    None => DEFAULT_STATUS_IMAGES,
+                };
+                let (text, images) = {
+                    let report = report.borrow();
+                    let inner = report.data().inner.borrow();
+                    (inner.summary_text(), inner.status_images(max_images))
+                };
+                let image = images.first().cloned();
+                let images = (!images.is_empty()).then_some(images);
+                _emit_script_status(title, Some(text), image, images);
+                Ok(JsValue::undefined())
+            }),
+        );
+
+        class.method(
+            js_string!("size"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let report = _this_debug_report(this)?;
+                let size = report.borrow().data().inner.borrow().len();
+                Ok(JsValue::new(size as u32))
+            }),
+        );
+
+        class.method(
+            js_string!("clear"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let report = _this_debug_report(this)?;
+                report.borrow().data().inner.borrow_mut().clear();
+                Ok(JsValue::undefined())
+            }),
+        );
+
+        Ok(())
+    }
+
+    fn data_constructor(
+        _new_target: &JsValue,
+        args: &[JsValue],
+        context: &mut Context,
+    ) -> JsResult<Self> {
+        let title = match args.first().filter(|v| !v.is_undefined() && !v.is_null()) {
+            Some(value) => value.to_string(context)?.to_std_string_lossy(),
+            None => String::new(),
+        };
+        Ok(Self {
+            inner: RefCell::new(DebugReport::new(title)),
+        })
+    }
+}
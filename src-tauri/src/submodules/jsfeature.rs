@@ -0,0 +1,212 @@
+use crate::submodules::{
+    feature_library::{FeatureKind, FeatureLibrary, IdentifyOptions},
+    jsmat::{_arg_mat, _arg_string, IntoJs},
+    script_builtin::{_get_option_number, _resolve_script_resource_path},
+};
+use boa_engine::{
+    Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsValue, Trace,
+    class::{Class, ClassBuilder},
+    js_object, js_string,
+    native_function::NativeFunction,
+    object::builtins::JsArray,
+};
+use std::{cell::RefCell, path::Path};
+
+/// JS 侧 ORB/SIFT 特征库对象封装。
+#[derive(Debug, Trace, Finalize, JsData)]
+pub struct JsFeatureLibrary {
+    #[unsafe_ignore_trace]
+    pub(crate) inner: RefCell<FeatureLibrary>,
+}
+
+/// 取出 `this` 对应的 `FeatureLibrary` 对象。
+fn _this_feature_library(this: &JsValue) -> JsResult<JsObject<JsFeatureLibrary>> {
+    this.as_object()
+        .and_then(|obj| obj.downcast::<JsFeatureLibrary>().ok())
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Object is not a FeatureLibrary")
+                .into()
+        })
+}
+
+/// 解析特征算法名称，缺省为 ORB。
+pub(crate) fn parse_feature_kind(
+    value: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<FeatureKind> {
+    match value.filter(|v| !v.is_undefined() && !v.is_null()) {
+        Some(value) => {
+            let name = value.to_string(ctx)?.to_std_string_lossy();
+            FeatureKind::from_name(&name).ok_or_else(|| {
+                JsNativeError::typ()
+                    .with_message(format!("未知的特征算法: {name}"))
+                    .into()
+            })
+        }
+        None => Ok(FeatureKind::default()),
+    }
+}
+
+/// 解析识别选项（`topK` / `candidates` / `minInliers` / `ratio`）。
+fn _parse_identify_options(
+    options: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<IdentifyOptions> {
+    let mut parsed = IdentifyOptions::default();
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(parsed);
+    };
+    if let Some(top_k) = _get_option_number(&options, "topK", ctx)? {
+        parsed.top_k = top_k.max(0.0) as usize;
+    }
+    if let Some(candidates) = _get_option_number(&options, "candidates", ctx)? {
+        parsed.candidates = candidates.max(1.0) as usize;
+    }
+    if let Some(min_inliers) = _get_option_number(&options, "minInliers", ctx)? {
+        parsed.min_inliers = min_inliers.clamp(0.0, i32::MAX as f64) as i32;
+    }
+    if let Some(ratio) = _get_option_number(&options, "ratio", ctx)? {
+        parsed.ratio = ratio.clamp(0.0, 1.0) as f32;
+    }
+    Ok(parsed)
+}
+
+impl Class for JsFeatureLibrary {
+    /// 绑定到 JS 的类型名。
+    const NAME: &'static str = "FeatureLibrary";
+    /// 构造函数参数个数（`new FeatureLibrary(kind?)`）。
+    const LENGTH: usize = 1;
+
+    /// 注册 `FeatureLibrary` 原型方法。
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        class.method(
+            js_string!("add"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let label = _arg_string(args.first(), ctx)?;
+                let mat = _arg_mat(args.get(1))?;
+                let library = _this_feature_library(this)?;
+                let count = library
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .add(&label, &mat)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Ok(JsValue::new(count as u32))
+            }),
+        );
+
+        class.method(
+            js_string!("identify"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let library = _this_feature_library(this)?;
+                let mat = _arg_mat(args.first())?;
+                let options = _parse_identify_options(args.get(1), ctx)?;
+                let matches = library
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .identify(&mat, &options)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+
+                let result = JsArray::new(ctx);
+                for hit in matches {
+                    let item = js_object!({
+                        label: js_string!(hit.label),
+                        inliers: hit.inliers,
+                        goodMatches: hit.good_matches,
+                        score: hit.score,
+                    }, ctx);
+                    result.push(item, ctx)?;
+                }
+                Ok(result.into())
+            }),
+        );
+
+        class.method(
+            js_string!("save"),
+            1,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let path = _arg_string(args.first(), ctx)?;
+                let resolved = _resolve_script_resource_path(&path);
+                let library = _this_feature_library(this)?;
+                library
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow()
+                    .save(Path::new(resolved.as_ref()))
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Ok(JsValue::undefined())
+            }),
+        );
+
+        class.method(
+            js_string!("labels"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, ctx| {
+                let library = _this_feature_library(this)?;
+                let labels = library.borrow().data().inner.borrow().labels();
+                let result = JsArray::new(ctx);
+                for label in labels {
+                    result.push(js_string!(label), ctx)?;
+                }
+                Ok(result.into())
+            }),
+        );
+
+        class.method(
+            js_string!("size"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let library = _this_feature_library(this)?;
+                let size = library.borrow().data().inner.borrow().len();
+                Ok(JsValue::new(size as u32))
+            }),
+        );
+
+        class.method(
+            js_string!("kind"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let library = _this_feature_library(this)?;
+                let kind = library.borrow().data().inner.borrow().kind();
+                Ok(JsValue::from(js_string!(kind.name())))
+            }),
+        );
+
+        Ok(())
+    }
+
+    fn data_constructor(
+        _new_target: &JsValue,
+        args: &[JsValue],
+        context: &mut Context,
+    ) -> JsResult<Self> {
+        let kind = parse_feature_kind(args.first(), context)?;
+        Ok(Self {
+            inner: RefCell::new(FeatureLibrary::new(kind)),
+        })
+    }
+}
+
+/// 为 `Box<FeatureLibrary>` 提供 JS 对象转换能力。
+impl IntoJs for Box<FeatureLibrary> {
+    fn into_js(self, context: &mut Context) -> JsResult<JsValue> {
+        let prototype = context
+            .get_global_class::<JsFeatureLibrary>()
+            .ok_or_else(|| {
+                JsNativeError::typ().with_message("JsFeatureLibrary class not registered")
+            })?
+            .prototype();
+        let data = JsFeatureLibrary {
+            inner: RefCell::new(*self),
+        };
+        let obj = JsObject::from_proto_and_data(prototype, data);
+        Ok(obj.into())
+    }
+}
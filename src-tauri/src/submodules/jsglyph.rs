@@ -0,0 +1,196 @@
+use crate::submodules::{
+    glyph::{GlyphMethod, GlyphReadOptions, GlyphReading, GlyphSet},
+    jsmat::{_arg_mat, _arg_string, IntoJs},
+};
+use boa_engine::{
+    Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsValue, Trace,
+    class::{Class, ClassBuilder},
+    js_object, js_string, js_value,
+    native_function::NativeFunction,
+    object::builtins::JsArray,
+};
+use std::cell::RefCell;
+
+/// JS 侧字形集对象封装。
+#[derive(Debug, Trace, Finalize, JsData)]
+pub struct JsGlyphSet {
+    #[unsafe_ignore_trace]
+    pub(crate) inner: RefCell<GlyphSet>,
+}
+
+/// 取出 `this` 对应的 `GlyphSet` 对象。
+fn _this_glyph_set(this: &JsValue) -> JsResult<JsObject<JsGlyphSet>> {
+    this.as_object()
+        .and_then(|obj| obj.downcast::<JsGlyphSet>().ok())
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Object is not a GlyphSet")
+                .into()
+        })
+}
+
+/// 读取可选整数参数，缺省时返回 `default`。
+fn _arg_i32(value: Option<&JsValue>, default: i32, ctx: &mut Context) -> JsResult<i32> {
+    match value.filter(|v| !v.is_undefined() && !v.is_null()) {
+        Some(value) => value.to_i32(ctx),
+        None => Ok(default),
+    }
+}
+
+/// 读取参数中的 `GlyphSet` 对象。
+pub(crate) fn arg_glyph_set(value: Option<&JsValue>) -> JsResult<JsObject<JsGlyphSet>> {
+    _this_glyph_set(value.unwrap_or(&JsValue::undefined()))
+}
+
+/// 解析识别选项（`method` / `minConfidence` / `minGapWidth` / `minCharWidth` / `unknown`）。
+pub(crate) fn parse_glyph_read_options(
+    options: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<GlyphReadOptions> {
+    let mut parsed = GlyphReadOptions::default();
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(parsed);
+    };
+    let method = options.get(js_string!("method"), ctx)?;
+    if !method.is_undefined() && !method.is_null() {
+        let name = method.to_string(ctx)?.to_std_string_lossy();
+        parsed.method = GlyphMethod::from_name(&name).ok_or_else(|| {
+            JsNativeError::typ().with_message(format!("未知的字形比对方式: {name}"))
+        })?;
+    }
+    let min_confidence = options.get(js_string!("minConfidence"), ctx)?;
+    if !min_confidence.is_undefined() && !min_confidence.is_null() {
+        parsed.min_confidence = min_confidence.to_number(ctx)?.clamp(0.0, 1.0);
+    }
+    let min_gap_width = options.get(js_string!("minGapWidth"), ctx)?;
+    parsed.min_gap_width = _arg_i32(Some(&min_gap_width), parsed.min_gap_width, ctx)?;
+    let min_char_width = options.get(js_string!("minCharWidth"), ctx)?;
+    parsed.min_char_width = _arg_i32(Some(&min_char_width), parsed.min_char_width, ctx)?;
+    let unknown = options.get(js_string!("unknown"), ctx)?;
+    if !unknown.is_undefined() && !unknown.is_null() {
+        parsed.unknown = unknown.to_string(ctx)?.to_std_string_lossy();
+    }
+    Ok(parsed)
+}
+
+/// 将识别结果转换为 `{ text, confidence, chars }` JS 对象。
+pub(crate) fn glyph_reading_to_js(reading: &GlyphReading, ctx: &mut Context) -> JsResult<JsObject> {
+    let chars = JsArray::new(ctx);
+    for item in &reading.chars {
+        let (x, y, w, h) = item.bbox;
+        let label = match &item.label {
+            Some(label) => JsValue::from(js_string!(label.as_str())),
+            None => JsValue::null(),
+        };
+        let obj = js_object!({
+            char: label,
+            confidence: item.confidence,
+            bbox: js_value!([x, y, w, h], ctx),
+        }, ctx);
+        chars.push(obj, ctx)?;
+    }
+    Ok(js_object!({
+        text: js_string!(reading.text.as_str()),
+        confidence: reading.confidence,
+        chars: chars,
+    }, ctx))
+}
+
+impl Class for JsGlyphSet {
+    /// 绑定到 JS 的类型名。
+    const NAME: &'static str = "GlyphSet";
+    /// 构造函数参数个数（`new GlyphSet()`）。
+    const LENGTH: usize = 0;
+
+    /// 注册 `GlyphSet` 原型方法。
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        class.method(
+            js_string!("add"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let label = _arg_string(args.first(), ctx)?;
+                let mat = _arg_mat(args.get(1))?;
+                let set = _this_glyph_set(this)?;
+                set.borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .add(&label, &mat)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Ok(JsValue::undefined())
+            }),
+        );
+
+        class.method(
+            js_string!("addLine"),
+            4,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let mat = _arg_mat(args.first())?;
+                let text = _arg_string(args.get(1), ctx)?;
+                let defaults = GlyphReadOptions::default();
+                let min_gap_width = _arg_i32(args.get(2), defaults.min_gap_width, ctx)?;
+                let min_char_width = _arg_i32(args.get(3), defaults.min_char_width, ctx)?;
+                let set = _this_glyph_set(this)?;
+                let added = set
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .add_line(&mat, &text, min_gap_width, min_char_width)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Ok(JsValue::new(added as u32))
+            }),
+        );
+
+        class.method(
+            js_string!("labels"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, ctx| {
+                let set = _this_glyph_set(this)?;
+                let labels = set.borrow().data().inner.borrow().labels();
+                let result = JsArray::new(ctx);
+                for label in labels {
+                    result.push(js_string!(label), ctx)?;
+                }
+                Ok(result.into())
+            }),
+        );
+
+        class.method(
+            js_string!("size"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let set = _this_glyph_set(this)?;
+                let size = set.borrow().data().inner.borrow().len();
+                Ok(JsValue::new(size as u32))
+            }),
+        );
+
+        Ok(())
+    }
+
+    fn data_constructor(
+        _new_target: &JsValue,
+        _args: &[JsValue],
+        _context: &mut Context,
+    ) -> JsResult<Self> {
+        Ok(Self {
+            inner: RefCell::new(GlyphSet::new()),
+        })
+    }
+}
+
+/// 为 `Box<GlyphSet>` 提供 JS 对象转换能力。
+impl IntoJs for Box<GlyphSet> {
+    fn into_js(self, context: &mut Context) -> JsResult<JsValue> {
+        let prototype = context
+            .get_global_class::<JsGlyphSet>()
+            .ok_or_else(|| JsNativeError::typ().with_message("JsGlyphSet class not registered"))?
+            .prototype();
+        let data = JsGlyphSet {
+            inner: RefCell::new(*self),
+        };
+        let obj = JsObject::from_proto_and_data(prototype, data);
+        Ok(obj.into())
+    }
+}
@@ -0,0 +1,192 @@
+use crate::submodules::{
+    hash_index::HashIndex,
+    jsmat::{IntoJs, JsMat},
+    script_builtin::_get_option_u32,
+    script_vision::{ImageHashKind, perceptual_hash_impl},
+};
+use boa_engine::{
+    Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsValue, Trace,
+    class::{Class, ClassBuilder},
+    js_object, js_string,
+    native_function::NativeFunction,
+    object::builtins::JsArray,
+};
+use std::cell::RefCell;
+
+/// JS 侧感知哈希索引对象封装（BK 树）。
+#[derive(Debug, Trace, Finalize, JsData)]
+pub struct JsHashIndex {
+    #[unsafe_ignore_trace]
+    pub(crate) inner: RefCell<HashIndex>,
+}
+
+/// 取出 `this` 对应的 `HashIndex` 对象。
+fn _this_hash_index(this: &JsValue) -> JsResult<JsObject<JsHashIndex>> {
+    this.as_object()
+        .and_then(|obj| obj.downcast::<JsHashIndex>().ok())
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Object is not a HashIndex")
+                .into()
+        })
+}
+
+/// 解析查询参数：Mat 按索引的算法与颜色模式计算哈希，其余按十六进制哈希字符串处理。
+fn _query_hash(
+    index: &JsObject<JsHashIndex>,
+    value: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<String> {
+    let (kind, color) = {
+        let index = index.borrow();
+        let inner = index.data().inner.borrow();
+        (inner.kind(), inner.color())
+    };
+    let value = value.cloned().unwrap_or_else(|| JsValue::undefined());
+    if let Some(mat) = value
+        .as_object()
+        .and_then(|obj| obj.downcast::<JsMat>().ok())
+    {
+        let mat = (*mat.borrow().data().inner).clone();
+        return perceptual_hash_impl(&mat, color, kind)
+            .map_err(|msg| JsNativeError::error().with_message(msg).into());
+    }
+    Ok(value.to_string(ctx)?.to_std_string_lossy())
+}
+
+impl Class for JsHashIndex {
+    /// 绑定到 JS 的类型名。
+    const NAME: &'static str = "HashIndex";
+    /// 构造函数参数个数（`new HashIndex(kind?, color?)`）。
+    const LENGTH: usize = 2;
+
+    /// 注册 `HashIndex` 原型方法。
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        class.method(
+            js_string!("add"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let label = args
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| JsValue::undefined())
+                    .to_string(ctx)?
+                    .to_std_string_lossy();
+                let hash = args
+                    .get(1)
+                    .cloned()
+                    .unwrap_or_else(|| JsValue::undefined())
+                    .to_string(ctx)?
+                    .to_std_string_lossy();
+                let index = _this_hash_index(this)?;
+                index
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .insert(&label, &hash)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Ok(JsValue::undefined())
+            }),
+        );
+
+        class.method(
+            js_string!("query"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let index = _this_hash_index(this)?;
+                let hash = _query_hash(&index, args.first(), ctx)?;
+                let options = args
+                    .get(1)
+                    .and_then(JsValue::as_object)
+                    .unwrap_or_else(JsObject::with_null_proto);
+                let k = _get_option_u32(&options, "k", 1, ctx)? as usize;
+                let max_distance = _get_option_u32(&options, "maxDistance", 10, ctx)?;
+                let hits = index
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow()
+                    .query(&hash, k, max_distance)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+
+                let result = JsArray::new(ctx);
+                for hit in hits {
+                    let item = js_object!({
+                        label: js_string!(hit.label),
+                        distance: hit.distance,
+                    }, ctx);
+                    result.push(item, ctx)?;
+                }
+                Ok(result.into())
+            }),
+        );
+
+        class.method(
+            js_string!("hash"),
+            1,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let index = _this_hash_index(this)?;
+                let hash = _query_hash(&index, args.first(), ctx)?;
+                Ok(JsValue::from(js_string!(hash)))
+            }),
+        );
+
+        class.method(
+            js_string!("size"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let index = _this_hash_index(this)?;
+                let size = index.borrow().data().inner.borrow().len();
+                Ok(JsValue::new(size as u32))
+            }),
+        );
+
+        class.method(
+            js_string!("kind"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let index = _this_hash_index(this)?;
+                let kind = index.borrow().data().inner.borrow().kind();
+                Ok(JsValue::from(js_string!(kind.name())))
+            }),
+        );
+
+        Ok(())
+    }
+
+    fn data_constructor(
+        _new_target: &JsValue,
+        args: &[JsValue],
+        context: &mut Context,
+    ) -> JsResult<Self> {
+        let kind = match args.first().filter(|v| !v.is_undefined() && !v.is_null()) {
+            Some(value) => {
+                let name = value.to_string(context)?.to_std_string_lossy();
+                ImageHashKind::from_name(&name).ok_or_else(|| {
+                    JsNativeError::typ().with_message(format!("未知的哈希算法: {name}"))
+                })?
+            }
+            None => ImageHashKind::default(),
+        };
+        let color = args.get(1).is_some_and(JsValue::to_boolean);
+        Ok(Self {
+            inner: RefCell::new(HashIndex::new(kind, color)),
+        })
+    }
+}
+
+/// 为 `Box<HashIndex>` 提供 JS 对象转换能力。
+impl IntoJs for Box<HashIndex> {
+    fn into_js(self, context: &mut Context) -> JsResult<JsValue> {
+        let prototype = context
+            .get_global_class::<JsHashIndex>()
+            .ok_or_else(|| JsNativeError::typ().with_message("JsHashIndex class not registered"))?
+            .prototype();
+        let data = JsHashIndex {
+            inner: RefCell::new(*self),
+        };
+        let obj = JsObject::from_proto_and_data(prototype, data);
+        Ok(obj.into())
+    }
+}
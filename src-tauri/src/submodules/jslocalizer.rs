@@ -0,0 +1,195 @@
+use crate::submodules::{
+    jsfeature::parse_feature_kind,
+    jsmat::_arg_mat,
+    minimap_localizer::{LocalizerConfig, LocalizerPose, MinimapLocalizer},
+    script_builtin::{_get_option_number, _get_option_value},
+};
+use boa_engine::{
+    Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsValue, Trace,
+    class::{Class, ClassBuilder},
+    js_object, js_string,
+    native_function::NativeFunction,
+};
+use std::cell::RefCell;
+
+/// JS 侧小地图连续定位器封装。
+#[derive(Debug, Trace, Finalize, JsData)]
+pub struct JsMinimapLocalizer {
+    #[unsafe_ignore_trace]
+    pub(crate) inner: RefCell<MinimapLocalizer>,
+}
+
+/// 取出 `this` 对应的 `MinimapLocalizer` 对象。
+fn _this_localizer(this: &JsValue) -> JsResult<JsObject<JsMinimapLocalizer>> {
+    this.as_object()
+        .and_then(|obj| obj.downcast::<JsMinimapLocalizer>().ok())
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Object is not a MinimapLocalizer")
+                .into()
+        })
+}
+
+/// 解析定位器选项，未提供的字段使用默认值。
+fn _parse_localizer_config(
+    options: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<LocalizerConfig> {
+    let mut config = LocalizerConfig::default();
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(config);
+    };
+    if let Some(kind) = _get_option_value(&options, "kind", ctx)? {
+        config.kind = parse_feature_kind(Some(&kind), ctx)?;
+    }
+    if let Some(value) = _get_option_number(&options, "searchRadius", ctx)? {
+        config.search_radius = value.max(1.0);
+    }
+    if let Some(value) = _get_option_number(&options, "lostAfter", ctx)? {
+        config.lost_after = value.clamp(1.0, u32::MAX as f64) as u32;
+    }
+    if let Some(value) = _get_option_number(&options, "minInliers", ctx)? {
+        config.min_inliers = value.clamp(3.0, i32::MAX as f64) as i32;
+    }
+    if let Some(value) = _get_option_number(&options, "maxJump", ctx)? {
+        config.max_jump = value.max(0.0);
+    }
+    if let Some(value) = _get_option_number(&options, "alpha", ctx)? {
+        config.alpha = value.clamp(0.0, 1.0);
+    }
+    if let Some(value) = _get_option_number(&options, "beta", ctx)? {
+        config.beta = value.clamp(0.0, 1.0);
+    }
+    if let Some(value) = _get_option_number(&options, "minScale", ctx)? {
+        config.min_scale = value.max(0.0);
+    }
+    if let Some(value) = _get_option_number(&options, "maxScale", ctx)? {
+        config.max_scale = value.max(config.min_scale);
+    }
+    if let Some(value) = _get_option_number(&options, "ratio", ctx)? {
+        config.ratio = value.clamp(0.0, 1.0) as f32;
+    }
+    Ok(config)
+}
+
+/// 将位姿转换为 JS 对象。
+fn _pose_to_js(pose: &LocalizerPose, ctx: &mut Context) -> JsValue {
+    js_object!({
+        x: pose.x,
+        y: pose.y,
+        heading: pose.heading,
+        scale: pose.scale,
+        confidence: pose.confidence,
+        inliers: pose.inliers,
+        lost: pose.lost,
+        source: js_string!(pose.source.name()),
+    }, ctx)
+    .into()
+}
+
+impl Class for JsMinimapLocalizer {
+    /// 绑定到 JS 的类型名。
+    const NAME: &'static str = "MinimapLocalizer";
+    /// 构造函数参数个数（`new MinimapLocalizer(map, options?)`）。
+    const LENGTH: usize = 2;
+
+    /// 注册 `MinimapLocalizer` 原型方法。
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        class.method(
+            js_string!("update"),
+            1,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let mat = _arg_mat(args.first())?;
+                let localizer = _this_localizer(this)?;
+                let pose = localizer
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .update(&mat)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Ok(_pose_to_js(&pose, ctx))
+            }),
+        );
+
+        class.method(
+            js_string!("pose"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, ctx| {
+                let localizer = _this_localizer(this)?;
+                let pose = localizer.borrow().data().inner.borrow().last_pose();
+                Ok(pose.map_or_else(JsValue::null, |pose| _pose_to_js(&pose, ctx)))
+            }),
+        );
+
+        class.method(
+            js_string!("isLost"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let localizer = _this_localizer(this)?;
+                let lost = localizer.borrow().data().inner.borrow().is_lost();
+                Ok(JsValue::new(lost))
+            }),
+        );
+
+        class.method(
+            js_string!("setPose"),
+            3,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let x = args
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| JsValue::undefined())
+                    .to_number(ctx)?;
+                let y = args
+                    .get(1)
+                    .cloned()
+                    .unwrap_or_else(|| JsValue::undefined())
+                    .to_number(ctx)?;
+                if !x.is_finite() || !y.is_finite() {
+                    return Err(JsNativeError::typ()
+                        .with_message("setPose 坐标必须是有限数字")
+                        .into());
+                }
+                let heading = match args.get(2).filter(|v| !v.is_undefined() && !v.is_null()) {
+                    Some(value) => Some(value.to_number(ctx)?).filter(|v| v.is_finite()),
+                    None => None,
+                };
+                let localizer = _this_localizer(this)?;
+                localizer
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .set_pose(x, y, heading);
+                Ok(JsValue::undefined())
+            }),
+        );
+
+        class.method(
+            js_string!("reset"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let localizer = _this_localizer(this)?;
+                localizer.borrow().data().inner.borrow_mut().reset();
+                Ok(JsValue::undefined())
+            }),
+        );
+
+        Ok(())
+    }
+
+    fn data_constructor(
+        _new_target: &JsValue,
+        args: &[JsValue],
+        context: &mut Context,
+    ) -> JsResult<Self> {
+        let map = _arg_mat(args.first())?;
+        let config = _parse_localizer_config(args.get(1), context)?;
+        let localizer = MinimapLocalizer::new(&map, config)
+            .map_err(|msg| JsNativeError::error().with_message(msg))?;
+        Ok(Self {
+            inner: RefCell::new(localizer),
+        })
+    }
+}
@@ -609,6 +609,24 @@ impl Class for JsMat {
     }
 }
 
+/// 读取参数中的 Mat（深拷贝），非 Mat 时抛出类型错误。
+pub(crate) fn _arg_mat(value: Option<&JsValue>) -> JsResult<opencv::core::Mat> {
+    let mat = value
+        .cloned()
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    Ok((*mat.borrow().data().inner).clone())
+}
+
+/// 读取字符串参数，缺省时按 `undefined` 转换。
+pub(crate) fn _arg_string(value: Option<&JsValue>, ctx: &mut Context) -> JsResult<String> {
+    Ok(value
+        .cloned()
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_lossy())
+}
+
 pub trait IntoJs {
     fn into_js(self, context: &mut Context) -> JsResult<JsValue>;
 }
@@ -0,0 +1,283 @@
+use crate::submodules::{
+    jsmat::{_arg_mat, IntoJs},
+    script_builtin::{_get_option_number, _resolve_script_resource_path},
+    world_mosaic::{MosaicConfig, WorldMosaic},
+};
+use boa_engine::{
+    Context, Finalize, JsData, JsNativeError, JsObject, JsResult, JsValue, Trace,
+    class::{Class, ClassBuilder},
+    js_object, js_string, js_value,
+    native_function::NativeFunction,
+    object::builtins::JsArray,
+};
+use opencv::core::Rect;
+use std::{cell::RefCell, path::Path};
+
+/// JS 侧世界地图拼图对象封装。
+#[derive(Debug, Trace, Finalize, JsData)]
+pub struct JsWorldMosaic {
+    #[unsafe_ignore_trace]
+    pub(crate) inner: RefCell<WorldMosaic>,
+}
+
+/// 取出 `this` 对应的 `WorldMosaic` 对象。
+fn _this_mosaic(this: &JsValue) -> JsResult<JsObject<JsWorldMosaic>> {
+    this.as_object()
+        .and_then(|obj| obj.downcast::<JsWorldMosaic>().ok())
+        .ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message("Object is not a WorldMosaic")
+                .into()
+        })
+}
+
+/// 解析拼图选项，未提供的字段使用默认值。
+fn _parse_mosaic_config(options: Option<&JsValue>, ctx: &mut Context) -> JsResult<MosaicConfig> {
+    let mut config = MosaicConfig::default();
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(config);
+    };
+    if let Some(value) = _get_option_number(&options, "tileSize", ctx)? {
+        config.tile_size = value.clamp(64.0, 8192.0) as i32;
+    }
+    if let Some(value) = _get_option_number(&options, "searchMargin", ctx)? {
+        config.search_margin = value.max(0.0) as i32;
+    }
+    if let Some(value) = _get_option_number(&options, "minGoodMatches", ctx)? {
+        config.min_good_matches = value.max(4.0) as i32;
+    }
+    if let Some(value) = _get_option_number(&options, "minInliers", ctx)? {
+        config.min_inliers = value.max(4.0) as i32;
+    }
+    if let Some(value) = _get_option_number(&options, "minInlierRatio", ctx)? {
+        config.min_inlier_ratio = value.clamp(0.0, 1.0);
+    }
+    if let Some(value) = _get_option_number(&options, "maxScaleChange", ctx)? {
+        config.max_scale_change = value.max(1.0);
+    }
+    if let Some(value) = _get_option_number(&options, "maxPerspective", ctx)? {
+        config.max_perspective = value.max(0.0);
+    }
+    if let Some(value) = _get_option_number(&options, "ransacThreshold", ctx)? {
+        config.ransac_threshold = value.max(0.5);
+    }
+    if let Some(value) = _get_option_number(&options, "maxCachedTiles", ctx)? {
+        config.max_cached_tiles = value.clamp(4.0, 4096.0) as usize;
+    }
+    Ok(config)
+}
+
+impl Class for JsWorldMosaic {
+    /// 绑定到 JS 的类型名。
+    const NAME: &'static str = "WorldMosaic";
+    /// 构造函数参数个数（`new WorldMosaic(dir, options?)`）。
+    const LENGTH: usize = 2;
+
+    /// 注册 `WorldMosaic` 原型方法。
+    fn init(class: &mut ClassBuilder<'_>) -> JsResult<()> {
+        class.method(
+            js_string!("addFrame"),
+            1,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let mat = _arg_mat(args.first())?;
+                let mosaic = _this_mosaic(this)?;
+                let result = mosaic
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .add_frame(&mat)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+
+                let bbox = match result.bbox {
+                    Some(r) => js_value!([r.x, r.y, r.width, r.height], ctx),
+                    None => JsValue::undefined(),
+                };
+                let reason = match result.reason {
+                    Some(reason) => JsValue::from(js_string!(reason.as_str())),
+                    None => JsValue::undefined(),
+                };
+                let obj = js_object!({
+                    accepted: result.accepted,
+                    reason: reason,
+                    bbox: bbox,
+                    goodMatches: result.good_matches,
+                    inliers: result.inliers,
+                }, ctx);
+                Ok(obj.into())
+            }),
+        );
+
+        class.method(
+            js_string!("locate"),
+            2,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let mat = _arg_mat(args.first())?;
+                let options = args
+                    .get(1)
+                    .and_then(JsValue::as_object)
+                    .unwrap_or_else(JsObject::with_null_proto);
+                let x = _get_option_number(&options, "x", ctx)?;
+                let y = _get_option_number(&options, "y", ctx)?;
+                let radius = _get_option_number(&options, "radius", ctx)?.unwrap_or(0.0);
+                let hint = x.zip(y);
+                let mosaic = _this_mosaic(this)?;
+                let location = mosaic
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .locate(&mat, hint, radius)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                let Some(location) = location else {
+                    return Ok(JsValue::undefined());
+                };
+
+                let corners = JsArray::new(ctx);
+                for point in &location.corners {
+                    corners.push(js_value!([point.x, point.y], ctx), ctx)?;
+                }
+                let bbox = location.bbox;
+                let obj = js_object!({
+                    pos: js_value!([location.center.0, location.center.1], ctx),
+                    bbox: js_value!([bbox.x, bbox.y, bbox.width, bbox.height], ctx),
+                    corners: corners,
+                    goodMatches: location.good_matches,
+                    inliers: location.inliers,
+                }, ctx);
+                Ok(obj.into())
+            }),
+        );
+
+        class.method(
+            js_string!("render"),
+            4,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let mut values = [0i32; 4];
+                for (i, slot) in values.iter_mut().enumerate() {
+                    *slot = args
+                        .get(i)
+                        .cloned()
+                        .unwrap_or_else(|| JsValue::undefined())
+                        .to_i32(ctx)?;
+                }
+                let [x, y, width, height] = values;
+                if width <= 0 || height <= 0 {
+                    return Err(JsNativeError::range()
+                        .with_message("render 区域宽高必须大于 0")
+                        .into());
+                }
+                let mosaic = _this_mosaic(this)?;
+                let mat = mosaic
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .render_region(Rect::new(x, y, width, height))
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Box::new(mat).into_js(ctx)
+            }),
+        );
+
+        class.method(
+            js_string!("export"),
+            1,
+            NativeFunction::from_fn_ptr(|this, args, ctx| {
+                let zoom = match args.first().filter(|v| !v.is_undefined() && !v.is_null()) {
+                    Some(value) => value.to_number(ctx)?,
+                    None => 1.0,
+                };
+                let mosaic = _this_mosaic(this)?;
+                let mat = mosaic
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .export(zoom)
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Box::new(mat).into_js(ctx)
+            }),
+        );
+
+        class.method(
+            js_string!("save"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let mosaic = _this_mosaic(this)?;
+                mosaic
+                    .borrow()
+                    .data()
+                    .inner
+                    .borrow_mut()
+                    .save()
+                    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+                Ok(JsValue::undefined())
+            }),
+        );
+
+        class.method(
+            js_string!("bounds"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, ctx| {
+                let mosaic = _this_mosaic(this)?;
+                let bounds = mosaic.borrow().data().inner.borrow().bounds();
+                Ok(match bounds {
+                    Some(r) => js_value!([r.x, r.y, r.width, r.height], ctx),
+                    None => JsValue::null(),
+                })
+            }),
+        );
+
+        class.method(
+            js_string!("offset"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, ctx| {
+                let mosaic = _this_mosaic(this)?;
+                let (x, y) = mosaic.borrow().data().inner.borrow().offset();
+                Ok(js_value!([x, y], ctx))
+            }),
+        );
+
+        class.method(
+            js_string!("frameCount"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let mosaic = _this_mosaic(this)?;
+                let frames = mosaic.borrow().data().inner.borrow().frame_count();
+                Ok(JsValue::new(frames as f64))
+            }),
+        );
+
+        class.method(
+            js_string!("tileCount"),
+            0,
+            NativeFunction::from_fn_ptr(|this, _args, _ctx| {
+                let mosaic = _this_mosaic(this)?;
+                let tiles = mosaic.borrow().data().inner.borrow().tile_count();
+                Ok(JsValue::new(tiles as u32))
+            }),
+        );
+
+        Ok(())
+    }
+
+    fn data_constructor(
+        _new_target: &JsValue,
+        args: &[JsValue],
+        context: &mut Context,
+    ) -> JsResult<Self> {
+        let dir = args
+            .first()
+            .cloned()
+            .unwrap_or_else(|| JsValue::undefined())
+            .to_string(context)?
+            .to_std_string_lossy();
+        let resolved = _resolve_script_resource_path(&dir);
+        let config = _parse_mosaic_config(args.get(1), context)?;
+        let mosaic = WorldMosaic::open(Path::new(resolved.as_ref()), config)
+            .map_err(|msg| JsNativeError::error().with_message(msg))?;
+        Ok(Self {
+            inner: RefCell::new(mosaic),
+        })
+    }
+}
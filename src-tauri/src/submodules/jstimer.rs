@@ -1,3 +1,4 @@
+use crate::submodules::jsabort::{ScriptAbort, run_abortable};
 use boa_engine::{
     Context, Finalize, JsData, JsNativeError, JsResult, JsValue, Trace,
     class::{Class, ClassBuilder},
@@ -79,17 +80,32 @@ impl Class for JsTimer {
                     + tokio::time::Duration::from_millis(duration_ms as u64);
                 *js_timer.start_time.lock().unwrap() = target_instant;
 
+                // 可选的中止信号：已中止时直接拒绝
+                let abort = ScriptAbort::from_options(args.get(1), ctx)?;
+                if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+                    return Ok(promise.into());
+                }
+                let token = abort.as_ref().map(ScriptAbort::token);
+
                 // 将 resolvers 克隆到异步任务中
                 let resolvers_clone = resolvers.clone();
 
                 // 入队异步任务
                 ctx.enqueue_job(
                     NativeAsyncJob::new(async move |context| {
-                        // 使用 tokio::time::sleep_until 等待到指定时间
-                        tokio::time::sleep_until(target_instant).await;
+                        // 使用 tokio::time::sleep_until 等待到指定时间，信号中止时提前结束
+                        let sleep = tokio::time::sleep_until(target_instant);
+                        let finished = run_abortable(token.as_ref(), sleep).await;
 
                         // 解析 Promise
                         let context = &mut context.borrow_mut();
+                        if finished.is_none() {
+                            return ScriptAbort::reject_aborted(
+                                abort.as_ref(),
+                                &resolvers_clone.reject,
+                                context,
+                            );
+                        }
                         resolvers_clone
                             .resolve
                             .call(&JsValue::undefined(), &[], context)
@@ -125,17 +141,32 @@ impl Class for JsTimer {
                 let target_instant = *js_timer.start_time.lock().unwrap()
                     + tokio::time::Duration::from_millis(target_ms as u64);
 
+                // 可选的中止信号：已中止时直接拒绝
+                let abort = ScriptAbort::from_options(args.get(1), ctx)?;
+                if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+                    return Ok(promise.into());
+                }
+                let token = abort.as_ref().map(ScriptAbort::token);
+
                 // 将 resolvers 克隆到异步任务中
                 let resolvers_clone = resolvers.clone();
 
                 // 入队异步任务
                 ctx.enqueue_job(
                     NativeAsyncJob::new(async move |context| {
-                        // 使用 tokio::time::sleep_until 等待到指定时间
-                        tokio::time::sleep_until(target_instant).await;
+                        // 使用 tokio::time::sleep_until 等待到指定时间，信号中止时提前结束
+                        let sleep = tokio::time::sleep_until(target_instant);
+                        let finished = run_abortable(token.as_ref(), sleep).await;
 
                         // 解析 Promise
                         let context = &mut context.borrow_mut();
+                        if finished.is_none() {
+                            return ScriptAbort::reject_aborted(
+                                abort.as_ref(),
+                                &resolvers_clone.reject,
+                                context,
+                            );
+                        }
                         resolvers_clone
                             .resolve
                             .call(&JsValue::undefined(), &[], context)
@@ -0,0 +1,630 @@
+use opencv::{
+    calib3d,
+    core::{self, DMatch, Mat, Point2f, Vector},
+    features2d,
+    prelude::{DescriptorMatcherTraitConst, MatTraitConst, MatTraitConstManual, MatTraitManual},
+};
+
+use crate::submodules::feature_library::{FeatureKind, detect_features};
+
+/// 大地图特征点网格单元边长（像素）
+const GRID_CELL: f64 = 128.0;
+
+/// 定位器参数
+#[derive(Debug, Clone, Copy)]
+pub struct LocalizerConfig {
+    /// 特征算法（默认 SIFT，与 `siftLocate` 一致）
+    pub kind: FeatureKind,
+    /// 跟踪时围绕预测位置的搜索半径（大地图像素，连续丢失时按次数放大）
+    pub search_radius: f64,
+    /// 连续失败多少帧后判定为丢失并改用全图搜索
+    pub lost_after: u32,
+    /// 接受一次定位所需的最少 RANSAC 内点数
+    pub min_inliers: i32,
+    /// 跟踪时单帧允许的最大位移（大地图像素），超出视为误匹配
+    pub max_jump: f64,
+    /// α-β 滤波的位置/朝向平滑系数（0-1，越大越信任新观测）
+    pub alpha: f64,
+    /// α-β 滤波的速度更新系数（0-1）
+    pub beta: f64,
+    /// 允许的最小缩放（小地图像素到大地图像素）
+    pub min_scale: f64,
+    /// 允许的最大缩放
+    pub max_scale: f64,
+    /// Lowe Ratio Test 阈值
+    pub ratio: f32,
+}
+
+impl Default for LocalizerConfig {
+    fn default() -> Self {
+        Self {
+            kind: FeatureKind::Sift,
+            search_radius: 320.0,
+            lost_after: 3,
+            min_inliers: 10,
+            max_jump: 160.0,
+            alpha: 0.6,
+            beta: 0.2,
+            min_scale: 0.25,
+            max_scale: 4.0,
+            ratio: 0.75,
+        }
+    }
+}
+
+/// 位姿来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoseSource {
+    /// 局部窗口跟踪成功
+    Track,
+    /// 全图搜索（重定位）成功
+    Global,
+    /// 本帧匹配失败，按运动模型外推
+    Predicted,
+    /// 尚未定位或已丢失
+    None,
+}
+
+impl PoseSource {
+    /// 来源名称
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Track => "track",
+            Self::Global => "global",
+            Self::Predicted => "predicted",
+            Self::None => "none",
+        }
+    }
+}
+
+/// 定位结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalizerPose {
+    /// 小地图中心在大地图上的 X 坐标（平滑后）
+    pub x: f64,
+    /// 小地图中心在大地图上的 Y 坐标（平滑后）
+    pub y: f64,
+    /// 小地图相对大地图的旋转角（度，图像坐标系顺时针为正，范围 `(-180, 180]`）
+    pub heading: f64,
+    /// 小地图像素到大地图像素的缩放
+    pub scale: f64,
+    /// 置信度（0-1），外推帧逐帧衰减
+    pub confidence: f64,
+    /// 本帧 RANSAC 内点数（失败时为 0）
+    pub inliers: i32,
+    /// 是否处于丢失状态
+    pub lost: bool,
+    /// 位姿来源
+    pub source: PoseSource,
+}
+
+/// 单帧观测
+#[derive(Debug, Clone, Copy)]
+struct Measurement {
+    x: f64,
+    y: f64,
+    heading: f64,
+    scale: f64,
+    inliers: i32,
+    confidence: f64,
+}
+
+/// 跟踪状态（α-β 滤波）
+#[derive(Debug, Clone, Copy)]
+struct TrackState {
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+    heading: f64,
+    scale: f64,
+    confidence: f64,
+}
+
+/// 将角度规整到 `(-180, 180]`
+fn wrap_degrees(angle: f64) -> f64 {
+    let wrapped = (angle + 180.0).rem_euclid(360.0) - 180.0;
+    if wrapped <= -180.0 {
+        wrapped + 360.0
+    } else {
+        wrapped
+    }
+}
+
+/// 连续小地图定位器
+///
+/// 说明：
+/// - 大地图特征只在创建时提取一次，并按网格分桶，跟踪时只匹配预测位置附近窗口内的特征；
+/// - 连续失败 `lost_after` 帧后判定丢失，改用全图搜索重定位；
+/// - 位置使用常速度 α-β 滤波平滑，朝向按最短角差平滑；
+/// - 几何模型为相似变换（`estimateAffinePartial2D`），可同时得到旋转与缩放。
+pub struct MinimapLocalizer {
+    config: LocalizerConfig,
+    map_width: i32,
+    map_height: i32,
+    points: Vec<Point2f>,
+    descriptors: Mat,
+    grid: Vec<Vec<u32>>,
+    grid_cols: usize,
+    grid_rows: usize,
+    state: Option<TrackState>,
+    misses: u32,
+    last: Option<LocalizerPose>,
+}
+
+impl std::fmt::Debug for MinimapLocalizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MinimapLocalizer")
+            .field("config", &self.config)
+            .field("map", &(self.map_width, self.map_height))
+            .field("keypoints", &self.points.len())
+            .field("last", &self.last)
+            .finish()
+    }
+}
+
+impl MinimapLocalizer {
+    /// 提取大地图特征并创建定位器
+    pub fn new(map: &Mat, config: LocalizerConfig) -> Result<Self, String> {
+        let (points, descriptors) = detect_features(map, config.kind)?;
+        if points.len() < 4 {
+            return Err("大地图特征点不足，无法定位".to_string());
+        }
+        let grid_cols = ((map.cols() as f64) / GRID_CELL).ceil().max(1.0) as usize;
+        let grid_rows = ((map.rows() as f64) / GRID_CELL).ceil().max(1.0) as usize;
+        let mut grid = vec![Vec::new(); grid_cols * grid_rows];
+        for (idx, point) in points.iter().enumerate() {
+            let col = ((point.x as f64 / GRID_CELL) as usize).min(grid_cols - 1);
+            let row = ((point.y as f64 / GRID_CELL) as usize).min(grid_rows - 1);
+            grid[row * grid_cols + col].push(idx as u32);
+        }
+        Ok(Self {
+            config,
+            map_width: map.cols(),
+            map_height: map.rows(),
+            points,
+            descriptors,
+            grid,
+            grid_cols,
+            grid_rows,
+            state: None,
+            misses: 0,
+            last: None,
+        })
+    }
+
+    /// 是否处于丢失状态（尚未定位也视为丢失）
+    pub fn is_lost(&self) -> bool {
+        self.state.is_none() || self.misses >= self.config.lost_after
+    }
+
+    /// 上一次 `update` 的结果
+    pub fn last_pose(&self) -> Option<LocalizerPose> {
+        self.last
+    }
+
+    /// 清空跟踪状态，下一帧走全图搜索
+    pub fn reset(&mut self) {
+        self.state = None;
+        self.misses = 0;
+        self.last = None;
+    }
+
+    /// 手动指定位置（例如传送后已知坐标），下一帧从该位置开始窗口跟踪
+    pub fn set_pose(&mut self, x: f64, y: f64, heading: Option<f64>) {
+        let (prev_heading, scale) = self
+            .state
+            .map(|state| (state.heading, state.scale))
+            .unwrap_or((0.0, 1.0));
+        self.state = Some(TrackState {
+            x,
+            y,
+            vx: 0.0,
+            vy: 0.0,
+            heading: heading.map(wrap_degrees).unwrap_or(prev_heading),
+            scale,
+            confidence: 0.0,
+        });
+        self.misses = 0;
+    }
+
+    /// 收集正方形窗口内的大地图特征点下标
+    fn window_indices(&self, cx: f64, cy: f64, radius: f64) -> Vec<u32> {
+        let cell_range = |center: f64, limit: usize| {
+            let start = ((center - radius) / GRID_CELL).floor().max(0.0) as usize;
+            let end = (((center + radius) / GRID_CELL).floor().max(0.0) as usize).min(limit - 1);
+            start..=end
+        };
+        let mut indices = Vec::new();
+        if cx + radius < 0.0
+            || cy + radius < 0.0
+            || cx - radius > self.map_width as f64
+            || cy - radius > self.map_height as f64
+        {
+            return indices;
+        }
+        for row in cell_range(cy, self.grid_rows) {
+            for col in cell_range(cx, self.grid_cols) {
+                indices.extend(self.grid[row * self.grid_cols + col].iter().filter(|idx| {
+                    let point = self.points[**idx as usize];
+                    (point.x as f64 - cx).abs() <= radius && (point.y as f64 - cy).abs() <= radius
+                }));
+            }
+        }
+        indices
+    }
+
+    /// 按下标挑选描述子行，组成新的描述子矩阵
+    fn select_descriptors(&self, indices: &[u32]) -> Result<Mat, String> {
+        let kind = self.config.kind;
+        let row_bytes = kind.row_bytes();
+        let source = self
+            .descriptors
+            .data_bytes()
+            .map_err(|e| format!("读取大地图描述子失败: {e}"))?;
+        let mut selected = Mat::new_rows_cols_with_default(
+            indices.len() as i32,
+            kind.descriptor_cols(),
+            kind.descriptor_type(),
+            core::Scalar::all(0.0),
+        )
+        .map_err(|e| format!("创建窗口描述子失败: {e}"))?;
+        let target = selected
+            .data_bytes_mut()
+            .map_err(|e| format!("写入窗口描述子失败: {e}"))?;
+        for (row, idx) in indices.iter().enumerate() {
+            let src = *idx as usize * row_bytes;
+            target[row * row_bytes..(row + 1) * row_bytes]
+                .copy_from_slice(&source[src..src + row_bytes]);
+        }
+        Ok(selected)
+    }
+
+    /// 将小地图特征匹配到大地图（`indices` 为空表示全图），估计相似变换并换算小地图中心位置
+    fn estimate(
+        &self,
+        query_points: &[Point2f],
+        query_descriptors: &Mat,
+        center: Point2f,
+        indices: Option<&[u32]>,
+    ) -> Result<Option<Measurement>, String> {
+        let window;
+        let train = match indices {
+            Some(indices) => {
+                if indices.len() < 4 {
+                    return Ok(None);
+                }
+                window = self.select_descriptors(indices)?;
+                &window
+            }
+            None => &self.descriptors,
+        };
+
+        let matcher = features2d::BFMatcher::create(self.config.kind.norm_type(), false)
+            .map_err(|e| format!("创建特征匹配器失败: {e}"))?;
+        let mut knn_matches = Vector::<Vector<DMatch>>::new();
+        matcher
+            .knn_train_match_def(query_descriptors, train, &mut knn_matches, 2)
+            .map_err(|e| format!("小地图 knn 匹配失败: {e}"))?;
+
+        let mut src_points = Vector::<Point2f>::new();
+        let mut dst_points = Vector::<Point2f>::new();
+        for pair in knn_matches.iter() {
+            let (Ok(first), Ok(second)) = (pair.get(0), pair.get(1)) else {
+                continue;
+            };
+            if first.distance >= second.distance * self.config.ratio {
+                continue;
+            }
+            let train_idx = match indices {
+                Some(indices) => indices
+                    .get(first.train_idx as usize)
+                    .map(|idx| *idx as usize),
+                None => Some(first.train_idx as usize),
+            };
+            let (Some(src), Some(dst)) = (
+                query_points.get(first.query_idx as usize),
+                train_idx.and_then(|idx| self.points.get(idx)),
+            ) else {
+                continue;
+            };
+            src_points.push(*src);
+            dst_points.push(*dst);
+        }
+
+        let good_matches = src_points.len() as i32;
+        if good_matches < self.config.min_inliers.max(3) {
+            return Ok(None);
+        }
+        let mut inlier_mask = Mat::default();
+        let transform = calib3d::estimate_affine_partial_2d(
+            &src_points,
+            &dst_points,
+            &mut inlier_mask,
+            calib3d::RANSAC,
+            3.0,
+            2000,
+            0.99,
+            10,
+        )
+        .map_err(|e| format!("估计相似变换失败: {e}"))?;
+        if transform.empty() {
+            return Ok(None);
+        }
+        let inliers =
+            core::count_non_zero(&inlier_mask).map_err(|e| format!("统计内点失败: {e}"))?;
+        if inliers < self.config.min_inliers {
+            return Ok(None);
+        }
+
+        let at = |row: i32, col: i32| -> Result<f64, String> {
+            transform
+                .at_2d::<f64>(row, col)
+                .copied()
+                .map_err(|e| format!("读取变换矩阵失败: {e}"))
+        };
+        let (a, b, tx) = (at(0, 0)?, at(1, 0)?, at(0, 2)?);
+        let (c, d, ty) = (at(0, 1)?, at(1, 1)?, at(1, 2)?);
+        let scale = a.hypot(b);
+        if !scale.is_finite() || scale < self.config.min_scale || scale > self.config.max_scale {
+            return Ok(None);
+        }
+        let (cx, cy) = (center.x as f64, center.y as f64);
+        let inlier_ratio = inliers as f64 / good_matches as f64;
+        Ok(Some(Measurement {
+            x: a * cx + c * cy + tx,
+            y: b * cx + d * cy + ty,
+            heading: wrap_degrees(b.atan2(a).to_degrees()),
+            scale,
+            inliers,
+            confidence: (inlier_ratio
+                * (inliers as f64 / (self.config.min_inliers.max(1) as f64 * 3.0)).min(1.0))
+            .clamp(0.0, 1.0),
+        }))
+    }
+
+    /// 输入一帧小地图，返回平滑后的位姿
+    ///
+    /// # 参数
+    /// - `minimap`: 小地图图像（建议先经 `preprocessMinimapForSift` 处理），中心即角色位置
+    ///
+    /// # 返回值
+    /// 跟踪或重定位成功时返回滤波后的位姿；失败时按运动模型外推并衰减置信度，
+    /// 丢失后 `lost = true` 并在后续帧持续尝试全图搜索。
+    pub fn update(&mut self, minimap: &Mat) -> Result<LocalizerPose, String> {
+        let (query_points, query_descriptors) = detect_features(minimap, self.config.kind)?;
+        let center = Point2f::new(minimap.cols() as f32 / 2.0, minimap.rows() as f32 / 2.0);
+        let has_features = query_points.len() >= 4;
+
+        let mut measurement = None;
+        let mut source = PoseSource::Track;
+        if has_features
+            && !self.is_lost()
+            && let Some(state) = self.state
+        {
+            let growth = (self.misses + 1) as f64;
+            let (px, py) = (state.x + state.vx, state.y + state.vy);
+            let indices = self.window_indices(px, py, self.config.search_radius * growth);
+            measurement = self
+                .estimate(&query_points, &query_descriptors, center, Some(&indices))?
+                .filter(|m| (m.x - px).hypot(m.y - py) <= self.config.max_jump * growth);
+        }
+        if measurement.is_none() && has_features {
+            let lost_after_this_miss =
+                self.state.is_none() || self.misses + 1 >= self.config.lost_after;
+            if lost_after_this_miss {
+                source = PoseSource::Global;
+                measurement = self.estimate(&query_points, &query_descriptors, center, None)?;
+            }
+        }
+
+        let pose = match measurement {
+            Some(m) => {
+                let state = match (source, self.state) {
+                    (PoseSource::Track, Some(prev)) => {
+                        let (px, py) = (prev.x + prev.vx, prev.y + prev.vy);
+                        let (rx, ry) = (m.x - px, m.y - py);
+                        let alpha = self.config.alpha.clamp(0.0, 1.0);
+                        let beta = self.config.beta.clamp(0.0, 1.0);
+                        TrackState {
+                            x: px + alpha * rx,
+                            y: py + alpha * ry,
+                            vx: prev.vx + beta * rx,
+                            vy: prev.vy + beta * ry,
+                            heading: wrap_degrees(
+                                prev.heading + alpha * wrap_degrees(m.heading - prev.heading),
+                            ),
+                            scale: prev.scale + alpha * (m.scale - prev.scale),
+                            confidence: m.confidence,
+                        }
+                    }
+                    _ => TrackState {
+                        x: m.x,
+                        y: m.y,
+                        vx: 0.0,
+                        vy: 0.0,
+                        heading: m.heading,
+                        scale: m.scale,
+                        confidence: m.confidence,
+                    },
+                };
+                self.state = Some(state);
+                self.misses = 0;
+                LocalizerPose {
+                    x: state.x,
+                    y: state.y,
+                    heading: state.heading,
+                    scale: state.scale,
+                    confidence: state.confidence,
+                    inliers: m.inliers,
+                    lost: false,
+                    source,
+                }
+            }
+            None => {
+                self.misses = self.misses.saturating_add(1);
+                let lost = self.is_lost();
+                match self.state.as_mut() {
+                    Some(state) if !lost => {
+                        state.x += state.vx;
+                        state.y += state.vy;
+                        state.vx *= 0.5;
+                        state.vy *= 0.5;
+                        state.confidence *= 0.5;
+                        LocalizerPose {
+                            x: state.x,
+                            y: state.y,
+                            heading: state.heading,
+                            scale: state.scale,
+                            confidence: state.confidence,
+                            inliers: 0,
+                            lost: false,
+                            source: PoseSource::Predicted,
+                        }
+                    }
+                    state => {
+                        let (x, y, heading, scale) = state
+                            .map(|s| (s.x, s.y, s.heading, s.scale))
+                            .unwrap_or((0.0, 0.0, 0.0, 1.0));
+                        LocalizerPose {
+                            x,
+                            y,
+                            heading,
+                            scale,
+                            confidence: 0.0,
+                            inliers: 0,
+                            lost: true,
+                            source: PoseSource::None,
+                        }
+                    }
+                }
+            }
+        };
+        self.last = Some(pose);
+        Ok(pose)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submodules::test_support::ShapeTexture;
+    use opencv::{
+        core::{CV_8UC3, Rect, Scalar, Size},
+        imgproc,
+    };
+
+    /// 参考大地图的纹理参数
+    const TEXTURE: ShapeTexture = ShapeTexture {
+        background: 30.0,
+        area_per_shape: 1500,
+        rect_bound: (40, 40),
+        radius_bound: 18,
+        min_channel: 0,
+    };
+
+    /// 以 (cx, cy) 为中心裁出小地图，并按 `angle` 度旋转
+    fn minimap_at(map: &Mat, cx: i32, cy: i32, angle: f64) -> Mat {
+        let size = 180;
+        let crop = Mat::roi(map, Rect::new(cx - size / 2, cy - size / 2, size, size))
+            .unwrap()
+            .try_clone()
+            .unwrap();
+        if angle == 0.0 {
+            return crop;
+        }
+        let center = Point2f::new(size as f32 / 2.0, size as f32 / 2.0);
+        let matrix = imgproc::get_rotation_matrix_2d(center, angle, 1.0).unwrap();
+        let mut rotated = Mat::default();
+        imgproc::warp_affine(
+            &crop,
+            &mut rotated,
+            &matrix,
+            Size::new(size, size),
+            imgproc::INTER_LINEAR,
+            core::BORDER_CONSTANT,
+            Scalar::all(0.0),
+        )
+        .unwrap();
+        rotated
+    }
+
+    fn assert_near(pose: &LocalizerPose, x: f64, y: f64, tolerance: f64) {
+        assert!(
+            (pose.x - x).abs() <= tolerance && (pose.y - y).abs() <= tolerance,
+            "pose ({:.1}, {:.1}) != ({x}, {y})",
+            pose.x,
+            pose.y
+        );
+    }
+
+    #[test]
+    fn tracks_a_path_with_window_search() {
+        let map = TEXTURE.render(7, 1000, 800);
+        let mut localizer = MinimapLocalizer::new(&map, LocalizerConfig::default()).unwrap();
+
+        let first = localizer.update(&minimap_at(&map, 200, 200, 0.0)).unwrap();
+        assert_eq!(first.source, PoseSource::Global);
+        assert_near(&first, 200.0, 200.0, 2.0);
+
+        // 匀速运动：滤波初期有滞后，速度收敛后贴近真实位置
+        let mut pose = first;
+        for step in 1..=10 {
+            let (x, y) = (200 + step * 30, 200 + step * 20);
+            pose = localizer.update(&minimap_at(&map, x, y, 0.0)).unwrap();
+            assert_eq!(pose.source, PoseSource::Track, "step {step}");
+            assert!(!pose.lost);
+            assert!(pose.confidence > 0.0);
+            assert_near(&pose, x as f64, y as f64, 16.0);
+        }
+        assert_near(&pose, 500.0, 400.0, 3.0);
+    }
+
+    #[test]
+    fn estimates_heading_of_rotated_minimap() {
+        let map = TEXTURE.render(7, 800, 600);
+        let mut localizer = MinimapLocalizer::new(&map, LocalizerConfig::default()).unwrap();
+        let pose = localizer.update(&minimap_at(&map, 400, 300, 30.0)).unwrap();
+        assert_near(&pose, 400.0, 300.0, 3.0);
+        assert!(
+            (pose.heading - 30.0).abs() < 2.0,
+            "heading {}",
+            pose.heading
+        );
+        assert!((pose.scale - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn relocalizes_globally_after_being_lost() {
+        let map = TEXTURE.render(7, 1000, 800);
+        let config = LocalizerConfig {
+            lost_after: 2,
+            search_radius: 200.0,
+            ..LocalizerConfig::default()
+        };
+        let mut localizer = MinimapLocalizer::new(&map, config).unwrap();
+        localizer.update(&minimap_at(&map, 200, 200, 0.0)).unwrap();
+
+        let blank = Mat::new_rows_cols_with_default(180, 180, CV_8UC3, Scalar::all(0.0)).unwrap();
+        let coast = localizer.update(&blank).unwrap();
+        assert_eq!(coast.source, PoseSource::Predicted);
+        assert!(!coast.lost);
+        let lost = localizer.update(&blank).unwrap();
+        assert!(lost.lost);
+        assert_eq!(lost.source, PoseSource::None);
+
+        // 远离窗口的位置只能靠全图搜索找回
+        let found = localizer.update(&minimap_at(&map, 780, 600, 0.0)).unwrap();
+        assert_eq!(found.source, PoseSource::Global);
+        assert!(!found.lost);
+        assert_near(&found, 780.0, 600.0, 2.0);
+    }
+
+    #[test]
+    fn wrap_degrees_stays_in_half_open_range() {
+        assert_eq!(wrap_degrees(190.0), -170.0);
+        assert_eq!(wrap_degrees(-180.0), 180.0);
+        assert_eq!(wrap_degrees(540.0), 180.0);
+        assert_eq!(wrap_degrees(-30.0), -30.0);
+    }
+}
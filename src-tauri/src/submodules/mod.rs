@@ -2,14 +2,28 @@ pub mod async_tokio;
 pub mod color;
 pub mod color_match;
 pub mod d3d11;
+pub mod debug_report;
 pub mod dll_call;
+pub mod feature_library;
+pub mod frame_diff;
+pub mod frame_source;
 pub mod fx;
+pub mod glyph;
+pub mod hash_index;
 pub mod hotkey;
 pub mod input;
+pub mod jsabort;
+pub mod jsdebugreport;
 pub mod jsdnn;
+pub mod jsfeature;
+pub mod jsglyph;
+pub mod jshash;
+pub mod jslocalizer;
 pub mod jsmat;
+pub mod jsmosaic;
 pub mod jstimer;
 pub mod logger;
+pub mod minimap_localizer;
 pub mod mono_depth;
 pub mod ocr;
 pub mod predict_rotation;
@@ -18,11 +32,18 @@ pub mod route;
 pub mod script;
 pub mod script_builtin;
 pub mod script_console;
+pub mod script_log;
 pub mod script_mcp;
 pub mod script_module;
+pub mod script_net;
+pub mod script_profile;
 pub mod script_vision;
 pub mod setvol;
+#[cfg(test)]
+pub(crate) mod test_support;
 pub mod tpl;
 pub mod tpl_match;
 pub mod util;
+pub mod wait_engine;
 pub mod win;
+pub mod world_mosaic;
@@ -13,10 +13,13 @@ use ort::{
     session::{Session, builder::AutoDevicePolicy},
     value::TensorRef,
 };
+use regex::Regex;
 use std::{
+    collections::HashSet,
     fs,
     path::{Path, PathBuf},
     sync::{Mutex, OnceLock},
+    time::Instant,
 };
 
 /// OCR 资源默认 CDN 根地址。
@@ -43,6 +46,10 @@ const DEFAULT_UNCLIP_RATIO: f32 = 2.0;
 const REC_IMAGE_HEIGHT: i32 = 48;
 /// 识别模型输入最大宽度。
 const REC_IMAGE_WIDTH: i32 = 320;
+/// 默认识别/分类批大小。
+const DEFAULT_MAX_BATCH_SIZE: usize = 8;
+/// 同一识别批次内最宽与最窄输入的宽度比上限，超出则另起一批以减少补边计算。
+const REC_BATCH_MAX_WIDTH_RATIO: f32 = 1.5;
 /// 检测模型均值。
 const DET_MEAN_VALUES: [f32; 3] = [0.485 * 255.0, 0.456 * 255.0, 0.406 * 255.0];
 /// 检测模型归一化系数。
@@ -61,7 +68,10 @@ const REC_NORM_VALUES: [f32; 3] = [1.0 / 127.5, 1.0 / 127.5, 1.0 / 127.5];
 pub struct OcrInitConfig {
     pub local_root_dir: Option<PathBuf>,
     pub cdn_base_url: Option<String>,
+    /// 推理线程数（intra/inter op）
     pub num_thread: i32,
+    /// 识别/分类单次推理的最大文本块数，1 表示逐块推理
+    pub max_batch_size: usize,
 }
 
 impl Default for OcrInitConfig {
@@ -70,6 +80,7 @@ impl Default for OcrInitConfig {
             local_root_dir: None,
             cdn_base_url: None,
             num_thread: 2,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
         }
     }
 }
@@ -80,6 +91,11 @@ struct OcrRuntime {
     cls_session: Option<Session>,
     rec_session: Session,
     dictionary: Vec<String>,
+    /// 创建会话时使用的资源目录、CDN 地址与线程数，相同参数重复初始化时直接复用会话
+    root_dir: PathBuf,
+    cdn_base_url: String,
+    num_thread: i32,
+    max_batch_size: usize,
 }
 
 /// 图像缩放参数。
@@ -93,6 +109,112 @@ struct ScaleParam {
 /// 检测框点集。
 type TextBoxPoints = [Point; 4];
 
+/// OCR 识别选项。
+#[derive(Debug, Clone, Default)]
+pub struct OcrOptions {
+    /// 是否输出逐字符偏移
+    pub char_offsets: bool,
+    /// 允许输出的字符集合；设置后解码前屏蔽其余字符的 logits
+    pub whitelist: Option<HashSet<char>>,
+    /// 文本块识别结果必须满足的正则，不满足的文本块被丢弃
+    pub pattern: Option<Regex>,
+}
+
+/// 查询预置字符集（`digits` / `latin` / `punct`，`punct` 也可写作 `punctuation`）。
+pub fn named_charset(name: &str) -> Option<&'static str> {
+    match name {
+        "digits" => Some("0123456789"),
+        "latin" => Some("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"),
+        "punct" | "punctuation" => {
+            Some("!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~，。、：；！？（）《》【】「」“”‘’…—·％")
+        }
+        _ => None,
+    }
+}
+
+/// 由预置字符集名与自定义字符构建白名单。
+///
+/// 说明：`names` 只接受预置字符集名，未知名称返回错误；`custom` 中的字符按字面加入；
+/// 结果为空时返回错误，避免屏蔽全部类别后静默返回空文本。
+pub fn build_whitelist<S: AsRef<str>>(names: &[S], custom: &str) -> Result<HashSet<char>, String> {
+    let mut whitelist: HashSet<char> = custom.chars().collect();
+    for name in names {
+        let name = name.as_ref();
+        let charset = named_charset(name)
+            .ok_or_else(|| format!("未知的 OCR 字符集: {name}（可选 digits / latin / punct）"))?;
+        whitelist.extend(charset.chars());
+    }
+    if whitelist.is_empty() {
+        return Err("OCR 字符白名单为空".to_string());
+    }
+    Ok(whitelist)
+}
+
+/// 按白名单生成字典类别掩码（下标 0 的 CTC blank 始终保留）。
+fn class_mask(dictionary: &[String], whitelist: &HashSet<char>) -> Vec<bool> {
+    dictionary
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            index == 0 || (!entry.is_empty() && entry.chars().all(|ch| whitelist.contains(&ch)))
+        })
+        .collect()
+}
+
+/// 单个识别文本块。
+#[derive(Debug, Clone)]
+pub struct OcrTextBlock {
+    /// 旋转文本框四点（左上、右上、右下、左下），原图坐标
+    pub points: [Point; 4],
+    /// 检测框得分
+    pub det_score: f32,
+    pub text: String,
+    /// CTC 解码的字符平均置信度
+    pub confidence: f32,
+    /// 每个字符中心沿文字方向相对块起点的像素偏移（仅在 `OcrOptions::char_offsets` 时输出）
+    pub char_offsets: Option<Vec<f32>>,
+    /// 所在行号（阅读顺序）
+    pub line: usize,
+}
+
+impl OcrTextBlock {
+    /// 四点框的轴对齐外接矩形
+    pub fn bbox(&self) -> Rect {
+        box_bounding_rect(&self.points)
+    }
+}
+
+/// 按阅读顺序分组后的文本行。
+#[derive(Debug, Clone)]
+pub struct OcrLine {
+    pub text: String,
+    /// 行内文本块在 `OcrResult::blocks` 中的下标（从左到右）
+    pub blocks: Vec<usize>,
+    /// 行外接矩形
+    pub bbox: Rect,
+}
+
+/// OCR 各阶段耗时（毫秒），用于性能分析。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OcrTimings {
+    /// 检测（含补边、推理与裁剪）
+    pub det_ms: f64,
+    /// 方向分类（含 180 度旋转）
+    pub cls_ms: f64,
+    /// 识别（含预处理、推理与 CTC 解码）
+    pub rec_ms: f64,
+    /// 识别模型推理次数
+    pub rec_batches: usize,
+}
+
+/// 结构化 OCR 结果：文本块按阅读顺序排列。
+#[derive(Debug, Clone)]
+pub struct OcrResult {
+    pub blocks: Vec<OcrTextBlock>,
+    pub lines: Vec<OcrLine>,
+    pub timings: OcrTimings,
+}
+
 unsafe impl Send for OcrRuntime {}
 
 /// OCR 全局单例。
@@ -615,8 +737,8 @@ fn mat_to_det_array(input: &Mat, scale: &ScaleParam) -> Result<Array4<f32>, Stri
     .map_err(|e| format!("构造检测输入张量失败: {e}"))
 }
 
-/// 将识别输入图像转成 C++ 同款动态宽度 NCHW 张量。
-fn mat_to_rec_array(input: &Mat, image_shape: (i32, i32, i32)) -> Result<Array4<f32>, String> {
+/// 将识别输入图像按 C++ 同款动态宽度缩放并归一化，返回 CHW 数据与缩放后宽度。
+fn mat_to_rec_input(input: &Mat, image_shape: (i32, i32, i32)) -> Result<(Vec<f32>, i32), String> {
     let (_, img_h, _img_w) = image_shape;
     let normalized = normalize_input_mat(input)?;
     let height = normalized.rows();
@@ -641,12 +763,64 @@ fn mat_to_rec_array(input: &Mat, image_shape: (i32, i32, i32)) -> Result<Array4<
     .map_err(|e| format!("识别图像缩放失败: {e}"))?;
 
     let data = substract_mean_normalize(&resized, REC_MEAN_VALUES, REC_NORM_VALUES)?;
-    Array4::from_shape_vec((1, 3, img_h as usize, resized_w as usize), data)
-        .map_err(|e| format!("构造识别输入张量失败: {e}"))
+    Ok((data, resized_w))
+}
+
+/// 按缩放后宽度将识别输入分批。
+///
+/// 规则：
+/// - 按宽度升序排列（宽度相同保持原顺序），相近宽高比的文本块落在同一批；
+/// - 批内数量达到 `max_batch` 或宽度超过批内最窄输入的 `REC_BATCH_MAX_WIDTH_RATIO` 倍时另起一批。
+fn plan_rec_batches(widths: &[i32], max_batch: usize) -> Vec<Vec<usize>> {
+    let max_batch = max_batch.max(1);
+    let mut order: Vec<usize> = (0..widths.len()).collect();
+    order.sort_by_key(|&idx| widths[idx]);
+
+    let mut batches: Vec<Vec<usize>> = Vec::new();
+    for idx in order {
+        match batches.last_mut() {
+            Some(batch)
+                if batch.len() < max_batch
+                    && widths[idx] as f32
+                        <= widths[batch[0]].max(1) as f32 * REC_BATCH_MAX_WIDTH_RATIO =>
+            {
+                batch.push(idx)
+            }
+            _ => batches.push(vec![idx]),
+        }
+    }
+    batches
 }
 
-/// 将分类输入图像转成 C++ 同款固定尺寸 NCHW 张量。
-fn mat_to_cls_array(input: &Mat, image_shape: (i32, i32, i32)) -> Result<Array4<f32>, String> {
+/// 将同批识别输入拼成 NCHW 张量，右侧以 0（归一化后的中灰）补齐到批内最大宽度。
+fn build_rec_batch(items: &[(&[f32], i32)], img_h: i32) -> Result<Array4<f32>, String> {
+    let height = img_h.max(1) as usize;
+    let max_width = items
+        .iter()
+        .map(|(_, width)| *width)
+        .max()
+        .unwrap_or(1)
+        .max(1) as usize;
+    let mut batch = Array4::<f32>::zeros((items.len(), 3, height, max_width));
+    let dst = batch
+        .as_slice_mut()
+        .ok_or_else(|| "识别批次内存非连续".to_string())?;
+    for (i, (data, width)) in items.iter().enumerate() {
+        let width = (*width).max(1) as usize;
+        if data.len() != 3 * height * width {
+            return Err("识别输入数据长度异常".to_string());
+        }
+        for row in 0..3 * height {
+            let src = &data[row * width..(row + 1) * width];
+            let offset = (i * 3 * height + row) * max_width;
+            dst[offset..offset + width].copy_from_slice(src);
+        }
+    }
+    Ok(batch)
+}
+
+/// 将分类输入图像按 C++ 同款固定尺寸缩放并归一化，返回 CHW 数据。
+fn mat_to_cls_input(input: &Mat, image_shape: (i32, i32, i32)) -> Result<Vec<f32>, String> {
     let (_, img_h, img_w) = image_shape;
     let scale = img_h as f32 / input.rows() as f32;
     let mut angle_width = (input.cols() as f32 * scale) as i32;
@@ -683,26 +857,44 @@ fn mat_to_cls_array(input: &Mat, image_shape: (i32, i32, i32)) -> Result<Array4<
             .map_err(|e| format!("拷贝分类缩放图失败: {e}"))?;
     }
 
-    let data = substract_mean_normalize(&fitted, REC_MEAN_VALUES, REC_NORM_VALUES)?;
-    Array4::from_shape_vec((1, 3, img_h as usize, img_w as usize), data)
-        .map_err(|e| format!("构造分类输入张量失败: {e}"))
+    substract_mean_normalize(&fitted, REC_MEAN_VALUES, REC_NORM_VALUES)
+}
+
+/// CTC 解码结果。
+#[derive(Debug, Clone, PartialEq)]
+struct CtcDecoding {
+    text: String,
+    /// 输出字符得分的均值
+    score: f32,
+    /// 每个输出字符所在的时间步
+    steps: Vec<usize>,
 }
 
 /// 计算文本行的 CTC 解码结果。
+///
+/// 说明：传入 `mask` 时，被屏蔽的类别不参与逐帧 argmax（先屏蔽再合并重复与去 blank），
+/// 超出掩码长度的类别同样视为屏蔽。
 fn decode_ctc(
     prediction: &[f32],
     time_dim: usize,
     class_dim: usize,
     dictionary: &[String],
-) -> (String, f32) {
+    mask: Option<&[bool]>,
+) -> CtcDecoding {
     let mut last_index = 0usize;
     let mut chars = Vec::new();
     let mut scores = Vec::new();
+    let mut steps = Vec::new();
 
     for t in 0..time_dim {
         let mut max_index = 0usize;
         let mut max_score = f32::NEG_INFINITY;
         for c in 0..class_dim {
+            if let Some(mask) = mask
+                && !mask.get(c).copied().unwrap_or(false)
+            {
+                continue;
+            }
             let score = prediction[t * class_dim + c];
             if score > max_score {
                 max_score = score;
@@ -717,6 +909,7 @@ fn decode_ctc(
         if let Some(character) = dictionary.get(max_index) {
             chars.push(character.clone());
             scores.push(max_score);
+            steps.push(t);
         }
         last_index = max_index;
     }
@@ -727,7 +920,7 @@ fn decode_ctc(
     } else {
         scores.iter().sum::<f32>() / scores.len() as f32
     };
-    (text, score)
+    CtcDecoding { text, score, steps }
 }
 
 /// 从检测概率图中提取文本框。
@@ -935,6 +1128,13 @@ fn detect_and_crop_text_blocks(
 
         for (box_points, det_score) in boxes {
             let crop = get_rotate_crop_image(&padding_src, &box_points)?;
+            // 检测框基于补边后的图像，输出前换算回原图坐标。
+            let box_points = box_points.map(|point| {
+                Point::new(
+                    (point.x - padding).clamp(0, original_size.width - 1),
+                    (point.y - padding).clamp(0, original_size.height - 1),
+                )
+            });
             crops.push((box_points, det_score, crop));
         }
     } else {
@@ -966,18 +1166,31 @@ fn detect_and_crop_text_blocks(
     Ok(crops)
 }
 
-/// 将裁剪块解码为最终文本。
+/// 将裁剪块识别为文本块（保持检测顺序，`line` 暂为 0）。
+///
+/// 说明：分类与识别按 `max_batch_size` 分批推理，识别批次按宽度分组并补齐到批内最大宽度；
+/// 各阶段耗时累加到 `timings`。
 fn recognize_text_blocks(
     blocks: Vec<(TextBoxPoints, f32, Mat)>,
     runtime: &mut OcrRuntime,
-) -> Result<String, String> {
+    options: &OcrOptions,
+    timings: &mut OcrTimings,
+) -> Result<Vec<OcrTextBlock>, String> {
     let mut crops: Vec<(TextBoxPoints, f32, Mat)> = blocks;
+    let max_batch = runtime.max_batch_size.max(1);
+    let mut rotated_180 = false;
+    let cls_start = Instant::now();
     if let Some(cls_session) = runtime.cls_session.as_mut() {
         let cls_output_name = first_output_name(cls_session, "分类模型")?;
         let mut angles = Vec::with_capacity(crops.len());
 
-        for (_, _, crop) in &crops {
-            let cls_array = mat_to_cls_array(crop, (3, 48, 192))?;
+        for chunk in crops.chunks(max_batch) {
+            let mut data = Vec::with_capacity(chunk.len() * 3 * 48 * 192);
+            for (_, _, crop) in chunk {
+                data.extend(mat_to_cls_input(crop, (3, 48, 192))?);
+            }
+            let cls_array = Array4::from_shape_vec((chunk.len(), 3, 48, 192), data)
+                .map_err(|e| format!("构造分类输入张量失败: {e}"))?;
             let cls_outputs = cls_session
                 .run(ort::inputs! {
                     "x" => TensorRef::from_array_view(cls_array.view())
@@ -992,16 +1205,18 @@ fn recognize_text_blocks(
                 .map_err(|e| format!("提取分类输出失败: {e}"))?
                 .to_owned();
             let shape = cls_array.shape().to_vec();
-            let angle_index = if shape.len() == 2
-                && shape[0] == 1
-                && shape[1] >= 2
-                && cls_array[[0, 1]] > cls_array[[0, 0]]
-            {
-                1
-            } else {
-                0
-            };
-            angles.push(angle_index);
+            for i in 0..chunk.len() {
+                let angle_index = if shape.len() == 2
+                    && shape[0] == chunk.len()
+                    && shape[1] >= 2
+                    && cls_array[[i, 1]] > cls_array[[i, 0]]
+                {
+                    1
+                } else {
+                    0
+                };
+                angles.push(angle_index);
+            }
         }
 
         if !angles.is_empty() {
@@ -1009,6 +1224,7 @@ fn recognize_text_blocks(
             let half_percent = angles.len() as f64 / 2.0;
             let most_angle_index = if sum < half_percent { 0 } else { 1 };
             if most_angle_index == 1 {
+                rotated_180 = true;
                 for (_, _, crop) in &mut crops {
                     let mut rotated = Mat::default();
                     core::rotate(crop, &mut rotated, core::RotateFlags::ROTATE_180.into())
@@ -1018,11 +1234,34 @@ fn recognize_text_blocks(
             }
         }
     }
-
-    let mut lines = Vec::new();
-    for (box_points, det_score, crop) in crops {
-        let rec_array = mat_to_rec_array(&crop, (3, REC_IMAGE_HEIGHT, REC_IMAGE_WIDTH))?;
-        let rec_output_name = first_output_name(&runtime.rec_session, "识别模型")?;
+    timings.cls_ms += cls_start.elapsed().as_secs_f64() * 1000.0;
+
+    let rec_start = Instant::now();
+    let mask = options
+        .whitelist
+        .as_ref()
+        .map(|whitelist| class_mask(&runtime.dictionary, whitelist));
+    let inputs = crops
+        .iter()
+        .map(|(_, _, crop)| mat_to_rec_input(crop, (3, REC_IMAGE_HEIGHT, REC_IMAGE_WIDTH)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let widths: Vec<i32> = inputs.iter().map(|(_, width)| *width).collect();
+    let rec_output_name = first_output_name(&runtime.rec_session, "识别模型")?;
+    // 每个裁剪块的解码结果与有效时间步数，按检测顺序存放。
+    let mut decoded: Vec<Option<(CtcDecoding, usize)>> = vec![None; crops.len()];
+
+    for batch in plan_rec_batches(&widths, max_batch) {
+        let items: Vec<(&[f32], i32)> = batch
+            .iter()
+            .map(|&idx| (inputs[idx].0.as_slice(), inputs[idx].1))
+            .collect();
+        let batch_width = items
+            .iter()
+            .map(|(_, width)| *width)
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let rec_array = build_rec_batch(&items, REC_IMAGE_HEIGHT)?;
         let rec_outputs = runtime
             .rec_session
             .run(ort::inputs! {
@@ -1030,6 +1269,7 @@ fn recognize_text_blocks(
                     .map_err(|e| format!("创建识别输入张量失败: {e}"))?
             })
             .map_err(|e| format!("识别模型推理失败: {e}"))?;
+        timings.rec_batches += 1;
         let rec_output = rec_outputs
             .get(rec_output_name.as_str())
             .ok_or_else(|| format!("识别模型没有输出: {rec_output_name}"))?;
@@ -1038,36 +1278,167 @@ fn recognize_text_blocks(
             .map_err(|e| format!("提取识别输出失败: {e}"))?
             .to_owned();
         let shape = rec_array.shape().to_vec();
-        if shape.len() != 3 {
+        if shape.len() != 3 || shape[0] != batch.len() || shape[1] == 0 || shape[2] == 0 {
             return Err("识别输出维度异常".to_string());
         }
 
         let rec_view = rec_array
             .as_slice()
             .ok_or_else(|| "识别输出内存非连续".to_string())?;
-        let (time_dim, class_dim, offset) = if shape[2] >= shape[1] {
-            (shape[1], shape[2], 0usize)
+        let (time_dim, class_dim) = if shape[2] >= shape[1] {
+            (shape[1], shape[2])
         } else {
-            (shape[2], shape[1], 0usize)
+            (shape[2], shape[1])
         };
-        let (decoded, rec_score) = decode_ctc(
-            &rec_view[offset..],
-            time_dim,
-            class_dim,
-            &runtime.dictionary,
-        );
+        let item_stride = time_dim * class_dim;
+        for (i, &idx) in batch.iter().enumerate() {
+            // 补边区域只产生 blank，按真实宽度截取有效时间步，保证字符偏移按原宽度换算。
+            let item_time = ((time_dim as f32 * widths[idx] as f32 / batch_width as f32).ceil()
+                as usize)
+                .clamp(1, time_dim.max(1));
+            let offset = i * item_stride;
+            let result = decode_ctc(
+                &rec_view[offset..offset + item_time * class_dim],
+                item_time,
+                class_dim,
+                &runtime.dictionary,
+                mask.as_deref(),
+            );
+            decoded[idx] = Some((result, item_time));
+        }
+    }
+
+    let mut results = Vec::with_capacity(crops.len());
+    for ((box_points, det_score, _), decoded) in crops.into_iter().zip(decoded) {
+        let Some((decoded, time_dim)) = decoded else {
+            continue;
+        };
+        if let Some(pattern) = &options.pattern
+            && !pattern.is_match(&decoded.text)
+        {
+            continue;
+        }
+
+        let char_offsets = options
+            .char_offsets
+            .then(|| char_offsets_along_box(&box_points, &decoded.steps, time_dim, rotated_180));
+        results.push(OcrTextBlock {
+            points: box_points,
+            det_score,
+            text: decoded.text,
+            confidence: decoded.score,
+            char_offsets,
+            line: 0,
+        });
+    }
+    timings.rec_ms += rec_start.elapsed().as_secs_f64() * 1000.0;
+    Ok(results)
+}
+
+/// 将字符所在时间步换算为沿文字方向、相对文本块起点的像素偏移（字符中心）。
+///
+/// 说明：
+/// - 识别输入按比例缩放，时间步与裁剪图宽度近似线性对应；
+/// - 竖排裁剪（高 >= 1.5 倍宽）会被转置，此时文字方向为左边缘 `points[0] -> points[3]`；
+/// - 分类模型判定整体旋转 180 度时，偏移从另一端起算后取反。
+fn char_offsets_along_box(
+    points: &TextBoxPoints,
+    steps: &[usize],
+    time_dim: usize,
+    rotated_180: bool,
+) -> Vec<f32> {
+    let edge =
+        |a: Point, b: Point| (((a.x - b.x) as f32).powi(2) + ((a.y - b.y) as f32).powi(2)).sqrt();
+    let width = edge(points[0], points[1]);
+    let height = edge(points[0], points[3]);
+    let length = if height >= width * 1.5 { height } else { width };
+    if time_dim == 0 {
+        return Vec::new();
+    }
+    steps
+        .iter()
+        .map(|&step| {
+            let ratio = (step as f32 + 0.5) / time_dim as f32;
+            let ratio = if rotated_180 { 1.0 - ratio } else { ratio };
+            ratio * length
+        })
+        .collect()
+}
 
-        lines.push((box_points, det_score, decoded, rec_score));
+/// 计算四点框的轴对齐外接矩形。
+fn box_bounding_rect(points: &TextBoxPoints) -> Rect {
+    let left = points.iter().map(|p| p.x).min().unwrap_or(0);
+    let right = points.iter().map(|p| p.x).max().unwrap_or(0);
+    let top = points.iter().map(|p| p.y).min().unwrap_or(0);
+    let bottom = points.iter().map(|p| p.y).max().unwrap_or(0);
+    Rect::new(left, top, right - left + 1, bottom - top + 1)
+}
+
+/// 将文本块整理为阅读顺序并分组成行，返回行列表。
+///
+/// 规则：
+/// - 按外接矩形中心 y 升序扫描，中心 y 与当前行均值相差不超过两者较小高度的一半时并入该行；
+/// - 行内按左边缘 x 升序排列，块的 `line` 字段写入行号；
+/// - 行文本为行内各块文本以空格连接。
+fn order_text_blocks(blocks: &mut Vec<OcrTextBlock>) -> Vec<OcrLine> {
+    struct LineGroup {
+        center_y: f32,
+        height: f32,
+        members: Vec<usize>,
     }
 
-    let mut result = String::new();
-    for (index, (_box_points, _det_score, text, _rec_score)) in lines.iter().enumerate() {
-        if index > 0 {
-            result.push('\n');
+    let rects: Vec<Rect> = blocks.iter().map(OcrTextBlock::bbox).collect();
+    let center_y = |rect: &Rect| rect.y as f32 + rect.height as f32 / 2.0;
+    let mut order: Vec<usize> = (0..blocks.len()).collect();
+    order.sort_by(|&a, &b| {
+        center_y(&rects[a])
+            .total_cmp(&center_y(&rects[b]))
+            .then(rects[a].x.cmp(&rects[b].x))
+    });
+
+    let mut groups: Vec<LineGroup> = Vec::new();
+    for index in order {
+        let rect = &rects[index];
+        let (cy, height) = (center_y(rect), rect.height as f32);
+        match groups.last_mut() {
+            Some(group) if (cy - group.center_y).abs() <= 0.5 * height.min(group.height) => {
+                let count = group.members.len() as f32;
+                group.center_y = (group.center_y * count + cy) / (count + 1.0);
+                group.height = (group.height * count + height) / (count + 1.0);
+                group.members.push(index);
+            }
+            _ => groups.push(LineGroup {
+                center_y: cy,
+                height,
+                members: vec![index],
+            }),
         }
-        result.push_str(text);
     }
-    Ok(result)
+
+    let mut slots: Vec<Option<OcrTextBlock>> = blocks.drain(..).map(Some).collect();
+    let mut lines = Vec::with_capacity(groups.len());
+    for (line_index, mut group) in groups.into_iter().enumerate() {
+        group.members.sort_by_key(|&index| rects[index].x);
+        let mut bbox = rects[group.members[0]];
+        let mut texts = Vec::with_capacity(group.members.len());
+        let mut members = Vec::with_capacity(group.members.len());
+        for index in group.members {
+            let Some(mut block) = slots[index].take() else {
+                continue;
+            };
+            bbox = bbox | rects[index];
+            block.line = line_index;
+            texts.push(block.text.clone());
+            members.push(blocks.len());
+            blocks.push(block);
+        }
+        lines.push(OcrLine {
+            text: texts.join(" "),
+            blocks: members,
+            bbox,
+        });
+    }
+    lines
 }
 
 #[cfg(test)]
@@ -1091,10 +1462,28 @@ pub(crate) fn ocr_test_load_mat(image_path: &Path) -> Result<Mat, String> {
 }
 
 /// 初始化 OCR：下载资源、加载 ONNX 模型、创建推理会话。
+///
+/// 说明：
+/// - 每次调用都会检查本地资源，缺失的文件从 `cdn_base_url` 重新下载；
+/// - 资源目录、CDN 地址与线程数都未变化时复用已创建的会话，仅更新批大小。
 pub fn init_ocr(config: OcrInitConfig) -> Result<PathBuf, String> {
     let root_dir = config.local_root_dir.unwrap_or_else(default_ocr_root_dir);
+    let max_batch_size = config.max_batch_size.max(1);
     let cdn_base_url = normalize_cdn_base_url(config.cdn_base_url);
     ensure_ocr_resources(root_dir.as_path(), &cdn_base_url)?;
+    {
+        let mut guard = ocr_runtime_cell()
+            .lock()
+            .map_err(|e| format!("获取 OCR 状态锁失败: {e:?}"))?;
+        if let Some(runtime) = guard.as_mut()
+            && runtime.root_dir == root_dir
+            && runtime.cdn_base_url == cdn_base_url
+            && runtime.num_thread == config.num_thread
+        {
+            runtime.max_batch_size = max_batch_size;
+            return Ok(root_dir);
+        }
+    }
 
     let dictionary_path = resolve_model_path("OCR_DICT_PATH", default_dict_path(&root_dir));
     let dictionary = load_dictionary(&dictionary_path)?;
@@ -1140,6 +1529,10 @@ pub fn init_ocr(config: OcrInitConfig) -> Result<PathBuf, String> {
         cls_session,
         rec_session,
         dictionary,
+        root_dir: root_dir.clone(),
+        cdn_base_url,
+        num_thread: config.num_thread,
+        max_batch_size,
     };
 
     let mut guard = ocr_runtime_cell()
@@ -1149,8 +1542,11 @@ pub fn init_ocr(config: OcrInitConfig) -> Result<PathBuf, String> {
     Ok(root_dir)
 }
 
-/// 识别 Mat 中的文本。
-pub fn ocr_text_from_mat(input: &Mat) -> Result<String, String> {
+/// 检测并识别 Mat 中的文本块（检测顺序），同时返回各阶段耗时。
+fn recognize_mat(
+    input: &Mat,
+    options: &OcrOptions,
+) -> Result<(Vec<OcrTextBlock>, OcrTimings), String> {
     let mut guard = ocr_runtime_cell()
         .lock()
         .map_err(|e| format!("获取 OCR 状态锁失败: {e:?}"))?;
@@ -1170,8 +1566,33 @@ pub fn ocr_text_from_mat(input: &Mat) -> Result<String, String> {
         return Err("OCR 输入 Mat 尺寸无效".to_string());
     }
 
+    let mut timings = OcrTimings::default();
+    let det_start = Instant::now();
     let crops = detect_and_crop_text_blocks(&normalized, runtime)?;
-    let text = recognize_text_blocks(crops, runtime)?;
+    timings.det_ms = det_start.elapsed().as_secs_f64() * 1000.0;
+    let blocks = recognize_text_blocks(crops, runtime, options, &mut timings)?;
+    Ok((blocks, timings))
+}
+
+/// 识别 Mat 中的文本，返回带文本框、置信度与行分组的结构化结果。
+pub fn ocr_from_mat(input: &Mat, options: &OcrOptions) -> Result<OcrResult, String> {
+    let (mut blocks, timings) = recognize_mat(input, options)?;
+    let lines = order_text_blocks(&mut blocks);
+    Ok(OcrResult {
+        blocks,
+        lines,
+        timings,
+    })
+}
+
+/// 识别 Mat 中的文本（各文本块按检测顺序以换行连接）。
+pub fn ocr_text_from_mat(input: &Mat, options: &OcrOptions) -> Result<String, String> {
+    let (blocks, _) = recognize_mat(input, options)?;
+    let text = blocks
+        .iter()
+        .map(|block| block.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
     if text.is_empty() {
         Ok(text)
     } else {
@@ -1185,7 +1606,6 @@ mod tests {
     use std::ffi::{CString, OsStr, c_char, c_int, c_void};
     use std::os::windows::ffi::OsStrExt;
     use std::path::Path;
-    use std::time::Instant;
     use windows::{
         Win32::{
             Foundation::{FreeLibrary, HMODULE},
@@ -1354,13 +1774,184 @@ mod tests {
             local_root_dir: Some(root_dir.to_path_buf()),
             cdn_base_url: None,
             num_thread: 2,
+            // 逐块推理，与 DLL 的输入尺寸保持一致
+            max_batch_size: 1,
         };
         let _ = init_ocr(config)?;
         Ok(())
     }
 
     fn run_direct_ocr(mat: &Mat) -> Result<String, String> {
-        ocr_text_from_mat(mat)
+        ocr_text_from_mat(mat, &OcrOptions::default())
+    }
+
+    /// 构造轴对齐的测试文本块。
+    fn test_block(text: &str, x: i32, y: i32, w: i32, h: i32) -> OcrTextBlock {
+        OcrTextBlock {
+            points: [
+                Point::new(x, y),
+                Point::new(x + w - 1, y),
+                Point::new(x + w - 1, y + h - 1),
+                Point::new(x, y + h - 1),
+            ],
+            det_score: 1.0,
+            text: text.to_string(),
+            confidence: 1.0,
+            char_offsets: None,
+            line: 0,
+        }
+    }
+
+    #[test]
+    fn decode_ctc_records_char_steps() {
+        let dictionary: Vec<String> = ["#", "a", "b"].iter().map(|s| s.to_string()).collect();
+        // 时间步 argmax: a a blank b b a
+        let argmax = [1usize, 1, 0, 2, 2, 1];
+        let mut logits = vec![0.0f32; argmax.len() * 3];
+        for (t, &class) in argmax.iter().enumerate() {
+            logits[t * 3 + class] = 0.5 + t as f32 * 0.1;
+        }
+        let decoded = decode_ctc(&logits, argmax.len(), 3, &dictionary, None);
+        assert_eq!(decoded.text, "aba");
+        assert_eq!(decoded.steps, vec![0, 3, 5]);
+        assert!((decoded.score - (0.5 + 0.8 + 1.0) / 3.0).abs() < 1e-6);
+    }
+
+    /// 按 `[时间步][类别]` 构造合成 logits。
+    fn synthetic_logits(frames: &[&[f32]]) -> (Vec<f32>, usize, usize) {
+        let class_dim = frames[0].len();
+        (frames.concat(), frames.len(), class_dim)
+    }
+
+    #[test]
+    fn whitelist_masks_logits_before_argmax() {
+        let dictionary: Vec<String> = ["#", "0", "O", "口", "1", "l", " "]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        // 每帧的最大值都落在相似的非数字字符上，次大值为数字。
+        let (logits, time_dim, class_dim) = synthetic_logits(&[
+            &[0.01, 0.30, 0.10, 0.55, 0.02, 0.01, 0.01],
+            &[0.90, 0.02, 0.02, 0.02, 0.02, 0.01, 0.01],
+            &[0.01, 0.01, 0.01, 0.01, 0.40, 0.55, 0.01],
+            &[0.01, 0.01, 0.01, 0.01, 0.45, 0.50, 0.01],
+        ]);
+        let unmasked = decode_ctc(&logits, time_dim, class_dim, &dictionary, None);
+        assert_eq!(unmasked.text, "口l");
+
+        let mask = class_mask(&dictionary, &build_whitelist(&["digits"], "").unwrap());
+        assert_eq!(mask, vec![true, true, false, false, true, false, false]);
+        let masked = decode_ctc(&logits, time_dim, class_dim, &dictionary, Some(&mask));
+        // 屏蔽后第 3、4 帧都取 "1"，按 CTC 规则合并为一个字符。
+        assert_eq!(masked.text, "01");
+        assert_eq!(masked.steps, vec![0, 2]);
+        assert!((masked.score - (0.30 + 0.40) / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn whitelist_mask_keeps_blank_and_drops_out_of_range_classes() {
+        let dictionary: Vec<String> = ["#", "a", "b"].iter().map(|s| s.to_string()).collect();
+        // 第 4 个类别超出字典范围，掩码模式下不应被选中。
+        let (logits, time_dim, class_dim) = synthetic_logits(&[
+            &[0.1, 0.2, 0.3, 0.9],
+            &[0.8, 0.1, 0.1, 0.0],
+            &[0.1, 0.1, 0.2, 0.6],
+        ]);
+        let mask = class_mask(&dictionary, &build_whitelist::<&str>(&[], "b").unwrap());
+        let decoded = decode_ctc(&logits, time_dim, class_dim, &dictionary, Some(&mask));
+        assert_eq!(decoded.text, "bb");
+
+        let empty = class_mask(&dictionary, &HashSet::new());
+        let decoded = decode_ctc(&logits, time_dim, class_dim, &dictionary, Some(&empty));
+        assert_eq!(decoded.text, "");
+    }
+
+    #[test]
+    fn build_whitelist_expands_named_sets_and_literals() {
+        let whitelist = build_whitelist(&["digits", "punct"], "x/").unwrap();
+        assert!(whitelist.contains(&'7') && whitelist.contains(&'%') && whitelist.contains(&'：'));
+        assert!(whitelist.contains(&'x') && whitelist.contains(&'/'));
+        assert!(!whitelist.contains(&'a'));
+        let latin = build_whitelist(&["latin"], "").unwrap();
+        assert_eq!(latin.len(), 52);
+        let punctuation = build_whitelist(&["punctuation"], "").unwrap();
+        assert_eq!(punctuation, build_whitelist(&["punct"], "").unwrap());
+    }
+
+    #[test]
+    fn build_whitelist_rejects_unknown_names_and_empty_sets() {
+        let err = build_whitelist(&["digit"], "").unwrap_err();
+        assert!(err.contains("digit"));
+        assert!(build_whitelist(&["digits", "x/"], "").is_err());
+        assert!(build_whitelist::<&str>(&[], "").is_err());
+    }
+
+    #[test]
+    fn char_offsets_follow_text_direction() {
+        let block = test_block("ab", 10, 10, 101, 21);
+        let offsets = char_offsets_along_box(&block.points, &[0, 3], 4, false);
+        assert_eq!(offsets, vec![12.5, 87.5]);
+        let flipped = char_offsets_along_box(&block.points, &[0, 3], 4, true);
+        assert_eq!(flipped, vec![87.5, 12.5]);
+        let vertical = test_block("ab", 0, 0, 21, 101);
+        assert_eq!(
+            char_offsets_along_box(&vertical.points, &[1], 2, false),
+            vec![75.0]
+        );
+    }
+
+    #[test]
+    fn order_text_blocks_groups_lines_in_reading_order() {
+        let mut blocks = vec![
+            test_block("第二行右", 120, 52, 60, 20),
+            test_block("标题", 10, 10, 80, 24),
+            test_block("第二行左", 10, 50, 60, 20),
+            test_block("副标题", 100, 14, 40, 18),
+            test_block("第三行", 10, 90, 60, 20),
+        ];
+        let lines = order_text_blocks(&mut blocks);
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, vec!["标题 副标题", "第二行左 第二行右", "第三行"]);
+        let order: Vec<(&str, usize)> = blocks.iter().map(|b| (b.text.as_str(), b.line)).collect();
+        assert_eq!(
+            order,
+            vec![
+                ("标题", 0),
+                ("副标题", 0),
+                ("第二行左", 1),
+                ("第二行右", 1),
+                ("第三行", 2)
+            ]
+        );
+        assert_eq!(lines[1].blocks, vec![2, 3]);
+        assert_eq!(lines[1].bbox, Rect::new(10, 50, 170, 22));
+    }
+
+    #[test]
+    fn rec_batches_group_similar_widths() {
+        let widths = [100, 320, 110, 60, 140, 330, 90, 105];
+        let batches = plan_rec_batches(&widths, 3);
+        assert_eq!(
+            batches,
+            vec![vec![3, 6], vec![0, 7, 2], vec![4], vec![1, 5]]
+        );
+        let single = plan_rec_batches(&widths, 0);
+        assert_eq!(single.len(), widths.len());
+        assert!(plan_rec_batches(&[], 8).is_empty());
+    }
+
+    #[test]
+    fn rec_batch_pads_to_max_width() {
+        let narrow: Vec<f32> = (0..3 * 2 * 2).map(|v| v as f32 + 1.0).collect();
+        let wide: Vec<f32> = vec![-1.0; 3 * 2 * 3];
+        let batch = build_rec_batch(&[(narrow.as_slice(), 2), (wide.as_slice(), 3)], 2)
+            .expect("构造识别批次失败");
+        assert_eq!(batch.shape(), &[2, 3, 2, 3]);
+        assert_eq!(batch[[0, 1, 1, 0]], narrow[(2 + 1) * 2]);
+        assert_eq!(batch[[0, 1, 1, 1]], narrow[(2 + 1) * 2 + 1]);
+        assert_eq!(batch[[0, 1, 1, 2]], 0.0);
+        assert_eq!(batch[[1, 2, 0, 2]], -1.0);
+        assert!(build_rec_batch(&[(narrow.as_slice(), 3)], 2).is_err());
     }
 
     #[test]
@@ -1408,14 +1999,47 @@ mod tests {
             let crops = detect_and_crop_text_blocks(&mat, runtime).expect("直接检测失败");
             let detect_elapsed = detect_start.elapsed().as_millis();
             let recognize_start = Instant::now();
-            let text = recognize_text_blocks(crops, runtime).expect("直接识别失败");
+            let mut timings = OcrTimings::default();
+            let blocks =
+                recognize_text_blocks(crops, runtime, &OcrOptions::default(), &mut timings)
+                    .expect("直接识别失败");
             let recognize_elapsed = recognize_start.elapsed().as_millis();
             eprintln!(
-                "direct stage detect={}ms recognize={}ms blocks={}",
+                "direct stage detect={}ms recognize={}ms (cls={:.2}ms rec={:.2}ms batches={}) blocks={}",
                 detect_elapsed,
                 recognize_elapsed,
-                text.lines().count()
+                timings.cls_ms,
+                timings.rec_ms,
+                timings.rec_batches,
+                blocks.len()
+            );
+        }
+
+        // 与 DLL 对拍使用逐块推理；默认批量推理需在同一张图上得到完全相同的文本与文本框
+        {
+            let mut guard = ocr_runtime_cell().lock().expect("获取 OCR 状态锁失败");
+            let runtime = guard.as_mut().expect("OCR 未初始化");
+            let [unbatched, batched] = [1, DEFAULT_MAX_BATCH_SIZE].map(|max_batch_size| {
+                runtime.max_batch_size = max_batch_size;
+                let crops = detect_and_crop_text_blocks(&mat, runtime).expect("直接检测失败");
+                let mut timings = OcrTimings::default();
+                recognize_text_blocks(crops, runtime, &OcrOptions::default(), &mut timings)
+                    .expect("直接识别失败")
+            });
+            runtime.max_batch_size = 1;
+            assert!(!unbatched.is_empty(), "测试图像应识别出文本");
+            assert_eq!(
+                batched.len(),
+                unbatched.len(),
+                "批量与逐块识别的文本块数量不一致"
             );
+            for (batched, unbatched) in batched.iter().zip(&unbatched) {
+                assert_eq!(batched.text, unbatched.text, "批量与逐块识别的文本不一致");
+                assert_eq!(
+                    batched.points, unbatched.points,
+                    "批量与逐块识别的文本框不一致"
+                );
+            }
         }
 
         let iterations = 10usize;
@@ -1,8 +1,16 @@
 use crate::submodules::async_tokio::TokioJobExecutor;
+use crate::submodules::frame_source::FrameSourceRunGuard;
 use crate::submodules::fx::hide_border_immediately;
 use crate::submodules::input::clear_last_background_activated_hwnd;
+use crate::submodules::jsabort::{JsAbortController, JsAbortSignal};
+use crate::submodules::jsdebugreport::JsDebugReport;
 use crate::submodules::jsdnn::JsDnnNet;
+use crate::submodules::jsfeature::JsFeatureLibrary;
+use crate::submodules::jsglyph::JsGlyphSet;
+use crate::submodules::jshash::JsHashIndex;
+use crate::submodules::jslocalizer::JsMinimapLocalizer;
 use crate::submodules::jsmat::JsMat;
+use crate::submodules::jsmosaic::JsWorldMosaic;
 use crate::submodules::jstimer::JsTimer;
 #[cfg(feature = "dob-script-cli")]
 use crate::submodules::logger::StdioLogger;
@@ -13,7 +21,15 @@ use crate::submodules::script_builtin::{
     register_builtin_functions, set_current_script_path, set_script_event_app_handle,
 };
 use crate::submodules::script_console::{Console, ConsoleState, Logger};
+use crate::submodules::script_log::{
+    FanoutLogger, FileLogSink, ScriptLogConfig, ScriptLogRunGuard, start_script_log_run,
+};
 use crate::submodules::script_module::ScriptModuleLoader;
+use crate::submodules::script_net::ScriptNetworkRunGuard;
+use crate::submodules::script_profile::{
+    PROFILE_FILE_NAME, ScriptProfileConfig, ScriptProfiler, ScriptProfilerGuard,
+    register_builtin_functions_with_profiling,
+};
 use boa_engine::builtins::error::Error as BoaErrorObject;
 use boa_engine::builtins::promise::PromiseState;
 use boa_engine::context::ContextBuilder;
@@ -27,7 +43,7 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, LazyLock, Mutex};
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
 
 thread_local! {
     /// 当前执行线程绑定的停止快照（用于并行脚本精确停止）。
@@ -458,17 +474,75 @@ pub fn normalize_script_path(script_path: String) -> Result<String, String> {
     Ok(canonical.to_string_lossy().to_string())
 }
 
+/// 获取脚本文件日志根目录（`<应用日志目录>/scripts`）。
+pub fn script_log_root(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_log_dir()
+        .map(|dir| dir.join("scripts"))
+        .map_err(|e| format!("获取应用日志目录失败: {e}"))
+}
+
+/// 结束性能分析并输出结果；失败只记录到标准错误，不影响脚本结果。
+fn finish_script_profiler(profiler: Option<&ScriptProfiler>, context: &mut boa_engine::Context) {
+    if let Some(profiler) = profiler
+        && let Err(error) = profiler.finish(context)
+    {
+        eprintln!("输出脚本性能分析失败: {error}");
+    }
+}
+
+/// 注册脚本运行时的全部原生类（各运行入口共用，新增类只需在此处登记）
+fn register_script_classes(context: &mut boa_engine::Context) -> Result<(), String> {
+    context
+        .register_global_class::<JsMat>()
+        .map_err(|e| format!("注册 JsMat 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsDnnNet>()
+        .map_err(|e| format!("注册 JsDnnNet 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsTimer>()
+        .map_err(|e| format!("注册 JsTimer 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsAbortSignal>()
+        .map_err(|e| format!("注册 JsAbortSignal 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsAbortController>()
+        .map_err(|e| format!("注册 JsAbortController 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsHashIndex>()
+        .map_err(|e| format!("注册 JsHashIndex 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsFeatureLibrary>()
+        .map_err(|e| format!("注册 JsFeatureLibrary 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsMinimapLocalizer>()
+        .map_err(|e| format!("注册 JsMinimapLocalizer 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsWorldMosaic>()
+        .map_err(|e| format!("注册 JsWorldMosaic 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsGlyphSet>()
+        .map_err(|e| format!("注册 JsGlyphSet 失败: {:?}", e))?;
+    context
+        .register_global_class::<JsDebugReport>()
+        .map_err(|e| format!("注册 JsDebugReport 失败: {:?}", e))?;
+    Ok(())
+}
+
 /// 运行脚本并将控制台输出发送到 Tauri 事件系统
 ///
 /// # 参数
 /// - `script_path`: 脚本文件路径
 /// - `app_handle`: Tauri 应用句柄，用于发送事件
+/// - `profile`: 是否启用性能分析（汇总输出到控制台，折叠栈写入本次运行日志目录）
 ///
 /// # 返回
 /// 返回执行结果字符串，如果成功则返回 Ok(String)，否则返回错误信息
 pub async fn run_script_with_tauri_console(
     script_path: String,
     app_handle: tauri::AppHandle,
+    profile: bool,
 ) -> Result<String, String> {
     // 使用 spawn_blocking 在阻塞线程中执行脚本，避免 Context 的 Send 约束问题
     tokio::task::spawn_blocking(move || {
@@ -480,37 +554,54 @@ pub async fn run_script_with_tauri_console(
             .build()
             .unwrap();
 
-        context
-            .register_global_class::<JsMat>()
-            .map_err(|e| format!("注册 JsMat 失败: {:?}", e))?;
-        context
-            .register_global_class::<JsDnnNet>()
-            .map_err(|e| format!("注册 JsDnnNet 失败: {:?}", e))?;
-        context
-            .register_global_class::<JsTimer>()
-            .map_err(|e| format!("注册 JsTimer 失败: {:?}", e))?;
-
-        // 创建自定义的 Tauri Logger
-        let tauri_logger = TauriLogger {
+        register_script_classes(context)?;
+
+        // 创建自定义的 Tauri Logger，并按需叠加滚动文件日志。
+        let mut console_logger = FanoutLogger::new().with_sink(TauriLogger {
             app_handle: logger_app_handle,
+        });
+        let log_run = match script_log_root(&app_handle).and_then(|log_root| {
+            start_script_log_run(&log_root, &script_path, &ScriptLogConfig::default())
+        }) {
+            Ok(log_run) => {
+                console_logger = console_logger.with_sink(FileLogSink::new(log_run.clone()));
+                Some(log_run)
+            }
+            Err(error) => {
+                eprintln!("创建脚本文件日志失败: {error}");
+                None
+            }
         };
+        let _log_run_guard = log_run.clone().map(ScriptLogRunGuard::enter);
+        let profiler = profile.then(|| {
+            let output_path = log_run
+                .as_ref()
+                .map(|run| run.dir().join(PROFILE_FILE_NAME));
+            std::rc::Rc::new(ScriptProfiler::new(ScriptProfileConfig::new(output_path)))
+        });
+        let _profiler_guard = profiler.clone().map(ScriptProfilerGuard::enter);
+        let _network_guard = ScriptNetworkRunGuard::enter();
+        let _frame_source_guard = FrameSourceRunGuard::enter();
 
         // 注册 timeout 扩展，并挂载自定义 console 实现。
         boa_runtime::register((boa_runtime::extensions::TimeoutExtension,), None, context)
             .map_err(|e| format!("注册 Timeout Extension 失败: {:?}", e))?;
-        Console::register_with_logger(tauri_logger, context)
+        Console::register_with_logger(console_logger, context)
             .map_err(|e| format!("注册自定义 Console 失败: {:?}", e))?;
 
         // 设置脚本内置函数的事件发送器，供 setStatus 等函数推送到前端。
         set_script_event_app_handle(app_handle.clone());
         set_current_script_path(script_path.clone());
-        register_builtin_functions(context).map_err(|e| format!("注册内置函数失败: {:?}", e))?;
+        register_builtin_functions_with_profiling(context, profiler.is_some())
+            .map_err(|e| format!("注册内置函数失败: {:?}", e))?;
         let _running_guard = ScriptRunningGuard::enter(script_path.clone(), app_handle.clone());
         let source_bytes = std::fs::read(Path::new(&script_path))
             .map_err(|e| format!("无法读取文件 {:?}: {}", script_path, e))?;
         let program = parse_script_program(&source_bytes, Some(Path::new(&script_path)), context)
             .map_err(|e| format!("解析脚本失败: {:?}", e))?;
-        match evaluate_script_program(program, &job_executor, context) {
+        let evaluation = evaluate_script_program(program, &job_executor, context);
+        finish_script_profiler(profiler.as_deref(), context);
+        match evaluation {
             Ok(result) => {
                 // 某些脚本会“返回 Error 对象”而不是直接 throw，
                 // 这类场景也视为异常退出，避免前端误判为执行成功。
@@ -589,15 +680,7 @@ pub async fn exec_script_with_tauri_console(
             .build()
             .unwrap();
 
-        context
-            .register_global_class::<JsMat>()
-            .map_err(|e| format!("注册 JsMat 失败: {:?}", e))?;
-        context
-            .register_global_class::<JsDnnNet>()
-            .map_err(|e| format!("注册 JsDnnNet 失败: {:?}", e))?;
-        context
-            .register_global_class::<JsTimer>()
-            .map_err(|e| format!("注册 JsTimer 失败: {:?}", e))?;
+        register_script_classes(context)?;
 
         let exec_logger = ExecScriptLogger {
             collector: console_collector.clone(),
@@ -616,6 +699,8 @@ pub async fn exec_script_with_tauri_console(
             .clone()
             .unwrap_or_else(|| "__exec_script__".to_string());
         let _running_guard = ScriptRunningGuard::enter(runtime_scope.clone(), app_handle.clone());
+        let _network_guard = ScriptNetworkRunGuard::enter();
+        let _frame_source_guard = FrameSourceRunGuard::enter();
         let runtime_source_path = Path::new(runtime_scope.as_str());
         let program =
             parse_script_program(script_source.as_bytes(), Some(runtime_source_path), context)
@@ -683,6 +768,7 @@ pub async fn run_script_with_stdio_console(
     script_path: String,
     script_config: Option<serde_json::Value>,
     script_config_file_path: Option<String>,
+    profile_output_path: Option<PathBuf>,
 ) -> Result<String, String> {
     // 使用 spawn_blocking 在阻塞线程中执行脚本，避免 Context 的 Send 约束问题
     tokio::task::spawn_blocking(move || {
@@ -693,32 +779,35 @@ pub async fn run_script_with_stdio_console(
             .build()
             .unwrap();
 
-        context
-            .register_global_class::<JsMat>()
-            .map_err(|e| format!("注册 JsMat 失败: {:?}", e))?;
-        context
-            .register_global_class::<JsDnnNet>()
-            .map_err(|e| format!("注册 JsDnnNet 失败: {:?}", e))?;
-        context
-            .register_global_class::<JsTimer>()
-            .map_err(|e| format!("注册 JsTimer 失败: {:?}", e))?;
+        register_script_classes(context)?;
 
         // 注册 timeout 扩展，并挂载终端 console 实现。
         boa_runtime::register((boa_runtime::extensions::TimeoutExtension,), None, context)
             .map_err(|e| format!("注册 Timeout Extension 失败: {:?}", e))?;
         Console::register_with_logger(StdioLogger, context)
             .map_err(|e| format!("注册终端 Console 失败: {:?}", e))?;
+        let profiler = profile_output_path.map(|output_path| {
+            std::rc::Rc::new(ScriptProfiler::new(ScriptProfileConfig::new(Some(
+                output_path,
+            ))))
+        });
+        let _profiler_guard = profiler.clone().map(ScriptProfilerGuard::enter);
+        let _network_guard = ScriptNetworkRunGuard::enter();
+        let _frame_source_guard = FrameSourceRunGuard::enter();
 
         // CLI 模式下不绑定 Tauri 事件发送器，但保持脚本路径上下文可用。
         set_script_cli_config(script_config, script_config_file_path)
             .map_err(|e| format!("设置 CLI 脚本配置失败: {e}"))?;
         set_current_script_path(script_path.clone());
-        register_builtin_functions(context).map_err(|e| format!("注册内置函数失败: {:?}", e))?;
+        register_builtin_functions_with_profiling(context, profiler.is_some())
+            .map_err(|e| format!("注册内置函数失败: {:?}", e))?;
         let source_bytes = std::fs::read(Path::new(&script_path))
             .map_err(|e| format!("无法读取文件 {:?}: {}", script_path, e))?;
         let program = parse_script_program(&source_bytes, Some(Path::new(&script_path)), context)
             .map_err(|e| format!("解析脚本失败: {:?}", e))?;
-        match evaluate_script_program(program, &job_executor, context) {
+        let evaluation = evaluate_script_program(program, &job_executor, context);
+        finish_script_profiler(profiler.as_deref(), context);
+        match evaluation {
             Ok(result) => {
                 // 某些脚本会“返回 Error 对象”而不是直接 throw，
                 // 这类场景也视为异常退出，避免 CLI 误判为执行成功。
@@ -770,10 +859,19 @@ pub async fn run_script_with_stdio_console(
 pub async fn run_script_file(
     script_path: String,
     app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    run_script_file_with_profile(script_path, app_handle, false).await
+}
+
+/// 运行脚本并可选启用性能分析。
+pub async fn run_script_file_with_profile(
+    script_path: String,
+    app_handle: tauri::AppHandle,
+    profile: bool,
 ) -> Result<String, String> {
     let normalized_path = normalize_script_path(script_path)?;
     clear_last_background_activated_hwnd();
-    run_script_with_tauri_console(normalized_path, app_handle).await
+    run_script_with_tauri_console(normalized_path, app_handle, profile).await
 }
 
 /// CLI 对外入口：先做路径规范化，再执行脚本。
@@ -782,10 +880,17 @@ pub async fn run_script_file_cli(
     script_path: String,
     script_config: Option<serde_json::Value>,
     script_config_file_path: Option<String>,
+    profile_output_path: Option<PathBuf>,
 ) -> Result<String, String> {
     let normalized_path = normalize_script_path(script_path)?;
     clear_last_background_activated_hwnd();
-    run_script_with_stdio_console(normalized_path, script_config, script_config_file_path).await
+    run_script_with_stdio_console(
+        normalized_path,
+        script_config,
+        script_config_file_path,
+        profile_output_path,
+    )
+    .await
 }
 
 pub static SCRIPT_RUNNING: LazyLock<Arc<AtomicBool>> =
@@ -13,6 +13,7 @@ use opencv::{
     imgcodecs, imgproc,
     prelude::{MatTraitConst, MatTraitConstManual, VectorToVec},
 };
+use regex::Regex;
 use serde::Deserialize;
 use std::{
     borrow::Cow,
@@ -37,34 +38,57 @@ use zip::ZipArchive;
 
 use crate::submodules::script_mcp::record_script_console;
 use crate::submodules::{
-    color_match::{check_color_mat, find_color_and_match_template, rgb_to_bgr},
+    color::{ColorDistance, rgb_to_lab},
+    color_match::{
+        check_color_distance, check_color_mat, find_color_and_match_template_with, rgb_to_bgr,
+    },
     dll_call::dll_call_js,
+    feature_library::FeatureLibrary,
+    frame_diff::{FrameDiffOptions, frame_change, frame_diff_mask},
+    frame_source::{
+        FrameSource, FrameStep, close_frame_source, register_frame_source, with_frame_source,
+    },
     fx::draw_border,
+    glyph::GlyphSet,
+    hash_index::HashIndex,
     input::*,
+    jsabort::{AbortToken, ScriptAbort, run_abortable},
     jsdnn::register_cv_dnn_namespace,
+    jsfeature::parse_feature_kind,
+    jsglyph::{arg_glyph_set, glyph_reading_to_js, parse_glyph_read_options},
     jsmat::{IntoJs, JsMat},
     mono_depth::{
         MonoDepthInitConfig, init_mono_depth, predict_mono_depth, predict_mono_depth_model_space,
     },
-    ocr::{self, OcrInitConfig},
+    ocr::{self, OcrInitConfig, OcrOptions},
     predict_rotation::predict_rotation,
     route::{find_path_direction_coords, predict_depth, predict_mono_route},
     script::{
         SCRIPT_STOP_INTERRUPT_MESSAGE, capture_current_script_stop_snapshot,
         run_with_script_stop_snapshot, should_stop_current_script,
     },
+    script_net::register_network_api,
     script_vision::{
-        batch_match_color_impl, color_filter_hsl_impl, color_filter_impl, color_key_match_impl,
-        draw_bboxes_impl, draw_contours_impl, find_contours_impl, hamming_distance_hex,
-        match_orb_feature_impl, morphology_ex_impl, normalize_hash_hex, orb_feature_impl,
-        orb_match_count_impl, perceptual_hash_impl, preprocess_minimap_for_sift_impl,
-        segment_single_line_chars_impl, sift_locate_impl, sift_stitch_impl,
+        ImageHashKind, batch_match_color_impl, color_filter_hsl_impl, color_filter_impl,
+        color_key_match_impl, draw_bboxes_impl, draw_contours_impl, find_contours_impl,
+        hamming_distance_hex, match_orb_feature_impl, morphology_ex_impl, normalize_hash_hex,
+        orb_feature_impl, orb_match_count_impl, perceptual_hash_impl,
+        preprocess_minimap_for_sift_impl, segment_single_line_chars_impl, sift_locate_impl,
+        sift_stitch_impl,
+    },
+    tpl::{
+        clear_template_cache, get_template, get_template_b64, get_template_meta,
+        template_cache_stats,
+    },
+    tpl_match::{
+        MatchAllOptions, MatchConfig, MatchMethod, MatchPreprocess, ScaledMatchOptions,
+        match_template_all, match_template_scaled, match_template_with,
     },
-    tpl::{get_template, get_template_b64},
-    tpl_match::match_template,
     util::{
-        capture_window, capture_window_roi, capture_window_wgc, capture_window_wgc_roi, check_size,
+        capture_window, capture_window_roi, capture_window_wgc, capture_window_wgc_into,
+        capture_window_wgc_roi, check_size,
     },
+    wait_engine::{ColorPoint, WaitCondition, WaitMatch, WaitOptions, WaitOutcome, wait_until},
     win::{
         apply_window_style_expression, find_window, get_window_by_process_name, move_window,
         set_window_style, win_get_client_pos,
@@ -422,7 +446,7 @@ fn _current_script_dir() -> Option<PathBuf> {
 /// 均直接借用输入切片 `Cow::Borrowed` 返回，避免原实现中
 /// `String::from(path).trim().to_string()` 的双次堆分配；
 /// 仅当相对路径需要拼接脚本目录时才生成 owned 字符串。
-fn _resolve_script_resource_path(path: &str) -> Cow<'_, str> {
+pub(crate) fn _resolve_script_resource_path(path: &str) -> Cow<'_, str> {
     let trimmed = path.trim();
     if trimmed.is_empty() {
         return Cow::Borrowed(trimmed);
@@ -580,12 +604,34 @@ fn _parse_u32_array(arg: JsValue, ctx: &mut Context) -> JsResult<Vec<u32>> {
     Ok(values)
 }
 
+/// 将 JS `number[]` 数组参数解析为 Rust `Vec<f64>`，`name` 用于错误提示。
+fn _parse_f64_array(arg: JsValue, name: &str, ctx: &mut Context) -> JsResult<Vec<f64>> {
+    let array = arg
+        .as_object()
+        .and_then(|obj| JsArray::from_object(obj.clone()).ok())
+        .ok_or_else(|| JsNativeError::typ().with_message(format!("{name} 参数必须是 number[]")))?;
+    let length = array.length(ctx)? as usize;
+    let mut values = Vec::with_capacity(length);
+
+    for idx in 0..length {
+        let value = array.get(idx as u32, ctx)?;
+        let number = value
+            .to_number(ctx)
+            .map_err(|_| JsNativeError::typ().with_message(format!("{name}[{idx}] 必须是数字")))?;
+        values.push(number);
+    }
+
+    Ok(values)
+}
+
 /// 解析色键相关函数的容差参数，支持单个数字或与 colors 一一对应的数字数组。
+///
+/// 说明：结果截断到 0-255；RGB 模式下按逐通道容差使用，ΔE 模式下按最大色差使用。
 fn _parse_color_tolerances(
     arg: JsValue,
     color_count: usize,
     ctx: &mut Context,
-) -> JsResult<Vec<u8>> {
+) -> JsResult<Vec<f64>> {
     if let Some(obj) = arg.as_object() {
         if let Ok(array) = JsArray::from_object(obj.clone()) {
             let length = array.length(ctx)? as usize;
@@ -603,7 +649,7 @@ fn _parse_color_tolerances(
                 let tolerance = value.to_number(ctx).map_err(|_| {
                     JsNativeError::typ().with_message(format!("tolerance[{idx}] 必须是数字"))
                 })?;
-                tolerances.push(tolerance.clamp(0.0, 255.0) as u8);
+                tolerances.push(tolerance.clamp(0.0, 255.0));
             }
 
             return Ok(tolerances);
@@ -611,7 +657,7 @@ fn _parse_color_tolerances(
     }
 
     let tolerance = arg.to_number(ctx)?;
-    Ok(vec![tolerance.clamp(0.0, 255.0) as u8; color_count])
+    Ok(vec![tolerance.clamp(0.0, 255.0); color_count])
 }
 
 /// 将 JS `string[]` 数组参数解析为 Rust `Vec<String>`。
@@ -1110,7 +1156,10 @@ fn _parse_bbox_tuple(
 /// 支持两种元素格式：
 /// 1. `[x, y, w, h]`
 /// 2. `{ bbox: [x, y, w, h] }`
-fn _parse_bbox_array(arg: JsValue, ctx: &mut Context) -> JsResult<Vec<(i32, i32, i32, i32)>> {
+pub(crate) fn _parse_bbox_array(
+    arg: JsValue,
+    ctx: &mut Context,
+) -> JsResult<Vec<(i32, i32, i32, i32)>> {
     let array_obj = arg
         .as_object()
         .ok_or_else(|| JsNativeError::typ().with_message("bboxes 参数必须是数组"))?;
@@ -2362,7 +2411,7 @@ fn _capture_window_wgc(
     }
 }
 
-/// 从文件加载模板Mat对象函数
+/// 从文件加载模板Mat对象函数（支持 `atlas.json#name` 图集引用）
 fn _get_template(path: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
     let path = path
         .unwrap_or_else(|| JsValue::undefined())
@@ -2378,6 +2427,74 @@ fn _get_template(path: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue>
     }
 }
 
+/// 清空模板缓存函数，返回清除的条目数
+fn _clear_template_cache(_ctx: &mut Context) -> JsResult<JsValue> {
+    match clear_template_cache() {
+        Ok(count) => Ok(JsValue::new(count as u32)),
+        Err(e) => Err(JsNativeError::error().with_message(e.to_string()).into()),
+    }
+}
+
+/// 模板缓存统计函数
+///
+/// 返回 `{ entries, fileEntries, base64Entries, atlasEntries, bytes, budgetBytes, hits, misses, evictions, invalidations }`。
+fn _template_cache_stats(ctx: &mut Context) -> JsResult<JsValue> {
+    let stats =
+        template_cache_stats().map_err(|e| JsNativeError::error().with_message(e.to_string()))?;
+    let result = js_object!({
+        entries: stats.entries as f64,
+        fileEntries: stats.file_entries as f64,
+        base64Entries: stats.base64_entries as f64,
+        atlasEntries: stats.atlas_entries as f64,
+        bytes: stats.bytes as f64,
+        budgetBytes: stats.budget_bytes as f64,
+        hits: stats.hits as f64,
+        misses: stats.misses as f64,
+        evictions: stats.evictions as f64,
+        invalidations: stats.invalidations as f64,
+    }, ctx);
+    Ok(result.into())
+}
+
+/// 获取图集模板元数据函数
+///
+/// `path` 为 `atlas.json#name` 形式时返回 `{ name, rect, threshold?, roi?, colorKey?, offset? }`，
+/// 普通模板路径返回 `undefined`。
+fn _get_template_meta(path: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
+    let path = path
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_lossy();
+    let resolved_path = _resolve_script_resource_path(&path);
+
+    let meta = get_template_meta(&resolved_path)
+        .map_err(|e| JsNativeError::error().with_message(e.to_string()))?;
+    let Some(meta) = meta else {
+        return Ok(JsValue::undefined());
+    };
+    let name = path
+        .rsplit_once('#')
+        .map(|(_, name)| name)
+        .unwrap_or_default();
+    let mut value = serde_json::json!({ "name": name, "rect": meta.rect });
+    if let Some(threshold) = meta.threshold {
+        value["threshold"] = serde_json::json!(threshold);
+    }
+    if let Some(roi) = meta.roi {
+        value["roi"] = serde_json::json!(roi);
+    }
+    if let Some(color_key) = &meta.color_key {
+        let rgb = color_key
+            .rgb()
+            .map_err(|e| JsNativeError::error().with_message(e.to_string()))?;
+        value["colorKey"] = serde_json::json!(rgb);
+    }
+    if let Some(offset) = meta.offset {
+        value["offset"] = serde_json::json!(offset);
+    }
+    JsValue::from_json(&value, ctx)
+}
+
 /// 从 base64 字符串加载模板Mat对象函数
 fn _get_template_b64(b64_str: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
     let b64_str = b64_str
@@ -2666,10 +2783,13 @@ fn _exists_file(path: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
 /// 从本地或网络加载图像Mat对象函数
 /// 如果 local_path 不为空，先尝试从本地路径加载，失败则从网络下载并保存到本地
 /// 如果 local_path 为空，直接从网络加载不保存到本地
+///
+/// `options.signal`（可选）: AbortSignal，中止时以其原因拒绝且不写入文件
 fn _download_file(
     url: Option<JsValue>,
     filename: Option<JsValue>,
     force: Option<JsValue>,
+    options: Option<JsValue>,
     ctx: &mut Context,
 ) -> JsResult<JsValue> {
     let url = url
@@ -2698,53 +2818,69 @@ fn _download_file(
     }
 
     let resolved_filename = _resolve_script_resource_path(&filename).into_owned();
+    let abort = ScriptAbort::from_options(options.as_ref(), ctx)?;
     let (promise, resolvers) = JsPromise::new_pending(ctx);
+    if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+        return Ok(promise.into());
+    }
     let resolvers_clone = resolvers.clone();
+    let token = abort.as_ref().map(ScriptAbort::token);
+    let task_token = token.clone();
 
     ctx.enqueue_job(
         NativeAsyncJob::new(async move |context| {
-            let async_result =
-                _spawn_blocking_with_script_stop_snapshot(move || -> Result<(), String> {
-                    let target_path = Path::new(&*resolved_filename);
+            let task = _spawn_blocking_with_script_stop_snapshot(move || -> Result<(), String> {
+                let target_path = Path::new(&*resolved_filename);
 
-                    // force=false 且文件已存在时直接返回成功，不重复下载。
-                    if !force && target_path.exists() {
-                        return Ok(());
-                    }
+                // force=false 且文件已存在时直接返回成功，不重复下载。
+                if !force && target_path.exists() {
+                    return Ok(());
+                }
 
-                    if let Some(parent) = target_path.parent()
-                        && !parent.as_os_str().is_empty()
-                    {
-                        std::fs::create_dir_all(parent).map_err(|e| {
-                            format!("创建下载目录失败: {}, {e}", parent.to_string_lossy())
-                        })?;
-                    }
+                if let Some(parent) = target_path.parent()
+                    && !parent.as_os_str().is_empty()
+                {
+                    std::fs::create_dir_all(parent).map_err(|e| {
+                        format!("创建下载目录失败: {}, {e}", parent.to_string_lossy())
+                    })?;
+                }
 
-                    let client = reqwest::blocking::Client::builder()
-                        .connect_timeout(Duration::from_secs(8))
-                        .timeout(Duration::from_secs(30))
-                        .build()
-                        .map_err(|e| format!("初始化下载客户端失败: {e}"))?;
-                    let response = client
-                        .get(url.as_str())
-                        .send()
-                        .map_err(|e| format!("请求下载地址失败: {e}"))?;
-                    if !response.status().is_success() {
-                        return Err(format!("下载失败，HTTP 状态码: {}", response.status()));
-                    }
+                let client = reqwest::blocking::Client::builder()
+                    .connect_timeout(Duration::from_secs(8))
+                    .timeout(Duration::from_secs(30))
+                    .build()
+                    .map_err(|e| format!("初始化下载客户端失败: {e}"))?;
+                let response = client
+                    .get(url.as_str())
+                    .send()
+                    .map_err(|e| format!("请求下载地址失败: {e}"))?;
+                if !response.status().is_success() {
+                    return Err(format!("下载失败，HTTP 状态码: {}", response.status()));
+                }
 
-                    let bytes = response
-                        .bytes()
-                        .map_err(|e| format!("读取下载内容失败: {e}"))?;
-                    std::fs::write(target_path, &bytes).map_err(|e| {
-                        format!("写入下载文件失败: {}, {e}", target_path.to_string_lossy())
-                    })?;
+                let bytes = response
+                    .bytes()
+                    .map_err(|e| format!("读取下载内容失败: {e}"))?;
+                // 已中止的下载丢弃内容，避免在 Promise 拒绝后仍落盘。
+                if task_token.as_ref().is_some_and(|token| token.is_aborted()) {
+                    return Ok(());
+                }
+                std::fs::write(target_path, &bytes).map_err(|e| {
+                    format!("写入下载文件失败: {}, {e}", target_path.to_string_lossy())
+                })?;
 
-                    Ok(())
-                })
-                .await;
+                Ok(())
+            });
+            let async_result = run_abortable(token.as_ref(), task).await;
 
             let context = &mut context.borrow_mut();
+            let Some(async_result) = async_result else {
+                return ScriptAbort::reject_aborted(
+                    abort.as_ref(),
+                    &resolvers_clone.reject,
+                    context,
+                );
+            };
             match async_result {
                 Ok(Ok(())) => resolvers_clone
                     .resolve
@@ -2907,7 +3043,8 @@ fn _imread_url_rgba(
 /// 参数：
 /// - `local_root_dir`：可选，本地资源目录（为空时使用默认目录）；
 /// - `cdn_base_url`：可选，CDN 根地址（默认 `https://cdn.dna-builder.cn/ocr`）；
-/// - `num_thread`：可选，OCR 线程数（默认 2）。
+/// - `num_thread`：可选，OCR 线程数（默认 2）；
+/// - `max_batch_size`：可选，识别/分类单次推理的最大文本块数（默认 8，1 为逐块推理）。
 ///
 /// 返回：
 /// - 实际使用的本地资源目录绝对路径。
@@ -2915,6 +3052,7 @@ fn _init_ocr(
     local_root_dir: Option<JsValue>,
     cdn_base_url: Option<JsValue>,
     num_thread: Option<JsValue>,
+    max_batch_size: Option<JsValue>,
     ctx: &mut Context,
 ) -> JsResult<JsValue> {
     let local_root_dir = local_root_dir.unwrap_or_else(|| JsValue::undefined());
@@ -2946,11 +3084,15 @@ fn _init_ocr(
     };
 
     let num_thread = num_thread.to_number(ctx)? as i32;
-    let config = OcrInitConfig {
+    let mut config = OcrInitConfig {
         local_root_dir,
         cdn_base_url,
         num_thread,
+        ..Default::default()
     };
+    if let Some(value) = max_batch_size.filter(|v| !v.is_undefined() && !v.is_null()) {
+        config.max_batch_size = value.to_number(ctx)?.max(1.0) as usize;
+    }
     let root_dir = ocr::init_ocr(config)
         .map_err(|e| JsNativeError::error().with_message(format!("initOcr 失败: {e}")))?;
     Ok(JsValue::from(js_string!(
@@ -3009,17 +3151,151 @@ fn _init_mono_depth(
     )))
 }
 
-/// OCR 文字识别：输入 Mat，返回识别文本。
-fn _ocr_text(js_img_mat: Option<JsValue>, _ctx: &mut Context) -> JsResult<JsValue> {
+/// 解析 OCR 选项（`chars` / `charset` / `customChars` / `pattern`）。
+///
+/// 说明：
+/// - `charset`：预置字符集名（`digits` / `latin` / `punct`）或其数组，未知名称抛出错误；
+/// - `customChars`：按字面加入白名单的自定义字符（如 `"+-/%"`），可与 `charset` 组合；
+/// - 白名单为空（如 `charset: []`）时抛出错误，避免屏蔽全部字符后静默返回空文本；
+/// - `pattern`：字符串或 RegExp，识别结果不满足的文本块被丢弃。
+fn _parse_ocr_options(options: Option<&JsValue>, ctx: &mut Context) -> JsResult<OcrOptions> {
+    let mut parsed = OcrOptions::default();
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(parsed);
+    };
+    if let Some(value) = _get_option_value(&options, "chars", ctx)? {
+        parsed.char_offsets = value.to_boolean();
+    }
+    let charset = _get_option_value(&options, "charset", ctx)?;
+    let custom = _get_option_value(&options, "customChars", ctx)?;
+    if charset.is_some() || custom.is_some() {
+        let mut names = Vec::new();
+        if let Some(value) = charset {
+            let array = value
+                .as_object()
+                .and_then(|obj| JsArray::from_object(obj.clone()).ok());
+            if let Some(array) = array {
+                let length = array.length(ctx)? as usize;
+                for idx in 0..length {
+                    let item = array.get(idx as u32, ctx)?;
+                    names.push(item.to_string(ctx)?.to_std_string_escaped());
+                }
+            } else {
+                names.push(value.to_string(ctx)?.to_std_string_escaped());
+            }
+        }
+        let custom = match custom {
+            Some(value) => value.to_string(ctx)?.to_std_string_escaped(),
+            None => String::new(),
+        };
+        let whitelist = ocr::build_whitelist(&names, &custom)
+            .map_err(|e| JsNativeError::range().with_message(e))?;
+        parsed.whitelist = Some(whitelist);
+    }
+    if let Some(value) = _get_option_value(&options, "pattern", ctx)? {
+        parsed.pattern = Some(_parse_text_pattern(Some(&value), ctx)?);
+    }
+    Ok(parsed)
+}
+
+/// OCR 文字识别：输入 Mat，返回识别文本（`options` 同 `ocr` 的 `charset` / `customChars` / `pattern`）。
+fn _ocr_text(
+    js_img_mat: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
     let js_img_mat = js_img_mat
         .unwrap_or_else(|| JsValue::undefined())
         .get_native::<JsMat>()?;
+    let options = _parse_ocr_options(options.as_ref(), ctx)?;
     let mat = (*js_img_mat.borrow().data().inner).clone();
-    let text = ocr::ocr_text_from_mat(&mat)
+    let text = ocr::ocr_text_from_mat(&mat, &options)
         .map_err(|e| JsNativeError::error().with_message(format!("ocrText 失败: {e}")))?;
     Ok(JsValue::from(js_string!(text)))
 }
 
+/// 结构化 OCR：返回 `{ text, blocks, lines }`。
+///
+/// 说明：
+/// - `blocks` 按阅读顺序排列，每项为 `{ text, confidence, score, box, bbox, line, offsets? }`，
+///   `box` 为旋转框四点（左上、右上、右下、左下），`score` 为检测框得分；
+/// - `lines` 每项为 `{ text, bbox, blocks }`，`blocks` 为行内文本块下标；`text` 为各行以换行连接；
+/// - `options.chars` 为 true 时输出 `offsets`（各字符中心沿文字方向相对块起点的像素偏移）；
+/// - `options.charset` / `options.customChars` / `options.pattern` 限定解码字符集与结果格式，
+///   见 `_parse_ocr_options`。
+fn _ocr(
+    js_img_mat: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let js_img_mat = js_img_mat
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let ocr_options = _parse_ocr_options(options.as_ref(), ctx)?;
+    let result = ocr::ocr_from_mat(&js_img_mat.borrow().data().inner, &ocr_options)
+        .map_err(|e| JsNativeError::error().with_message(format!("ocr 失败: {e}")))?;
+
+    let blocks = JsArray::new(ctx);
+    for block in &result.blocks {
+        let points = JsArray::new(ctx);
+        for point in &block.points {
+            points.push(js_value!([point.x, point.y], ctx), ctx)?;
+        }
+        let bbox = block.bbox();
+        let item = js_object!({
+            text: js_string!(block.text.clone()),
+            confidence: block.confidence as f64,
+            score: block.det_score as f64,
+            bbox: js_value!([bbox.x, bbox.y, bbox.width, bbox.height], ctx),
+            line: block.line as f64,
+        }, ctx);
+        item.set(js_string!("box"), points, false, ctx)?;
+        if let Some(offsets) = &block.char_offsets {
+            let offsets = JsArray::from_iter(
+                offsets.iter().map(|&offset| JsValue::new(offset as f64)),
+                ctx,
+            );
+            item.set(js_string!("offsets"), offsets, false, ctx)?;
+        }
+        blocks.push(item, ctx)?;
+    }
+
+    let lines = JsArray::new(ctx);
+    for line in &result.lines {
+        let members = JsArray::from_iter(
+            line.blocks.iter().map(|&index| JsValue::new(index as f64)),
+            ctx,
+        );
+        let bbox = line.bbox;
+        let item = js_object!({
+            text: js_string!(line.text.clone()),
+            bbox: js_value!([bbox.x, bbox.y, bbox.width, bbox.height], ctx),
+            blocks: members,
+        }, ctx);
+        lines.push(item, ctx)?;
+    }
+
+    let text = result
+        .lines
+        .iter()
+        .map(|line| line.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let timings = js_object!({
+        det: result.timings.det_ms,
+        cls: result.timings.cls_ms,
+        rec: result.timings.rec_ms,
+        batches: result.timings.rec_batches as u32,
+    }, ctx);
+    let result = js_object!({
+        text: js_string!(text),
+        blocks: blocks,
+        lines: lines,
+        timings: timings,
+    }, ctx);
+    Ok(result.into())
+}
+
 /// 显示图片函数 (异步)
 fn _imshow(
     title: Option<JsValue>,
@@ -3154,10 +3430,13 @@ fn _select_roi(
 }
 
 /// 颜色键过滤函数，返回根据色键过滤后的灰度图像。
+///
+/// `options.distance` 可选 `rgb`（默认）/ `deltaE76` / `deltaE2000`。
 fn _color_filter(
     js_img_mat: Option<JsValue>,
     colors: Option<JsValue>,
     tolerance: Option<JsValue>,
+    options: Option<JsValue>,
     ctx: &mut Context,
 ) -> JsResult<JsValue> {
     let js_img_mat = js_img_mat
@@ -3169,7 +3448,9 @@ fn _color_filter(
     let tolerances =
         _parse_color_tolerances(tolerance.unwrap_or_else(|| js_value!(0)), colors.len(), ctx)?;
 
-    match color_filter_impl(&img_mat, &colors, &tolerances) {
+    let distance = _parse_color_distance(options.as_ref(), ctx)?;
+
+    match color_filter_impl(&img_mat, &colors, &tolerances, distance) {
         Ok(mask) => Box::new(mask).into_js(ctx),
         Err(msg) => Err(JsNativeError::error().with_message(msg).into()),
     }
@@ -3204,11 +3485,14 @@ fn _color_filter_hsl(
 }
 
 /// 色键匹配函数，返回匹配像素均值最大的颜色索引（支持最小 mean 与颜色容差）。
+///
+/// `options.distance` 可选 `rgb`（默认）/ `deltaE76` / `deltaE2000`。
 fn _color_key_match(
     js_img_mat: Option<JsValue>,
     colors: Option<JsValue>,
     min_mean: Option<JsValue>,
     tolerance: Option<JsValue>,
+    options: Option<JsValue>,
     ctx: &mut Context,
 ) -> JsResult<JsValue> {
     let js_img_mat = js_img_mat
@@ -3226,16 +3510,21 @@ fn _color_key_match(
     let tolerances =
         _parse_color_tolerances(tolerance.unwrap_or_else(|| js_value!(0)), colors.len(), ctx)?;
 
-    let index = color_key_match_impl(&img_mat, &colors, min_mean, &tolerances)
+    let distance = _parse_color_distance(options.as_ref(), ctx)?;
+
+    let index = color_key_match_impl(&img_mat, &colors, min_mean, &tolerances, distance)
         .map_err(|msg| JsNativeError::error().with_message(msg))?;
     Ok(JsValue::new(index))
 }
 
 /// 批量颜色模板匹配函数，返回首个命中模板的位置和索引。
+///
+/// `options.distance` 为 ΔE 模式时，命中区域与模板的平均色差须不超过 `options.maxDelta`（默认 10）。
 fn _batch_match_color(
     js_src_mat: Option<JsValue>,
     js_tpl_mats: Option<JsValue>,
     cap: Option<JsValue>,
+    options: Option<JsValue>,
     ctx: &mut Context,
 ) -> JsResult<JsValue> {
     let js_src_mat = js_src_mat
@@ -3247,7 +3536,15 @@ fn _batch_match_color(
     let cap = cap.unwrap_or_else(|| js_value!(0.8)).to_number(ctx)?;
     let cap = cap.clamp(0.0, 1.0);
 
-    match batch_match_color_impl(&src_mat, tpl_mats, cap) {
+    let distance = _parse_color_distance(options.as_ref(), ctx)?;
+    let mut max_delta = 10.0;
+    if let Some(options) = options.as_ref().and_then(JsValue::as_object)
+        && let Some(value) = _get_option_value(&options, "maxDelta", ctx)?
+    {
+        max_delta = value.to_number(ctx)?.max(0.0);
+    }
+
+    match batch_match_color_impl(&src_mat, tpl_mats, cap, distance, max_delta) {
         Ok(Some((index, x, y))) => {
             let pos = js_value!([x, y], ctx);
             let result = js_object!({
@@ -3454,23 +3751,179 @@ fn _sift_stitch(
     Ok(result_obj.into())
 }
 
-/// 计算输入图像的感知哈希（支持彩色模式）。
+/// 计算输入图像的感知哈希（支持彩色模式与 `phash` / `ahash` / `dhash` / `blockmean` 算法）。
 fn _perceptual_hash(
     js_img_mat: Option<JsValue>,
     color: Option<JsValue>,
-    _ctx: &mut Context,
+    kind: Option<JsValue>,
+    ctx: &mut Context,
 ) -> JsResult<JsValue> {
     let js_img_mat = js_img_mat
         .unwrap_or_else(|| JsValue::undefined())
         .get_native::<JsMat>()?;
     let img_mat = (*js_img_mat.borrow().data().inner).clone();
     let color = color.is_some_and(|v| v.to_boolean());
+    let kind = match kind.filter(|v| !v.is_undefined() && !v.is_null()) {
+        Some(value) => {
+            let name = value.to_string(ctx)?.to_std_string_escaped();
+            ImageHashKind::from_name(&name).ok_or_else(|| {
+                JsNativeError::typ().with_message(format!("未知的哈希算法: {name}"))
+            })?
+        }
+        None => ImageHashKind::default(),
+    };
 
-    let hash = perceptual_hash_impl(&img_mat, color)
+    let hash = perceptual_hash_impl(&img_mat, color, kind)
         .map_err(|msg| JsNativeError::error().with_message(msg))?;
     Ok(JsValue::from(js_string!(hash)))
 }
 
+/// 从标注 JSON 文件加载感知哈希索引（相对路径按当前脚本目录解析）。
+fn _load_hash_index(path: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
+    let path = path
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_lossy();
+    let resolved = _resolve_script_resource_path(&path);
+    let index = HashIndex::load(Path::new(resolved.as_ref()))
+        .map_err(|msg| JsNativeError::error().with_message(msg))?;
+    Box::new(index).into_js(ctx)
+}
+
+/// 从图片目录构建 ORB/SIFT 特征库（相对路径按当前脚本目录解析）。
+///
+/// 说明：无法读取或检测不到特征点的图片会被跳过，并记录在返回对象的 `skipped` 数组中。
+fn _build_feature_library(
+    dir: Option<JsValue>,
+    kind: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let dir = dir
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_lossy();
+    let kind = parse_feature_kind(kind.as_ref(), ctx)?;
+    let resolved = _resolve_script_resource_path(&dir);
+    let (library, skipped) = FeatureLibrary::build_from_dir(Path::new(resolved.as_ref()), kind)
+        .map_err(|msg| JsNativeError::error().with_message(msg))?;
+    let value = Box::new(library).into_js(ctx)?;
+    let skipped = JsArray::from_iter(
+        skipped
+            .into_iter()
+            .map(|path| JsValue::from(js_string!(path))),
+        ctx,
+    );
+    if let Some(obj) = value.as_object() {
+        obj.set(js_string!("skipped"), skipped, false, ctx)?;
+    }
+    Ok(value)
+}
+
+/// 从文件加载特征库（相对路径按当前脚本目录解析）。
+fn _load_feature_library(path: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
+    let path = path
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_lossy();
+    let resolved = _resolve_script_resource_path(&path);
+    let library = FeatureLibrary::load(Path::new(resolved.as_ref()))
+        .map_err(|msg| JsNativeError::error().with_message(msg))?;
+    Box::new(library).into_js(ctx)
+}
+
+/// 从目录加载字形集（相对路径按当前脚本目录解析），无法加载的文件记录在返回对象的 `skipped` 中。
+fn _load_glyph_set(dir: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
+    let dir = dir
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_lossy();
+    let resolved = _resolve_script_resource_path(&dir);
+    let (set, skipped) = GlyphSet::load_dir(Path::new(resolved.as_ref()))
+        .map_err(|msg| JsNativeError::error().with_message(msg))?;
+    let value = Box::new(set).into_js(ctx)?;
+    let skipped = JsArray::from_iter(
+        skipped
+            .into_iter()
+            .map(|path| JsValue::from(js_string!(path))),
+        ctx,
+    );
+    if let Some(obj) = value.as_object() {
+        obj.set(js_string!("skipped"), skipped, false, ctx)?;
+    }
+    Ok(value)
+}
+
+/// 字形识别函数：按 `segmentChars` 分割单行文本后逐字与字形集比对。
+fn _read_glyphs(
+    js_img_mat: Option<JsValue>,
+    glyphs: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let js_img_mat = js_img_mat
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let glyphs = arg_glyph_set(glyphs.as_ref())?;
+    let read_options = parse_glyph_read_options(options.as_ref(), ctx)?;
+
+    let reading = glyphs
+        .borrow()
+        .data()
+        .inner
+        .borrow()
+        .read(&js_img_mat.borrow().data().inner, &read_options, |_| true)
+        .map_err(|msg| JsNativeError::error().with_message(msg))?;
+    Ok(glyph_reading_to_js(&reading, ctx)?.into())
+}
+
+/// 数字识别函数：只使用数字字形（以及 `options.extra` 中的字符）比对，
+/// 并在结果中附加 `value`（文本可解析为数字时为数值，否则为 null）。
+fn _read_digits(
+    js_img_mat: Option<JsValue>,
+    glyphs: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let js_img_mat = js_img_mat
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let glyphs = arg_glyph_set(glyphs.as_ref())?;
+    let read_options = parse_glyph_read_options(options.as_ref(), ctx)?;
+    let extra = match options.as_ref().and_then(JsValue::as_object) {
+        Some(obj) => match _get_option_value(&obj, "extra", ctx)? {
+            Some(value) => value.to_string(ctx)?.to_std_string_lossy(),
+            None => String::new(),
+        },
+        None => String::new(),
+    };
+
+    let reading = glyphs
+        .borrow()
+        .data()
+        .inner
+        .borrow()
+        .read(&js_img_mat.borrow().data().inner, &read_options, |label| {
+            label
+                .chars()
+                .all(|c| c.is_ascii_digit() || extra.contains(c))
+        })
+        .map_err(|msg| JsNativeError::error().with_message(msg))?;
+
+    let value = if reading.chars.iter().all(|c| c.label.is_some()) {
+        reading.text.parse::<f64>().ok()
+    } else {
+        None
+    };
+    let result = glyph_reading_to_js(&reading, ctx)?;
+    result.set(
+        js_string!("value"),
+        value.map_or_else(JsValue::null, JsValue::new),
+        false,
+        ctx,
+    )?;
+    Ok(result.into())
+}
+
 /// 计算输入图像的 ORB 特征字符串（压缩后的原始 ORB 描述子）。
 fn _orb_feature(js_img_mat: Option<JsValue>, _ctx: &mut Context) -> JsResult<JsValue> {
     let js_img_mat = js_img_mat
@@ -3762,11 +4215,16 @@ fn _draw_contours(
 }
 
 /// 使用两个Mat对象进行颜色和模板匹配函数
+///
+/// `options`（可选）:
+/// - `method` / `preprocess`: 匹配算法与预处理，见 `_parse_match_config`
+/// - `signal`: AbortSignal，中止时以其原因拒绝
 fn _find_color_and_match_template(
     js_img_mat: Option<JsValue>,
     js_tpl_mat: Option<JsValue>,
     color: Option<JsValue>,
     tolerance: Option<JsValue>,
+    options: Option<JsValue>,
     ctx: &mut Context,
 ) -> JsResult<JsValue> {
     // 获取第一个参数 (图像Mat)
@@ -3787,20 +4245,35 @@ fn _find_color_and_match_template(
         .unwrap_or_else(|| JsValue::undefined())
         .to_number(ctx)? as u8;
     let bgr_color = rgb_to_bgr(color);
+    let config = _parse_match_config(options.as_ref(), ctx)?;
+    let abort = ScriptAbort::from_options(options.as_ref(), ctx)?;
 
     let (promise, resolvers) = JsPromise::new_pending(ctx);
+    if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+        return Ok(promise.into());
+    }
     let img_mat = (*js_img_mat.borrow().data().inner).clone();
     let tpl_mat = (*js_tpl_mat.borrow().data().inner).clone();
     let resolvers_clone = resolvers.clone();
+    let token = abort.as_ref().map(ScriptAbort::token);
 
     ctx.enqueue_job(
         NativeAsyncJob::new(async move |context| {
-            let async_result = _spawn_blocking_with_script_stop_snapshot(move || {
-                find_color_and_match_template(&img_mat, &tpl_mat, bgr_color, tolerance)
-            })
-            .await;
+            let task = _spawn_blocking_with_script_stop_snapshot(move || {
+                find_color_and_match_template_with(
+                    &img_mat, &tpl_mat, bgr_color, tolerance, &config,
+                )
+            });
+            let async_result = run_abortable(token.as_ref(), task).await;
 
             let context = &mut context.borrow_mut();
+            let Some(async_result) = async_result else {
+                return ScriptAbort::reject_aborted(
+                    abort.as_ref(),
+                    &resolvers_clone.reject,
+                    context,
+                );
+            };
             match async_result {
                 Ok(Ok(Some((x, y)))) => resolvers_clone.resolve.call(
                     &JsValue::undefined(),
@@ -3835,10 +4308,15 @@ fn _find_color_and_match_template(
 }
 
 /// 模板匹配函数
+///
+/// `options`（可选）:
+/// - `method` / `preprocess`: 匹配算法与预处理，见 `_parse_match_config`
+/// - `signal`: AbortSignal，中止时以其原因拒绝
 fn _match_template(
     js_img_mat: Option<JsValue>,
     js_tpl_mat: Option<JsValue>,
     tolerance: Option<JsValue>,
+    options: Option<JsValue>,
     ctx: &mut Context,
 ) -> JsResult<JsValue> {
     // 获取第一个参数 (图像Mat)
@@ -3855,20 +4333,33 @@ fn _match_template(
     let tolerance = tolerance
         .unwrap_or_else(|| JsValue::undefined())
         .to_number(ctx)? as f64;
+    let config = _parse_match_config(options.as_ref(), ctx)?;
+    let abort = ScriptAbort::from_options(options.as_ref(), ctx)?;
 
     let (promise, resolvers) = JsPromise::new_pending(ctx);
+    if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+        return Ok(promise.into());
+    }
     let img_mat = (*js_img_mat.borrow().data().inner).clone();
     let tpl_mat = (*js_tpl_mat.borrow().data().inner).clone();
     let resolvers_clone = resolvers.clone();
+    let token = abort.as_ref().map(ScriptAbort::token);
 
     ctx.enqueue_job(
         NativeAsyncJob::new(async move |context| {
-            let async_result = _spawn_blocking_with_script_stop_snapshot(move || {
-                match_template(&img_mat, &tpl_mat, tolerance)
-            })
-            .await;
+            let task = _spawn_blocking_with_script_stop_snapshot(move || {
+                match_template_with(&img_mat, &tpl_mat, tolerance, &config)
+            });
+            let async_result = run_abortable(token.as_ref(), task).await;
 
             let context = &mut context.borrow_mut();
+            let Some(async_result) = async_result else {
+                return ScriptAbort::reject_aborted(
+                    abort.as_ref(),
+                    &resolvers_clone.reject,
+                    context,
+                );
+            };
             match async_result {
                 Ok(Ok(Some((x, y)))) => resolvers_clone.resolve.call(
                     &JsValue::undefined(),
@@ -3902,67 +4393,455 @@ fn _match_template(
     Ok(promise.into())
 }
 
-/// 颜色矩阵检查函数
-fn _cc(
-    js_img_mat: Option<JsValue>,
-    x: Option<JsValue>,
-    y: Option<JsValue>,
-    color: Option<JsValue>,
-    tolerance: Option<JsValue>,
+/// 读取 options 对象中的可选字段，`undefined` / `null` 视为未提供。
+pub(crate) fn _get_option_value(
+    options: &JsObject,
+    key: &str,
     ctx: &mut Context,
-) -> JsResult<JsValue> {
-    // 获取第一个参数 (图像Mat)
-    let js_img_mat = js_img_mat
-        .unwrap_or_else(|| JsValue::undefined())
-        .get_native::<JsMat>()?;
-
-    let x = x.unwrap_or_else(|| JsValue::undefined()).to_number(ctx)? as i32;
-    let y = y.unwrap_or_else(|| JsValue::undefined()).to_number(ctx)? as i32;
-    let color = color
-        .unwrap_or_else(|| JsValue::undefined())
-        .to_number(ctx)? as u32;
-    let tolerance = tolerance
-        .unwrap_or_else(|| JsValue::undefined())
-        .to_number(ctx)? as u8;
-
-    Ok(JsValue::new(check_color_mat(
-        &js_img_mat.borrow().data().inner,
-        x,
-        y,
-        color,
-        tolerance,
-    )))
+) -> JsResult<Option<JsValue>> {
+    let value = options.get(js_string!(key), ctx)?;
+    if value.is_undefined() || value.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(value))
 }
 
-/// 等待窗口指定坐标颜色达到条件（异步）。
-///
-/// 规则：
-/// - `tolerance >= 0`：等待颜色“满足”条件后返回 `true`。
-/// - `tolerance < 0`：等待颜色“变为不满足”条件后返回 `true`。
-/// - 超时后返回 `false`。
-fn _wait_color(
-    hwnd: Option<JsValue>,
-    x: Option<JsValue>,
-    y: Option<JsValue>,
-    color: Option<JsValue>,
-    tolerance: Option<JsValue>,
-    timeout: Option<JsValue>,
+/// 读取 options 对象中的有限数字字段，缺省或非有限值视为未提供。
+pub(crate) fn _get_option_number(
+    options: &JsObject,
+    key: &str,
     ctx: &mut Context,
-) -> JsResult<JsValue> {
-    let hwnd_raw = hwnd
-        .unwrap_or_else(|| JsValue::undefined())
-        .to_number(ctx)? as isize;
-    let x = x.unwrap_or_else(|| JsValue::undefined()).to_number(ctx)? as i32;
-    let y = y.unwrap_or_else(|| JsValue::undefined()).to_number(ctx)? as i32;
-    let color = color
-        .unwrap_or_else(|| JsValue::undefined())
-        .to_number(ctx)? as u32;
+) -> JsResult<Option<f64>> {
+    let Some(value) = _get_option_value(options, key, ctx)? else {
+        return Ok(None);
+    };
+    let number = value.to_number(ctx)?;
+    Ok(number.is_finite().then_some(number))
+}
 
-    let tolerance_raw = tolerance.unwrap_or_else(|| js_value!(0)).to_number(ctx)?;
-    let wait_for_match = tolerance_raw >= 0.0;
-    let tolerance_abs = tolerance_raw.abs().clamp(0.0, 255.0) as u8;
+/// 读取 options 对象中的非负整数字段，缺省或非有限值时返回 `default`。
+pub(crate) fn _get_option_u32(
+    options: &JsObject,
+    key: &str,
+    default: u32,
+    ctx: &mut Context,
+) -> JsResult<u32> {
+    Ok(_get_option_number(options, key, ctx)?
+        .map_or(default, |number| number.clamp(0.0, u32::MAX as f64) as u32))
+}
 
-    let timeout_raw = timeout
+/// 解析 options 中的 `roi: [x, y, w, h]` 搜索区域。
+fn _parse_roi_option(options: &JsObject, ctx: &mut Context) -> JsResult<Option<core::Rect>> {
+    let Some(roi) = _get_option_value(options, "roi", ctx)? else {
+        return Ok(None);
+    };
+    let roi_array = roi
+        .as_object()
+        .and_then(|obj| JsArray::from_object(obj.clone()).ok())
+        .ok_or_else(|| JsNativeError::typ().with_message("roi 必须是 [x, y, w, h] 数组"))?;
+    let (x, y, w, h) = _parse_bbox_tuple(&roi_array, 0, ctx)?;
+    Ok(Some(core::Rect::new(x, y, w, h)))
+}
+
+/// 解析颜色距离模式 `options.distance`，缺省为 `rgb`。
+fn _parse_color_distance(options: Option<&JsValue>, ctx: &mut Context) -> JsResult<ColorDistance> {
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(ColorDistance::Rgb);
+    };
+    let Some(value) = _get_option_value(&options, "distance", ctx)? else {
+        return Ok(ColorDistance::Rgb);
+    };
+    let name = value.to_string(ctx)?.to_std_string_escaped();
+    ColorDistance::from_name(&name).ok_or_else(|| {
+        JsNativeError::typ()
+            .with_message(format!("未知的颜色距离模式: {name}"))
+            .into()
+    })
+}
+
+/// 解析 options 中的 `method` / `preprocess` 匹配配置。
+///
+/// - `method`: `"ccoeff_normed"`（默认）/ `"ccoeff"` / `"ccorr_normed"` / `"ccorr"` /
+///   `"sqdiff_normed"` / `"sqdiff"`；
+/// - `preprocess`: `"none"` / `"gray"` / `"canny"` / `"clahe"` / `"lightness"` / `"b"` / `"g"` / `"r"`，
+///   或 `{ type, low, high, clipLimit, tileSize, channel }` 对象以覆盖默认参数。
+fn _parse_match_config(options: Option<&JsValue>, ctx: &mut Context) -> JsResult<MatchConfig> {
+    let mut config = MatchConfig::default();
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(config);
+    };
+
+    if let Some(method) = _get_option_value(&options, "method", ctx)? {
+        let name = method.to_string(ctx)?.to_std_string_escaped();
+        config.method = MatchMethod::from_name(&name).ok_or_else(|| {
+            JsNativeError::typ().with_message(format!("未知的模板匹配算法: {name}"))
+        })?;
+    }
+
+    let Some(preprocess) = _get_option_value(&options, "preprocess", ctx)? else {
+        return Ok(config);
+    };
+    let preprocess_obj = preprocess.as_object();
+    let name = match &preprocess_obj {
+        Some(obj) => obj
+            .get(js_string!("type"), ctx)?
+            .to_string(ctx)?
+            .to_std_string_escaped(),
+        None => preprocess.to_string(ctx)?.to_std_string_escaped(),
+    };
+    let mut parsed = MatchPreprocess::from_name(&name).ok_or_else(|| {
+        JsNativeError::typ().with_message(format!("未知的模板匹配预处理: {name}"))
+    })?;
+    if let Some(obj) = preprocess_obj {
+        match &mut parsed {
+            MatchPreprocess::Canny { low, high } => {
+                if let Some(value) = _get_option_value(&obj, "low", ctx)? {
+                    *low = value.to_number(ctx)?;
+                }
+                if let Some(value) = _get_option_value(&obj, "high", ctx)? {
+                    *high = value.to_number(ctx)?;
+                }
+            }
+            MatchPreprocess::Clahe {
+                clip_limit,
+                tile_size,
+            } => {
+                if let Some(value) = _get_option_value(&obj, "clipLimit", ctx)? {
+                    *clip_limit = value.to_number(ctx)?;
+                }
+                if let Some(value) = _get_option_value(&obj, "tileSize", ctx)? {
+                    *tile_size = value.to_number(ctx)? as i32;
+                }
+            }
+            MatchPreprocess::Channel(channel) => {
+                if let Some(value) = _get_option_value(&obj, "channel", ctx)? {
+                    *channel = value.to_number(ctx)? as i32;
+                }
+            }
+            _ => {}
+        }
+    }
+    config.preprocess = parsed;
+    Ok(config)
+}
+
+/// 解析 `matchTemplateAll` 的 options 参数（`maxResults` / `nmsIou` / `roi`）。
+fn _parse_match_all_options(
+    threshold: Option<JsValue>,
+    options: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<MatchAllOptions> {
+    let mut parsed = MatchAllOptions::default();
+    if let Some(threshold) = threshold.filter(|v| !v.is_undefined() && !v.is_null()) {
+        parsed.threshold = threshold.to_number(ctx)?;
+    }
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(parsed);
+    };
+
+    if let Some(max_results) = _get_option_value(&options, "maxResults", ctx)? {
+        parsed.max_results = max_results.to_number(ctx)?.max(0.0) as usize;
+    }
+    if let Some(nms_iou) = _get_option_value(&options, "nmsIou", ctx)? {
+        parsed.nms_iou = nms_iou.to_number(ctx)?;
+    }
+    parsed.roi = _parse_roi_option(&options, ctx)?;
+    parsed.config = _parse_match_config(Some(&JsValue::from(options)), ctx)?;
+    Ok(parsed)
+}
+
+/// 多目标模板匹配函数
+///
+/// 返回 `{ x, y, w, h, score }` 数组（按得分降序，已做 IoU 非极大值抑制）。
+///
+/// `options`（可选）:
+/// - `maxResults`: 最多返回数量（默认 32，0 表示不限制）
+/// - `nmsIou`: NMS 的 IoU 阈值（默认 0.3）
+/// - `roi`: 搜索区域 `[x, y, w, h]`
+/// - `method` / `preprocess`: 匹配算法与预处理，见 `_parse_match_config`
+/// - `signal`: AbortSignal，中止时以其原因拒绝
+fn _match_template_all(
+    js_img_mat: Option<JsValue>,
+    js_tpl_mat: Option<JsValue>,
+    threshold: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let js_img_mat = js_img_mat
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let js_tpl_mat = js_tpl_mat
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let match_options = _parse_match_all_options(threshold, options.as_ref(), ctx)?;
+    let abort = ScriptAbort::from_options(options.as_ref(), ctx)?;
+
+    let (promise, resolvers) = JsPromise::new_pending(ctx);
+    if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+        return Ok(promise.into());
+    }
+    let img_mat = (*js_img_mat.borrow().data().inner).clone();
+    let tpl_mat = (*js_tpl_mat.borrow().data().inner).clone();
+    let resolvers_clone = resolvers.clone();
+    let token = abort.as_ref().map(ScriptAbort::token);
+
+    ctx.enqueue_job(
+        NativeAsyncJob::new(async move |context| {
+            let task = _spawn_blocking_with_script_stop_snapshot(move || {
+                match_template_all(&img_mat, &tpl_mat, &match_options)
+            });
+            let async_result = run_abortable(token.as_ref(), task).await;
+
+            let context = &mut context.borrow_mut();
+            let Some(async_result) = async_result else {
+                return ScriptAbort::reject_aborted(
+                    abort.as_ref(),
+                    &resolvers_clone.reject,
+                    context,
+                );
+            };
+            match async_result {
+                Ok(Ok(matches)) => {
+                    let array = JsArray::new(context);
+                    for m in matches {
+                        let item = js_object!({
+                            x: m.x,
+                            y: m.y,
+                            w: m.w,
+                            h: m.h,
+                            score: m.score,
+                        }, context);
+                        array.push(item, context)?;
+                    }
+                    resolvers_clone
+                        .resolve
+                        .call(&JsValue::undefined(), &[array.into()], context)
+                }
+                Ok(Err(e)) => {
+                    let msg = format!("matchTemplateAll 匹配失败: {:?}", e);
+                    resolvers_clone.reject.call(
+                        &JsValue::undefined(),
+                        &[JsValue::from(js_string!(msg))],
+                        context,
+                    )
+                }
+                Err(e) => {
+                    let msg = format!("matchTemplateAll 线程执行失败: {e}");
+                    resolvers_clone.reject.call(
+                        &JsValue::undefined(),
+                        &[JsValue::from(js_string!(msg))],
+                        context,
+                    )
+                }
+            }
+        })
+        .into(),
+    );
+
+    Ok(promise.into())
+}
+
+/// 解析 `matchTemplateScaled` 的 options 参数。
+fn _parse_scaled_match_options(
+    threshold: Option<JsValue>,
+    options: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<ScaledMatchOptions> {
+    let mut parsed = ScaledMatchOptions::default();
+    if let Some(threshold) = threshold.filter(|v| !v.is_undefined() && !v.is_null()) {
+        parsed.threshold = threshold.to_number(ctx)?;
+    }
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(parsed);
+    };
+
+    if let Some(scales) = _get_option_value(&options, "scales", ctx)? {
+        parsed.scales = _parse_f64_array(scales, "scales", ctx)?;
+    } else {
+        let min_scale = _get_option_value(&options, "minScale", ctx)?;
+        let max_scale = _get_option_value(&options, "maxScale", ctx)?;
+        let scale_step = _get_option_value(&options, "scaleStep", ctx)?;
+        if min_scale.is_some() || max_scale.is_some() || scale_step.is_some() {
+            let min_scale = min_scale.map_or(Ok(0.5), |v| v.to_number(ctx))?;
+            let max_scale = max_scale.map_or(Ok(2.0), |v| v.to_number(ctx))?;
+            let scale_step = scale_step.map_or(Ok(0.1), |v| v.to_number(ctx))?;
+            parsed.scales = ScaledMatchOptions::scale_range(min_scale, max_scale, scale_step);
+        }
+    }
+    if let Some(angles) = _get_option_value(&options, "angles", ctx)? {
+        parsed.angles = _parse_f64_array(angles, "angles", ctx)?;
+    }
+    if let Some(coarse_scale) = _get_option_value(&options, "coarseScale", ctx)? {
+        parsed.coarse_scale = coarse_scale.to_number(ctx)?;
+    }
+    if let Some(candidates) = _get_option_value(&options, "candidates", ctx)? {
+        parsed.refine_candidates = candidates.to_number(ctx)?.max(1.0) as usize;
+    }
+    parsed.roi = _parse_roi_option(&options, ctx)?;
+    parsed.config = _parse_match_config(Some(&JsValue::from(options)), ctx)?;
+    Ok(parsed)
+}
+
+/// 多尺度 / 多角度模板匹配函数（由粗到细）
+///
+/// 命中时返回 `{ x, y, w, h, score, scale, angle }`，否则返回 `undefined`。
+///
+/// `options`（可选）:
+/// - `scales`: 缩放比例列表；或用 `minScale` / `maxScale` / `scaleStep` 生成（默认 0.5~2.0，步长 0.1）
+/// - `angles`: 旋转角度列表（度，逆时针为正，默认 `[0]`）
+/// - `coarseScale`: 粗匹配缩放比例（默认 0.5，1 表示关闭粗匹配）
+/// - `candidates`: 进入全分辨率精修的候选数（默认 3）
+/// - `roi`: 搜索区域 `[x, y, w, h]`
+/// - `method` / `preprocess`: 匹配算法与预处理，见 `_parse_match_config`
+/// - `signal`: AbortSignal，中止时以其原因拒绝
+fn _match_template_scaled(
+    js_img_mat: Option<JsValue>,
+    js_tpl_mat: Option<JsValue>,
+    threshold: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let js_img_mat = js_img_mat
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let js_tpl_mat = js_tpl_mat
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let match_options = _parse_scaled_match_options(threshold, options.as_ref(), ctx)?;
+    let abort = ScriptAbort::from_options(options.as_ref(), ctx)?;
+
+    let (promise, resolvers) = JsPromise::new_pending(ctx);
+    if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+        return Ok(promise.into());
+    }
+    let img_mat = (*js_img_mat.borrow().data().inner).clone();
+    let tpl_mat = (*js_tpl_mat.borrow().data().inner).clone();
+    let resolvers_clone = resolvers.clone();
+    let token = abort.as_ref().map(ScriptAbort::token);
+
+    ctx.enqueue_job(
+        NativeAsyncJob::new(async move |context| {
+            let task = _spawn_blocking_with_script_stop_snapshot(move || {
+                match_template_scaled(&img_mat, &tpl_mat, &match_options)
+            });
+            let async_result = run_abortable(token.as_ref(), task).await;
+
+            let context = &mut context.borrow_mut();
+            let Some(async_result) = async_result else {
+                return ScriptAbort::reject_aborted(
+                    abort.as_ref(),
+                    &resolvers_clone.reject,
+                    context,
+                );
+            };
+            match async_result {
+                Ok(Ok(Some(m))) => {
+                    let result = js_object!({
+                        x: m.x,
+                        y: m.y,
+                        w: m.w,
+                        h: m.h,
+                        score: m.score,
+                        scale: m.scale,
+                        angle: m.angle,
+                    }, context);
+                    resolvers_clone
+                        .resolve
+                        .call(&JsValue::undefined(), &[result.into()], context)
+                }
+                Ok(Ok(None)) => resolvers_clone
+                    .resolve
+                    .call(&JsValue::undefined(), &[], context),
+                Ok(Err(e)) => {
+                    let msg = format!("matchTemplateScaled 匹配失败: {:?}", e);
+                    resolvers_clone.reject.call(
+                        &JsValue::undefined(),
+                        &[JsValue::from(js_string!(msg))],
+                        context,
+                    )
+                }
+                Err(e) => {
+                    let msg = format!("matchTemplateScaled 线程执行失败: {e}");
+                    resolvers_clone.reject.call(
+                        &JsValue::undefined(),
+                        &[JsValue::from(js_string!(msg))],
+                        context,
+                    )
+                }
+            }
+        })
+        .into(),
+    );
+
+    Ok(promise.into())
+}
+
+/// 颜色矩阵检查函数
+fn _cc(
+    js_img_mat: Option<JsValue>,
+    x: Option<JsValue>,
+    y: Option<JsValue>,
+    color: Option<JsValue>,
+    tolerance: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    // 获取第一个参数 (图像Mat)
+    let js_img_mat = js_img_mat
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+
+    let x = x.unwrap_or_else(|| JsValue::undefined()).to_number(ctx)? as i32;
+    let y = y.unwrap_or_else(|| JsValue::undefined()).to_number(ctx)? as i32;
+    let color = color
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as u32;
+    let tolerance = tolerance
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as u8;
+
+    Ok(JsValue::new(check_color_mat(
+        &js_img_mat.borrow().data().inner,
+        x,
+        y,
+        color,
+        tolerance,
+    )))
+}
+
+/// 等待窗口指定坐标颜色达到条件（异步）。
+///
+/// 规则：
+/// - `tolerance >= 0`：等待颜色“满足”条件后返回 `true`。
+/// - `tolerance < 0`：等待颜色“变为不满足”条件后返回 `true`。
+/// - 超时后返回 `false`。
+/// - `options.signal`（可选）：AbortSignal，中止时以其原因拒绝。
+/// - `options.distance`（可选）：`rgb`（默认）/ `deltaE76` / `deltaE2000`，ΔE 模式下 `tolerance` 为最大色差。
+fn _wait_color(
+    hwnd: Option<JsValue>,
+    x: Option<JsValue>,
+    y: Option<JsValue>,
+    color: Option<JsValue>,
+    tolerance: Option<JsValue>,
+    timeout: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let hwnd_raw = hwnd
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as isize;
+    let x = x.unwrap_or_else(|| JsValue::undefined()).to_number(ctx)? as i32;
+    let y = y.unwrap_or_else(|| JsValue::undefined()).to_number(ctx)? as i32;
+    let color = color
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as u32;
+
+    let tolerance_raw = tolerance.unwrap_or_else(|| js_value!(0)).to_number(ctx)?;
+    let wait_for_match = tolerance_raw >= 0.0;
+    let tolerance_abs = tolerance_raw.abs().clamp(0.0, 255.0);
+    let distance = _parse_color_distance(options.as_ref(), ctx)?;
+    let target_lab = rgb_to_lab(color);
+
+    let timeout_raw = timeout
         .unwrap_or_else(|| js_value!(20_000))
         .to_number(ctx)?;
     let timeout_ms = if timeout_raw.is_finite() {
@@ -3970,46 +4849,485 @@ fn _wait_color(
     } else {
         20_000_u64
     };
+    let abort = ScriptAbort::from_options(options.as_ref(), ctx)?;
+
+    let (promise, resolvers) = JsPromise::new_pending(ctx);
+    if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+        return Ok(promise.into());
+    }
+    let resolvers_clone = resolvers.clone();
+    let token = abort.as_ref().map(ScriptAbort::token);
+    let task_token = token.clone();
+    ctx.enqueue_job(
+        NativeAsyncJob::new(async move |context| {
+            let task = _spawn_blocking_with_script_stop_snapshot(move || {
+                let hwnd = HWND(hwnd_raw as *mut std::ffi::c_void);
+                let deadline = Duration::from_millis(timeout_ms);
+                let start = std::time::Instant::now();
+                let poll_interval = Duration::from_millis(30);
+
+                loop {
+                    if should_stop_current_script()
+                        || task_token.as_ref().is_some_and(|token| token.is_aborted())
+                    {
+                        return false;
+                    }
+                    if let Some(img_mat) = capture_window_wgc(hwnd) {
+                        let matched = match distance {
+                            ColorDistance::Rgb => {
+                                check_color_mat(&img_mat, x, y, color, tolerance_abs as u8)
+                            }
+                            _ => check_color_distance(
+                                &img_mat,
+                                x,
+                                y,
+                                target_lab,
+                                tolerance_abs,
+                                distance,
+                            ),
+                        };
+                        let condition_met = if wait_for_match { matched } else { !matched };
+                        if condition_met {
+                            return true;
+                        }
+                    }
+
+                    if start.elapsed() >= deadline {
+                        return false;
+                    }
+                    thread::sleep(poll_interval);
+                }
+            });
+            let async_result = run_abortable(token.as_ref(), task).await;
+
+            let context = &mut context.borrow_mut();
+            let Some(async_result) = async_result else {
+                return ScriptAbort::reject_aborted(
+                    abort.as_ref(),
+                    &resolvers_clone.reject,
+                    context,
+                );
+            };
+            match async_result {
+                Ok(result) => resolvers_clone.resolve.call(
+                    &JsValue::undefined(),
+                    &[JsValue::new(result)],
+                    context,
+                ),
+                Err(e) => {
+                    let msg = format!("waitColor 线程执行失败: {e}");
+                    resolvers_clone.reject.call(
+                        &JsValue::undefined(),
+                        &[JsValue::from(js_string!(msg))],
+                        context,
+                    )
+                }
+            }
+        })
+        .into(),
+    );
+
+    Ok(promise.into())
+}
+
+/// 解析帧差分选项 `{ threshold, blur, denoise, merge, minArea }`，未提供的字段使用默认值。
+fn _parse_frame_diff_options(
+    options: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<FrameDiffOptions> {
+    let mut diff = FrameDiffOptions::default();
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok(diff);
+    };
+    if let Some(value) = _get_option_value(&options, "threshold", ctx)? {
+        diff.threshold = value.to_number(ctx)?.clamp(0.0, 254.0);
+    }
+    if let Some(value) = _get_option_value(&options, "blur", ctx)? {
+        diff.blur = value.to_i32(ctx)?.clamp(0, 31);
+    }
+    if let Some(value) = _get_option_value(&options, "denoise", ctx)? {
+        diff.denoise = value.to_boolean();
+    }
+    if let Some(value) = _get_option_value(&options, "merge", ctx)? {
+        diff.merge = value.to_i32(ctx)?.clamp(0, 64);
+    }
+    if let Some(value) = _get_option_value(&options, "minArea", ctx)? {
+        diff.min_area = value.to_i32(ctx)?.max(0);
+    }
+    Ok(diff)
+}
+
+/// 帧差分掩码函数：返回两帧间变化像素为 255 的单通道 Mat。
+fn _frame_diff(
+    js_prev: Option<JsValue>,
+    js_curr: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let js_prev = js_prev
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let js_curr = js_curr
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let diff = _parse_frame_diff_options(options.as_ref(), ctx)?;
+
+    let mask = frame_diff_mask(
+        &js_prev.borrow().data().inner,
+        &js_curr.borrow().data().inner,
+        &diff,
+    )
+    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+    Box::new(mask).into_js(ctx)
+}
+
+/// 变化区域检测函数：返回 `{ bboxes, changedPixels, ratio }`，bbox 按面积降序。
+fn _diff_regions(
+    js_prev: Option<JsValue>,
+    js_curr: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let js_prev = js_prev
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let js_curr = js_curr
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let diff = _parse_frame_diff_options(options.as_ref(), ctx)?;
+
+    let change = frame_change(
+        &js_prev.borrow().data().inner,
+        &js_curr.borrow().data().inner,
+        &diff,
+    )
+    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+
+    let bboxes = JsArray::new(ctx);
+    for (x, y, w, h) in change.regions {
+        bboxes.push(js_value!([x, y, w, h], ctx), ctx)?;
+    }
+    let result = js_object!({
+        bboxes: bboxes,
+        changedPixels: change.changed_pixels,
+        ratio: change.ratio,
+    }, ctx);
+    Ok(result.into())
+}
+
+/// `waitStable` 轮询主体：在工作线程中截图并与参考帧比较，直到静止、超时或中止。
+fn _poll_until_stable(
+    hwnd_raw: isize,
+    roi: Option<(i32, i32, i32, i32)>,
+    quiet_ms: u64,
+    timeout_ms: u64,
+    diff: &FrameDiffOptions,
+    max_ratio: f64,
+    token: Option<&AbortToken>,
+) -> Result<bool, String> {
+    let hwnd = HWND(hwnd_raw as *mut std::ffi::c_void);
+    let deadline = Duration::from_millis(timeout_ms);
+    let quiet = Duration::from_millis(quiet_ms);
+    let start = Instant::now();
+    let poll_interval = Duration::from_millis(30);
+    let mut reference: Option<(Box<Mat>, Instant)> = None;
+
+    loop {
+        if should_stop_current_script() || token.is_some_and(|token| token.is_aborted()) {
+            return Ok(false);
+        }
+        let frame = match roi {
+            Some((x, y, w, h)) => capture_window_wgc_roi(hwnd, x, y, w, h),
+            None => capture_window_wgc(hwnd),
+        };
+        if let Some(frame) = frame {
+            let now = Instant::now();
+            let still = match reference.as_ref() {
+                Some((prev, _)) if prev.size().ok() == frame.size().ok() => {
+                    frame_change(prev, &frame, diff)?.ratio <= max_ratio
+                }
+                _ => false,
+            };
+            if !still {
+                reference = Some((frame, now));
+            }
+            if let Some((_, since)) = reference.as_ref()
+                && now.duration_since(*since) >= quiet
+            {
+                return Ok(true);
+            }
+        }
+
+        if start.elapsed() >= deadline {
+            return Ok(false);
+        }
+        thread::sleep(poll_interval);
+    }
+}
+
+/// 等待窗口区域画面静止（异步）。
+///
+/// 规则：
+/// - 以静止期开始时的帧为参考，之后每帧与参考帧比较；变化像素比例超过 `options.maxRatio`
+///   （默认 0）时以当前帧作为新参考并重新计时，可避免缓慢渐变被逐帧比较漏掉。
+/// - 连续静止 `quietMs` 毫秒后返回 `true`，超时返回 `false`。
+/// - `roi`：`[x, y, w, h]`（相对客户区），省略或为 null 时比较整个窗口。
+/// - `options`：帧差分参数（`threshold` / `blur` / `denoise` / `merge` / `minArea`）、
+///   `maxRatio` 与 `signal`（AbortSignal，中止时以其原因拒绝）。
+fn _wait_stable(
+    hwnd: Option<JsValue>,
+    roi: Option<JsValue>,
+    quiet_ms: Option<JsValue>,
+    timeout: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let hwnd_raw = hwnd
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as isize;
+    let roi = match roi.filter(|v| !v.is_undefined() && !v.is_null()) {
+        Some(value) => {
+            let array = value
+                .as_object()
+                .and_then(|obj| JsArray::from_object(obj.clone()).ok())
+                .ok_or_else(|| JsNativeError::typ().with_message("roi 必须是 [x, y, w, h] 数组"))?;
+            Some(_parse_bbox_tuple(&array, 0, ctx)?)
+        }
+        None => None,
+    };
+
+    let quiet_raw = quiet_ms.unwrap_or_else(|| js_value!(500)).to_number(ctx)?;
+    let quiet_ms = if quiet_raw.is_finite() {
+        quiet_raw.clamp(0.0, u64::MAX as f64) as u64
+    } else {
+        500_u64
+    };
+    let timeout_raw = timeout
+        .unwrap_or_else(|| js_value!(20_000))
+        .to_number(ctx)?;
+    let timeout_ms = if timeout_raw.is_finite() {
+        timeout_raw.clamp(0.0, u64::MAX as f64) as u64
+    } else {
+        20_000_u64
+    };
+    let diff = _parse_frame_diff_options(options.as_ref(), ctx)?;
+    let max_ratio = match options
+        .as_ref()
+        .and_then(JsValue::as_object)
+        .map(|obj| _get_option_value(&obj, "maxRatio", ctx))
+        .transpose()?
+        .flatten()
+    {
+        Some(value) => value.to_number(ctx)?.clamp(0.0, 1.0),
+        None => 0.0,
+    };
+    let abort = ScriptAbort::from_options(options.as_ref(), ctx)?;
 
     let (promise, resolvers) = JsPromise::new_pending(ctx);
+    if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+        return Ok(promise.into());
+    }
     let resolvers_clone = resolvers.clone();
+    let token = abort.as_ref().map(ScriptAbort::token);
+    let task_token = token.clone();
     ctx.enqueue_job(
         NativeAsyncJob::new(async move |context| {
-            let async_result = _spawn_blocking_with_script_stop_snapshot(move || {
-                let hwnd = HWND(hwnd_raw as *mut std::ffi::c_void);
-                let deadline = Duration::from_millis(timeout_ms);
-                let start = std::time::Instant::now();
-                let poll_interval = Duration::from_millis(30);
-
-                loop {
-                    if should_stop_current_script() {
-                        return false;
-                    }
-                    if let Some(img_mat) = capture_window_wgc(hwnd) {
-                        let matched = check_color_mat(&img_mat, x, y, color, tolerance_abs);
-                        let condition_met = if wait_for_match { matched } else { !matched };
-                        if condition_met {
-                            return true;
-                        }
-                    }
-
-                    if start.elapsed() >= deadline {
-                        return false;
-                    }
-                    thread::sleep(poll_interval);
-                }
-            })
-            .await;
+            let task = _spawn_blocking_with_script_stop_snapshot(move || {
+                _poll_until_stable(
+                    hwnd_raw,
+                    roi,
+                    quiet_ms,
+                    timeout_ms,
+                    &diff,
+                    max_ratio,
+                    task_token.as_ref(),
+                )
+            });
+            let async_result = run_abortable(token.as_ref(), task).await;
 
             let context = &mut context.borrow_mut();
+            let Some(async_result) = async_result else {
+                return ScriptAbort::reject_aborted(
+                    abort.as_ref(),
+                    &resolvers_clone.reject,
+                    context,
+                );
+            };
             match async_result {
-                Ok(result) => resolvers_clone.resolve.call(
+                Ok(Ok(result)) => resolvers_clone.resolve.call(
                     &JsValue::undefined(),
                     &[JsValue::new(result)],
                     context,
                 ),
+                Ok(Err(e)) => {
+                    let msg = format!("waitStable 帧差分失败: {e}");
+                    resolvers_clone.reject.call(
+                        &JsValue::undefined(),
+                        &[JsValue::from(js_string!(msg))],
+                        context,
+                    )
+                }
                 Err(e) => {
-                    let msg = format!("waitColor 线程执行失败: {e}");
+                    let msg = format!("waitStable 线程执行失败: {e}");
+                    resolvers_clone.reject.call(
+                        &JsValue::undefined(),
+                        &[JsValue::from(js_string!(msg))],
+                        context,
+                    )
+                }
+            }
+        })
+        .into(),
+    );
+
+    Ok(promise.into())
+}
+
+/// 解析等待类函数的通用选项 `{ roi, interval, timeout }`。
+///
+/// 返回截图 ROI（相对客户区）与轮询参数；ROI 起点同时作为取色点与命中坐标的换算原点。
+fn _parse_wait_options(
+    options: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<(Option<(i32, i32, i32, i32)>, WaitOptions)> {
+    let mut wait = WaitOptions::default();
+    let Some(options) = options.and_then(JsValue::as_object) else {
+        return Ok((None, wait));
+    };
+    let roi =
+        _parse_roi_option(&options, ctx)?.map(|rect| (rect.x, rect.y, rect.width, rect.height));
+    if let Some((x, y, _, _)) = roi {
+        // 截图时 ROI 起点会被约束到客户区内，这里保持一致
+        wait.origin = (x.max(0), y.max(0));
+    }
+    if let Some(value) = _get_option_value(&options, "interval", ctx)? {
+        let interval = value.to_number(ctx)?;
+        if interval.is_finite() {
+            wait.interval = Duration::from_millis(interval.clamp(0.0, 60_000.0) as u64);
+        }
+    }
+    if let Some(value) = _get_option_value(&options, "timeout", ctx)? {
+        let timeout = value.to_number(ctx)?;
+        if timeout.is_finite() {
+            wait.timeout = Duration::from_millis(timeout.clamp(0.0, u64::MAX as f64) as u64);
+        }
+    }
+    Ok((roi, wait))
+}
+
+/// 将轮询结果转换为 JS 值：超时或停止时为 `null`，命中时为附带 `polls` / `elapsed` 的详情对象。
+fn _wait_outcome_to_js(outcome: WaitOutcome, ctx: &mut Context) -> JsResult<JsValue> {
+    let Some(found) = outcome.found else {
+        return Ok(JsValue::null());
+    };
+    let polls = outcome.polls;
+    let elapsed = outcome.elapsed.as_millis() as f64;
+    let result = match found {
+        WaitMatch::Template(m) => js_object!({
+            x: m.x,
+            y: m.y,
+            w: m.w,
+            h: m.h,
+            score: m.score,
+            polls: polls,
+            elapsed: elapsed,
+        }, ctx),
+        WaitMatch::Colors => js_object!({
+            polls: polls,
+            elapsed: elapsed,
+        }, ctx),
+        WaitMatch::Hash { hash, distance } => js_object!({
+            hash: js_string!(hash),
+            distance: distance,
+            polls: polls,
+            elapsed: elapsed,
+        }, ctx),
+        WaitMatch::Text {
+            text,
+            matched,
+            groups,
+        } => {
+            let array = JsArray::new(ctx);
+            for group in groups {
+                let value = match group {
+                    Some(group) => JsValue::from(js_string!(group)),
+                    None => JsValue::null(),
+                };
+                array.push(value, ctx)?;
+            }
+            js_object!({
+                text: js_string!(text),
+                matched: js_string!(matched),
+                groups: array,
+                polls: polls,
+                elapsed: elapsed,
+            }, ctx)
+        }
+    };
+    Ok(result.into())
+}
+
+/// 通用轮询等待：在工作线程中反复截图（复用同一块缓冲）并检查条件，以 Promise 返回结果。
+///
+/// 说明：`name` 用于错误信息；脚本停止或 `options.signal` 中止时结束轮询，后者以其原因拒绝。
+fn _spawn_wait(
+    name: &'static str,
+    hwnd_raw: isize,
+    condition: WaitCondition,
+    options: Option<&JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let (roi, wait) = _parse_wait_options(options, ctx)?;
+    let abort = ScriptAbort::from_options(options, ctx)?;
+
+    let (promise, resolvers) = JsPromise::new_pending(ctx);
+    if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+        return Ok(promise.into());
+    }
+    let resolvers_clone = resolvers.clone();
+    let token = abort.as_ref().map(ScriptAbort::token);
+    let task_token = token.clone();
+    ctx.enqueue_job(
+        NativeAsyncJob::new(async move |context| {
+            let task = _spawn_blocking_with_script_stop_snapshot(move || {
+                let hwnd = HWND(hwnd_raw as *mut std::ffi::c_void);
+                wait_until(
+                    &condition,
+                    &wait,
+                    |frame| capture_window_wgc_into(hwnd, roi, frame),
+                    || {
+                        should_stop_current_script()
+                            || task_token.as_ref().is_some_and(|token| token.is_aborted())
+                    },
+                )
+            });
+            let async_result = run_abortable(token.as_ref(), task).await;
+
+            let context = &mut context.borrow_mut();
+            let Some(async_result) = async_result else {
+                return ScriptAbort::reject_aborted(
+                    abort.as_ref(),
+                    &resolvers_clone.reject,
+                    context,
+                );
+            };
+            match async_result {
+                Ok(Ok(outcome)) => {
+                    let result = _wait_outcome_to_js(outcome, context)?;
+                    resolvers_clone
+                        .resolve
+                        .call(&JsValue::undefined(), &[result], context)
+                }
+                Ok(Err(e)) => {
+                    let msg = format!("{name} 条件检查失败: {e}");
+                    resolvers_clone.reject.call(
+                        &JsValue::undefined(),
+                        &[JsValue::from(js_string!(msg))],
+                        context,
+                    )
+                }
+                Err(e) => {
+                    let msg = format!("{name} 线程执行失败: {e}");
                     resolvers_clone.reject.call(
                         &JsValue::undefined(),
                         &[JsValue::from(js_string!(msg))],
@@ -4024,6 +5342,306 @@ fn _wait_color(
     Ok(promise.into())
 }
 
+/// 等待模板出现（异步）。
+///
+/// 规则：
+/// - 得分高于 `threshold`（默认 0.8）时返回 `{ x, y, w, h, score, polls, elapsed }`，坐标相对客户区；
+///   超时返回 `null`。
+/// - `options`：`roi` / `interval` / `timeout` / `signal`，以及 `method` / `preprocess` 匹配配置。
+fn _wait_template(
+    hwnd: Option<JsValue>,
+    js_tpl_mat: Option<JsValue>,
+    threshold: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let hwnd_raw = hwnd
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as isize;
+    let js_tpl_mat = js_tpl_mat
+        .unwrap_or_else(|| JsValue::undefined())
+        .get_native::<JsMat>()?;
+    let threshold = match threshold.filter(|v| !v.is_undefined() && !v.is_null()) {
+        Some(value) => value.to_number(ctx)?,
+        None => 0.8,
+    };
+    let condition = WaitCondition::Template {
+        template: (*js_tpl_mat.borrow().data().inner).clone(),
+        threshold,
+        config: _parse_match_config(options.as_ref(), ctx)?,
+    };
+    _spawn_wait("waitTemplate", hwnd_raw, condition, options.as_ref(), ctx)
+}
+
+/// 等待多个取色点同时满足颜色条件（异步）。
+///
+/// 规则：
+/// - `points`：`[x, y, color][]`，坐标相对客户区，`color` 为 0xRRGGBB。
+/// - `tolerance`：`rgb` 模式下为逐通道容差（0-255，默认 0），ΔE 模式下为最大色差。
+/// - 全部命中时返回 `{ polls, elapsed }`，超时返回 `null`。
+/// - `options`：`roi` / `interval` / `timeout` / `signal` / `distance`。
+fn _wait_colors(
+    hwnd: Option<JsValue>,
+    points: Option<JsValue>,
+    tolerance: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let hwnd_raw = hwnd
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as isize;
+    let points_array = points
+        .as_ref()
+        .and_then(JsValue::as_object)
+        .and_then(|obj| JsArray::from_object(obj.clone()).ok())
+        .ok_or_else(|| JsNativeError::typ().with_message("points 必须是 [x, y, color] 数组"))?;
+    let len = points_array.length(ctx)?;
+    if len == 0 {
+        return Err(JsNativeError::typ().with_message("points 不能为空").into());
+    }
+    let mut parsed = Vec::with_capacity(len as usize);
+    for idx in 0..len {
+        let point = points_array
+            .get(idx, ctx)?
+            .as_object()
+            .and_then(|obj| JsArray::from_object(obj.clone()).ok())
+            .ok_or_else(|| {
+                JsNativeError::typ()
+                    .with_message(format!("points[{idx}] 必须是 [x, y, color] 数组"))
+            })?;
+        parsed.push(ColorPoint {
+            x: point.get(0, ctx)?.to_number(ctx)? as i32,
+            y: point.get(1, ctx)?.to_number(ctx)? as i32,
+            color: point.get(2, ctx)?.to_number(ctx)? as u32,
+        });
+    }
+    let tolerance = match tolerance.filter(|v| !v.is_undefined() && !v.is_null()) {
+        Some(value) => value.to_number(ctx)?.max(0.0),
+        None => 0.0,
+    };
+    let condition = WaitCondition::Colors {
+        points: parsed,
+        tolerance,
+        distance: _parse_color_distance(options.as_ref(), ctx)?,
+    };
+    _spawn_wait("waitColors", hwnd_raw, condition, options.as_ref(), ctx)
+}
+
+/// 等待画面感知哈希接近目标哈希（异步）。
+///
+/// 规则：
+/// - 与 `hash` 的汉明距离不超过 `maxDistance`（默认 10）时返回 `{ hash, distance, polls, elapsed }`，
+///   超时返回 `null`；`hash` 须与 `options.kind` / `options.color` 对应的 `perceptualHash` 结果同长度。
+/// - `options`：`roi` / `interval` / `timeout` / `signal` / `kind`（默认 `phash`）/ `color`（默认 false）。
+fn _wait_hash(
+    hwnd: Option<JsValue>,
+    hash: Option<JsValue>,
+    max_distance: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let hwnd_raw = hwnd
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as isize;
+    let hash = hash
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_string(ctx)?
+        .to_std_string_escaped();
+    let max_distance = match max_distance.filter(|v| !v.is_undefined() && !v.is_null()) {
+        Some(value) => value.to_number(ctx)?.max(0.0) as u32,
+        None => 10,
+    };
+    let (mut kind, mut color) = (ImageHashKind::default(), false);
+    if let Some(options) = options.as_ref().and_then(JsValue::as_object) {
+        if let Some(value) = _get_option_value(&options, "kind", ctx)? {
+            let name = value.to_string(ctx)?.to_std_string_escaped();
+            kind = ImageHashKind::from_name(&name).ok_or_else(|| {
+                JsNativeError::typ().with_message(format!("未知的哈希算法: {name}"))
+            })?;
+        }
+        if let Some(value) = _get_option_value(&options, "color", ctx)? {
+            color = value.to_boolean();
+        }
+    }
+    let condition = WaitCondition::hash(&hash, kind, color, max_distance)
+        .map_err(|msg| JsNativeError::typ().with_message(msg))?;
+    _spawn_wait("waitHash", hwnd_raw, condition, options.as_ref(), ctx)
+}
+
+/// 将字符串或 RegExp 转换为 Rust 正则；RegExp 的 `i` / `m` / `s` 标志转换为内联标志，其余标志忽略。
+fn _parse_text_pattern(pattern: Option<&JsValue>, ctx: &mut Context) -> JsResult<Regex> {
+    let pattern = pattern.cloned().unwrap_or_else(|| JsValue::undefined());
+    let source = match pattern.as_object() {
+        Some(obj) if obj.has_property(js_string!("source"), ctx)? => {
+            let source = obj
+                .get(js_string!("source"), ctx)?
+                .to_string(ctx)?
+                .to_std_string_escaped();
+            let flags: String = obj
+                .get(js_string!("flags"), ctx)?
+                .to_string(ctx)?
+                .to_std_string_escaped()
+                .chars()
+                .filter(|flag| matches!(flag, 'i' | 'm' | 's'))
+                .collect();
+            if flags.is_empty() {
+                source
+            } else {
+                format!("(?{flags}){source}")
+            }
+        }
+        _ => pattern.to_string(ctx)?.to_std_string_escaped(),
+    };
+    Regex::new(&source).map_err(|e| {
+        JsNativeError::syntax()
+            .with_message(format!("无效的正则表达式: {e}"))
+            .into()
+    })
+}
+
+/// 等待 OCR 识别文本匹配正则（异步，需先调用 `initOcr`）。
+///
+/// 规则：
+/// - `pattern`：字符串或 RegExp。
+/// - 命中时返回 `{ text, matched, groups, polls, elapsed }`（`groups` 中未参与匹配的捕获组为 `null`），
+///   超时返回 `null`。
+/// - `options`：`roi` / `interval` / `timeout` / `signal` / `charset` / `customChars`
+///   （限定解码字符集，同 `ocr`）；
+///   OCR 较慢，建议配合 `roi` 使用。
+fn _wait_text(
+    hwnd: Option<JsValue>,
+    pattern: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let hwnd_raw = hwnd
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as isize;
+    let condition = WaitCondition::Text {
+        pattern: _parse_text_pattern(pattern.as_ref(), ctx)?,
+        options: OcrOptions {
+            whitelist: _parse_ocr_options(options.as_ref(), ctx)?.whitelist,
+            ..Default::default()
+        },
+    };
+    _spawn_wait("waitText", hwnd_raw, condition, options.as_ref(), ctx)
+}
+
+/// 创建帧源虚拟窗口，返回可传给 `captureWindow` / `waitColor` 等截图函数的窗口 id。
+///
+/// 规则：
+/// - `source` 为目录时按自然序读取其中的图片，为文件时按视频打开，为 Mat 时固定返回该帧；
+///   相对路径按当前脚本目录解析。
+/// - `options.step`：`capture`（默认，每次截图后前进一帧）/ `manual`（仅 `stepFrameSource` / `seekFrameSource` 切帧）。
+/// - `options.loop`：播放到末尾后是否回到第一帧（默认 false，停留在最后一帧）。
+fn _create_frame_source(
+    source: Option<JsValue>,
+    options: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let source = source.unwrap_or_else(|| JsValue::undefined());
+    let frame_source = if source.is_string() {
+        let raw_path = source.to_string(ctx)?.to_std_string_escaped();
+        let path = PathBuf::from(_resolve_script_resource_path(&raw_path).as_ref());
+        if path.is_dir() {
+            FrameSource::from_image_dir(&path)
+        } else {
+            FrameSource::from_video(&path)
+        }
+    } else {
+        let js_mat = source.get_native::<JsMat>()?;
+        FrameSource::from_mat(&js_mat.borrow().data().inner)
+    }
+    .map_err(|msg| JsNativeError::error().with_message(msg))?;
+
+    let (mut step, mut looping) = (FrameStep::default(), false);
+    if let Some(options) = options.as_ref().and_then(JsValue::as_object) {
+        if let Some(value) = _get_option_value(&options, "step", ctx)? {
+            let name = value.to_string(ctx)?.to_std_string_escaped();
+            step = FrameStep::from_name(&name).ok_or_else(|| {
+                JsNativeError::typ().with_message(format!("不支持的 step: {name}"))
+            })?;
+        }
+        if let Some(value) = _get_option_value(&options, "loop", ctx)? {
+            looping = value.to_boolean();
+        }
+    }
+
+    let id = register_frame_source(frame_source.with_playback(step, looping))
+        .map_err(|msg| JsNativeError::error().with_message(msg))?;
+    Ok(JsValue::new(id as f64))
+}
+
+/// 按 id 访问帧源，id 不是已注册的虚拟窗口时抛出错误。
+fn _with_js_frame_source<R>(
+    hwnd: Option<JsValue>,
+    ctx: &mut Context,
+    f: impl FnOnce(&mut FrameSource) -> R,
+) -> JsResult<R> {
+    let id = hwnd
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as isize;
+    with_frame_source(id, f).ok_or_else(|| {
+        JsNativeError::error()
+            .with_message(format!("帧源不存在: {id}"))
+            .into()
+    })
+}
+
+/// 将帧源前进 `count` 帧（默认 1，可为负数），返回切换后的帧序号。
+fn _step_frame_source(
+    hwnd: Option<JsValue>,
+    count: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let count = match count.filter(|v| !v.is_undefined() && !v.is_null()) {
+        Some(value) => value.to_number(ctx)? as i64,
+        None => 1,
+    };
+    let index = _with_js_frame_source(hwnd, ctx, |source| source.step(count))?;
+    Ok(JsValue::new(index as f64))
+}
+
+/// 将帧源跳转到指定帧序号（越界时循环取模或夹到最后一帧），返回实际帧序号。
+fn _seek_frame_source(
+    hwnd: Option<JsValue>,
+    index: Option<JsValue>,
+    ctx: &mut Context,
+) -> JsResult<JsValue> {
+    let index = index
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)?
+        .max(0.0) as usize;
+    let index = _with_js_frame_source(hwnd, ctx, |source| source.seek(index))?;
+    Ok(JsValue::new(index as f64))
+}
+
+/// 查询帧源状态：返回 `{ kind, index, length }`，id 不存在时返回 `null`。
+///
+/// 说明：`length` 为 0 表示视频未提供总帧数。
+fn _frame_source_info(hwnd: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
+    let id = hwnd
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as isize;
+    let Some(info) = with_frame_source(id, |source| source.info()) else {
+        return Ok(JsValue::null());
+    };
+    let result = js_object!({
+        kind: js_string!(info.kind),
+        index: info.index as f64,
+        length: info.len as f64,
+    }, ctx);
+    Ok(result.into())
+}
+
+/// 关闭帧源并释放其资源，返回是否存在该帧源。
+fn _close_frame_source(hwnd: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
+    let id = hwnd
+        .unwrap_or_else(|| JsValue::undefined())
+        .to_number(ctx)? as isize;
+    Ok(JsValue::new(close_frame_source(id)))
+}
+
 /// 读取脚本配置项并返回当前值。
 ///
 /// 行为说明：
@@ -4478,8 +6096,30 @@ fn _s(ms: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
 }
 
 /// 异步延迟函数
-fn _sleep(ms: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
+///
+/// `options.signal`（可选）: AbortSignal，中止时以其原因拒绝
+fn _sleep(ms: Option<JsValue>, options: Option<JsValue>, ctx: &mut Context) -> JsResult<JsValue> {
     let ms = ms.unwrap_or_else(|| JsValue::undefined()).to_number(ctx)? as u64;
+    if let Some(abort) = ScriptAbort::from_options(options.as_ref(), ctx)? {
+        let (promise, resolvers) = JsPromise::new_pending(ctx);
+        if ScriptAbort::reject_if_aborted(Some(&abort), &resolvers.reject, ctx)? {
+            return Ok(promise.into());
+        }
+        let token = abort.token();
+        ctx.enqueue_job(
+            NativeAsyncJob::new(async move |context| {
+                let sleep = tokio::time::sleep(Duration::from_millis(ms));
+                let finished = run_abortable(Some(&token), sleep).await;
+                let context = &mut context.borrow_mut();
+                match finished {
+                    Some(()) => resolvers.resolve.call(&JsValue::undefined(), &[], context),
+                    None => ScriptAbort::reject_aborted(Some(&abort), &resolvers.reject, context),
+                }
+            })
+            .into(),
+        );
+        return Ok(promise.into());
+    }
     let mut cb: Option<JsFunction> = None;
     let promise = JsPromise::new(
         |resolvers, _context| {
@@ -5280,7 +6920,7 @@ fn _runoks(hwnd: Option<JsValue>, path: Option<JsValue>, ctx: &mut Context) -> J
 }
 
 /// 将 Mat 编码为 PNG data URL，便于前端直接显示。
-fn _mat_to_png_data_url(mat: &Mat) -> Result<String, String> {
+pub(crate) fn _mat_to_png_data_url(mat: &Mat) -> Result<String, String> {
     let mut buf = opencv::core::Vector::<u8>::new();
     opencv::imgcodecs::imencode(".png", mat, &mut buf, &opencv::core::Vector::new())
         .map_err(|e| format!("状态图片编码失败: {e}"))?;
@@ -5289,7 +6929,7 @@ fn _mat_to_png_data_url(mat: &Mat) -> Result<String, String> {
 }
 
 /// 向前端发送脚本状态事件（支持按标题新增/更新/删除）。
-fn _emit_script_status(
+pub(crate) fn _emit_script_status(
     title: String,
     text: Option<String>,
     image: Option<String>,
@@ -5307,6 +6947,14 @@ fn _emit_script_status(
         } else {
             "upsert"
         };
+        crate::submodules::script_log::record_script_log_status(
+            scope.as_deref(),
+            &title,
+            text.as_deref(),
+            image.as_deref(),
+            images.as_deref(),
+            timestamp,
+        );
         if crate::submodules::script_mcp::should_record_script_mcp_cache() {
             crate::submodules::script_mcp::record_script_status(
                 scope.clone(),
@@ -5596,7 +7244,7 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
 
     // 异步延迟函数
     let f = _sleep.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("sleep"), 1, f)?;
+    context.register_global_builtin_callable(js_string!("sleep"), 2, f)?;
 
     // 运行 OK 外部 mod 宏（目录 / zip）
     let f = _runoks.into_js_function_copied(context);
@@ -5663,10 +7311,22 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
     let f = _get_template.into_js_function_copied(context);
     context.register_global_builtin_callable(js_string!("getTemplate"), 1, f)?;
 
+    // 获取图集模板元数据
+    let f = _get_template_meta.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("getTemplateMeta"), 1, f)?;
+
     // 从 base64 字符串加载模板Mat对象
     let f = _get_template_b64.into_js_function_copied(context);
     context.register_global_builtin_callable(js_string!("getTemplateB64"), 1, f)?;
 
+    // 清空模板缓存
+    let f = _clear_template_cache.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("clearTemplateCache"), 0, f)?;
+
+    // 模板缓存统计
+    let f = _template_cache_stats.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("templateCacheStats"), 0, f)?;
+
     // 从文件加载模板Mat对象
     let f = _imread.into_js_function_copied(context);
     context.register_global_builtin_callable(js_string!("imread"), 1, f)?;
@@ -5697,7 +7357,7 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
 
     // 下载文件（异步）
     let f = _download_file.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("downloadFile"), 3, f)?;
+    context.register_global_builtin_callable(js_string!("downloadFile"), 4, f)?;
 
     // 删除文件
     let f = _delete_file.into_js_function_copied(context);
@@ -5709,7 +7369,7 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
 
     // OCR 初始化（自动下载资源）
     let f = _init_ocr.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("initOcr"), 3, f)?;
+    context.register_global_builtin_callable(js_string!("initOcr"), 4, f)?;
 
     // Lite-Mono 初始化（自动下载模型并预热运行时）
     let f = _init_mono_depth.into_js_function_copied(context);
@@ -5717,7 +7377,11 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
 
     // OCR 文字识别
     let f = _ocr_text.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("ocrText"), 1, f)?;
+    context.register_global_builtin_callable(js_string!("ocrText"), 2, f)?;
+
+    // 结构化 OCR（文本框、置信度与行分组）
+    let f = _ocr.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("ocr"), 2, f)?;
 
     // 显示图片
     let f = _imshow.into_js_function_copied(context);
@@ -5729,11 +7393,11 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
 
     // 使用两个Mat对象进行颜色和模板匹配
     let f = _find_color_and_match_template.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("findColorAndMatchTemplate"), 4, f)?;
+    context.register_global_builtin_callable(js_string!("findColorAndMatchTemplate"), 5, f)?;
 
     // 颜色键过滤函数
     let f = _color_filter.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("colorFilter"), 3, f)?;
+    context.register_global_builtin_callable(js_string!("colorFilter"), 4, f)?;
 
     // HSL 加权颜色键过滤函数
     let f = _color_filter_hsl.into_js_function_copied(context);
@@ -5741,11 +7405,11 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
 
     // 色键匹配函数
     let f = _color_key_match.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("colorKeyMatch"), 4, f)?;
+    context.register_global_builtin_callable(js_string!("colorKeyMatch"), 5, f)?;
 
     // 并行批量模板匹配函数
     let f = _batch_match_color.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("batchMatchColor"), 3, f)?;
+    context.register_global_builtin_callable(js_string!("batchMatchColor"), 4, f)?;
 
     // ORB 优质匹配计数函数
     let f = _orb_match_count.into_js_function_copied(context);
@@ -5765,7 +7429,17 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
 
     // 感知哈希函数（支持彩色）
     let f = _perceptual_hash.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("perceptualHash"), 2, f)?;
+    context.register_global_builtin_callable(js_string!("perceptualHash"), 3, f)?;
+
+    // 感知哈希索引加载函数
+    let f = _load_hash_index.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("loadHashIndex"), 1, f)?;
+
+    // 特征库构建与加载函数
+    let f = _build_feature_library.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("buildFeatureLibrary"), 2, f)?;
+    let f = _load_feature_library.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("loadFeatureLibrary"), 1, f)?;
 
     // AHK 风格动态 DLL 调用函数（可变参数）
     let f = NativeFunction::from_fn_ptr(dll_call_js);
@@ -5799,13 +7473,33 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
     let f = _segment_chars.into_js_function_copied(context);
     context.register_global_builtin_callable(js_string!("segmentChars"), 3, f)?;
 
+    // 字形集加载函数
+    let f = _load_glyph_set.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("loadGlyphSet"), 1, f)?;
+
+    // 字形识别函数（基于字符分割 + 模板比对）
+    let f = _read_glyphs.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("readGlyphs"), 3, f)?;
+
+    // 数字识别函数（仅使用数字字形）
+    let f = _read_digits.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("readDigits"), 3, f)?;
+
     // 轮廓绘制函数
     let f = _draw_contours.into_js_function_copied(context);
     context.register_global_builtin_callable(js_string!("drawContours"), 6, f)?;
 
     // 模板匹配函数
     let f = _match_template.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("matchTemplate"), 3, f)?;
+    context.register_global_builtin_callable(js_string!("matchTemplate"), 4, f)?;
+
+    // 多目标模板匹配函数
+    let f = _match_template_all.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("matchTemplateAll"), 4, f)?;
+
+    // 多尺度 / 多角度模板匹配函数
+    let f = _match_template_scaled.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("matchTemplateScaled"), 4, f)?;
 
     // 边框绘制函数
     let f = _draw_border.into_js_function_copied(context);
@@ -5817,7 +7511,55 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
 
     // 等待颜色达到条件函数（异步）
     let f = _wait_color.into_js_function_copied(context);
-    context.register_global_builtin_callable(js_string!("waitColor"), 6, f)?;
+    context.register_global_builtin_callable(js_string!("waitColor"), 7, f)?;
+
+    // 帧差分掩码函数
+    let f = _frame_diff.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("frameDiff"), 3, f)?;
+
+    // 变化区域检测函数
+    let f = _diff_regions.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("diffRegions"), 3, f)?;
+
+    // 等待画面静止函数（异步）
+    let f = _wait_stable.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("waitStable"), 5, f)?;
+
+    // 等待模板出现函数（异步）
+    let f = _wait_template.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("waitTemplate"), 4, f)?;
+
+    // 等待多点颜色条件函数（异步）
+    let f = _wait_colors.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("waitColors"), 4, f)?;
+
+    // 等待画面哈希接近函数（异步）
+    let f = _wait_hash.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("waitHash"), 4, f)?;
+
+    // 等待 OCR 文本匹配函数（异步）
+    let f = _wait_text.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("waitText"), 3, f)?;
+
+    // 创建帧源虚拟窗口函数
+    let f = _create_frame_source.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("createFrameSource"), 2, f)?;
+
+    // 帧源前进函数
+    let f = _step_frame_source.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("stepFrameSource"), 2, f)?;
+
+    // 帧源跳转函数
+    let f = _seek_frame_source.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("seekFrameSource"), 2, f)?;
+
+    // 帧源状态查询函数
+    let f = _frame_source_info.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("frameSourceInfo"), 1, f)?;
+
+    // 关闭帧源函数
+    let f = _close_frame_source.into_js_function_copied(context);
+    context.register_global_builtin_callable(js_string!("closeFrameSource"), 1, f)?;
 
     // 脚本配置读取函数
     let f = _read_config.into_js_function_copied(context);
@@ -5846,6 +7588,9 @@ pub fn register_builtin_functions(context: &mut Context) -> JsResult<()> {
     // OpenCV DNN 命名空间（cv.dnn.*）
     register_cv_dnn_namespace(context)?;
 
+    // 网络 API（fetch/Headers/Request/Response/WebSocket），受网络权限控制
+    register_network_api(context)?;
+
     Ok(())
 }
 
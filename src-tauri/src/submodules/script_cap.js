@@ -509,10 +509,11 @@ export class Cap {
      * @param {number} color 目标颜色
      * @param {number} tolerance 颜色容差
      * @param {number | undefined} [timeout] 超时时间
+     * @param {{ signal?: AbortSignal } | undefined} [options] 可选中止信号
      * @returns {Promise<boolean>}
      */
-    async waitColor(x, y, color, tolerance, timeout) {
-        return await waitColor(this.hwnd, x, y + this.yof, color, tolerance, timeout)
+    async waitColor(x, y, color, tolerance, timeout, options) {
+        return await waitColor(this.hwnd, x, y + this.yof, color, tolerance, timeout, options)
     }
 
     /**
@@ -1,3 +1,5 @@
+use crate::submodules::jsmat::JsMat;
+use boa_engine::builtins::error::Error as BoaErrorObject;
 use boa_engine::property::Attribute;
 use boa_engine::{
     Context, JsArgs, JsData, JsResult, JsString, JsSymbol, JsValue, js_str, js_string,
@@ -9,7 +11,7 @@ use boa_engine::{
 use boa_gc::{Finalize, Trace};
 use std::{
     cell::RefCell,
-    collections::{HashMap, HashSet, hash_map::Entry},
+    collections::{HashMap, hash_map::Entry},
     fmt::Write as _,
     rc::Rc,
     time::SystemTime,
@@ -93,78 +95,300 @@ pub struct Console {
     state: ConsoleState,
 }
 
-/// 将值转为控制台输出字符串。
-///
-/// 关键改造：
-/// - 只遍历对象自有属性，不输出原型链；
-/// - 数组展开元素，避免 `Array(1)` 这种摘要输出；
-/// - 处理循环引用，输出 `[Cycle]`。
-fn value_to_console_string(value: &JsValue, context: &mut Context) -> JsResult<String> {
-    fn format_js_value(
-        value: &JsValue,
-        context: &mut Context,
-        seen: &mut HashSet<usize>,
-        depth: usize,
-    ) -> JsResult<String> {
-        if let JsVariant::String(text) = value.variant() {
-            return Ok(text.to_std_string_escaped());
+/// `console.log` 等默认展开深度（与 Node.js 一致）。
+const DEFAULT_INSPECT_DEPTH: usize = 2;
+/// 单行输出超过该宽度时换行展开。
+const INSPECT_BREAK_LENGTH: usize = 72;
+/// 数组最多展开的元素数量。
+const INSPECT_MAX_ARRAY_LENGTH: usize = 100;
+/// `console.table` 索引列标题。
+const TABLE_INDEX_HEADER: &str = "(index)";
+/// `console.table` 原始值列标题。
+const TABLE_VALUES_HEADER: &str = "Values";
+
+/// 对象检查选项。
+#[derive(Debug, Clone, Copy)]
+struct InspectOptions {
+    /// 最大展开深度；`None` 表示不限制。
+    max_depth: Option<usize>,
+    /// 超过该宽度时多行展开；`usize::MAX` 表示始终单行。
+    break_length: usize,
+}
+
+impl Default for InspectOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: Some(DEFAULT_INSPECT_DEPTH),
+            break_length: INSPECT_BREAK_LENGTH,
         }
-        let Some(obj) = value.as_object() else {
-            return Ok(value.display().to_string());
-        };
+    }
+}
 
-        let addr = std::ptr::from_ref(obj.as_ref()).addr();
-        if seen.contains(&addr) {
-            return Ok("[Cycle]".to_string());
+/// 判断属性名是否可以不加引号输出。
+fn is_plain_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// 以单引号包裹字符串并转义特殊字符。
+fn quote_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for c in text.chars() {
+        match c {
+            '\'' => quoted.push_str("\\'"),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
         }
-        if depth >= 8 {
-            return Ok("[Object]".to_string());
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// 输出 `Mat(rows x cols, type)` 摘要。
+fn mat_summary(mat: &JsMat) -> String {
+    let type_name = opencv::core::type_to_string(mat.inner.typ())
+        .unwrap_or_else(|_| format!("type={}", mat.inner.typ()));
+    format!(
+        "Mat({} x {}, {type_name})",
+        mat.inner.rows(),
+        mat.inner.cols()
+    )
+}
+
+/// 读取函数名，匿名函数返回 `None`。
+fn function_name(obj: &JsObject, context: &mut Context) -> JsResult<Option<String>> {
+    let name = obj.get(js_string!("name"), context)?;
+    Ok(name
+        .as_string()
+        .map(JsString::to_std_string_escaped)
+        .filter(|name| !name.is_empty()))
+}
+
+/// 读取对象构造器名；普通对象（`Object`）返回 `None`。
+fn constructor_name(obj: &JsObject, context: &mut Context) -> JsResult<Option<String>> {
+    let constructor = obj.get(js_string!("constructor"), context)?;
+    let Some(constructor) = constructor.as_object() else {
+        return Ok(None);
+    };
+    if !constructor.is_callable() {
+        return Ok(None);
+    }
+    Ok(function_name(&constructor, context)?.filter(|name| name != "Object"))
+}
+
+/// 将条目拼接为单行或多行容器文本。
+fn join_entries(
+    open: &str,
+    close: &str,
+    prefix: Option<&str>,
+    entries: &[String],
+    depth: usize,
+    options: InspectOptions,
+) -> String {
+    let prefix = prefix.map(|p| format!("{p} ")).unwrap_or_default();
+    if entries.is_empty() {
+        return format!("{prefix}{open}{close}");
+    }
+    let single_line_len = prefix.len()
+        + entries.iter().map(|entry| entry.len() + 2).sum::<usize>()
+        + open.len()
+        + close.len()
+        + 2 * depth;
+    let has_multiline = entries.iter().any(|entry| entry.contains('\n'));
+    if options.break_length == usize::MAX
+        || (!has_multiline && single_line_len <= options.break_length)
+    {
+        return format!("{prefix}{open} {} {close}", entries.join(", "));
+    }
+    let indent = "  ".repeat(depth + 1);
+    let closing_indent = "  ".repeat(depth);
+    format!(
+        "{prefix}{open}\n{indent}{}\n{closing_indent}{close}",
+        entries.join(&format!(",\n{indent}"))
+    )
+}
+
+/// Node.js 风格的值检查。
+///
+/// 说明：
+/// - 顶层字符串原样输出，嵌套字符串加单引号；
+/// - 超出深度输出 `[Object]` / `[Array]`，循环引用输出 `[Cycle]`；
+/// - `Mat` 输出 `Mat(rows x cols, type)`；
+/// - 只遍历对象自有属性，不输出原型链。
+fn inspect_value(
+    value: &JsValue,
+    context: &mut Context,
+    seen: &mut Vec<usize>,
+    depth: usize,
+    options: InspectOptions,
+) -> JsResult<String> {
+    match value.variant() {
+        JsVariant::String(text) => {
+            let text = text.to_std_string_escaped();
+            return Ok(if depth == 0 {
+                text
+            } else {
+                quote_string(&text)
+            });
         }
-        seen.insert(addr);
-
-        let result = if obj.is_array() {
-            let len = obj
-                .get(js_string!("length"), context)?
-                .to_length(context)?
-                .min(200) as u32;
-            let mut items = Vec::<String>::with_capacity(len as usize);
-            for i in 0..len {
-                if obj.has_own_property(i, context)? {
-                    let item = obj.get(i, context)?;
-                    items.push(format_js_value(&item, context, seen, depth + 1)?);
-                } else {
-                    items.push("<empty>".to_string());
-                }
-            }
-            format!("[ {} ]", items.join(", "))
+        JsVariant::BigInt(int) => return Ok(format!("{int}n")),
+        JsVariant::Object(_) => {}
+        _ => return Ok(value.display().to_string()),
+    }
+    let Some(obj) = value.as_object() else {
+        return Ok(value.display().to_string());
+    };
+
+    if let Some(mat) = obj.downcast_ref::<JsMat>() {
+        return Ok(mat_summary(&mat));
+    }
+    if obj.is_callable() {
+        return Ok(match function_name(&obj, context)? {
+            Some(name) => format!("[Function: {name}]"),
+            None => "[Function (anonymous)]".to_string(),
+        });
+    }
+    if obj.downcast_ref::<BoaErrorObject>().is_some() {
+        let text = value.to_string(context)?.to_std_string_escaped();
+        return Ok(if depth == 0 {
+            text
         } else {
-            let keys = obj.own_property_keys(context)?;
-            let mut pairs = Vec::<String>::with_capacity(keys.len());
-            for key in keys {
-                let value = obj.get(key.clone(), context)?;
-                let key_text = match key {
-                    PropertyKey::String(ref s) => s.to_std_string_escaped(),
-                    PropertyKey::Index(i) => i.get().to_string(),
-                    PropertyKey::Symbol(s) => s.descriptive_string().to_std_string_escaped(),
-                };
-                let value_text = format_js_value(&value, context, seen, depth + 1)?;
-                pairs.push(format!("{key_text}: {value_text}"));
+            format!("[{text}]")
+        });
+    }
+
+    let addr = std::ptr::from_ref(obj.as_ref()).addr();
+    if seen.contains(&addr) {
+        return Ok("[Cycle]".to_string());
+    }
+    let is_array = obj.is_array();
+    if options.max_depth.is_some_and(|max_depth| depth > max_depth) {
+        return Ok(if is_array { "[Array]" } else { "[Object]" }.to_string());
+    }
+    seen.push(addr);
+
+    let result = if is_array {
+        let len = obj.get(js_string!("length"), context)?.to_length(context)? as usize;
+        let shown = len.min(INSPECT_MAX_ARRAY_LENGTH);
+        let mut items = Vec::<String>::with_capacity(shown + 1);
+        for i in 0..shown as u32 {
+            if obj.has_own_property(i, context)? {
+                let item = obj.get(i, context)?;
+                items.push(inspect_value(&item, context, seen, depth + 1, options)?);
+            } else {
+                items.push("<empty>".to_string());
             }
-            format!("{{ {} }}", pairs.join(", "))
-        };
+        }
+        if len > shown {
+            let rest = len - shown;
+            items.push(format!(
+                "... {rest} more item{}",
+                if rest > 1 { "s" } else { "" }
+            ));
+        }
+        join_entries("[", "]", None, &items, depth, options)
+    } else {
+        let keys = obj.own_property_keys(context)?;
+        let mut pairs = Vec::<String>::with_capacity(keys.len());
+        for key in keys {
+            let value = obj.get(key.clone(), context)?;
+            let key_text = match key {
+                PropertyKey::String(ref s) => {
+                    let key = s.to_std_string_escaped();
+                    if is_plain_identifier(&key) {
+                        key
+                    } else {
+                        quote_string(&key)
+                    }
+                }
+                PropertyKey::Index(i) => i.get().to_string(),
+                PropertyKey::Symbol(s) => {
+                    format!("[{}]", s.descriptive_string().to_std_string_escaped())
+                }
+            };
+            let value_text = inspect_value(&value, context, seen, depth + 1, options)?;
+            pairs.push(format!("{key_text}: {value_text}"));
+        }
+        let prefix = constructor_name(&obj, context)?;
+        join_entries("{", "}", prefix.as_deref(), &pairs, depth, options)
+    };
+
+    seen.pop();
+    Ok(result)
+}
+
+/// 按指定选项将值转为控制台输出字符串。
+fn inspect_with_options(
+    value: &JsValue,
+    context: &mut Context,
+    options: InspectOptions,
+) -> JsResult<String> {
+    let mut seen = Vec::<usize>::new();
+    inspect_value(value, context, &mut seen, 0, options)
+}
 
-        seen.remove(&addr);
-        Ok(result)
+/// 将值转为控制台输出字符串（默认选项）。
+fn value_to_console_string(value: &JsValue, context: &mut Context) -> JsResult<String> {
+    inspect_with_options(value, context, InspectOptions::default())
+}
+
+/// 判断对象的 `toString` 是否为内置实现（内置实现按检查结果输出）。
+fn has_builtin_to_string(to_string: &JsObject, context: &mut Context) -> JsResult<bool> {
+    let object_proto = context.intrinsics().constructors().object().prototype();
+    let array_proto = context.intrinsics().constructors().array().prototype();
+    for proto in [object_proto, array_proto] {
+        let builtin = proto.get(js_string!("toString"), context)?;
+        if builtin
+            .as_object()
+            .is_some_and(|builtin| JsObject::equals(&builtin, to_string))
+        {
+            return Ok(true);
+        }
     }
+    Ok(false)
+}
 
-    if let JsVariant::String(text) = value.variant() {
+/// `%s` 占位符：字符串原样输出，无自定义 `toString` 的对象按浅层检查输出。
+fn format_string_specifier(arg: &JsValue, context: &mut Context) -> JsResult<String> {
+    match arg.variant() {
+        JsVariant::BigInt(int) => return Ok(format!("{int}n")),
+        JsVariant::Symbol(_) => return Ok(arg.display().to_string()),
+        JsVariant::Object(_) => {}
+        _ => return Ok(arg.to_string(context)?.to_std_string_escaped()),
+    }
+    let Some(obj) = arg.as_object() else {
+        return Ok(arg.to_string(context)?.to_std_string_escaped());
+    };
+    if obj.downcast_ref::<JsMat>().is_none()
+        && obj.downcast_ref::<BoaErrorObject>().is_none()
+        && let Ok(to_string) = obj.get(js_string!("toString"), context)
+        && let Some(to_string_fn) = to_string.as_function()
+        && !has_builtin_to_string(&to_string_fn, context)?
+    {
+        let text = to_string_fn.call(arg, &[], context)?.to_string(context)?;
         return Ok(text.to_std_string_escaped());
     }
-    let mut seen = HashSet::<usize>::new();
-    format_js_value(value, context, &mut seen, 0)
+    inspect_with_options(
+        arg,
+        context,
+        InspectOptions {
+            max_depth: Some(0),
+            break_length: usize::MAX,
+        },
+    )
 }
 
 /// 控制台格式化器（基于 boa_runtime 实现改造）。
+///
+/// 支持占位符：`%s` `%d` `%i` `%f` `%o` `%O` `%c` `%%`；
+/// `%c` 的 CSS 参数会被消费但不输出。
 fn formatter(data: &[JsValue], context: &mut Context) -> JsResult<String> {
     match data {
         [] => Ok(String::new()),
@@ -190,54 +414,74 @@ fn formatter(data: &[JsValue], context: &mut Context) -> JsResult<String> {
             let mut chars = target.chars();
 
             while let Some(c) = chars.next() {
-                if c == '%' {
-                    let fmt = chars.next().unwrap_or('%');
-                    match fmt {
-                        'd' | 'i' => {
-                            let arg = match data.get_or_undefined(arg_index).to_numeric(context)? {
-                                Numeric::Number(r) => (r.floor() + 0.0).to_string(),
-                                Numeric::BigInt(int) => int.to_string(),
-                            };
-                            formatted.push_str(&arg);
-                            arg_index += 1;
-                        }
-                        'f' => {
-                            let arg = data.get_or_undefined(arg_index).to_number(context)?;
-                            let _ = write!(formatted, "{arg:.6}");
-                            arg_index += 1;
-                        }
-                        // 对象占位符：强制深度展示。
-                        'o' | 'O' => {
-                            let arg = data.get_or_undefined(arg_index);
-                            formatted.push_str(&value_to_console_string(arg, context)?);
-                            arg_index += 1;
-                        }
-                        's' => {
-                            let arg = data.get_or_undefined(arg_index);
-                            let mut written = false;
-                            if let Some(obj) = arg.as_object()
-                                && let Ok(to_string) = obj.get(js_string!("toString"), context)
-                                && let Some(to_string_fn) = to_string.as_function()
-                            {
-                                let arg =
-                                    to_string_fn.call(arg, &[], context)?.to_string(context)?;
-                                formatted.push_str(&arg.to_std_string_escaped());
-                                written = true;
+                if c != '%' {
+                    formatted.push(c);
+                    continue;
+                }
+                let Some(fmt) = chars.next() else {
+                    formatted.push('%');
+                    break;
+                };
+                if fmt != '%' && arg_index >= data.len() {
+                    // 参数不足时保留原始占位符。
+                    formatted.push('%');
+                    formatted.push(fmt);
+                    continue;
+                }
+                match fmt {
+                    'd' | 'i' => {
+                        let arg = data.get_or_undefined(arg_index);
+                        let text = if arg.is_object() {
+                            "NaN".to_string()
+                        } else {
+                            match arg.to_numeric(context)? {
+                                Numeric::Number(r) => {
+                                    let r = if fmt == 'i' { r.trunc() } else { r };
+                                    JsValue::new(r).to_string(context)?.to_std_string_escaped()
+                                }
+                                Numeric::BigInt(int) => format!("{int}n"),
                             }
-                            if !written {
-                                let arg = arg.to_string(context)?.to_std_string_escaped();
-                                formatted.push_str(&arg);
+                        };
+                        formatted.push_str(&text);
+                        arg_index += 1;
+                    }
+                    'f' => {
+                        let arg = data.get_or_undefined(arg_index).to_number(context)?;
+                        let _ = write!(formatted, "{arg:.6}");
+                        arg_index += 1;
+                    }
+                    // 对象占位符：%o 展开更深，%O 使用默认深度。
+                    'o' | 'O' => {
+                        let arg = data.get_or_undefined(arg_index);
+                        let options = InspectOptions {
+                            max_depth: Some(if fmt == 'o' { 4 } else { DEFAULT_INSPECT_DEPTH }),
+                            ..InspectOptions::default()
+                        };
+                        let mut seen = Vec::new();
+                        // 深度从 1 开始计算，使嵌套字符串按引号形式输出。
+                        let text = match arg.variant() {
+                            JsVariant::String(_) => {
+                                inspect_value(arg, context, &mut seen, 1, options)?
                             }
-                            arg_index += 1;
-                        }
-                        '%' => formatted.push('%'),
-                        unknown => {
-                            formatted.push('%');
-                            formatted.push(unknown);
-                        }
+                            _ => inspect_with_options(arg, context, options)?,
+                        };
+                        formatted.push_str(&text);
+                        arg_index += 1;
+                    }
+                    's' => {
+                        let arg = data.get_or_undefined(arg_index);
+                        formatted.push_str(&format_string_specifier(arg, context)?);
+                        arg_index += 1;
+                    }
+                    'c' => {
+                        // CSS 样式在文本后端中没有意义，直接忽略。
+                        arg_index += 1;
+                    }
+                    '%' => formatted.push('%'),
+                    unknown => {
+                        formatted.push('%');
+                        formatted.push(unknown);
                     }
-                } else {
-                    formatted.push(c);
                 }
             }
 
@@ -250,6 +494,167 @@ fn formatter(data: &[JsValue], context: &mut Context) -> JsResult<String> {
     }
 }
 
+/// 计算字符串在等宽终端中的显示宽度（CJK 等宽字符计 2）。
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F300..=0x1F64F
+            | 0x1F900..=0x1F9FF
+            | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// 将表头与单元格渲染为带边框的对齐文本表格。
+pub(crate) fn render_table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| display_width(h)).collect();
+    for row in rows {
+        for (index, cell) in row.iter().enumerate() {
+            widths[index] = widths[index].max(display_width(cell));
+        }
+    }
+
+    let border = |left: &str, mid: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+        format!("{left}{}{right}", segments.join(mid))
+    };
+    let line = |cells: &[String]| {
+        let segments: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!(" {cell}{} ", " ".repeat(width - display_width(cell))))
+            .collect();
+        format!("│{}│", segments.join("│"))
+    };
+
+    let mut lines = Vec::with_capacity(rows.len() + 4);
+    lines.push(border("┌", "┬", "┐"));
+    lines.push(line(headers));
+    lines.push(border("├", "┼", "┤"));
+    for row in rows {
+        lines.push(line(row));
+    }
+    lines.push(border("└", "┴", "┘"));
+    lines.join("\n")
+}
+
+/// 将属性键转为表格中的列名/索引文本。
+fn property_key_text(key: &PropertyKey) -> String {
+    match key {
+        PropertyKey::String(s) => s.to_std_string_escaped(),
+        PropertyKey::Index(i) => i.get().to_string(),
+        PropertyKey::Symbol(s) => s.descriptive_string().to_std_string_escaped(),
+    }
+}
+
+/// 生成 `console.table` 文本；`data` 不是对象时返回 `None`。
+fn table_to_string(
+    data: &JsValue,
+    columns: Option<&JsValue>,
+    context: &mut Context,
+) -> JsResult<Option<String>> {
+    let Some(obj) = data.as_object() else {
+        return Ok(None);
+    };
+    if obj.downcast_ref::<JsMat>().is_some() {
+        return Ok(None);
+    }
+    let cell_options = InspectOptions {
+        max_depth: Some(0),
+        break_length: usize::MAX,
+    };
+
+    let mut column_keys: Vec<String> = Vec::new();
+    let mut has_values_column = false;
+    let mut rows: Vec<(String, HashMap<String, String>, Option<String>)> = Vec::new();
+    for key in obj.own_property_keys(context)? {
+        if obj.is_array() && matches!(key, PropertyKey::String(ref s) if s == &js_string!("length"))
+        {
+            continue;
+        }
+        let row_value = obj.get(key.clone(), context)?;
+        let mut cells = HashMap::new();
+        let mut primitive = None;
+        match row_value.as_object() {
+            Some(row_obj)
+                if !row_obj.is_callable() && row_obj.downcast_ref::<JsMat>().is_none() =>
+            {
+                for cell_key in row_obj.own_property_keys(context)? {
+                    if row_obj.is_array()
+                        && matches!(cell_key, PropertyKey::String(ref s) if s == &js_string!("length"))
+                    {
+                        continue;
+                    }
+                    let name = property_key_text(&cell_key);
+                    let cell_value = row_obj.get(cell_key, context)?;
+                    let mut seen = Vec::new();
+                    let text = inspect_value(&cell_value, context, &mut seen, 1, cell_options)?;
+                    if !column_keys.contains(&name) {
+                        column_keys.push(name.clone());
+                    }
+                    cells.insert(name, text);
+                }
+            }
+            _ => {
+                let mut seen = Vec::new();
+                primitive = Some(inspect_value(
+                    &row_value,
+                    context,
+                    &mut seen,
+                    1,
+                    cell_options,
+                )?);
+                has_values_column = true;
+            }
+        }
+        rows.push((property_key_text(&key), cells, primitive));
+    }
+
+    if let Some(columns) = columns.and_then(JsValue::as_object)
+        && columns.is_array()
+    {
+        let len = columns
+            .get(js_string!("length"), context)?
+            .to_length(context)?;
+        let mut filtered = Vec::with_capacity(len as usize);
+        for i in 0..len as u32 {
+            let name = columns.get(i, context)?.to_string(context)?;
+            filtered.push(name.to_std_string_escaped());
+        }
+        column_keys = filtered;
+    }
+
+    let mut headers = Vec::with_capacity(column_keys.len() + 2);
+    headers.push(TABLE_INDEX_HEADER.to_string());
+    headers.extend(column_keys.iter().cloned());
+    if has_values_column {
+        headers.push(TABLE_VALUES_HEADER.to_string());
+    }
+    let table_rows: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|(index, mut cells, primitive)| {
+            let mut row = Vec::with_capacity(headers.len());
+            row.push(index);
+            for key in &column_keys {
+                row.push(cells.remove(key).unwrap_or_default());
+            }
+            if has_values_column {
+                row.push(primitive.unwrap_or_default());
+            }
+            row
+        })
+        .collect();
+    Ok(Some(render_table(&headers, &table_rows)))
+}
+
 impl Console {
     /// 全局 `console` 名称。
     pub const NAME: JsString = js_string!("console");
@@ -393,6 +798,11 @@ impl Console {
             js_string!("timeEnd"),
             0,
         )
+        .function(
+            console_method(Self::table, state.clone(), logger.clone()),
+            js_string!("table"),
+            0,
+        )
         .function(
             console_method(Self::dir, state.clone(), logger.clone()),
             js_string!("dir"),
@@ -715,8 +1125,27 @@ impl Console {
         Ok(JsValue::undefined())
     }
 
+    /// `console.table(data, columns)`
+    ///
+    /// 说明：`data` 不是对象时退化为 `console.log`。
+    fn table(
+        _: &JsValue,
+        args: &[JsValue],
+        console: &Self,
+        logger: &impl Logger,
+        context: &mut Context,
+    ) -> JsResult<JsValue> {
+        let data = args.get_or_undefined(0);
+        match table_to_string(data, args.get(1), context)? {
+            Some(table) => logger.log(table, &console.state, context)?,
+            None => logger.log(formatter(args, context)?, &console.state, context)?,
+        }
+        Ok(JsValue::undefined())
+    }
+
     /// `console.dir(item, options)`
-    #[allow(clippy::unnecessary_wraps)]
+    ///
+    /// 说明：`options.depth` 指定展开深度，`null` 表示不限制。
     fn dir(
         _: &JsValue,
         args: &[JsValue],
@@ -724,11 +1153,97 @@ impl Console {
         logger: &impl Logger,
         context: &mut Context,
     ) -> JsResult<JsValue> {
+        let mut options = InspectOptions::default();
+        if let Some(opts) = args.get(1).and_then(JsValue::as_object) {
+            let depth = opts.get(js_string!("depth"), context)?;
+            if depth.is_null() {
+                options.max_depth = None;
+            } else if !depth.is_undefined() {
+                let depth = depth.to_number(context)?;
+                options.max_depth = if depth.is_finite() {
+                    Some(depth.max(0.0) as usize)
+                } else {
+                    None
+                };
+            }
+        }
+        let mut seen = Vec::new();
+        let item = args.get_or_undefined(0);
+        // 与 Node.js 一致：dir 的顶层字符串也带引号输出。
+        let start_depth = usize::from(item.is_string());
         logger.info(
-            value_to_console_string(args.get_or_undefined(0), context)?,
+            inspect_value(item, context, &mut seen, start_depth, options)?,
             &console.state,
             context,
         )?;
         Ok(JsValue::undefined())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boa_engine::Source;
+
+    fn eval(source: &str, context: &mut Context) -> JsValue {
+        context
+            .eval(Source::from_bytes(source))
+            .expect("脚本执行失败")
+    }
+
+    #[test]
+    fn inspect_quotes_nested_strings_and_marks_cycles() {
+        let context = &mut Context::default();
+        let value = eval(
+            "const a = { name: 'x', list: [1, 'b'], 'a-b': null }; a.self = a; a",
+            context,
+        );
+        let text = value_to_console_string(&value, context).unwrap();
+        assert_eq!(
+            text,
+            "{ name: 'x', list: [ 1, 'b' ], 'a-b': null, self: [Cycle] }"
+        );
+    }
+
+    #[test]
+    fn inspect_respects_depth_limit() {
+        let context = &mut Context::default();
+        let value = eval("({ a: { b: { c: { d: 1 } } }, e: [[[[]]]] })", context);
+        let text = value_to_console_string(&value, context).unwrap();
+        assert_eq!(text, "{ a: { b: { c: [Object] } }, e: [ [ [Array] ] ] }");
+    }
+
+    #[test]
+    fn formatter_supports_specifiers() {
+        let context = &mut Context::default();
+        let args = [
+            JsValue::new(js_string!("%c%s=%d %i %o %s")),
+            JsValue::new(js_string!("color: red")),
+            JsValue::new(js_string!("v")),
+            JsValue::new(1.5),
+            JsValue::new(2.7),
+            eval("({ k: 'v' })", context),
+            eval("({ n: 1 })", context),
+        ];
+        let text = formatter(&args, context).unwrap();
+        assert_eq!(text, "v=1.5 2 { k: 'v' } { n: 1 }");
+    }
+
+    #[test]
+    fn table_renders_aligned_columns() {
+        let context = &mut Context::default();
+        let value = eval("[{ a: 1, b: '名' }, { a: 22 }, 3]", context);
+        let text = table_to_string(&value, None, context).unwrap().unwrap();
+        let expected = [
+            "┌─────────┬────┬──────┬────────┐",
+            "│ (index) │ a  │ b    │ Values │",
+            "├─────────┼────┼──────┼────────┤",
+            "│ 0       │ 1  │ '名' │        │",
+            "│ 1       │ 22 │      │        │",
+            "│ 2       │    │      │ 3      │",
+            "└─────────┴────┴──────┴────────┘",
+        ]
+        .join("\n");
+        assert_eq!(text, expected);
+    }
+}
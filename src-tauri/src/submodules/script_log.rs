@@ -0,0 +1,775 @@
+use crate::submodules::script_builtin::get_current_script_path;
+use crate::submodules::script_console::{ConsoleState, Logger};
+use base64::{Engine as _, engine::general_purpose};
+use boa_engine::{Context, JsResult};
+use boa_gc::{Finalize, Trace};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// 单个日志文件默认大小上限（字节）。
+const DEFAULT_MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+/// 单次运行默认保留的日志文件数量（含当前文件）。
+const DEFAULT_MAX_FILES: usize = 8;
+/// 每个脚本默认保留的运行记录数量。
+const DEFAULT_MAX_RUNS: usize = 20;
+/// 运行记录默认最长保留天数。
+const DEFAULT_MAX_AGE_DAYS: u64 = 14;
+/// 控制台日志文件名前缀。
+const CONSOLE_FILE_STEM: &str = "console";
+/// 状态截图所在子目录。
+const STATUS_DIR_NAME: &str = "status";
+
+/// 运行记录序号，区分同一毫秒内创建的多条运行记录。
+static RUN_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// 当前执行线程绑定的日志运行记录（供 setStatus 写入截图）。
+    static CURRENT_SCRIPT_LOG_RUN: RefCell<Option<Arc<ScriptLogRun>>> = const { RefCell::new(None) };
+}
+
+/// 脚本文件日志配置。
+#[derive(Debug, Clone)]
+pub struct ScriptLogConfig {
+    /// 单个 JSONL 文件大小上限（字节），超过后滚动。
+    pub max_file_bytes: u64,
+    /// 单次运行保留的文件数量（含当前文件）。
+    pub max_files: usize,
+    /// 每个脚本保留的运行记录数量。
+    pub max_runs: usize,
+    /// 运行记录最长保留时间。
+    pub max_age: Duration,
+}
+
+impl Default for ScriptLogConfig {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+            max_runs: DEFAULT_MAX_RUNS,
+            max_age: Duration::from_secs(DEFAULT_MAX_AGE_DAYS * 24 * 60 * 60),
+        }
+    }
+}
+
+/// 写入 JSONL 的单条日志记录。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScriptLogRecord<'a> {
+    timestamp: u64,
+    level: &'a str,
+    scope: Option<&'a str>,
+    message: &'a str,
+    group_depth: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    images: Option<&'a [String]>,
+}
+
+/// 对外展示的运行记录摘要。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptLogRunInfo {
+    pub run_id: String,
+    pub path: String,
+    pub started_at: u64,
+    pub size: u64,
+}
+
+/// 按大小滚动的 JSONL 写入器。
+///
+/// 说明：
+/// - 当前文件固定为 `console.jsonl`；
+/// - 滚动时依次重命名为 `console.1.jsonl`、`console.2.jsonl`……，编号越大越旧；
+/// - 超出 `max_files` 的旧文件直接删除。
+struct RotatingJsonlWriter {
+    dir: PathBuf,
+    max_file_bytes: u64,
+    max_files: usize,
+    writer: Option<BufWriter<File>>,
+    size: u64,
+}
+
+impl RotatingJsonlWriter {
+    fn new(dir: PathBuf, max_file_bytes: u64, max_files: usize) -> Self {
+        Self {
+            dir,
+            max_file_bytes: max_file_bytes.max(1024),
+            max_files: max_files.max(1),
+            writer: None,
+            size: 0,
+        }
+    }
+
+    /// 生成第 `index` 个日志文件路径（0 为当前文件）。
+    fn file_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(format!("{CONSOLE_FILE_STEM}.jsonl"))
+        } else {
+            self.dir.join(format!("{CONSOLE_FILE_STEM}.{index}.jsonl"))
+        }
+    }
+
+    /// 确保当前文件已打开。
+    fn ensure_open(&mut self) -> Result<&mut BufWriter<File>, String> {
+        if self.writer.is_none() {
+            fs::create_dir_all(&self.dir).map_err(|e| format!("创建日志目录失败: {e}"))?;
+            let path = self.file_path(0);
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| format!("打开日志文件失败: {}: {e}", path.display()))?;
+            self.size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+            self.writer = Some(BufWriter::new(file));
+        }
+        self.writer
+            .as_mut()
+            .ok_or_else(|| "日志文件未打开".to_string())
+    }
+
+    /// 关闭当前文件并整体后移编号。
+    fn rotate(&mut self) -> Result<(), String> {
+        if let Some(mut writer) = self.writer.take() {
+            let _ = writer.flush();
+        }
+        let oldest = self.file_path(self.max_files - 1);
+        if oldest.exists() {
+            fs::remove_file(&oldest).map_err(|e| format!("删除旧日志失败: {e}"))?;
+        }
+        for index in (0..self.max_files - 1).rev() {
+            let from = self.file_path(index);
+            if from.exists() {
+                fs::rename(&from, self.file_path(index + 1))
+                    .map_err(|e| format!("滚动日志文件失败: {e}"))?;
+            }
+        }
+        self.size = 0;
+        Ok(())
+    }
+
+    /// 追加一行 JSON，必要时先滚动。
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        let line_len = line.len() as u64 + 1;
+        self.ensure_open()?;
+        if self.size > 0 && self.size + line_len > self.max_file_bytes {
+            self.rotate()?;
+        }
+        let writer = self.ensure_open()?;
+        writer
+            .write_all(line.as_bytes())
+            .and_then(|_| writer.write_all(b"\n"))
+            .and_then(|_| writer.flush())
+            .map_err(|e| format!("写入日志文件失败: {e}"))?;
+        self.size += line_len;
+        Ok(())
+    }
+}
+
+/// 单次脚本运行对应的日志目录。
+pub struct ScriptLogRun {
+    dir: PathBuf,
+    writer: Mutex<RotatingJsonlWriter>,
+}
+
+impl ScriptLogRun {
+    /// 在 `script_dir` 下创建一条新的运行记录。
+    ///
+    /// 说明：运行 ID 为 `run-{毫秒时间戳}-{进程内序号}`，同一毫秒启动的运行也不会重名。
+    fn create(script_dir: &Path, config: &ScriptLogConfig) -> Result<Self, String> {
+        let sequence = RUN_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let run_id = format!("run-{}-{sequence}", now_millis());
+        let dir = script_dir.join(run_id);
+        fs::create_dir_all(&dir).map_err(|e| format!("创建运行日志目录失败: {e}"))?;
+        Ok(Self {
+            writer: Mutex::new(RotatingJsonlWriter::new(
+                dir.clone(),
+                config.max_file_bytes,
+                config.max_files,
+            )),
+            dir,
+        })
+    }
+
+    /// 运行记录目录。
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 追加一条日志记录。
+    fn append(&self, record: &ScriptLogRecord<'_>) -> Result<(), String> {
+        let line = serde_json::to_string(record).map_err(|e| format!("序列化日志失败: {e}"))?;
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|e| format!("获取日志写入锁失败: {e:?}"))?;
+        writer.write_line(&line)
+    }
+
+    /// 保存状态截图并写入一条 `status` 日志。
+    fn append_status(
+        &self,
+        scope: Option<&str>,
+        title: &str,
+        text: Option<&str>,
+        images: &[String],
+        timestamp: u64,
+    ) -> Result<(), String> {
+        let mut saved = Vec::new();
+        if !images.is_empty() {
+            let status_dir = self.dir.join(STATUS_DIR_NAME);
+            fs::create_dir_all(&status_dir).map_err(|e| format!("创建状态截图目录失败: {e}"))?;
+            let title_part = sanitize_file_component(title);
+            for (index, image) in images.iter().enumerate() {
+                let Some((bytes, ext)) = read_status_image(image) else {
+                    continue;
+                };
+                let file_name = format!("{timestamp}-{title_part}-{index}.{ext}");
+                fs::write(status_dir.join(&file_name), bytes)
+                    .map_err(|e| format!("保存状态截图失败: {e}"))?;
+                saved.push(format!("{STATUS_DIR_NAME}/{file_name}"));
+            }
+        }
+        let message = match text {
+            Some(text) => format!("{title}: {text}"),
+            None => title.to_string(),
+        };
+        self.append(&ScriptLogRecord {
+            timestamp,
+            level: "status",
+            scope,
+            message: &message,
+            group_depth: 0,
+            images: (!saved.is_empty()).then_some(saved.as_slice()),
+        })
+    }
+}
+
+/// 绑定当前线程日志运行记录的守卫，离开作用域时自动解绑。
+pub struct ScriptLogRunGuard {
+    previous: Option<Arc<ScriptLogRun>>,
+}
+
+impl ScriptLogRunGuard {
+    /// 将 `run` 设为当前线程的日志运行记录。
+    pub fn enter(run: Arc<ScriptLogRun>) -> Self {
+        let previous = CURRENT_SCRIPT_LOG_RUN.with(|slot| slot.borrow_mut().replace(run));
+        Self { previous }
+    }
+}
+
+impl Drop for ScriptLogRunGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_SCRIPT_LOG_RUN.with(|slot| {
+            *slot.borrow_mut() = previous;
+        });
+    }
+}
+
+/// 组合 Logger：按顺序将控制台输出分发到多个后端。
+///
+/// 说明：
+/// - 任一后端返回错误时继续分发其余后端，最后返回首个错误；
+/// - `trace` 逐个调用后端自身实现，保证各后端的堆栈输出方式一致。
+#[derive(Trace, Finalize)]
+pub struct FanoutLogger {
+    #[unsafe_ignore_trace]
+    sinks: Vec<Box<dyn Logger>>,
+}
+
+impl FanoutLogger {
+    /// 创建空的组合 Logger。
+    pub fn new() -> Self {
+        Self { sinks: Vec::new() }
+    }
+
+    /// 追加一个后端。
+    pub fn with_sink<L>(mut self, sink: L) -> Self
+    where
+        L: Logger + 'static,
+    {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// 依次调用所有后端。
+    fn dispatch(
+        &self,
+        msg: String,
+        state: &ConsoleState,
+        context: &mut Context,
+        f: fn(&dyn Logger, String, &ConsoleState, &mut Context) -> JsResult<()>,
+    ) -> JsResult<()> {
+        let mut first_error = None;
+        for sink in &self.sinks {
+            if let Err(error) = f(sink.as_ref(), msg.clone(), state, context) {
+                first_error.get_or_insert(error);
+            }
+        }
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for FanoutLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Logger for FanoutLogger {
+    fn trace(&self, msg: String, state: &ConsoleState, context: &mut Context) -> JsResult<()> {
+        self.dispatch(msg, state, context, |sink, msg, state, context| {
+            sink.trace(msg, state, context)
+        })
+    }
+
+    fn debug(&self, msg: String, state: &ConsoleState, context: &mut Context) -> JsResult<()> {
+        self.dispatch(msg, state, context, |sink, msg, state, context| {
+            sink.debug(msg, state, context)
+        })
+    }
+
+    fn log(&self, msg: String, state: &ConsoleState, context: &mut Context) -> JsResult<()> {
+        self.dispatch(msg, state, context, |sink, msg, state, context| {
+            sink.log(msg, state, context)
+        })
+    }
+
+    fn info(&self, msg: String, state: &ConsoleState, context: &mut Context) -> JsResult<()> {
+        self.dispatch(msg, state, context, |sink, msg, state, context| {
+            sink.info(msg, state, context)
+        })
+    }
+
+    fn warn(&self, msg: String, state: &ConsoleState, context: &mut Context) -> JsResult<()> {
+        self.dispatch(msg, state, context, |sink, msg, state, context| {
+            sink.warn(msg, state, context)
+        })
+    }
+
+    fn error(&self, msg: String, state: &ConsoleState, context: &mut Context) -> JsResult<()> {
+        self.dispatch(msg, state, context, |sink, msg, state, context| {
+            sink.error(msg, state, context)
+        })
+    }
+}
+
+/// 文件 Logger：将控制台输出写入当前运行目录下的滚动 JSONL 文件。
+#[derive(Trace, Finalize)]
+pub struct FileLogSink {
+    #[unsafe_ignore_trace]
+    run: Arc<ScriptLogRun>,
+}
+
+impl FileLogSink {
+    /// 基于已创建的运行记录构造文件 Logger。
+    pub fn new(run: Arc<ScriptLogRun>) -> Self {
+        Self { run }
+    }
+
+    /// 写入一条日志；文件写入失败只打印到 stderr，不中断脚本。
+    fn write(&self, level: &str, msg: &str, state: &ConsoleState) {
+        let scope = get_current_script_path();
+        let record = ScriptLogRecord {
+            timestamp: now_millis(),
+            level,
+            scope: scope.as_deref(),
+            message: msg,
+            group_depth: state.groups().len(),
+            images: None,
+        };
+        if let Err(error) = self.run.append(&record) {
+            eprintln!("{error}");
+        }
+    }
+}
+
+impl Logger for FileLogSink {
+    fn debug(&self, msg: String, state: &ConsoleState, _context: &mut Context) -> JsResult<()> {
+        self.write("debug", &msg, state);
+        Ok(())
+    }
+
+    fn log(&self, msg: String, state: &ConsoleState, _context: &mut Context) -> JsResult<()> {
+        self.write("log", &msg, state);
+        Ok(())
+    }
+
+    fn info(&self, msg: String, state: &ConsoleState, _context: &mut Context) -> JsResult<()> {
+        self.write("info", &msg, state);
+        Ok(())
+    }
+
+    fn warn(&self, msg: String, state: &ConsoleState, _context: &mut Context) -> JsResult<()> {
+        self.write("warn", &msg, state);
+        Ok(())
+    }
+
+    fn error(&self, msg: String, state: &ConsoleState, _context: &mut Context) -> JsResult<()> {
+        self.write("error", &msg, state);
+        Ok(())
+    }
+}
+
+/// 当前毫秒时间戳。
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 将任意文本转换为安全的文件名片段。
+fn sanitize_file_component(value: &str) -> String {
+    let sanitized: String = value
+        .chars()
+        .map(|ch| {
+            if ch.is_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .take(48)
+        .collect();
+    if sanitized.is_empty() {
+        "_".to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// 32 位 FNV-1a 哈希；算法固定，不随 Rust 版本变化，保证日志目录名稳定。
+fn fnv1a_32(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// 计算脚本对应的日志目录：`<root>/<文件名>-<路径哈希>`。
+///
+/// 说明：同名脚本位于不同目录时通过路径哈希区分。
+pub fn script_log_dir(log_root: &Path, script_path: &str) -> PathBuf {
+    let stem = Path::new(script_path)
+        .file_stem()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_default();
+    log_root.join(format!(
+        "{}-{:08x}",
+        sanitize_file_component(&stem),
+        fnv1a_32(script_path.as_bytes())
+    ))
+}
+
+/// 读取状态图片内容，支持 data URL 与本地文件路径。
+///
+/// # 返回
+/// 返回 `(字节, 扩展名)`；无法识别时返回 `None`。
+fn read_status_image(image: &str) -> Option<(Vec<u8>, &'static str)> {
+    if let Some(rest) = image.strip_prefix("data:") {
+        let (meta, payload) = rest.split_once(',')?;
+        let ext = match meta.split(';').next().unwrap_or_default() {
+            "image/jpeg" => "jpg",
+            "image/webp" => "webp",
+            "image/bmp" => "bmp",
+            "image/gif" => "gif",
+            _ => "png",
+        };
+        let bytes = general_purpose::STANDARD.decode(payload).ok()?;
+        return Some((bytes, ext));
+    }
+    let path = Path::new(image);
+    if !path.is_file() {
+        return None;
+    }
+    let ext = match path
+        .extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("jpg") | Some("jpeg") => "jpg",
+        Some("webp") => "webp",
+        Some("bmp") => "bmp",
+        Some("gif") => "gif",
+        _ => "png",
+    };
+    fs::read(path).ok().map(|bytes| (bytes, ext))
+}
+
+/// 解析运行目录名中的起始时间戳与序号（兼容不带序号的旧目录名）。
+fn parse_run_id(run_id: &str) -> Option<(u64, u64)> {
+    let rest = run_id.strip_prefix("run-")?;
+    match rest.split_once('-') {
+        Some((started_at, sequence)) => Some((started_at.parse().ok()?, sequence.parse().ok()?)),
+        None => Some((rest.parse().ok()?, 0)),
+    }
+}
+
+/// 统计目录下文件总大小。
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| {
+                    let path = entry.path();
+                    if path.is_dir() {
+                        dir_size(&path)
+                    } else {
+                        entry.metadata().map(|meta| meta.len()).unwrap_or(0)
+                    }
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// 列出脚本日志目录下的全部运行记录（按时间从新到旧）。
+pub fn list_script_log_runs(script_dir: &Path) -> Vec<ScriptLogRunInfo> {
+    let mut runs: Vec<ScriptLogRunInfo> = fs::read_dir(script_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| {
+                    let run_id = entry.file_name().to_string_lossy().to_string();
+                    let (started_at, _) = parse_run_id(&run_id)?;
+                    let path = entry.path();
+                    Some(ScriptLogRunInfo {
+                        size: dir_size(&path),
+                        path: path.to_string_lossy().to_string(),
+                        run_id,
+                        started_at,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    runs.sort_by_key(|run| std::cmp::Reverse(parse_run_id(&run.run_id)));
+    runs
+}
+
+/// 按保留策略清理旧运行记录。
+///
+/// 说明：
+/// - 超过 `max_age` 的记录直接删除；
+/// - 其余记录按时间从新到旧只保留 `max_runs` 条。
+///
+/// # 返回
+/// 返回被删除的运行记录数量
+pub fn apply_log_retention(script_dir: &Path, config: &ScriptLogConfig) -> usize {
+    let now = now_millis();
+    let max_age_ms = config.max_age.as_millis() as u64;
+    let mut removed = 0usize;
+    for (index, run) in list_script_log_runs(script_dir).iter().enumerate() {
+        let expired = now.saturating_sub(run.started_at) > max_age_ms;
+        if (expired || index >= config.max_runs) && fs::remove_dir_all(&run.path).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// 为脚本开启一次新的日志运行记录，并先执行保留策略。
+///
+/// 说明：保留策略预留一个名额给即将创建的新记录。
+pub fn start_script_log_run(
+    log_root: &Path,
+    script_path: &str,
+    config: &ScriptLogConfig,
+) -> Result<Arc<ScriptLogRun>, String> {
+    let script_dir = script_log_dir(log_root, script_path);
+    let retention = ScriptLogConfig {
+        max_runs: config.max_runs.saturating_sub(1),
+        ..config.clone()
+    };
+    apply_log_retention(&script_dir, &retention);
+    ScriptLogRun::create(&script_dir, config).map(Arc::new)
+}
+
+/// 将状态更新写入当前线程绑定的运行日志（含截图）。
+///
+/// 说明：未绑定运行记录或没有任何内容（移除状态）时忽略。
+pub fn record_script_log_status(
+    scope: Option<&str>,
+    title: &str,
+    text: Option<&str>,
+    image: Option<&str>,
+    images: Option<&[String]>,
+    timestamp: u64,
+) {
+    let Some(run) = CURRENT_SCRIPT_LOG_RUN.with(|slot| slot.borrow().clone()) else {
+        return;
+    };
+    if text.is_none() && image.is_none() && images.is_none_or(|items| items.is_empty()) {
+        return;
+    }
+    let mut all_images = Vec::new();
+    if let Some(image) = image {
+        all_images.push(image.to_string());
+    }
+    if let Some(images) = images {
+        all_images.extend(images.iter().cloned());
+    }
+    if let Err(error) = run.append_status(scope, title, text, &all_images, timestamp) {
+        eprintln!("{error}");
+    }
+}
+
+/// 导出一次运行的日志包（ZIP）。
+///
+/// # 参数
+/// - `script_dir`: 脚本日志目录（见 [`script_log_dir`]）
+/// - `run_id`: 运行记录 ID；为空时导出最近一次运行
+/// - `output_path`: 输出 ZIP 路径
+///
+/// # 返回
+/// 返回实际导出的运行记录 ID
+pub fn export_script_log_bundle(
+    script_dir: &Path,
+    run_id: Option<&str>,
+    output_path: &Path,
+) -> Result<String, String> {
+    let runs = list_script_log_runs(script_dir);
+    let run = match run_id {
+        Some(run_id) => runs
+            .iter()
+            .find(|run| run.run_id == run_id)
+            .ok_or_else(|| format!("未找到运行日志: {run_id}"))?,
+        None => runs
+            .first()
+            .ok_or_else(|| "该脚本暂无运行日志".to_string())?,
+    };
+    let run_dir = PathBuf::from(&run.path);
+
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {e}"))?;
+    }
+    let file = File::create(output_path).map_err(|e| format!("创建日志包失败: {e}"))?;
+    let mut zip_writer = ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut pending = vec![run_dir.clone()];
+    let mut buffer = Vec::new();
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir).map_err(|e| format!("读取日志目录失败: {e}"))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let name = path
+                .strip_prefix(&run_dir)
+                .map_err(|e| format!("计算日志相对路径失败: {e}"))?
+                .to_string_lossy()
+                .replace('\\', "/");
+            buffer.clear();
+            File::open(&path)
+                .and_then(|mut file| file.read_to_end(&mut buffer))
+                .map_err(|e| format!("读取日志文件失败: {e}"))?;
+            zip_writer
+                .start_file(format!("{}/{name}", run.run_id), options)
+                .map_err(|e| format!("日志包写入条目失败: {e}"))?;
+            zip_writer
+                .write_all(&buffer)
+                .map_err(|e| format!("日志包写入失败: {e}"))?;
+        }
+    }
+    zip_writer
+        .finish()
+        .map_err(|e| format!("日志包结束失败: {e}"))?;
+    Ok(run.run_id.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dob-script-log-{name}-{}", now_millis()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn script_log_dir_uses_stable_path_hash() {
+        // FNV-1a 参考值，目录名不能随工具链变化
+        assert_eq!(fnv1a_32(b""), 0x811c_9dc5);
+        assert_eq!(fnv1a_32(b"a"), 0xe40c_292c);
+        assert_eq!(
+            script_log_dir(Path::new("logs"), "scripts/demo.js"),
+            Path::new("logs").join(format!("demo-{:08x}", fnv1a_32(b"scripts/demo.js")))
+        );
+    }
+
+    #[test]
+    fn rotating_writer_respects_size_and_count_limits() {
+        let dir = temp_dir("rotate");
+        let mut writer = RotatingJsonlWriter::new(dir.clone(), 1024, 3);
+        let line = "x".repeat(300);
+        for _ in 0..20 {
+            writer.write_line(&line).expect("写入失败");
+        }
+        drop(writer);
+
+        let files: Vec<_> = fs::read_dir(&dir).unwrap().flatten().collect();
+        assert_eq!(files.len(), 3);
+        for file in files {
+            assert!(file.metadata().unwrap().len() <= 1024);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn retention_keeps_newest_runs() {
+        let dir = temp_dir("retention");
+        let now = now_millis();
+        for offset in 0..5u64 {
+            fs::create_dir_all(dir.join(format!("run-{}", now - offset * 1000))).unwrap();
+        }
+        let old = now - 30 * 24 * 60 * 60 * 1000;
+        fs::create_dir_all(dir.join(format!("run-{old}"))).unwrap();
+
+        let config = ScriptLogConfig {
+            max_runs: 3,
+            ..ScriptLogConfig::default()
+        };
+        assert_eq!(apply_log_retention(&dir, &config), 3);
+        let runs = list_script_log_runs(&dir);
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].started_at, now);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn runs_created_in_same_millisecond_get_distinct_ids() {
+        let dir = temp_dir("run-id");
+        let config = ScriptLogConfig::default();
+        let first = ScriptLogRun::create(&dir, &config).expect("创建运行记录失败");
+        let second = ScriptLogRun::create(&dir, &config).expect("创建运行记录失败");
+        assert_ne!(first.dir(), second.dir());
+
+        let runs = list_script_log_runs(&dir);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(Path::new(&runs[0].path), second.dir());
+        assert_eq!(
+            parse_run_id("run-1700000000000"),
+            Some((1_700_000_000_000, 0))
+        );
+        assert_eq!(parse_run_id("run-17-3"), Some((17, 3)));
+        assert_eq!(parse_run_id("misc"), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,363 @@
+// 脚本网络 API：在原生收发原语之上实现标准形态的 fetch/Headers/Request/Response/WebSocket。
+// 本文件求值结果为安装函数，由 script_net.rs 传入原生对象调用。
+(native) => {
+    "use strict";
+
+    /** 统一请求/响应体为 ArrayBuffer（无内容时为 null）。 */
+    const toArrayBuffer = (body) => {
+        if (body === undefined || body === null) {
+            return null;
+        }
+        if (body instanceof ArrayBuffer) {
+            return body.slice(0);
+        }
+        if (ArrayBuffer.isView(body)) {
+            return body.buffer.slice(body.byteOffset, body.byteOffset + body.byteLength);
+        }
+        return native.encodeUtf8(String(body));
+    };
+
+    /** 校验并规范化请求头名称。 */
+    const normalizeHeaderName = (name) => {
+        const text = String(name);
+        if (!/^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/.test(text)) {
+            throw new TypeError(`无效的请求头名称: ${text}`);
+        }
+        return text.toLowerCase();
+    };
+
+    class Headers {
+        constructor(init) {
+            this._map = new Map();
+            if (init === undefined || init === null) {
+                return;
+            }
+            if (init instanceof Headers) {
+                init.forEach((value, name) => this.append(name, value));
+            } else if (Array.isArray(init)) {
+                for (const pair of init) {
+                    if (!Array.isArray(pair) || pair.length !== 2) {
+                        throw new TypeError("Headers 初始化数组的每一项必须是 [name, value]");
+                    }
+                    this.append(pair[0], pair[1]);
+                }
+            } else if (typeof init === "object") {
+                for (const name of Object.keys(init)) {
+                    this.append(name, init[name]);
+                }
+            } else {
+                throw new TypeError("无效的 Headers 初始化参数");
+            }
+        }
+
+        append(name, value) {
+            const key = normalizeHeaderName(name);
+            const text = String(value).trim();
+            const current = this._map.get(key);
+            this._map.set(key, current === undefined ? text : `${current}, ${text}`);
+        }
+
+        set(name, value) {
+            this._map.set(normalizeHeaderName(name), String(value).trim());
+        }
+
+        get(name) {
+            const value = this._map.get(normalizeHeaderName(name));
+            return value === undefined ? null : value;
+        }
+
+        has(name) {
+            return this._map.has(normalizeHeaderName(name));
+        }
+
+        delete(name) {
+            this._map.delete(normalizeHeaderName(name));
+        }
+
+        forEach(callback, thisArg) {
+            for (const [name, value] of this.entries()) {
+                callback.call(thisArg, value, name, this);
+            }
+        }
+
+        *entries() {
+            const names = [...this._map.keys()].sort();
+            for (const name of names) {
+                yield [name, this._map.get(name)];
+            }
+        }
+
+        *keys() {
+            for (const [name] of this.entries()) {
+                yield name;
+            }
+        }
+
+        *values() {
+            for (const [, value] of this.entries()) {
+                yield value;
+            }
+        }
+
+        [Symbol.iterator]() {
+            return this.entries();
+        }
+
+        get [Symbol.toStringTag]() {
+            return "Headers";
+        }
+    }
+
+    /** Request/Response 共享的请求体读取逻辑。 */
+    class Body {
+        constructor(body) {
+            this._body = toArrayBuffer(body);
+            this.bodyUsed = false;
+        }
+
+        _consume() {
+            if (this.bodyUsed) {
+                return Promise.reject(new TypeError("请求体已被读取"));
+            }
+            this.bodyUsed = true;
+            return Promise.resolve(this._body === null ? new ArrayBuffer(0) : this._body);
+        }
+
+        arrayBuffer() {
+            return this._consume().then((buffer) => buffer.slice(0));
+        }
+
+        text() {
+            return this._consume().then((buffer) => native.decodeUtf8(buffer));
+        }
+
+        json() {
+            return this.text().then((text) => JSON.parse(text));
+        }
+    }
+
+    class Request extends Body {
+        constructor(input, init = {}) {
+            const source = input instanceof Request ? input : null;
+            const body = init.body !== undefined ? init.body : source ? source._body : null;
+            super(body);
+            this.url = source ? source.url : String(input);
+            this.method = String(init.method || (source ? source.method : "GET")).toUpperCase();
+            this.headers = new Headers(init.headers || (source ? source.headers : undefined));
+            this.signal = init.signal !== undefined ? init.signal : source ? source.signal : null;
+            this.timeout = init.timeout !== undefined ? init.timeout : source ? source.timeout : undefined;
+            if ((this.method === "GET" || this.method === "HEAD") && this._body !== null) {
+                throw new TypeError(`${this.method} 请求不能携带请求体`);
+            }
+            if (typeof body === "string" && !this.headers.has("content-type")) {
+                this.headers.set("content-type", "text/plain;charset=UTF-8");
+            }
+        }
+
+        clone() {
+            if (this.bodyUsed) {
+                throw new TypeError("请求体已被读取，无法克隆");
+            }
+            return new Request(this);
+        }
+
+        get [Symbol.toStringTag]() {
+            return "Request";
+        }
+    }
+
+    class Response extends Body {
+        constructor(body = null, init = {}) {
+            super(body);
+            this.status = init.status === undefined ? 200 : Number(init.status);
+            this.statusText = init.statusText === undefined ? "" : String(init.statusText);
+            this.headers = new Headers(init.headers);
+            this.url = init.url || "";
+            this.redirected = Boolean(init.redirected);
+            this.type = "basic";
+        }
+
+        get ok() {
+            return this.status >= 200 && this.status <= 299;
+        }
+
+        clone() {
+            if (this.bodyUsed) {
+                throw new TypeError("响应体已被读取，无法克隆");
+            }
+            return new Response(this._body, this);
+        }
+
+        static json(data, init = {}) {
+            const headers = new Headers(init.headers);
+            if (!headers.has("content-type")) {
+                headers.set("content-type", "application/json");
+            }
+            return new Response(JSON.stringify(data), { ...init, headers });
+        }
+
+        static error() {
+            const response = new Response(null, { status: 0 });
+            response.type = "error";
+            return response;
+        }
+
+        get [Symbol.toStringTag]() {
+            return "Response";
+        }
+    }
+
+    /**
+     * fetch(input, init)
+     * - init.signal: AbortSignal，中止时取消请求并以其原因拒绝
+     * - init.timeout: 请求超时（毫秒，扩展字段）
+     */
+    const fetch = async (input, init) => {
+        const request = new Request(input, init);
+        const raw = await native.fetch(
+            request.url,
+            request.method,
+            [...request.headers],
+            request._body,
+            request.timeout,
+            request.signal,
+        );
+        return new Response(raw.body, {
+            status: raw.status,
+            statusText: raw.statusText,
+            headers: raw.headers,
+            url: raw.url,
+            redirected: raw.redirected,
+        });
+    };
+
+    class WebSocket {
+        static CONNECTING = 0;
+        static OPEN = 1;
+        static CLOSING = 2;
+        static CLOSED = 3;
+
+        constructor(url, protocols) {
+            this.url = String(url);
+            this.readyState = WebSocket.CONNECTING;
+            this.protocol = "";
+            this.binaryType = "arraybuffer";
+            this.onopen = null;
+            this.onmessage = null;
+            this.onerror = null;
+            this.onclose = null;
+            this._id = null;
+            this._listeners = new Map();
+            const list = protocols === undefined ? [] : Array.isArray(protocols) ? protocols : [protocols];
+
+            native.wsConnect(this.url, list.map(String)).then(
+                (info) => {
+                    this._id = info.id;
+                    this.protocol = info.protocol;
+                    if (this.readyState === WebSocket.CLOSING) {
+                        native.wsClose(this._id, this._closeCode, this._closeReason);
+                    } else {
+                        this.readyState = WebSocket.OPEN;
+                        this._emit("open", {});
+                    }
+                    this._pump();
+                },
+                (error) => {
+                    this.readyState = WebSocket.CLOSED;
+                    this._emit("error", { message: String(error && error.message ? error.message : error) });
+                    this._emit("close", { code: 1006, reason: "", wasClean: false });
+                },
+            );
+        }
+
+        /** 持续接收原生事件，直到连接关闭。 */
+        async _pump() {
+            for (;;) {
+                const event = await native.wsRecv(this._id);
+                if (event.type === "close") {
+                    this.readyState = WebSocket.CLOSED;
+                    this._emit("close", { code: event.code, reason: event.reason, wasClean: event.wasClean });
+                    return;
+                }
+                if (event.type === "error") {
+                    this._emit("error", { message: event.message });
+                    continue;
+                }
+                this._emit("message", { data: event.data, origin: this.url });
+            }
+        }
+
+        send(data) {
+            if (this.readyState === WebSocket.CONNECTING) {
+                throw new Error("InvalidStateError: WebSocket 尚未连接");
+            }
+            if (this.readyState !== WebSocket.OPEN) {
+                return;
+            }
+            native.wsSend(this._id, typeof data === "string" ? data : toArrayBuffer(data));
+        }
+
+        close(code = 1000, reason = "") {
+            if (this.readyState === WebSocket.CLOSING || this.readyState === WebSocket.CLOSED) {
+                return;
+            }
+            this._closeCode = code;
+            this._closeReason = String(reason);
+            const connecting = this.readyState === WebSocket.CONNECTING;
+            this.readyState = WebSocket.CLOSING;
+            if (!connecting) {
+                native.wsClose(this._id, code, this._closeReason);
+            }
+        }
+
+        addEventListener(type, listener) {
+            if (typeof listener !== "function") {
+                return;
+            }
+            const listeners = this._listeners.get(type) || [];
+            if (!listeners.includes(listener)) {
+                listeners.push(listener);
+            }
+            this._listeners.set(type, listeners);
+        }
+
+        removeEventListener(type, listener) {
+            const listeners = this._listeners.get(type);
+            if (listeners) {
+                this._listeners.set(
+                    type,
+                    listeners.filter((item) => item !== listener),
+                );
+            }
+        }
+
+        dispatchEvent(event) {
+            const handler = this[`on${event.type}`];
+            if (typeof handler === "function") {
+                handler.call(this, event);
+            }
+            for (const listener of [...(this._listeners.get(event.type) || [])]) {
+                listener.call(this, event);
+            }
+            return true;
+        }
+
+        _emit(type, init) {
+            try {
+                this.dispatchEvent({ ...init, type, target: this });
+            } catch (error) {
+                console.error(`WebSocket ${type} 回调异常:`, error);
+            }
+        }
+
+        get [Symbol.toStringTag]() {
+            return "WebSocket";
+        }
+    }
+
+    globalThis.Headers = Headers;
+    globalThis.Request = Request;
+    globalThis.Response = Response;
+    globalThis.fetch = fetch;
+    globalThis.WebSocket = WebSocket;
+};
@@ -0,0 +1,902 @@
+use crate::submodules::jsabort::{ScriptAbort, run_abortable};
+use crate::submodules::script::{
+    ScriptStopSnapshot, capture_current_script_stop_snapshot, run_with_script_stop_snapshot,
+    should_stop_current_script,
+};
+use boa_engine::job::NativeAsyncJob;
+use boa_engine::native_function::NativeFunction;
+use boa_engine::object::ObjectInitializer;
+use boa_engine::object::builtins::{JsArray, JsArrayBuffer, JsPromise};
+use boa_engine::property::Attribute;
+use boa_engine::{Context, JsArgs, JsNativeError, JsObject, JsResult, JsValue, Source, js_string};
+use futures_util::{SinkExt, StreamExt};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::task::Poll;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, Message};
+use url::Url;
+
+/// 脚本停止时网络任务的拒绝原因。
+const NETWORK_STOPPED_MESSAGE: &str = "脚本已停止，网络请求已取消";
+/// 停止请求轮询间隔（毫秒）。
+const STOP_POLL_INTERVAL_MS: u64 = 50;
+/// WebSocket 异常关闭码（RFC 6455）。
+const WS_ABNORMAL_CLOSE_CODE: u16 = 1006;
+/// WebSocket 未携带状态码时的关闭码（RFC 6455）。
+const WS_NO_STATUS_CODE: u16 = 1005;
+/// WebSocket 端点离开时的关闭码（RFC 6455）。
+const WS_GOING_AWAY_CODE: u16 = 1001;
+
+/// 脚本网络访问策略。
+#[derive(Debug, Clone)]
+pub struct ScriptNetworkPolicy {
+    /// 是否允许脚本访问网络（fetch/WebSocket）。
+    pub enabled: bool,
+    /// 允许访问的主机列表；为空表示不限制。
+    ///
+    /// 说明：`example.com` 同时匹配 `example.com` 与 `*.example.com`。
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for ScriptNetworkPolicy {
+    fn default() -> Self {
+        Self {
+            // 命令行模式由用户在终端显式运行脚本，默认放行；应用内需要用户授权。
+            enabled: cfg!(feature = "dob-script-cli"),
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
+/// 全局脚本网络访问策略。
+static SCRIPT_NETWORK_POLICY: LazyLock<RwLock<ScriptNetworkPolicy>> =
+    LazyLock::new(|| RwLock::new(ScriptNetworkPolicy::default()));
+/// WebSocket 连接序号。
+static WEBSOCKET_SEQ: AtomicU64 = AtomicU64::new(1);
+/// 活跃的 WebSocket 连接：id -> 连接通道。
+static WEBSOCKET_CONNECTIONS: LazyLock<Mutex<HashMap<u64, ScriptWebSocket>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+thread_local! {
+    /// 当前脚本运行开启的 WebSocket 会话任务：id -> 任务句柄。
+    static RUN_WEBSOCKET_SESSIONS: RefCell<HashMap<u64, AbortOnDrop<()>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// 更新脚本网络访问策略。
+pub fn set_script_network_policy(policy: ScriptNetworkPolicy) -> Result<(), String> {
+    let mut guard = SCRIPT_NETWORK_POLICY
+        .write()
+        .map_err(|e| format!("获取网络策略锁失败: {e:?}"))?;
+    *guard = policy;
+    Ok(())
+}
+
+/// 读取当前脚本网络访问策略。
+pub fn get_script_network_policy() -> ScriptNetworkPolicy {
+    SCRIPT_NETWORK_POLICY
+        .read()
+        .map(|guard| guard.clone())
+        .unwrap_or_default()
+}
+
+/// 按网络策略校验地址。
+///
+/// # 参数
+/// - `raw_url`: 目标地址
+/// - `schemes`: 允许的协议列表
+///
+/// # 返回
+/// 返回解析后的地址；未授权或地址无效时返回错误信息
+fn check_network_permission(raw_url: &str, schemes: &[&str]) -> Result<Url, String> {
+    let url = Url::parse(raw_url).map_err(|e| format!("无效的地址 {raw_url}: {e}"))?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(format!(
+            "不支持的协议 {}，仅支持: {}",
+            url.scheme(),
+            schemes.join("/")
+        ));
+    }
+    let policy = get_script_network_policy();
+    if !policy.enabled {
+        return Err("脚本未获得网络访问权限，请在设置中开启".to_string());
+    }
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+    if !policy.allowed_hosts.is_empty()
+        && !policy.allowed_hosts.iter().any(|allowed| {
+            let allowed = allowed.trim().to_ascii_lowercase();
+            host == allowed || host.ends_with(&format!(".{allowed}"))
+        })
+    {
+        return Err(format!("脚本无权访问主机: {host}"));
+    }
+    Ok(url)
+}
+
+/// 等待脚本停止请求；没有停止快照时永不返回。
+async fn wait_script_stop(snapshot: Option<ScriptStopSnapshot>) {
+    let Some(snapshot) = snapshot else {
+        return std::future::pending().await;
+    };
+    loop {
+        if run_with_script_stop_snapshot(Some(snapshot.clone()), should_stop_current_script) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(STOP_POLL_INTERVAL_MS)).await;
+    }
+}
+
+/// 被丢弃时自动中止的后台任务句柄。
+struct AbortOnDrop<T>(tauri::async_runtime::JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl<T> Future for AbortOnDrop<T> {
+    type Output = Result<T, tauri::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+/// 绑定当前线程 WebSocket 会话登记表的守卫。
+///
+/// 说明：离开作用域（脚本运行结束）时中止本次运行开启的全部会话，并注销对应连接。
+pub struct ScriptNetworkRunGuard {
+    previous: HashMap<u64, AbortOnDrop<()>>,
+}
+
+impl ScriptNetworkRunGuard {
+    /// 为当前线程启用新的会话登记表。
+    pub fn enter() -> Self {
+        let previous = RUN_WEBSOCKET_SESSIONS.with(|slot| std::mem::take(&mut *slot.borrow_mut()));
+        Self { previous }
+    }
+}
+
+impl Drop for ScriptNetworkRunGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.previous);
+        let sessions = RUN_WEBSOCKET_SESSIONS.with(|slot| slot.replace(previous));
+        if let Ok(mut connections) = WEBSOCKET_CONNECTIONS.lock() {
+            for id in sessions.keys() {
+                connections.remove(id);
+            }
+        }
+        // 句柄随 `sessions` 一起丢弃，会话任务随即中止。
+    }
+}
+
+/// 在应用异步运行时执行网络任务。
+///
+/// 说明：
+/// - 脚本线程的运行时未启用 IO 驱动，网络 IO 统一交给应用运行时；
+/// - 脚本停止或任务被丢弃（作业执行器退出）时立即取消。
+async fn run_network_task<T, F>(snapshot: Option<ScriptStopSnapshot>, task: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T, String>> + Send + 'static,
+{
+    let handle = AbortOnDrop(tauri::async_runtime::spawn(async move {
+        tokio::select! {
+            result = task => result,
+            _ = wait_script_stop(snapshot) => Err(NETWORK_STOPPED_MESSAGE.to_string()),
+        }
+    }));
+    handle.await.map_err(|e| format!("网络任务执行失败: {e}"))?
+}
+
+/// 构造 `TypeError` 错误对象（fetch 规范的网络错误类型）。
+fn type_error_value(message: String, context: &mut Context) -> JsValue {
+    JsNativeError::typ()
+        .with_message(message)
+        .to_opaque(context)
+        .into()
+}
+
+/// 读取字符串或 ArrayBuffer 形式的请求体/消息体。
+fn read_body_bytes(value: &JsValue, context: &mut Context) -> JsResult<Option<Vec<u8>>> {
+    if value.is_undefined() || value.is_null() {
+        return Ok(None);
+    }
+    if let Some(obj) = value.as_object()
+        && let Ok(buffer) = JsArrayBuffer::from_object(obj.clone())
+    {
+        return Ok(Some(
+            buffer.data().map(|data| data.to_vec()).unwrap_or_default(),
+        ));
+    }
+    Ok(Some(
+        value
+            .to_string(context)?
+            .to_std_string_escaped()
+            .into_bytes(),
+    ))
+}
+
+/// 解析 `[[name, value], ...]` 形式的请求头。
+fn read_header_pairs(value: &JsValue, context: &mut Context) -> JsResult<Vec<(String, String)>> {
+    let Some(obj) = value.as_object() else {
+        return Ok(Vec::new());
+    };
+    let len = obj.get(js_string!("length"), context)?.to_length(context)?;
+    let mut headers = Vec::with_capacity(len as usize);
+    for i in 0..len as u32 {
+        let item = obj.get(i, context)?;
+        let Some(pair) = item.as_object() else {
+            continue;
+        };
+        let name = pair
+            .get(0, context)?
+            .to_string(context)?
+            .to_std_string_escaped();
+        let value = pair
+            .get(1, context)?
+            .to_string(context)?
+            .to_std_string_escaped();
+        headers.push((name, value));
+    }
+    Ok(headers)
+}
+
+/// 由 Rust 字符串列表构造 `[[name, value], ...]` 数组。
+fn header_pairs_to_js(headers: Vec<(String, String)>, context: &mut Context) -> JsValue {
+    let pairs = headers.into_iter().map(|(name, value)| {
+        JsArray::from_iter(
+            [
+                JsValue::from(js_string!(name)),
+                JsValue::from(js_string!(value)),
+            ],
+            context,
+        )
+        .into()
+    });
+    let pairs: Vec<JsValue> = pairs.collect();
+    JsArray::from_iter(pairs, context).into()
+}
+
+/// 原生 fetch 响应。
+struct FetchResponse {
+    status: u16,
+    status_text: String,
+    url: String,
+    /// 最终地址与（规范化后的）请求地址不同，即发生过重定向
+    redirected: bool,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// 使用应用共享 HTTP 客户端发送请求并读取完整响应体。
+async fn send_fetch(
+    url: Url,
+    method: reqwest::Method,
+    headers: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+) -> Result<FetchResponse, String> {
+    let mut request = crate::HTTP_CLIENT.request(method, url.clone());
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    if let Some(body) = body {
+        request = request.body(body);
+    }
+    if let Some(timeout) = timeout {
+        request = request.timeout(timeout);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("fetch 请求失败: {e}"))?;
+    let status = response.status();
+    let redirected = *response.url() != url;
+    let url = response.url().to_string();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str().to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| format!("fetch 读取响应失败: {e}"))?
+        .to_vec();
+    Ok(FetchResponse {
+        status: status.as_u16(),
+        status_text: status.canonical_reason().unwrap_or_default().to_string(),
+        url,
+        redirected,
+        headers,
+        body,
+    })
+}
+
+/// 将原生响应转换为 JS 对象。
+fn fetch_response_to_js(response: FetchResponse, context: &mut Context) -> JsResult<JsValue> {
+    let body = JsArrayBuffer::from_byte_block(response.body, context)?;
+    let headers = header_pairs_to_js(response.headers, context);
+    let obj = ObjectInitializer::new(context)
+        .property(
+            js_string!("status"),
+            i32::from(response.status),
+            Attribute::all(),
+        )
+        .property(
+            js_string!("statusText"),
+            js_string!(response.status_text),
+            Attribute::all(),
+        )
+        .property(
+            js_string!("url"),
+            js_string!(response.url),
+            Attribute::all(),
+        )
+        .property(
+            js_string!("redirected"),
+            response.redirected,
+            Attribute::all(),
+        )
+        .property(js_string!("headers"), headers, Attribute::all())
+        .property(js_string!("body"), body, Attribute::all())
+        .build();
+    Ok(obj.into())
+}
+
+/// 原生 fetch：`fetch(url, method, headers, body, timeoutMs, signal)`。
+///
+/// 返回 Promise，解析为 `{ status, statusText, url, redirected, headers, body }`；
+/// `signal` 中止时取消请求，并以其原因拒绝。
+fn _net_fetch(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let raw_url = args
+        .get_or_undefined(0)
+        .to_string(ctx)?
+        .to_std_string_escaped();
+    let method = args.get_or_undefined(1);
+    let method = if method.is_undefined() {
+        "GET".to_string()
+    } else {
+        method
+            .to_string(ctx)?
+            .to_std_string_escaped()
+            .to_ascii_uppercase()
+    };
+    let headers = read_header_pairs(args.get_or_undefined(2), ctx)?;
+    let body = read_body_bytes(args.get_or_undefined(3), ctx)?;
+    let timeout = match args.get_or_undefined(4) {
+        value if value.is_undefined() || value.is_null() => None,
+        value => {
+            let ms = value.to_number(ctx)?;
+            (ms.is_finite() && ms > 0.0).then(|| Duration::from_millis(ms as u64))
+        }
+    };
+    let abort = ScriptAbort::from_signal(args.get_or_undefined(5))?;
+
+    let (promise, resolvers) = JsPromise::new_pending(ctx);
+    if ScriptAbort::reject_if_aborted(abort.as_ref(), &resolvers.reject, ctx)? {
+        return Ok(promise.into());
+    }
+    let checked = check_network_permission(&raw_url, &["http", "https"]).and_then(|url| {
+        reqwest::Method::from_bytes(method.as_bytes())
+            .map(|method| (url, method))
+            .map_err(|e| format!("无效的请求方法 {method}: {e}"))
+    });
+    let (url, method) = match checked {
+        Ok(value) => value,
+        Err(message) => {
+            let error = type_error_value(message, ctx);
+            resolvers
+                .reject
+                .call(&JsValue::undefined(), &[error], ctx)?;
+            return Ok(promise.into());
+        }
+    };
+
+    let snapshot = capture_current_script_stop_snapshot();
+    let token = abort.as_ref().map(ScriptAbort::token);
+    ctx.enqueue_job(
+        NativeAsyncJob::new(async move |context| {
+            // 中止时丢弃任务句柄，后台请求随之取消。
+            let task = run_network_task(snapshot, send_fetch(url, method, headers, body, timeout));
+            let result = run_abortable(token.as_ref(), task).await;
+            let context = &mut context.borrow_mut();
+            let Some(result) = result else {
+                return ScriptAbort::reject_aborted(abort.as_ref(), &resolvers.reject, context);
+            };
+            let value = result.and_then(|response| {
+                fetch_response_to_js(response, context).map_err(|e| e.to_string())
+            });
+            match value {
+                Ok(value) => resolvers
+                    .resolve
+                    .call(&JsValue::undefined(), &[value], context),
+                Err(message) => {
+                    let error = type_error_value(message, context);
+                    resolvers
+                        .reject
+                        .call(&JsValue::undefined(), &[error], context)
+                }
+            }
+        })
+        .into(),
+    );
+    Ok(promise.into())
+}
+
+/// UTF-8 解码 ArrayBuffer（供 `Response.text()` 使用）。
+fn _net_decode_utf8(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let bytes = read_body_bytes(args.get_or_undefined(0), ctx)?.unwrap_or_default();
+    Ok(js_string!(String::from_utf8_lossy(&bytes).into_owned()).into())
+}
+
+/// UTF-8 编码字符串为 ArrayBuffer（供 `Request/Response` 统一存储请求体）。
+fn _net_encode_utf8(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let bytes = read_body_bytes(args.get_or_undefined(0), ctx)?.unwrap_or_default();
+    Ok(JsArrayBuffer::from_byte_block(bytes, ctx)?.into())
+}
+
+/// WebSocket 消息体。
+enum WsPayload {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// 发往 WebSocket 会话的指令。
+enum WsOutgoing {
+    Send(WsPayload),
+    Close(u16, String),
+}
+
+/// WebSocket 会话推送给脚本的事件。
+enum WsIncoming {
+    Message(WsPayload),
+    Error(String),
+    Close {
+        code: u16,
+        reason: String,
+        was_clean: bool,
+    },
+}
+
+/// 已登记的 WebSocket 连接通道。
+struct ScriptWebSocket {
+    outgoing: mpsc::UnboundedSender<WsOutgoing>,
+    incoming: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<WsIncoming>>>,
+}
+
+/// WebSocket 会话：负责连接、收发与停止时关闭。
+async fn websocket_session(
+    id: u64,
+    request: tokio_tungstenite::tungstenite::handshake::client::Request,
+    snapshot: Option<ScriptStopSnapshot>,
+    mut outgoing: mpsc::UnboundedReceiver<WsOutgoing>,
+    incoming: mpsc::UnboundedSender<WsIncoming>,
+    opened: oneshot::Sender<Result<String, String>>,
+) {
+    let stop = wait_script_stop(snapshot);
+    tokio::pin!(stop);
+
+    let connected = tokio::select! {
+        result = connect_async(request) => result,
+        _ = &mut stop => {
+            let _ = opened.send(Err(NETWORK_STOPPED_MESSAGE.to_string()));
+            remove_websocket(id);
+            return;
+        }
+    };
+    let (stream, response) = match connected {
+        Ok(value) => value,
+        Err(e) => {
+            let _ = opened.send(Err(format!("WebSocket 连接失败: {e}")));
+            remove_websocket(id);
+            return;
+        }
+    };
+    let protocol = response
+        .headers()
+        .get("sec-websocket-protocol")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if opened.send(Ok(protocol)).is_err() {
+        remove_websocket(id);
+        return;
+    }
+
+    let (mut write, mut read) = stream.split();
+    let close_event = loop {
+        tokio::select! {
+            _ = &mut stop => {
+                let _ = write.send(Message::Close(None)).await;
+                break WsIncoming::Close {
+                    code: WS_GOING_AWAY_CODE,
+                    reason: NETWORK_STOPPED_MESSAGE.to_string(),
+                    was_clean: false,
+                };
+            }
+            command = outgoing.recv() => {
+                let message = match command {
+                    Some(WsOutgoing::Send(WsPayload::Text(text))) => Message::Text(text.into()),
+                    Some(WsOutgoing::Send(WsPayload::Binary(bytes))) => {
+                        Message::Binary(bytes.into())
+                    }
+                    Some(WsOutgoing::Close(code, reason)) => Message::Close(Some(CloseFrame {
+                        code: CloseCode::from(code),
+                        reason: reason.into(),
+                    })),
+                    None => Message::Close(None),
+                };
+                if let Err(e) = write.send(message).await {
+                    let _ = incoming.send(WsIncoming::Error(format!("WebSocket 发送失败: {e}")));
+                    break WsIncoming::Close {
+                        code: WS_ABNORMAL_CLOSE_CODE,
+                        reason: String::new(),
+                        was_clean: false,
+                    };
+                }
+            }
+            message = read.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let _ = incoming.send(WsIncoming::Message(WsPayload::Text(text.to_string())));
+                }
+                Some(Ok(Message::Binary(bytes))) => {
+                    let _ = incoming.send(WsIncoming::Message(WsPayload::Binary(bytes.to_vec())));
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    break match frame {
+                        Some(frame) => WsIncoming::Close {
+                            code: frame.code.into(),
+                            reason: frame.reason.to_string(),
+                            was_clean: true,
+                        },
+                        None => WsIncoming::Close {
+                            code: WS_NO_STATUS_CODE,
+                            reason: String::new(),
+                            was_clean: true,
+                        },
+                    };
+                }
+                // Ping/Pong 由 tungstenite 自动处理。
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    let _ = incoming.send(WsIncoming::Error(format!("WebSocket 接收失败: {e}")));
+                    break WsIncoming::Close {
+                        code: WS_ABNORMAL_CLOSE_CODE,
+                        reason: String::new(),
+                        was_clean: false,
+                    };
+                }
+                None => {
+                    break WsIncoming::Close {
+                        code: WS_ABNORMAL_CLOSE_CODE,
+                        reason: String::new(),
+                        was_clean: false,
+                    };
+                }
+            }
+        }
+    };
+    let _ = incoming.send(close_event);
+    remove_websocket(id);
+}
+
+/// 判断 WebSocket 连接是否仍在登记中。
+fn is_websocket_registered(id: u64) -> bool {
+    WEBSOCKET_CONNECTIONS
+        .lock()
+        .map(|connections| connections.contains_key(&id))
+        .unwrap_or(false)
+}
+
+/// 注销 WebSocket 连接。
+fn remove_websocket(id: u64) {
+    if let Ok(mut connections) = WEBSOCKET_CONNECTIONS.lock() {
+        connections.remove(&id);
+    }
+}
+
+/// 读取 WebSocket id 参数。
+fn read_websocket_id(value: &JsValue, ctx: &mut Context) -> JsResult<u64> {
+    Ok(value.to_number(ctx)? as u64)
+}
+
+/// 原生 WebSocket 连接：`wsConnect(url, protocols)`。
+///
+/// 返回 Promise，解析为 `{ id, protocol }`。
+fn _net_ws_connect(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let raw_url = args
+        .get_or_undefined(0)
+        .to_string(ctx)?
+        .to_std_string_escaped();
+    let mut protocol_list = Vec::new();
+    if let Some(list) = args.get_or_undefined(1).as_object() {
+        let len = list.get(js_string!("length"), ctx)?.to_length(ctx)?;
+        for i in 0..len as u32 {
+            protocol_list.push(list.get(i, ctx)?.to_string(ctx)?.to_std_string_escaped());
+        }
+    }
+
+    let (promise, resolvers) = JsPromise::new_pending(ctx);
+    let request = check_network_permission(&raw_url, &["ws", "wss"]).and_then(|url| {
+        let mut request = url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| format!("无效的 WebSocket 地址: {e}"))?;
+        if !protocol_list.is_empty() {
+            let value = protocol_list
+                .join(", ")
+                .parse::<http::HeaderValue>()
+                .map_err(|e| format!("无效的 WebSocket 子协议: {e}"))?;
+            request
+                .headers_mut()
+                .insert("sec-websocket-protocol", value);
+        }
+        Ok(request)
+    });
+    let request = match request {
+        Ok(request) => request,
+        Err(message) => {
+            let error = type_error_value(message, ctx);
+            resolvers
+                .reject
+                .call(&JsValue::undefined(), &[error], ctx)?;
+            return Ok(promise.into());
+        }
+    };
+
+    let id = WEBSOCKET_SEQ.fetch_add(1, Ordering::Relaxed);
+    let (out_tx, out_rx) = mpsc::unbounded_channel();
+    let (in_tx, in_rx) = mpsc::unbounded_channel();
+    let (open_tx, open_rx) = oneshot::channel();
+    if let Ok(mut connections) = WEBSOCKET_CONNECTIONS.lock() {
+        connections.insert(
+            id,
+            ScriptWebSocket {
+                outgoing: out_tx,
+                incoming: Arc::new(tokio::sync::Mutex::new(in_rx)),
+            },
+        );
+    }
+    let snapshot = capture_current_script_stop_snapshot();
+    let session = AbortOnDrop(tauri::async_runtime::spawn(websocket_session(
+        id, request, snapshot, out_rx, in_tx, open_tx,
+    )));
+    RUN_WEBSOCKET_SESSIONS.with(|slot| {
+        let mut sessions = slot.borrow_mut();
+        // 已结束的会话会自行注销连接，这里顺带清理其句柄。
+        sessions.retain(|id, _| is_websocket_registered(*id));
+        sessions.insert(id, session);
+    });
+
+    ctx.enqueue_job(
+        NativeAsyncJob::new(async move |context| {
+            let opened = open_rx
+                .await
+                .unwrap_or_else(|_| Err("WebSocket 会话已结束".to_string()));
+            let context = &mut context.borrow_mut();
+            match opened {
+                Ok(protocol) => {
+                    let info = ObjectInitializer::new(context)
+                        .property(js_string!("id"), id as f64, Attribute::all())
+                        .property(
+                            js_string!("protocol"),
+                            js_string!(protocol),
+                            Attribute::all(),
+                        )
+                        .build();
+                    resolvers
+                        .resolve
+                        .call(&JsValue::undefined(), &[info.into()], context)
+                }
+                Err(message) => {
+                    let error = type_error_value(message, context);
+                    resolvers
+                        .reject
+                        .call(&JsValue::undefined(), &[error], context)
+                }
+            }
+        })
+        .into(),
+    );
+    Ok(promise.into())
+}
+
+/// 原生 WebSocket 发送：`wsSend(id, data)`，字符串按文本帧，ArrayBuffer 按二进制帧。
+fn _net_ws_send(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let id = read_websocket_id(args.get_or_undefined(0), ctx)?;
+    let data = args.get_or_undefined(1);
+    let payload = if data.is_string() {
+        WsPayload::Text(data.to_string(ctx)?.to_std_string_escaped())
+    } else {
+        WsPayload::Binary(read_body_bytes(data, ctx)?.unwrap_or_default())
+    };
+    let sent = WEBSOCKET_CONNECTIONS
+        .lock()
+        .ok()
+        .and_then(|connections| {
+            connections
+                .get(&id)
+                .map(|connection| connection.outgoing.send(WsOutgoing::Send(payload)).is_ok())
+        })
+        .unwrap_or(false);
+    if !sent {
+        return Err(JsNativeError::error()
+            .with_message("WebSocket 已关闭")
+            .into());
+    }
+    Ok(JsValue::undefined())
+}
+
+/// 原生 WebSocket 关闭：`wsClose(id, code, reason)`。
+fn _net_ws_close(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let id = read_websocket_id(args.get_or_undefined(0), ctx)?;
+    let code = match args.get_or_undefined(1) {
+        value if value.is_undefined() => 1000,
+        value => value.to_number(ctx)? as u16,
+    };
+    let reason = match args.get_or_undefined(2) {
+        value if value.is_undefined() => String::new(),
+        value => value.to_string(ctx)?.to_std_string_escaped(),
+    };
+    if let Ok(connections) = WEBSOCKET_CONNECTIONS.lock()
+        && let Some(connection) = connections.get(&id)
+    {
+        let _ = connection.outgoing.send(WsOutgoing::Close(code, reason));
+    }
+    Ok(JsValue::undefined())
+}
+
+/// 将 WebSocket 事件转换为 JS 对象。
+fn ws_event_to_js(event: WsIncoming, context: &mut Context) -> JsResult<JsObject> {
+    let obj = match event {
+        WsIncoming::Message(payload) => {
+            let data: JsValue = match payload {
+                WsPayload::Text(text) => js_string!(text).into(),
+                WsPayload::Binary(bytes) => JsArrayBuffer::from_byte_block(bytes, context)?.into(),
+            };
+            ObjectInitializer::new(context)
+                .property(js_string!("type"), js_string!("message"), Attribute::all())
+                .property(js_string!("data"), data, Attribute::all())
+                .build()
+        }
+        WsIncoming::Error(message) => ObjectInitializer::new(context)
+            .property(js_string!("type"), js_string!("error"), Attribute::all())
+            .property(js_string!("message"), js_string!(message), Attribute::all())
+            .build(),
+        WsIncoming::Close {
+            code,
+            reason,
+            was_clean,
+        } => ObjectInitializer::new(context)
+            .property(js_string!("type"), js_string!("close"), Attribute::all())
+            .property(js_string!("code"), i32::from(code), Attribute::all())
+            .property(js_string!("reason"), js_string!(reason), Attribute::all())
+            .property(js_string!("wasClean"), was_clean, Attribute::all())
+            .build(),
+    };
+    Ok(obj)
+}
+
+/// 原生 WebSocket 接收：`wsRecv(id)`，返回 Promise 解析为下一条事件。
+fn _net_ws_recv(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let id = read_websocket_id(args.get_or_undefined(0), ctx)?;
+    let receiver = WEBSOCKET_CONNECTIONS.lock().ok().and_then(|connections| {
+        connections
+            .get(&id)
+            .map(|connection| connection.incoming.clone())
+    });
+    let (promise, resolvers) = JsPromise::new_pending(ctx);
+    ctx.enqueue_job(
+        NativeAsyncJob::new(async move |context| {
+            let event = match receiver {
+                Some(receiver) => receiver.lock().await.recv().await,
+                None => None,
+            };
+            let event = event.unwrap_or(WsIncoming::Close {
+                code: WS_ABNORMAL_CLOSE_CODE,
+                reason: String::new(),
+                was_clean: false,
+            });
+            let context = &mut context.borrow_mut();
+            let value = ws_event_to_js(event, context)?;
+            resolvers
+                .resolve
+                .call(&JsValue::undefined(), &[value.into()], context)
+        })
+        .into(),
+    );
+    Ok(promise.into())
+}
+
+/// 注册脚本网络 API（`fetch`/`Headers`/`Request`/`Response`/`WebSocket`）。
+///
+/// 说明：原生部分只提供收发原语，标准形态的类由 `script_net.js` 在此基础上实现。
+pub fn register_network_api(context: &mut Context) -> JsResult<()> {
+    let native = ObjectInitializer::new(context)
+        .function(
+            NativeFunction::from_fn_ptr(_net_fetch),
+            js_string!("fetch"),
+            5,
+        )
+        .function(
+            NativeFunction::from_fn_ptr(_net_decode_utf8),
+            js_string!("decodeUtf8"),
+            1,
+        )
+        .function(
+            NativeFunction::from_fn_ptr(_net_encode_utf8),
+            js_string!("encodeUtf8"),
+            1,
+        )
+        .function(
+            NativeFunction::from_fn_ptr(_net_ws_connect),
+            js_string!("wsConnect"),
+            2,
+        )
+        .function(
+            NativeFunction::from_fn_ptr(_net_ws_send),
+            js_string!("wsSend"),
+            2,
+        )
+        .function(
+            NativeFunction::from_fn_ptr(_net_ws_close),
+            js_string!("wsClose"),
+            3,
+        )
+        .function(
+            NativeFunction::from_fn_ptr(_net_ws_recv),
+            js_string!("wsRecv"),
+            1,
+        )
+        .build();
+
+    let installer = context.eval(Source::from_bytes(include_str!("script_net.js")))?;
+    let installer = installer
+        .as_function()
+        .ok_or_else(|| JsNativeError::typ().with_message("script_net.js 必须返回安装函数"))?;
+    installer.call(&JsValue::undefined(), &[native.into()], context)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_permission_checks_policy_and_hosts() {
+        set_script_network_policy(ScriptNetworkPolicy {
+            enabled: false,
+            allowed_hosts: Vec::new(),
+        })
+        .unwrap();
+        assert!(check_network_permission("https://example.com/a", &["https"]).is_err());
+
+        set_script_network_policy(ScriptNetworkPolicy {
+            enabled: true,
+            allowed_hosts: vec!["example.com".to_string()],
+        })
+        .unwrap();
+        assert!(check_network_permission("https://api.example.com/a", &["https"]).is_ok());
+        assert!(check_network_permission("https://example.com.evil.io/", &["https"]).is_err());
+        assert!(check_network_permission("ftp://example.com/", &["https"]).is_err());
+
+        set_script_network_policy(ScriptNetworkPolicy::default()).unwrap();
+    }
+}
@@ -0,0 +1,572 @@
+use crate::submodules::script_builtin::register_builtin_functions;
+use crate::submodules::script_console::render_table;
+use boa_engine::builtins::promise::PromiseState;
+use boa_engine::native_function::NativeFunction;
+use boa_engine::object::FunctionObjectBuilder;
+use boa_engine::object::builtins::JsPromise;
+use boa_engine::property::PropertyKey;
+use boa_engine::{Context, JsObject, JsResult, JsString, JsValue, js_string};
+use boa_gc::{Finalize, Trace};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// 默认 JS 堆栈采样间隔（毫秒）。
+const DEFAULT_SAMPLE_INTERVAL_MS: u64 = 10;
+/// 运行日志目录中折叠栈文件的文件名。
+pub const PROFILE_FILE_NAME: &str = "profile.folded";
+/// 事件循环空闲（等待异步任务/定时器）时的栈帧名。
+pub(crate) const IDLE_FRAME: &str = "(idle)";
+/// 事件循环执行微任务/定时任务时的栈帧名。
+pub(crate) const JOBS_FRAME: &str = "(jobs)";
+
+thread_local! {
+    /// 当前执行线程绑定的脚本性能分析器。
+    static CURRENT_SCRIPT_PROFILER: RefCell<Option<Rc<ScriptProfiler>>> = const { RefCell::new(None) };
+}
+
+/// 脚本性能分析配置。
+#[derive(Debug, Clone)]
+pub struct ScriptProfileConfig {
+    /// JS 堆栈采样间隔。
+    pub sample_interval: Duration,
+    /// 折叠栈（collapsed stack）输出路径；为空时只输出控制台汇总。
+    pub output_path: Option<PathBuf>,
+}
+
+impl ScriptProfileConfig {
+    /// 使用默认采样间隔创建配置。
+    pub fn new(output_path: Option<PathBuf>) -> Self {
+        Self {
+            sample_interval: Duration::from_millis(DEFAULT_SAMPLE_INTERVAL_MS),
+            output_path,
+        }
+    }
+}
+
+/// 单个内置函数的调用统计。
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct BuiltinStats {
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl BuiltinStats {
+    fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+    }
+}
+
+/// 单次脚本运行的性能分析器。
+///
+/// 说明：
+/// - 内置函数统计调用次数、总耗时与最大耗时；返回 Promise 的异步内置函数按敲定（settle）时刻计时；
+/// - 解释器无法被外部线程打断，堆栈采样按定时节拍累计，在安全点（内置函数调用前后、事件循环每轮）
+///   读取当前 JS 堆栈，并以期间经过的节拍数作为权重。
+#[derive(Debug)]
+pub struct ScriptProfiler {
+    config: ScriptProfileConfig,
+    started_at: Instant,
+    last_sample: Cell<Instant>,
+    builtins: RefCell<HashMap<String, BuiltinStats>>,
+    stacks: RefCell<HashMap<String, u64>>,
+}
+
+impl ScriptProfiler {
+    /// 创建性能分析器，并以当前时刻作为起点。
+    pub fn new(config: ScriptProfileConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            config,
+            started_at: now,
+            last_sample: Cell::new(now),
+            builtins: RefCell::new(HashMap::new()),
+            stacks: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// 记录一次内置函数调用耗时。
+    fn record_builtin(&self, name: &str, elapsed: Duration) {
+        self.builtins
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_default()
+            .record(elapsed);
+    }
+
+    /// 取出自上次采样以来经过的完整节拍数。
+    fn take_ticks(&self, now: Instant) -> u64 {
+        let interval = self.config.sample_interval.max(Duration::from_micros(100));
+        let last = self.last_sample.get();
+        let ticks = (now.saturating_duration_since(last).as_nanos() / interval.as_nanos()) as u64;
+        if ticks > 0 {
+            self.last_sample.set(last + interval * ticks as u32);
+        }
+        ticks
+    }
+
+    /// 将节拍计入折叠栈。
+    fn record_stack(&self, frames: &[String], ticks: u64) {
+        if ticks == 0 || frames.is_empty() {
+            return;
+        }
+        *self
+            .stacks
+            .borrow_mut()
+            .entry(frames.join(";"))
+            .or_default() += ticks;
+    }
+
+    /// 在安全点采样当前 JS 堆栈，`leaf` 为附加在栈顶的帧（如内置函数名）。
+    fn sample(&self, context: &Context, leaf: Option<&str>) {
+        let ticks = self.take_ticks(Instant::now());
+        if ticks == 0 {
+            return;
+        }
+        let mut frames = js_stack_frames(context);
+        if let Some(leaf) = leaf {
+            frames.push(sanitize_frame(leaf));
+        }
+        self.record_stack(&frames, ticks);
+    }
+
+    /// 在事件循环中采样（此时没有 JS 帧在执行）。
+    fn sample_event_loop(&self, frame: &str) {
+        let ticks = self.take_ticks(Instant::now());
+        self.record_stack(&[frame.to_string()], ticks);
+    }
+
+    /// 按总耗时降序生成汇总表。
+    fn summary_table(&self) -> String {
+        let builtins = self.builtins.borrow();
+        let mut entries: Vec<(&String, &BuiltinStats)> = builtins.iter().collect();
+        entries.sort_by(|a, b| b.1.total.cmp(&a.1.total).then_with(|| a.0.cmp(b.0)));
+
+        let headers =
+            ["内置函数", "调用次数", "总耗时(ms)", "平均(ms)", "最大(ms)"].map(String::from);
+        let rows: Vec<Vec<String>> = entries
+            .into_iter()
+            .map(|(name, stats)| {
+                let total_ms = stats.total.as_secs_f64() * 1000.0;
+                vec![
+                    name.clone(),
+                    stats.count.to_string(),
+                    format!("{total_ms:.2}"),
+                    format!("{:.2}", total_ms / stats.count.max(1) as f64),
+                    format!("{:.2}", stats.max.as_secs_f64() * 1000.0),
+                ]
+            })
+            .collect();
+        render_table(&headers, &rows)
+    }
+
+    /// 生成折叠栈文本（每行 `frame1;frame2;... count`，可直接交给 flamegraph 工具）。
+    pub fn collapsed_stacks(&self) -> String {
+        let stacks = self.stacks.borrow();
+        let mut lines: Vec<(&String, &u64)> = stacks.iter().collect();
+        lines.sort();
+        let mut text = String::new();
+        for (stack, count) in lines {
+            let _ = writeln!(text, "{stack} {count}");
+        }
+        text
+    }
+
+    /// 结束分析：在控制台输出汇总表，并写出折叠栈文件。
+    pub fn finish(&self, context: &mut Context) -> Result<(), String> {
+        let total_samples: u64 = self.stacks.borrow().values().sum();
+        let mut report = format!(
+            "脚本性能分析：运行 {:.0} ms，采样 {total_samples} 次（间隔 {} ms）\n{}",
+            self.started_at.elapsed().as_secs_f64() * 1000.0,
+            self.config.sample_interval.as_millis(),
+            self.summary_table(),
+        );
+        // 折叠栈写入失败时仍输出控制台汇总，最后再返回错误。
+        let mut write_error = None;
+        if let Some(path) = &self.config.output_path {
+            match self.write_collapsed_stacks(path) {
+                Ok(()) => {
+                    let _ = write!(report, "\n折叠栈文件: {}", path.to_string_lossy());
+                }
+                Err(error) => write_error = Some(error),
+            }
+        }
+        log_to_console(&report, context).map_err(|e| format!("输出性能分析汇总失败: {e}"))?;
+        write_error.map_or(Ok(()), Err)
+    }
+
+    /// 将折叠栈写入文件。
+    fn write_collapsed_stacks(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建性能分析目录失败: {e}"))?;
+        }
+        fs::write(path, self.collapsed_stacks()).map_err(|e| format!("写入折叠栈文件失败: {e}"))
+    }
+}
+
+/// 绑定当前线程性能分析器的守卫，离开作用域时自动解绑。
+pub struct ScriptProfilerGuard {
+    previous: Option<Rc<ScriptProfiler>>,
+}
+
+impl ScriptProfilerGuard {
+    /// 将 `profiler` 设为当前线程的性能分析器。
+    pub fn enter(profiler: Rc<ScriptProfiler>) -> Self {
+        let previous = CURRENT_SCRIPT_PROFILER.with(|slot| slot.borrow_mut().replace(profiler));
+        Self { previous }
+    }
+}
+
+impl Drop for ScriptProfilerGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_SCRIPT_PROFILER.with(|slot| {
+            *slot.borrow_mut() = previous;
+        });
+    }
+}
+
+/// 读取当前线程绑定的性能分析器。
+fn current_profiler() -> Option<Rc<ScriptProfiler>> {
+    CURRENT_SCRIPT_PROFILER.with(|slot| slot.borrow().clone())
+}
+
+/// 事件循环每轮调用的采样点（未启用分析时为空操作）。
+pub(crate) fn sample_event_loop(frame: &str) {
+    if let Some(profiler) = current_profiler() {
+        profiler.sample_event_loop(frame);
+    }
+}
+
+/// 折叠栈格式以 `;` 分隔帧、以最后一个空格分隔计数，帧名中的分隔符需要替换。
+fn sanitize_frame(name: &str) -> String {
+    name.replace(';', ":").replace(['\n', '\r'], " ")
+}
+
+/// 读取当前 JS 调用栈（自底向上）。
+fn js_stack_frames(context: &Context) -> Vec<String> {
+    let mut frames: Vec<String> = context
+        .stack_trace()
+        .map(|frame| frame.code_block().name().to_std_string_escaped())
+        .map(|name| {
+            if name.is_empty() {
+                "(anonymous)".to_string()
+            } else {
+                sanitize_frame(&name)
+            }
+        })
+        .collect();
+    frames.reverse();
+    frames
+}
+
+/// 通过脚本的 `console.log` 输出文本（同时进入界面与文件日志）。
+fn log_to_console(text: &str, context: &mut Context) -> JsResult<()> {
+    let console = context
+        .global_object()
+        .get(js_string!("console"), context)?;
+    let Some(console) = console.as_object() else {
+        return Ok(());
+    };
+    let log = console.get(js_string!("log"), context)?;
+    if let Some(log) = log.as_callable() {
+        log.call(
+            &console.clone().into(),
+            &[JsValue::from(js_string!(text))],
+            context,
+        )?;
+    }
+    Ok(())
+}
+
+/// 被计时包装的内置函数。
+#[derive(Trace, Finalize)]
+struct ProfiledBuiltin {
+    target: JsObject,
+    #[unsafe_ignore_trace]
+    name: Rc<str>,
+}
+
+/// 异步内置函数的敲定回调捕获。
+#[derive(Trace, Finalize)]
+struct PendingBuiltin {
+    #[unsafe_ignore_trace]
+    name: Rc<str>,
+    #[unsafe_ignore_trace]
+    started_at: Instant,
+}
+
+/// 计时包装层：统计耗时并在调用前后采样堆栈。
+fn call_profiled_builtin(
+    this: &JsValue,
+    args: &[JsValue],
+    builtin: &ProfiledBuiltin,
+    context: &mut Context,
+) -> JsResult<JsValue> {
+    let Some(profiler) = current_profiler() else {
+        return builtin.target.call(this, args, context);
+    };
+    // 调用前的节拍属于 JS 代码本身，调用后的节拍属于内置函数。
+    profiler.sample(context, None);
+    let started_at = Instant::now();
+    let result = builtin.target.call(this, args, context);
+    profiler.sample(context, Some(&builtin.name));
+
+    let pending = result
+        .as_ref()
+        .ok()
+        .and_then(JsValue::as_object)
+        .and_then(|obj| JsPromise::from_object(obj.clone()).ok())
+        .filter(|promise| matches!(promise.state(), PromiseState::Pending));
+    match pending {
+        Some(promise) => {
+            let on_settled = NativeFunction::from_copy_closure_with_captures(
+                |_, _, pending: &PendingBuiltin, _| {
+                    if let Some(profiler) = current_profiler() {
+                        profiler.record_builtin(&pending.name, pending.started_at.elapsed());
+                    }
+                    Ok(JsValue::undefined())
+                },
+                PendingBuiltin {
+                    name: builtin.name.clone(),
+                    started_at,
+                },
+            )
+            .to_js_function(context.realm());
+            // 派生 Promise 仅用于计时，拒绝时回调正常返回，不会产生额外的未处理拒绝。
+            let _ = promise.then(Some(on_settled.clone()), Some(on_settled), context);
+        }
+        None => profiler.record_builtin(&builtin.name, started_at.elapsed()),
+    }
+    result
+}
+
+/// 命名空间对象（如 `cv.dnn`）的最大递归深度。
+const MAX_NAMESPACE_DEPTH: usize = 3;
+
+/// 读取全局对象的自有属性键。
+fn global_property_keys(context: &mut Context) -> JsResult<HashSet<PropertyKey>> {
+    let global = context.global_object();
+    Ok(global.own_property_keys(context)?.into_iter().collect())
+}
+
+/// 读取对象自有数据属性的值；访问器属性返回 `None`，避免以原型为 `this` 触发 getter。
+fn own_data_value(target: &JsObject, key: &PropertyKey) -> Option<JsValue> {
+    target
+        .borrow()
+        .properties()
+        .get(key)
+        .and_then(|descriptor| descriptor.value().cloned())
+}
+
+/// 构造包裹 `target` 的计时函数，`name` 同时作为汇总名与函数名。
+fn profiled_wrapper(target: &JsObject, name: Rc<str>, context: &mut Context) -> JsResult<JsObject> {
+    let length = target
+        .get(js_string!("length"), context)?
+        .to_length(context)? as usize;
+    let wrapper = NativeFunction::from_copy_closure_with_captures(
+        call_profiled_builtin,
+        ProfiledBuiltin {
+            target: target.clone(),
+            name: name.clone(),
+        },
+    );
+    Ok(FunctionObjectBuilder::new(context.realm(), wrapper)
+        .name(JsString::from(&*name))
+        .length(length)
+        .build()
+        .into())
+}
+
+/// 为 `holder[key]` 包裹计时层。
+///
+/// 规则：
+/// - 普通函数直接替换为计时包装；
+/// - 构造函数（原生类）包裹其静态方法（`Mat.xxx`）与原型方法（`Mat#xxx`），类本身保持不变；
+/// - 普通对象视为命名空间（如 `cv`、`cv.dnn`），递归处理其中的函数，最多 `MAX_NAMESPACE_DEPTH` 层；
+/// - 访问器属性与 `constructor` / `prototype` 键跳过，同一对象只处理一次。
+fn instrument_property(
+    holder: &JsObject,
+    key: PropertyKey,
+    name: &str,
+    depth: usize,
+    visited: &mut Vec<JsObject>,
+    context: &mut Context,
+) -> JsResult<usize> {
+    let Some(value) = own_data_value(holder, &key) else {
+        return Ok(0);
+    };
+    let Some(target) = value.as_object() else {
+        return Ok(0);
+    };
+    if target.is_constructor() {
+        let mut instrumented = instrument_members(&target, name, '.', depth, visited, context)?;
+        if let Some(prototype) = own_data_value(&target, &js_string!("prototype").into())
+            .as_ref()
+            .and_then(JsValue::as_object)
+        {
+            instrumented += instrument_members(&prototype, name, '#', depth, visited, context)?;
+        }
+        return Ok(instrumented);
+    }
+    if target.is_callable() {
+        let wrapper = profiled_wrapper(&target, Rc::from(name), context)?;
+        holder.set(key, wrapper, false, context)?;
+        return Ok(1);
+    }
+    if depth >= MAX_NAMESPACE_DEPTH {
+        return Ok(0);
+    }
+    instrument_members(&target, name, '.', depth + 1, visited, context)
+}
+
+/// 遍历对象的自有属性并逐个包裹计时层，成员名为 `{prefix}{separator}{key}`。
+fn instrument_members(
+    target: &JsObject,
+    prefix: &str,
+    separator: char,
+    depth: usize,
+    visited: &mut Vec<JsObject>,
+    context: &mut Context,
+) -> JsResult<usize> {
+    if visited.iter().any(|seen| JsObject::equals(seen, target)) {
+        return Ok(0);
+    }
+    visited.push(target.clone());
+    let mut instrumented = 0;
+    for key in target.own_property_keys(context)? {
+        if matches!(key, PropertyKey::Symbol(_)) {
+            continue;
+        }
+        let member = key.to_string();
+        if member == "constructor" || member == "prototype" {
+            continue;
+        }
+        let name = format!("{prefix}{separator}{member}");
+        instrumented += instrument_property(target, key, &name, depth, visited, context)?;
+    }
+    Ok(instrumented)
+}
+
+/// 为 `standard` 之外的全局内置函数、命名空间对象与原生类方法包裹计时层。
+///
+/// # 返回
+/// 返回被包裹的内置函数数量
+fn instrument_global_builtins(
+    context: &mut Context,
+    standard: &HashSet<PropertyKey>,
+) -> JsResult<usize> {
+    let global = context.global_object();
+    let mut visited = vec![global.clone()];
+    let mut instrumented = 0;
+    for key in global.own_property_keys(context)? {
+        if standard.contains(&key) || matches!(key, PropertyKey::Symbol(_)) {
+            continue;
+        }
+        let name = key.to_string();
+        instrumented += instrument_property(&global, key, &name, 0, &mut visited, context)?;
+    }
+    Ok(instrumented)
+}
+
+/// 注册脚本内置函数；`profiling` 为 true 时为全部内置函数（含 `cv.*` 命名空间与原生类方法）包裹计时层。
+///
+/// 说明：以全新 `Context` 的全局属性作为 ECMAScript 标准内置对象基线，基线之外的全局项都视为脚本内置。
+pub fn register_builtin_functions_with_profiling(
+    context: &mut Context,
+    profiling: bool,
+) -> JsResult<()> {
+    register_builtin_functions(context)?;
+    if profiling {
+        let standard = global_property_keys(&mut Context::default())?;
+        instrument_global_builtins(context, &standard)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ScriptProfileConfig, ScriptProfiler, global_property_keys, instrument_global_builtins,
+    };
+    use boa_engine::{Context, Source};
+    use std::time::Duration;
+
+    #[test]
+    fn ticks_accumulate_into_collapsed_stacks() {
+        let profiler = ScriptProfiler::new(ScriptProfileConfig {
+            sample_interval: Duration::from_millis(10),
+            output_path: None,
+        });
+        let start = profiler.last_sample.get();
+        assert_eq!(profiler.take_ticks(start + Duration::from_millis(9)), 0);
+        assert_eq!(profiler.take_ticks(start + Duration::from_millis(35)), 3);
+        // 余下的 5ms 保留到下一次采样。
+        assert_eq!(profiler.take_ticks(start + Duration::from_millis(40)), 1);
+
+        let frames = ["<main>".to_string(), "loop".to_string()];
+        profiler.record_stack(&frames, 3);
+        profiler.record_stack(&frames, 2);
+        profiler.record_stack(&["(idle)".to_string()], 4);
+        assert_eq!(
+            profiler.collapsed_stacks(),
+            "(idle) 4\n<main>;loop 5\n".to_string()
+        );
+    }
+
+    #[test]
+    fn summary_sorts_by_total_time() {
+        let profiler = ScriptProfiler::new(ScriptProfileConfig::new(None));
+        profiler.record_builtin("captureWindow", Duration::from_millis(4));
+        profiler.record_builtin("matchTemplate", Duration::from_millis(30));
+        profiler.record_builtin("matchTemplate", Duration::from_millis(10));
+
+        let table = profiler.summary_table();
+        let capture_row = table.find("captureWindow").unwrap();
+        let match_row = table.find("matchTemplate").unwrap();
+        assert!(match_row < capture_row);
+        assert!(table.contains("40.00"));
+        assert!(table.contains("20.00"));
+    }
+
+    #[test]
+    fn instruments_namespaces_and_class_methods() {
+        let mut context = Context::default();
+        let standard = global_property_keys(&mut Context::default()).unwrap();
+        context
+            .eval(Source::from_bytes(
+                "globalThis.top = (a, b) => a + b;\
+                 globalThis.ns = { inner: { twice(v) { return v * 2 } } };\
+                 globalThis.Thing = class Thing {\
+                     constructor() { this.v = 3 }\
+                     get value() { return this.v }\
+                     add(n) { return this.v + n }\
+                     static make() { return new Thing() }\
+                 };",
+            ))
+            .unwrap();
+        let instrumented = instrument_global_builtins(&mut context, &standard).unwrap();
+        assert_eq!(instrumented, 4);
+
+        let result = context
+            .eval(Source::from_bytes(
+                "[top.name, ns.inner.twice.name, Thing.prototype.add.name, Thing.make.name,\
+                  top(1, 2), ns.inner.twice(4), Thing.make().add(1), new Thing().value].join(',')",
+            ))
+            .unwrap();
+        assert_eq!(
+            result
+                .to_string(&mut context)
+                .unwrap()
+                .to_std_string_escaped(),
+            "top,ns.inner.twice,Thing#add,Thing.make,3,8,4,3"
+        );
+    }
+}
@@ -16,7 +16,14 @@ use std::{
 };
 use zip::{CompressionMethod, ZipArchive, ZipWriter, write::FileOptions};
 
-use crate::submodules::{color::rgb_to_hsl, color_match::rgb_to_bgr, tpl_match::match_template};
+use crate::submodules::{
+    color::{ColorDistance, rgb_to_hsl, rgb_to_lab},
+    color_match::{
+        bgr_to_lab_image, color_distance_mask, color_tolerance_mask, mean_color_distance,
+        rgb_to_bgr,
+    },
+    tpl_match::match_template,
+};
 
 pub type SiftLocateResult = (f64, f64, f64, f64, i32, i32, Vec<Point2f>);
 pub type SiftStitchResult = (Mat, f64, f64, f64, f64, i32, i32, Vec<Point2f>);
@@ -28,38 +35,38 @@ const ORB_MATCH_RATIO_THRESHOLD: f32 = 0.75;
 const ORB_MATCH_MIN_GOOD_COUNT: i32 = 4;
 
 /// 使用 ZIP(Deflate) 压缩字节数组。
-fn _compress_bytes_zip_deflate(raw: &[u8]) -> Result<Vec<u8>, String> {
+pub(crate) fn _compress_bytes_zip_deflate(raw: &[u8]) -> Result<Vec<u8>, String> {
     let cursor = Cursor::new(Vec::<u8>::new());
     let mut zip_writer = ZipWriter::new(cursor);
     let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
 
     zip_writer
         .start_file("d", options)
-        .map_err(|e| format!("ORB 特征 ZIP 创建失败: {e}"))?;
+        .map_err(|e| format!("特征 ZIP 创建失败: {e}"))?;
     zip_writer
         .write_all(raw)
-        .map_err(|e| format!("ORB 特征 ZIP 写入失败: {e}"))?;
+        .map_err(|e| format!("特征 ZIP 写入失败: {e}"))?;
 
     let cursor = zip_writer
         .finish()
-        .map_err(|e| format!("ORB 特征 ZIP 结束失败: {e}"))?;
+        .map_err(|e| format!("特征 ZIP 结束失败: {e}"))?;
     Ok(cursor.into_inner())
 }
 
 /// 使用 ZIP(Deflate) 解压字节数组。
-fn _decompress_bytes_zip_deflate(compressed: &[u8]) -> Result<Vec<u8>, String> {
+pub(crate) fn _decompress_bytes_zip_deflate(compressed: &[u8]) -> Result<Vec<u8>, String> {
     let cursor = Cursor::new(compressed);
-    let mut archive = ZipArchive::new(cursor).map_err(|e| format!("ORB 特征 ZIP 打开失败: {e}"))?;
+    let mut archive = ZipArchive::new(cursor).map_err(|e| format!("特征 ZIP 打开失败: {e}"))?;
     if archive.is_empty() {
-        return Err("ORB 特征 ZIP 文件为空".to_string());
+        return Err("特征 ZIP 文件为空".to_string());
     }
 
     let mut file = archive
         .by_index(0)
-        .map_err(|e| format!("ORB 特征 ZIP 读取条目失败: {e}"))?;
+        .map_err(|e| format!("特征 ZIP 读取条目失败: {e}"))?;
     let mut raw = Vec::<u8>::new();
     file.read_to_end(&mut raw)
-        .map_err(|e| format!("ORB 特征 ZIP 解压失败: {e}"))?;
+        .map_err(|e| format!("特征 ZIP 解压失败: {e}"))?;
     Ok(raw)
 }
 
@@ -90,7 +97,7 @@ fn _unpack_orb_feature_bytes(packed: &[u8]) -> Result<(i32, i32, &[u8]), String>
 }
 
 /// 将图像统一转换为灰度图，便于后续匹配计算。
-fn _to_gray_mat(mat: &Mat) -> Result<Mat, String> {
+pub(crate) fn _to_gray_mat(mat: &Mat) -> Result<Mat, String> {
     match mat.channels() {
         1 => Ok(mat.clone()),
         3 => {
@@ -110,7 +117,7 @@ fn _to_gray_mat(mat: &Mat) -> Result<Mat, String> {
 }
 
 /// 将图像统一转换为 BGR 三通道，便于绘制彩色标注。
-fn _to_bgr_mat(mat: &Mat) -> Result<Mat, String> {
+pub(crate) fn _to_bgr_mat(mat: &Mat) -> Result<Mat, String> {
     let mut bgr_mat = Mat::default();
     match mat.channels() {
         1 => imgproc::cvt_color(mat, &mut bgr_mat, imgproc::COLOR_GRAY2BGR, 0)
@@ -473,6 +480,16 @@ pub fn preprocess_minimap_for_sift_impl(
     Ok(out)
 }
 
+/// 以行切片形式读取单通道 8 位图像，避免逐像素访问（兼容 ROI 等非连续内存）。
+fn _gray_rows(mat: &Mat) -> Result<Vec<&[u8]>, String> {
+    (0..mat.rows())
+        .map(|y| {
+            mat.at_row::<u8>(y)
+                .map_err(|e| format!("读取第 {y} 行像素失败: {e}"))
+        })
+        .collect()
+}
+
 /// 选择“前景为白色”的二值图，便于后续按投影做字符分割。
 ///
 /// 说明：
@@ -531,48 +548,25 @@ pub fn segment_single_line_chars_impl(
 
     let gray = _to_gray_mat(mat)?;
     let binary = _to_text_foreground_binary(&gray)?;
-
-    let rows = binary.rows();
+    let rows = _gray_rows(&binary)?;
     let cols = binary.cols();
-    let mut row_projection = vec![0i32; rows as usize];
-    for y in 0..rows {
-        let mut count = 0i32;
-        for x in 0..cols {
-            let value = *binary
-                .at_2d::<u8>(y, x)
-                .map_err(|e| format!("读取像素失败[{x},{y}]: {e}"))?;
-            if value > 0 {
-                count += 1;
-            }
-        }
-        row_projection[y as usize] = count;
-    }
 
-    let line_top = row_projection
-        .iter()
-        .position(|&v| v > 0)
-        .map(|idx| idx as i32);
-    let line_bottom = row_projection
-        .iter()
-        .rposition(|&v| v > 0)
-        .map(|idx| idx as i32);
-    let (line_top, line_bottom) = match (line_top, line_bottom) {
-        (Some(top), Some(bottom)) if bottom >= top => (top, bottom),
-        _ => return Ok(Vec::new()),
+    // 行投影：按行切片查找首末个含前景像素的行，定位文本行的上下边界。
+    let row_has_foreground = |row: &&[u8]| row.iter().any(|&v| v > 0);
+    let (Some(line_top), Some(line_bottom)) = (
+        rows.iter().position(row_has_foreground),
+        rows.iter().rposition(row_has_foreground),
+    ) else {
+        return Ok(Vec::new());
     };
+    let line_rows = &rows[line_top..=line_bottom];
 
+    // 列投影：仅累计文本行范围内的像素。
     let mut col_projection = vec![0i32; cols as usize];
-    for x in 0..cols {
-        let mut count = 0i32;
-        for y in line_top..=line_bottom {
-            let value = *binary
-                .at_2d::<u8>(y, x)
-                .map_err(|e| format!("读取像素失败[{x},{y}]: {e}"))?;
-            if value > 0 {
-                count += 1;
-            }
+    for row in line_rows {
+        for (count, &value) in col_projection.iter_mut().zip(row.iter()) {
+            *count += (value > 0) as i32;
         }
-        col_projection[x as usize] = count;
     }
 
     // 先提取原始字符段（连续非空列），再按最小空隙宽度做合并。
@@ -617,39 +611,63 @@ pub fn segment_single_line_chars_impl(
             continue;
         }
 
-        let mut top = line_bottom;
-        let mut bottom = line_top;
-        let mut has_foreground = false;
-        for y in line_top..=line_bottom {
-            let mut row_has_foreground = false;
-            for x in start_x..=end_x {
-                let value = *binary
-                    .at_2d::<u8>(y, x)
-                    .map_err(|e| format!("读取像素失败[{x},{y}]: {e}"))?;
-                if value > 0 {
-                    row_has_foreground = true;
-                    has_foreground = true;
-                    break;
-                }
-            }
-            if row_has_foreground {
-                top = top.min(y);
-                bottom = bottom.max(y);
-            }
-        }
-
-        if !has_foreground || bottom < top {
+        let span = start_x as usize..=end_x as usize;
+        let has_foreground = |row: &&[u8]| row[span.clone()].iter().any(|&v| v > 0);
+        let (Some(top), Some(bottom)) = (
+            line_rows.iter().position(has_foreground),
+            line_rows.iter().rposition(has_foreground),
+        ) else {
             continue;
-        }
+        };
 
-        bboxes.push((start_x, top, width, bottom - top + 1));
+        bboxes.push((
+            start_x,
+            (line_top + top) as i32,
+            width,
+            (bottom - top + 1) as i32,
+        ));
     }
 
     Ok(bboxes)
 }
 
+/// 按颜色距离模式生成单色掩码。
+///
+/// 说明：
+/// - `Rgb` 模式下 `tolerance` 为逐通道容差（截断到 0-255）；
+/// - ΔE 模式下 `tolerance` 为最大色差，`lab` 为整图 Lab 缓存，首次使用时才转换。
+fn _color_mask_by_distance(
+    bgr_mat: &Mat,
+    lab: &mut Option<Mat>,
+    color: u32,
+    tolerance: f64,
+    distance: ColorDistance,
+) -> Result<Mat, String> {
+    if distance == ColorDistance::Rgb {
+        return color_tolerance_mask(
+            bgr_mat,
+            rgb_to_bgr(color),
+            tolerance.clamp(0.0, 255.0) as u8,
+        )
+        .map_err(|e| format!("inRange 失败: {e}"));
+    }
+    if lab.is_none() {
+        *lab = Some(bgr_to_lab_image(bgr_mat).map_err(|e| format!("BGR 转 Lab 失败: {e}"))?);
+    }
+    let lab = lab.as_ref().expect("Lab 图像已初始化");
+    color_distance_mask(lab, rgb_to_lab(color), tolerance, distance)
+        .map_err(|e| format!("色差掩码计算失败: {e}"))
+}
+
 /// 根据色键集合生成二值灰度图（命中为 255，未命中为 0）。
-pub fn color_filter_impl(mat: &Mat, colors: &[u32], tolerances: &[u8]) -> Result<Mat, String> {
+///
+/// 说明：`tolerances` 在 `Rgb` 模式下为逐通道容差，在 ΔE 模式下为最大色差。
+pub fn color_filter_impl(
+    mat: &Mat,
+    colors: &[u32],
+    tolerances: &[f64],
+    distance: ColorDistance,
+) -> Result<Mat, String> {
     if mat.rows() <= 0 || mat.cols() <= 0 {
         return Err("源图像尺寸无效".to_string());
     }
@@ -664,15 +682,7 @@ pub fn color_filter_impl(mat: &Mat, colors: &[u32], tolerances: &[u8]) -> Result
         ));
     }
 
-    let mut bgr_mat = Mat::default();
-    match mat.channels() {
-        1 => imgproc::cvt_color(mat, &mut bgr_mat, imgproc::COLOR_GRAY2BGR, 0)
-            .map_err(|e| format!("灰度图转 BGR 失败: {e}"))?,
-        3 => bgr_mat = mat.clone(),
-        4 => imgproc::cvt_color(mat, &mut bgr_mat, imgproc::COLOR_BGRA2BGR, 0)
-            .map_err(|e| format!("BGRA 转 BGR 失败: {e}"))?,
-        channels => return Err(format!("不支持的通道数: {channels}")),
-    }
+    let bgr_mat = _to_bgr_mat(mat)?;
 
     let mask_expr = Mat::zeros(bgr_mat.rows(), bgr_mat.cols(), CV_8UC1)
         .map_err(|e| format!("创建掩码失败: {e}"))?;
@@ -680,24 +690,10 @@ pub fn color_filter_impl(mat: &Mat, colors: &[u32], tolerances: &[u8]) -> Result
         .to_mat()
         .map_err(|e| format!("初始化掩码失败: {e}"))?;
 
+    let mut lab = None;
     for (color, tolerance) in colors.iter().zip(tolerances.iter()) {
-        let (b, g, r) = rgb_to_bgr(*color);
-        let lower = Scalar::new(
-            b.saturating_sub(*tolerance) as f64,
-            g.saturating_sub(*tolerance) as f64,
-            r.saturating_sub(*tolerance) as f64,
-            0.0,
-        );
-        let upper = Scalar::new(
-            b.saturating_add(*tolerance) as f64,
-            g.saturating_add(*tolerance) as f64,
-            r.saturating_add(*tolerance) as f64,
-            255.0,
-        );
-
-        let mut single_mask = Mat::default();
-        core::in_range(&bgr_mat, &lower, &upper, &mut single_mask)
-            .map_err(|e| format!("inRange 失败: {e}"))?;
+        let single_mask =
+            _color_mask_by_distance(&bgr_mat, &mut lab, *color, *tolerance, distance)?;
 
         let mut next_mask = Mat::default();
         core::bitwise_or(&merged_mask, &single_mask, &mut next_mask, &Mat::default())
@@ -747,19 +743,9 @@ pub fn color_filter_hsl_impl(
         return Err("weights 不能全为 0".to_string());
     }
 
-    let mut bgr_mat = Mat::default();
-    match mat.channels() {
-        1 => imgproc::cvt_color(mat, &mut bgr_mat, imgproc::COLOR_GRAY2BGR, 0)
-            .map_err(|e| format!("灰度图转 BGR 失败: {e}"))?,
-        3 => bgr_mat = mat.clone(),
-        4 => imgproc::cvt_color(mat, &mut bgr_mat, imgproc::COLOR_BGRA2BGR, 0)
-            .map_err(|e| format!("BGRA 转 BGR 失败: {e}"))?,
-        channels => return Err(format!("不支持的通道数: {channels}")),
-    }
-
-    let mask_expr = Mat::zeros(bgr_mat.rows(), bgr_mat.cols(), CV_8UC1)
-        .map_err(|e| format!("创建掩码失败: {e}"))?;
-    let mut merged_mask = mask_expr
+    let bgr_mat = _to_bgr_mat(mat)?;
+    let mut merged_mask = Mat::zeros(bgr_mat.rows(), bgr_mat.cols(), CV_8UC1)
+        .map_err(|e| format!("创建掩码失败: {e}"))?
         .to_mat()
         .map_err(|e| format!("初始化掩码失败: {e}"))?;
 
@@ -836,6 +822,12 @@ pub fn color_filter_hsl_impl(
         candidate_mask = next_mask;
     }
 
+    // 粗筛没有候选像素时无需逐像素精算。
+    if core::count_non_zero(&candidate_mask).map_err(|e| format!("统计候选像素失败: {e}"))? == 0
+    {
+        return Ok(merged_mask);
+    }
+
     let hls_bytes = hls_mat
         .data_bytes()
         .map_err(|e| format!("读取 HLS 数据失败: {e}"))?;
@@ -853,7 +845,13 @@ pub fn color_filter_hsl_impl(
         return Err("图像内存布局异常，无法执行 HSL 过滤".to_string());
     }
 
-    // 大图使用并行分块，显著降低逐像素 HSL 匹配耗时。
+    let matcher = HslMatcher {
+        targets: &target_hsl,
+        weights,
+        tolerance_scaled,
+    };
+
+    // 大图使用并行分块，显著降低逐像素 HSL 匹配耗时；各线程直接写入掩码的不相交切片。
     let worker_count = if pixel_count >= 200_000 {
         thread::available_parallelism()
             .map(|n| n.get())
@@ -864,85 +862,76 @@ pub fn color_filter_hsl_impl(
     };
 
     if worker_count <= 1 {
-        for idx in 0..pixel_count {
-            if candidate_bytes[idx] == 0 {
-                continue;
-            }
-            let base = idx * 3;
-            // OpenCV HLS 8U 通道顺序是 H, L, S；其中 H 范围 0-180，对应角度 0-360。
-            let h_deg = hls_bytes[base] as i32 * 2;
-            let l_u8 = hls_bytes[base + 1] as i32;
-            let s_u8 = hls_bytes[base + 2] as i32;
-
-            let mut is_match = false;
-            for (th, ts, tl) in &target_hsl {
-                // 采用整数缩放后的等价距离：
-                // dist_scaled = abs(h1-h2)*wh + abs(s1-s2)*ws + abs(l1-l2)*wl
-                let distance_scaled = (h_deg - *th).abs() as f64 * weight_h
-                    + (s_u8 - *ts).abs() as f64 * weight_s
-                    + (l_u8 - *tl).abs() as f64 * weight_l;
-                if distance_scaled <= tolerance_scaled {
-                    is_match = true;
-                    break;
-                }
-            }
-
-            if is_match {
-                mask_bytes[idx] = 255;
-            }
-        }
+        matcher.fill(
+            &hls_bytes[..pixel_count * 3],
+            &candidate_bytes[..pixel_count],
+            &mut mask_bytes[..pixel_count],
+        );
     } else {
-        let mut out = vec![0u8; pixel_count];
         let chunk_size = pixel_count.div_ceil(worker_count);
-        let target_hsl_ref = &target_hsl;
-
+        let matcher = &matcher;
         thread::scope(|scope| {
-            for (chunk_idx, out_chunk) in out.chunks_mut(chunk_size).enumerate() {
-                let start = chunk_idx * chunk_size;
-                let end = start + out_chunk.len();
-                let hls_chunk = &hls_bytes[start * 3..end * 3];
-                let candidate_chunk = &candidate_bytes[start..end];
-                scope.spawn(move || {
-                    for (local_idx, out_px) in out_chunk.iter_mut().enumerate() {
-                        if candidate_chunk[local_idx] == 0 {
-                            continue;
-                        }
-                        let base = local_idx * 3;
-                        let h_deg = hls_chunk[base] as i32 * 2;
-                        let l_u8 = hls_chunk[base + 1] as i32;
-                        let s_u8 = hls_chunk[base + 2] as i32;
-
-                        let mut is_match = false;
-                        for (th, ts, tl) in target_hsl_ref {
-                            let distance_scaled = (h_deg - *th).abs() as f64 * weight_h
-                                + (s_u8 - *ts).abs() as f64 * weight_s
-                                + (l_u8 - *tl).abs() as f64 * weight_l;
-                            if distance_scaled <= tolerance_scaled {
-                                is_match = true;
-                                break;
-                            }
-                        }
-
-                        if is_match {
-                            *out_px = 255;
-                        }
-                    }
-                });
+            for ((out_chunk, hls_chunk), candidate_chunk) in mask_bytes[..pixel_count]
+                .chunks_mut(chunk_size)
+                .zip(hls_bytes[..pixel_count * 3].chunks(chunk_size * 3))
+                .zip(candidate_bytes.chunks(chunk_size))
+            {
+                scope.spawn(move || matcher.fill(hls_chunk, candidate_chunk, out_chunk));
             }
         });
-
-        mask_bytes[..pixel_count].copy_from_slice(&out);
     }
 
     Ok(merged_mask)
 }
 
+/// HSL 加权差精算器，供 `color_filter_hsl_impl` 串行或分块并行调用。
+struct HslMatcher<'a> {
+    /// 目标颜色 `(h 角度, s, l)`，s/l 已缩放到 0-255
+    targets: &'a [(i32, i32, i32)],
+    /// 权重 `[wh, ws, wl]`
+    weights: [f64; 3],
+    /// 容差 * 255
+    tolerance_scaled: f64,
+}
+
+impl HslMatcher<'_> {
+    /// 对候选像素逐一精算，命中时在 `out` 对应位置写入 255。
+    ///
+    /// 说明：`hls` 为 OpenCV HLS 8U 像素（H, L, S；H 范围 0-180，对应角度 0-360），
+    /// 长度须为 `out.len() * 3`。
+    fn fill(&self, hls: &[u8], candidates: &[u8], out: &mut [u8]) {
+        let [weight_h, weight_s, weight_l] = self.weights;
+        for ((pixel, &candidate), out_px) in hls.chunks_exact(3).zip(candidates).zip(out) {
+            if candidate == 0 {
+                continue;
+            }
+            let h_deg = pixel[0] as i32 * 2;
+            let l_u8 = pixel[1] as i32;
+            let s_u8 = pixel[2] as i32;
+            // 采用整数缩放后的等价距离：
+            // dist_scaled = abs(h1-h2)*wh + abs(s1-s2)*ws + abs(l1-l2)*wl
+            let is_match = self.targets.iter().any(|(th, ts, tl)| {
+                (h_deg - *th).abs() as f64 * weight_h
+                    + (s_u8 - *ts).abs() as f64 * weight_s
+                    + (l_u8 - *tl).abs() as f64 * weight_l
+                    <= self.tolerance_scaled
+            });
+            if is_match {
+                *out_px = 255;
+            }
+        }
+    }
+}
+
 /// 色键匹配，返回匹配像素均值最高的颜色索引；若无命中或低于阈值则返回 -1。
+///
+/// 说明：`tolerances` 在 `Rgb` 模式下为逐通道容差，在 ΔE 模式下为最大色差。
 pub fn color_key_match_impl(
     mat: &Mat,
     colors: &[u32],
     min_mean: f64,
-    tolerances: &[u8],
+    tolerances: &[f64],
+    distance: ColorDistance,
 ) -> Result<i32, String> {
     if mat.rows() <= 0 || mat.cols() <= 0 {
         return Err("源图像尺寸无效".to_string());
@@ -958,37 +947,14 @@ pub fn color_key_match_impl(
         ));
     }
 
-    let mut bgr_mat = Mat::default();
-    match mat.channels() {
-        1 => imgproc::cvt_color(mat, &mut bgr_mat, imgproc::COLOR_GRAY2BGR, 0)
-            .map_err(|e| format!("灰度图转 BGR 失败: {e}"))?,
-        3 => bgr_mat = mat.clone(),
-        4 => imgproc::cvt_color(mat, &mut bgr_mat, imgproc::COLOR_BGRA2BGR, 0)
-            .map_err(|e| format!("BGRA 转 BGR 失败: {e}"))?,
-        channels => return Err(format!("不支持的通道数: {channels}")),
-    }
+    let bgr_mat = _to_bgr_mat(mat)?;
 
     let mut best_index = -1i32;
     let mut best_mean = 0.0f64;
-    let mut mask = Mat::default();
+    let mut lab = None;
 
     for (index, (color, tolerance)) in colors.iter().zip(tolerances.iter()).enumerate() {
-        let (b, g, r) = rgb_to_bgr(*color);
-        let lower = Scalar::new(
-            b.saturating_sub(*tolerance) as f64,
-            g.saturating_sub(*tolerance) as f64,
-            r.saturating_sub(*tolerance) as f64,
-            0.0,
-        );
-        let upper = Scalar::new(
-            b.saturating_add(*tolerance) as f64,
-            g.saturating_add(*tolerance) as f64,
-            r.saturating_add(*tolerance) as f64,
-            255.0,
-        );
-
-        core::in_range(&bgr_mat, &lower, &upper, &mut mask)
-            .map_err(|e| format!("inRange 失败: {e}"))?;
+        let mask = _color_mask_by_distance(&bgr_mat, &mut lab, *color, *tolerance, distance)?;
         let mean =
             core::mean(&mask, &Mat::default()).map_err(|e| format!("计算 mean 失败: {e}"))?[0];
 
@@ -1005,11 +971,18 @@ pub fn color_key_match_impl(
     Ok(best_index)
 }
 
-/// 并行匹配多个模板，返回首个命中的模板索引和坐标。
+/// 并行匹配多个模板，并可按色差校验命中区域，返回首个命中的模板索引和坐标。
+///
+/// 说明：
+/// - 模板位置仍由灰度模板匹配确定；
+/// - `distance` 为 ΔE 模式时，命中区域与模板的平均色差须不超过 `max_delta`，否则视为未命中；
+/// - `distance` 为 `Rgb` 时不做颜色校验。
 pub fn batch_match_color_impl(
     src: &Mat,
     tpls: Vec<Mat>,
     cap: f64,
+    distance: ColorDistance,
+    max_delta: f64,
 ) -> Result<Option<(usize, i32, i32)>, String> {
     if src.rows() <= 0 || src.cols() <= 0 {
         return Err("源图像尺寸无效".to_string());
@@ -1018,7 +991,16 @@ pub fn batch_match_color_impl(
         return Ok(None);
     }
 
+    let to_lab = |mat: &Mat| -> Result<Mat, String> {
+        bgr_to_lab_image(&_to_bgr_mat(mat)?).map_err(|e| format!("BGR 转 Lab 失败: {e}"))
+    };
+    let check_color = distance != ColorDistance::Rgb;
     let src_gray = _to_gray_mat(src)?;
+    let src_lab = if check_color {
+        Some(to_lab(src)?)
+    } else {
+        None
+    };
     let mut gray_templates = Vec::with_capacity(tpls.len());
     for (idx, tpl) in tpls.into_iter().enumerate() {
         if tpl.rows() <= 0 || tpl.cols() <= 0 {
@@ -1028,7 +1010,12 @@ pub fn batch_match_color_impl(
         if src_gray.rows() < tpl_gray.rows() || src_gray.cols() < tpl_gray.cols() {
             continue;
         }
-        gray_templates.push((idx, tpl_gray));
+        let tpl_lab = if check_color {
+            Some(to_lab(&tpl)?)
+        } else {
+            None
+        };
+        gray_templates.push((idx, tpl_gray, tpl_lab));
     }
 
     if gray_templates.is_empty() {
@@ -1059,19 +1046,35 @@ pub fn batch_match_color_impl(
                 continue;
             }
 
-            let src_gray = src_gray.clone();
+            // 各线程共享同一份源图像，避免大图按线程数重复深拷贝。
+            let src_gray = &src_gray;
+            let src_lab = src_lab.as_ref();
             let first_match_index = &first_match_index;
             handles.push(
                 scope.spawn(move || -> Result<Option<(usize, i32, i32)>, String> {
                     let mut local_best: Option<(usize, i32, i32)> = None;
 
-                    for (idx, tpl_gray) in chunk {
+                    for (idx, tpl_gray, tpl_lab) in chunk {
                         if idx >= first_match_index.load(Ordering::Acquire) {
                             break;
                         }
 
-                        match match_template(&src_gray, &tpl_gray, cap) {
+                        match match_template(src_gray, &tpl_gray, cap) {
                             Ok(Some((x, y))) => {
+                                if let (Some(src_lab), Some(tpl_lab)) = (src_lab, &tpl_lab) {
+                                    let rect =
+                                        core::Rect::new(x, y, tpl_lab.cols(), tpl_lab.rows());
+                                    // ROI 视图由 mean_color_distance 内部拷贝为连续内存，这里无需再克隆。
+                                    let region = Mat::roi(src_lab, rect).map_err(|e| {
+                                        format!("模板[{idx}] 裁剪命中区域失败: {e}")
+                                    })?;
+                                    let delta = mean_color_distance(&region, tpl_lab, distance)
+                                        .map_err(|e| format!("模板[{idx}] 色差计算失败: {e}"))?;
+                                    if delta > max_delta {
+                                        continue;
+                                    }
+                                }
+
                                 if local_best.map_or(true, |(best_idx, _, _)| idx < best_idx) {
                                     local_best = Some((idx, x, y));
                                 }
@@ -1172,7 +1175,7 @@ pub fn hamming_distance_hex(a: &str, b: &str) -> Result<u32, String> {
 }
 
 /// 计算单通道图像的 64 位感知哈希（pHash）。
-fn _phash_gray_64(gray: &Mat) -> Result<u64, String> {
+pub(crate) fn _phash_gray_64(gray: &Mat) -> Result<u64, String> {
     let mut resized = Mat::default();
     imgproc::resize(
         gray,
@@ -1224,8 +1227,113 @@ fn _phash_gray_64(gray: &Mat) -> Result<u64, String> {
     Ok(hash)
 }
 
-/// 计算图像感知哈希，支持灰度/彩色模式。
-pub fn perceptual_hash_impl(mat: &Mat, color: bool) -> Result<String, String> {
+/// 计算单通道图像的 64 位均值哈希（aHash）：8x8 区域均值与整体均值比较。
+fn _ahash_gray_64(gray: &Mat) -> Result<u64, String> {
+    let pixels = _resize_gray_pixels(gray, 8, 8)?;
+    let mean = pixels.iter().map(|&v| v as u32).sum::<u32>() as f64 / pixels.len() as f64;
+    Ok(_pack_bits_64(pixels.iter().map(|&v| v as f64 > mean)))
+}
+
+/// 计算单通道图像的 64 位差异哈希（dHash）：9x8 缩略图中每行相邻像素的亮度梯度。
+fn _dhash_gray_64(gray: &Mat) -> Result<u64, String> {
+    let pixels = _resize_gray_pixels(gray, 9, 8)?;
+    Ok(_pack_bits_64(pixels.chunks_exact(9).flat_map(|row| {
+        row.windows(2).map(|pair| pair[1] > pair[0])
+    })))
+}
+
+/// 计算单通道图像的 256 位块均值哈希（block-mean）：16x16 区域均值与中位数比较。
+fn _block_mean_gray_256(gray: &Mat) -> Result<[u64; 4], String> {
+    let pixels = _resize_gray_pixels(gray, 16, 16)?;
+    let mut sorted = pixels.clone();
+    sorted.sort_unstable();
+    let median = sorted[sorted.len() / 2];
+    let mut words = [0u64; 4];
+    for (word, chunk) in words.iter_mut().zip(pixels.chunks_exact(64)) {
+        *word = _pack_bits_64(chunk.iter().map(|&v| v > median));
+    }
+    Ok(words)
+}
+
+/// 将灰度图按区域均值缩放为 `width x height`，返回行优先的像素数组。
+fn _resize_gray_pixels(gray: &Mat, width: i32, height: i32) -> Result<Vec<u8>, String> {
+    let mut resized = Mat::default();
+    imgproc::resize(
+        gray,
+        &mut resized,
+        Size::new(width, height),
+        0.0,
+        0.0,
+        imgproc::INTER_AREA,
+    )
+    .map_err(|e| format!("resize 失败: {e}"))?;
+    resized
+        .data_bytes()
+        .map(<[u8]>::to_vec)
+        .map_err(|e| format!("读取缩略图像素失败: {e}"))
+}
+
+/// 按顺序将布尔位写入 u64（第 i 位对应第 i 个元素），与 pHash 的位布局一致。
+fn _pack_bits_64(bits: impl Iterator<Item = bool>) -> u64 {
+    bits.take(64)
+        .enumerate()
+        .fold(0u64, |hash, (idx, bit)| hash | ((bit as u64) << idx))
+}
+
+/// 感知哈希算法类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageHashKind {
+    /// DCT 感知哈希（64 位），对缩放与轻微模糊稳定
+    #[default]
+    PHash,
+    /// 均值哈希（64 位），最快，适合纯色块图标
+    AHash,
+    /// 差异哈希（64 位），对整体亮度变化稳定
+    DHash,
+    /// 块均值哈希（256 位），对细节更敏感，适合相似图标区分
+    BlockMean,
+}
+
+impl ImageHashKind {
+    /// 解析算法名称：`phash` / `ahash` / `dhash` / `blockmean`（`block_mean` / `block-mean`），不区分大小写
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "phash" => Some(Self::PHash),
+            "ahash" => Some(Self::AHash),
+            "dhash" => Some(Self::DHash),
+            "blockmean" | "block_mean" | "block-mean" => Some(Self::BlockMean),
+            _ => None,
+        }
+    }
+
+    /// 算法名称（与 `from_name` 互逆）
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PHash => "phash",
+            Self::AHash => "ahash",
+            Self::DHash => "dhash",
+            Self::BlockMean => "blockmean",
+        }
+    }
+
+    /// 单通道哈希的十六进制字符串
+    fn hash_gray_hex(self, gray: &Mat) -> Result<String, String> {
+        Ok(match self {
+            Self::PHash => format!("{:016x}", _phash_gray_64(gray)?),
+            Self::AHash => format!("{:016x}", _ahash_gray_64(gray)?),
+            Self::DHash => format!("{:016x}", _dhash_gray_64(gray)?),
+            Self::BlockMean => _block_mean_gray_256(gray)?
+                .iter()
+                .map(|word| format!("{word:016x}"))
+                .collect(),
+        })
+    }
+}
+
+/// 计算图像感知哈希，支持灰度/彩色模式与多种哈希算法。
+///
+/// 说明：彩色模式按 B、G、R 通道分别计算后拼接。
+pub fn perceptual_hash_impl(mat: &Mat, color: bool, kind: ImageHashKind) -> Result<String, String> {
     if mat.rows() <= 0 || mat.cols() <= 0 {
         return Err("源图像尺寸无效".to_string());
     }
@@ -1241,19 +1349,17 @@ pub fn perceptual_hash_impl(mat: &Mat, color: bool) -> Result<String, String> {
             channels => return Err(format!("不支持的通道数: {channels}")),
         }
 
-        let mut parts = String::with_capacity(16 * 3);
+        let mut parts = String::with_capacity(64 * 3);
         for channel in 0..3 {
             let mut channel_mat = Mat::default();
             core::extract_channel(&bgr, &mut channel_mat, channel)
                 .map_err(|e| format!("提取通道[{channel}]失败: {e}"))?;
-            let channel_hash = _phash_gray_64(&channel_mat)?;
-            parts.push_str(&format!("{channel_hash:016x}"));
+            parts.push_str(&kind.hash_gray_hex(&channel_mat)?);
         }
         Ok(parts)
     } else {
         let gray = _to_gray_mat(mat)?;
-        let hash = _phash_gray_64(&gray)?;
-        Ok(format!("{hash:016x}"))
+        kind.hash_gray_hex(&gray)
     }
 }
 
@@ -1354,13 +1460,8 @@ fn _encode_orb_descriptors(descriptors: &Mat) -> Result<String, String> {
     let mut raw = Vec::<u8>::new();
     if rows > 0 {
         raw.reserve((rows as usize) * (ORB_DESCRIPTOR_COLS as usize));
-        for row in 0..rows {
-            for col in 0..ORB_DESCRIPTOR_COLS {
-                let value = *descriptors
-                    .at_2d::<u8>(row, col)
-                    .map_err(|e| format!("读取 ORB 描述子失败[{row},{col}]: {e}"))?;
-                raw.push(value);
-            }
+        for row in _gray_rows(descriptors)? {
+            raw.extend_from_slice(row);
         }
     }
 
@@ -1695,44 +1796,23 @@ pub fn sift_locate_impl(img1: &Mat, img2: &Mat) -> Result<Option<SiftLocateResul
     )))
 }
 
-/// 使用 SIFT + 单应性将 patch 拼接到 base，必要时自动扩展画布。
-///
-/// # 参数
-/// - `base`: 当前大图（作为 train）
-/// - `patch`: 新帧小图（作为 query）
-/// - `min_good_matches`: 最小优质匹配数量，<=0 表示不限制
-/// - `min_inliers`: 最小内点数量，<=0 表示不限制
-/// - `ransac_reproj_threshold`: RANSAC 重投影阈值（像素，非有限值时回退为 3.0）
+/// SIFT 匹配 patch 与 base 并用 RANSAC 估计 patch → base 的单应矩阵。
 ///
 /// # 返回
-/// - `None`: 匹配不足，无法可靠拼接
-/// - `Some`: 返回拼接后图像、bbox、匹配统计与变换角点
-pub fn sift_stitch_impl(
-    base: &Mat,
-    patch: &Mat,
-    min_good_matches: i32,
-    min_inliers: i32,
-    ransac_reproj_threshold: f64,
-) -> Result<Option<SiftStitchResult>, String> {
-    if base.rows() <= 0 || base.cols() <= 0 {
-        return Err("base 图像尺寸无效".to_string());
-    }
-    if patch.rows() <= 0 || patch.cols() <= 0 {
-        return Err("patch 图像尺寸无效".to_string());
-    }
-
-    let base_bgr = _to_bgr_mat(base)?;
-    let patch_bgr = _to_bgr_mat(patch)?;
-    let gray_base = _to_gray_mat(&base_bgr)?;
-    let gray_patch = _to_gray_mat(&patch_bgr)?;
-
+/// - `None`: 特征或优质匹配不足（少于 4 对），或单应矩阵估计失败
+/// - `Some((homography, good_matches, inliers))`: 3x3 单应矩阵（CV_64F）与匹配统计
+pub(crate) fn sift_patch_homography(
+    gray_base: &Mat,
+    gray_patch: &Mat,
+    ransac_threshold: f64,
+) -> Result<Option<(Mat, i32, i32)>, String> {
     let mut sift =
         features2d::SIFT::create_def().map_err(|e| format!("创建 SIFT 检测器失败: {e}"))?;
 
     let mut keypoints_base = core::Vector::<core::KeyPoint>::new();
     let mut descriptors_base = Mat::default();
     sift.detect_and_compute(
-        &gray_base,
+        gray_base,
         &Mat::default(),
         &mut keypoints_base,
         &mut descriptors_base,
@@ -1743,7 +1823,7 @@ pub fn sift_stitch_impl(
     let mut keypoints_patch = core::Vector::<core::KeyPoint>::new();
     let mut descriptors_patch = Mat::default();
     sift.detect_and_compute(
-        &gray_patch,
+        gray_patch,
         &Mat::default(),
         &mut keypoints_patch,
         &mut descriptors_patch,
@@ -1802,11 +1882,6 @@ pub fn sift_stitch_impl(
     }
 
     let mut inlier_mask = Mat::default();
-    let ransac_threshold = if ransac_reproj_threshold.is_finite() {
-        ransac_reproj_threshold.max(0.5)
-    } else {
-        3.0
-    };
     let homography = calib3d::find_homography(
         &src_points,
         &dst_points,
@@ -1821,6 +1896,117 @@ pub fn sift_stitch_impl(
 
     let inliers =
         core::count_non_zero(&inlier_mask).map_err(|e| format!("统计内点数量失败: {e}"))?;
+    Ok(Some((homography, good_matches, inliers)))
+}
+
+/// 将 patch 按单应矩阵透视变换后覆盖到画布，返回新画布。
+///
+/// 说明：仅覆盖 patch 灰度非零区域，避免 patch 背景黑边污染画布已有内容。
+pub(crate) fn overlay_warped_patch(
+    canvas: &Mat,
+    patch_bgr: &Mat,
+    gray_patch: &Mat,
+    homography: &Mat,
+) -> Result<Mat, String> {
+    let size = canvas
+        .size()
+        .map_err(|e| format!("读取画布尺寸失败: {e}"))?;
+    let mut warped_patch = Mat::zeros(size.height, size.width, canvas.typ())
+        .map_err(|e| format!("创建变换图失败: {e}"))?
+        .to_mat()
+        .map_err(|e| format!("初始化变换图失败: {e}"))?;
+    imgproc::warp_perspective(
+        patch_bgr,
+        &mut warped_patch,
+        homography,
+        size,
+        imgproc::INTER_LINEAR,
+        core::BORDER_CONSTANT,
+        Scalar::all(0.0),
+    )
+    .map_err(|e| format!("patch 透视变换失败: {e}"))?;
+
+    // 仅将 patch 的非零区域覆盖到画布，避免背景黑边污染已有大图。
+    let mut patch_mask = Mat::default();
+    imgproc::threshold(
+        gray_patch,
+        &mut patch_mask,
+        1.0,
+        255.0,
+        imgproc::THRESH_BINARY,
+    )
+    .map_err(|e| format!("构建 patch 有效区域掩码失败: {e}"))?;
+    let mut warped_mask = Mat::zeros(size.height, size.width, CV_8UC1)
+        .map_err(|e| format!("创建变换掩码失败: {e}"))?
+        .to_mat()
+        .map_err(|e| format!("初始化变换掩码失败: {e}"))?;
+    imgproc::warp_perspective(
+        &patch_mask,
+        &mut warped_mask,
+        homography,
+        size,
+        imgproc::INTER_NEAREST,
+        core::BORDER_CONSTANT,
+        Scalar::all(0.0),
+    )
+    .map_err(|e| format!("patch 掩码透视变换失败: {e}"))?;
+
+    let mut foreground = Mat::default();
+    core::bitwise_and(&warped_patch, &warped_patch, &mut foreground, &warped_mask)
+        .map_err(|e| format!("提取前景失败: {e}"))?;
+    let mut inverse_mask = Mat::default();
+    core::bitwise_not(&warped_mask, &mut inverse_mask, &Mat::default())
+        .map_err(|e| format!("反转掩码失败: {e}"))?;
+    let mut background = Mat::default();
+    core::bitwise_and(canvas, canvas, &mut background, &inverse_mask)
+        .map_err(|e| format!("提取背景失败: {e}"))?;
+    let mut merged = Mat::default();
+    core::add(&background, &foreground, &mut merged, &Mat::default(), -1)
+        .map_err(|e| format!("融合拼接图失败: {e}"))?;
+    Ok(merged)
+}
+
+/// 使用 SIFT + 单应性将 patch 拼接到 base，必要时自动扩展画布。
+///
+/// # 参数
+/// - `base`: 当前大图（作为 train）
+/// - `patch`: 新帧小图（作为 query）
+/// - `min_good_matches`: 最小优质匹配数量，<=0 表示不限制
+/// - `min_inliers`: 最小内点数量，<=0 表示不限制
+/// - `ransac_reproj_threshold`: RANSAC 重投影阈值（像素，非有限值时回退为 3.0）
+///
+/// # 返回
+/// - `None`: 匹配不足，无法可靠拼接
+/// - `Some`: 返回拼接后图像、bbox、匹配统计与变换角点
+pub fn sift_stitch_impl(
+    base: &Mat,
+    patch: &Mat,
+    min_good_matches: i32,
+    min_inliers: i32,
+    ransac_reproj_threshold: f64,
+) -> Result<Option<SiftStitchResult>, String> {
+    if base.rows() <= 0 || base.cols() <= 0 {
+        return Err("base 图像尺寸无效".to_string());
+    }
+    if patch.rows() <= 0 || patch.cols() <= 0 {
+        return Err("patch 图像尺寸无效".to_string());
+    }
+
+    let base_bgr = _to_bgr_mat(base)?;
+    let patch_bgr = _to_bgr_mat(patch)?;
+    let gray_base = _to_gray_mat(&base_bgr)?;
+    let gray_patch = _to_gray_mat(&patch_bgr)?;
+
+    let ransac_threshold = if ransac_reproj_threshold.is_finite() {
+        ransac_reproj_threshold.max(0.5)
+    } else {
+        3.0
+    };
+    let Some((homography, good_matches, inliers)) =
+        sift_patch_homography(&gray_base, &gray_patch, ransac_threshold)?
+    else {
+        return Ok(None);
+    };
     if min_good_matches > 0 && good_matches < min_good_matches {
         return Ok(None);
     }
@@ -1921,58 +2107,7 @@ pub fn sift_stitch_impl(
     )
     .map_err(|e| format!("计算画布单应矩阵失败: {e}"))?;
 
-    let mut warped_patch = Mat::zeros(canvas_h, canvas_w, base_bgr.typ())
-        .map_err(|e| format!("创建变换图失败: {e}"))?
-        .to_mat()
-        .map_err(|e| format!("初始化变换图失败: {e}"))?;
-    imgproc::warp_perspective(
-        &patch_bgr,
-        &mut warped_patch,
-        &homography_on_canvas,
-        Size::new(canvas_w, canvas_h),
-        imgproc::INTER_LINEAR,
-        core::BORDER_CONSTANT,
-        Scalar::all(0.0),
-    )
-    .map_err(|e| format!("patch 透视变换失败: {e}"))?;
-
-    // 仅将 patch 的非零区域覆盖到画布，避免背景黑边污染已有大图。
-    let mut patch_mask = Mat::default();
-    imgproc::threshold(
-        &gray_patch,
-        &mut patch_mask,
-        1.0,
-        255.0,
-        imgproc::THRESH_BINARY,
-    )
-    .map_err(|e| format!("构建 patch 有效区域掩码失败: {e}"))?;
-    let mut warped_mask = Mat::zeros(canvas_h, canvas_w, CV_8UC1)
-        .map_err(|e| format!("创建变换掩码失败: {e}"))?
-        .to_mat()
-        .map_err(|e| format!("初始化变换掩码失败: {e}"))?;
-    imgproc::warp_perspective(
-        &patch_mask,
-        &mut warped_mask,
-        &homography_on_canvas,
-        Size::new(canvas_w, canvas_h),
-        imgproc::INTER_NEAREST,
-        core::BORDER_CONSTANT,
-        Scalar::all(0.0),
-    )
-    .map_err(|e| format!("patch 掩码透视变换失败: {e}"))?;
-
-    let mut foreground = Mat::default();
-    core::bitwise_and(&warped_patch, &warped_patch, &mut foreground, &warped_mask)
-        .map_err(|e| format!("提取前景失败: {e}"))?;
-    let mut inverse_mask = Mat::default();
-    core::bitwise_not(&warped_mask, &mut inverse_mask, &Mat::default())
-        .map_err(|e| format!("反转掩码失败: {e}"))?;
-    let mut background = Mat::default();
-    core::bitwise_and(&stitched, &stitched, &mut background, &inverse_mask)
-        .map_err(|e| format!("提取背景失败: {e}"))?;
-    let mut merged = Mat::default();
-    core::add(&background, &foreground, &mut merged, &Mat::default(), -1)
-        .map_err(|e| format!("融合拼接图失败: {e}"))?;
+    let merged = overlay_warped_patch(&stitched, &patch_bgr, &gray_patch, &homography_on_canvas)?;
 
     let mut transformed_on_canvas = core::Vector::<Point2f>::new();
     core::perspective_transform(
@@ -2343,11 +2478,128 @@ pub fn draw_bboxes_impl(
 
 #[cfg(test)]
 mod tests {
-    use super::color_filter_impl;
+    use super::{BBoxResult, color_filter_impl};
+    use crate::submodules::color::{ColorDistance, rgb_to_hsl};
+    use crate::submodules::test_support::{Lcg64, random_bgr};
     use opencv::{
-        core::{self, CV_8UC3, Mat, Scalar},
+        core::{self, CV_8UC1, CV_8UC3, Mat, Rect, Scalar},
+        imgproc,
         prelude::{MatTrait, MatTraitConst},
     };
+    use std::time::Instant;
+
+    /// 生成伪随机的“单行文字”图像：深色背景上散布宽窄不一的亮色竖条块。
+    fn random_text_line(cols: i32, rows: i32, seed: u64) -> Mat {
+        let mut rng = Lcg64::new(seed);
+        let mut next = |max: i32| rng.below(max.max(1));
+        let mut img = Mat::new_rows_cols_with_default(rows, cols, CV_8UC1, Scalar::all(16.0))
+            .expect("创建测试图像失败");
+        let mut x = next(8);
+        while x < cols - 2 {
+            let w = (1 + next(9)).min(cols - x);
+            let y = rows / 4 + next(rows / 4);
+            let h = (1 + next(rows / 2)).min(rows - y);
+            imgproc::rectangle(
+                &mut img,
+                Rect::new(x, y, w, h),
+                Scalar::all(220.0),
+                -1,
+                imgproc::LINE_8,
+                0,
+            )
+            .expect("绘制字符块失败");
+            x += w + next(6);
+        }
+        img
+    }
+
+    /// 重写前的逐像素分割实现，作为投影分割的黄金参考。
+    fn reference_segment(mat: &Mat, min_gap_width: i32, min_char_width: i32) -> Vec<BBoxResult> {
+        let gray = super::_to_gray_mat(mat).unwrap();
+        let binary = super::_to_text_foreground_binary(&gray).unwrap();
+        let (rows, cols) = (binary.rows(), binary.cols());
+        let px = |y: i32, x: i32| *binary.at_2d::<u8>(y, x).unwrap() > 0;
+
+        let row_projection: Vec<i32> = (0..rows)
+            .map(|y| (0..cols).filter(|&x| px(y, x)).count() as i32)
+            .collect();
+        let (Some(top), Some(bottom)) = (
+            row_projection.iter().position(|&v| v > 0),
+            row_projection.iter().rposition(|&v| v > 0),
+        ) else {
+            return Vec::new();
+        };
+        let (top, bottom) = (top as i32, bottom as i32);
+        let col_projection: Vec<i32> = (0..cols)
+            .map(|x| (top..=bottom).filter(|&y| px(y, x)).count() as i32)
+            .collect();
+
+        let mut spans: Vec<(i32, i32)> = Vec::new();
+        for x in 0..cols {
+            if col_projection[x as usize] == 0 {
+                continue;
+            }
+            match spans.last_mut() {
+                Some(last) if x == last.1 + 1 || x - last.1 - 1 < min_gap_width.max(1) => {
+                    last.1 = x
+                }
+                _ => spans.push((x, x)),
+            }
+        }
+
+        spans
+            .into_iter()
+            .filter(|(start, end)| end - start + 1 >= min_char_width.max(1))
+            .filter_map(|(start, end)| {
+                let has_fg = |y: i32| (start..=end).any(|x| px(y, x));
+                let char_top = (top..=bottom).find(|&y| has_fg(y))?;
+                let char_bottom = (top..=bottom).rev().find(|&y| has_fg(y))?;
+                Some((start, char_top, end - start + 1, char_bottom - char_top + 1))
+            })
+            .collect()
+    }
+
+    /// 逐像素 HSL 加权差参考实现（不做 inRange 粗筛）。
+    fn reference_hsl_mask(mat: &Mat, colors: &[u32], tolerance: f64, weights: [f64; 3]) -> Mat {
+        let mut hls = Mat::default();
+        imgproc::cvt_color(mat, &mut hls, imgproc::COLOR_BGR2HLS, 0).unwrap();
+        let targets: Vec<(i32, i32, i32)> = colors
+            .iter()
+            .map(|color| {
+                let (h, s, l) = rgb_to_hsl(*color);
+                (
+                    h.round().clamp(0.0, 360.0) as i32,
+                    (s * 255.0).round().clamp(0.0, 255.0) as i32,
+                    (l * 255.0).round().clamp(0.0, 255.0) as i32,
+                )
+            })
+            .collect();
+        let mut mask =
+            Mat::new_rows_cols_with_default(mat.rows(), mat.cols(), CV_8UC1, Scalar::all(0.0))
+                .unwrap();
+        for y in 0..mat.rows() {
+            for x in 0..mat.cols() {
+                let px = *hls.at_2d::<core::Vec3b>(y, x).unwrap();
+                let (h, l, s) = (px[0] as i32 * 2, px[1] as i32, px[2] as i32);
+                let hit = targets.iter().any(|(th, ts, tl)| {
+                    (h - th).abs() as f64 * weights[0]
+                        + (s - ts).abs() as f64 * weights[1]
+                        + (l - tl).abs() as f64 * weights[2]
+                        <= tolerance * 255.0
+                });
+                if hit {
+                    *mask.at_2d_mut::<u8>(y, x).unwrap() = 255;
+                }
+            }
+        }
+        mask
+    }
+
+    fn assert_same_mask(actual: &Mat, expected: &Mat) {
+        let mut diff = Mat::default();
+        core::absdiff(actual, expected, &mut diff).unwrap();
+        assert_eq!(core::count_non_zero(&diff).unwrap(), 0);
+    }
 
     /// 构造最小测试图像，验证 colorFilter 支持按颜色分别指定容差。
     #[test]
@@ -2357,8 +2609,13 @@ mod tests {
         *mat.at_2d_mut::<core::Vec3b>(0, 0).expect("写入像素失败") = core::Vec3b::from([0, 0, 250]);
         *mat.at_2d_mut::<core::Vec3b>(0, 1).expect("写入像素失败") = core::Vec3b::from([0, 245, 0]);
 
-        let mask = color_filter_impl(&mat, &[0xff0000, 0x00ff00], &[10, 2])
-            .expect("执行 color_filter_impl 失败");
+        let mask = color_filter_impl(
+            &mat,
+            &[0xff0000, 0x00ff00],
+            &[10.0, 2.0],
+            ColorDistance::Rgb,
+        )
+        .expect("执行 color_filter_impl 失败");
 
         let first = *mask.at_2d::<u8>(0, 0).expect("读取首个掩码像素失败");
         let second = *mask.at_2d::<u8>(0, 1).expect("读取第二个掩码像素失败");
@@ -2375,9 +2632,288 @@ mod tests {
         *mat.at_2d_mut::<core::Vec3b>(0, 0).expect("写入像素失败") = core::Vec3b::from([0, 0, 250]);
         *mat.at_2d_mut::<core::Vec3b>(0, 1).expect("写入像素失败") = core::Vec3b::from([0, 245, 0]);
 
-        let matched = super::color_key_match_impl(&mat, &[0xff0000, 0x00ff00], 1.0, &[10, 2])
-            .expect("执行 color_key_match_impl 失败");
+        let matched = super::color_key_match_impl(
+            &mat,
+            &[0xff0000, 0x00ff00],
+            1.0,
+            &[10.0, 2.0],
+            ColorDistance::Rgb,
+        )
+        .expect("执行 color_key_match_impl 失败");
+
+        assert_eq!(matched, 0);
+    }
+
+    /// 验证 ΔE2000 模式能容忍泛光造成的提亮，同时拒绝色相不同的颜色。
+    #[test]
+    fn color_filter_delta_e_tolerates_bloom() {
+        let mut mat = Mat::new_rows_cols_with_default(1, 3, CV_8UC3, Scalar::all(0.0))
+            .expect("创建测试图像失败");
+        // 目标 0xFF3020 泛光后的颜色、偏粉的干扰色、灰色
+        *mat.at_2d_mut::<core::Vec3b>(0, 0).expect("写入像素失败") =
+            core::Vec3b::from([80, 96, 255]);
+        *mat.at_2d_mut::<core::Vec3b>(0, 1).expect("写入像素失败") =
+            core::Vec3b::from([120, 60, 235]);
+        *mat.at_2d_mut::<core::Vec3b>(0, 2).expect("写入像素失败") =
+            core::Vec3b::from([128, 128, 128]);
+
+        let read = |mask: &Mat| {
+            (0..3)
+                .map(|x| *mask.at_2d::<u8>(0, x).expect("读取掩码像素失败"))
+                .collect::<Vec<_>>()
+        };
+
+        let rgb = color_filter_impl(&mat, &[0xff3020], &[30.0], ColorDistance::Rgb)
+            .expect("执行 color_filter_impl 失败");
+        assert_eq!(read(&rgb), vec![0, 0, 0]);
 
+        let de2000 = color_filter_impl(&mat, &[0xff3020], &[10.0], ColorDistance::DeltaE2000)
+            .expect("执行 color_filter_impl 失败");
+        assert_eq!(read(&de2000), vec![255, 0, 0]);
+
+        let matched = super::color_key_match_impl(
+            &mat,
+            &[0x808080, 0xff3020],
+            1.0,
+            &[5.0, 10.0],
+            ColorDistance::DeltaE76,
+        )
+        .expect("执行 color_key_match_impl 失败");
         assert_eq!(matched, 0);
     }
+
+    /// 验证 batchMatchColor 在 ΔE 模式下会拒绝灰度纹理一致但颜色不同的命中。
+    #[test]
+    fn batch_match_color_delta_e_rejects_wrong_color() {
+        let patch = |paint: fn(u8) -> [u8; 3]| {
+            let mut tpl = Mat::new_rows_cols_with_default(12, 12, CV_8UC3, Scalar::all(0.0))
+                .expect("创建模板失败");
+            for y in 0..12 {
+                for x in 0..12 {
+                    let v = (((x * 7 + y * 13) % 11) * 20 + 30) as u8;
+                    *tpl.at_2d_mut::<core::Vec3b>(y, x).expect("写入像素失败") =
+                        core::Vec3b::from(paint(v));
+                }
+            }
+            tpl
+        };
+        let red = patch(|v| [0, 0, v]);
+        let gray = patch(|v| [v, v, v]);
+
+        let mut src = Mat::new_rows_cols_with_default(32, 48, CV_8UC3, Scalar::all(0.0))
+            .expect("创建源图像失败");
+        for y in 0..12 {
+            for x in 0..12 {
+                *src.at_2d_mut::<core::Vec3b>(10 + y, 20 + x)
+                    .expect("写入像素失败") =
+                    *red.at_2d::<core::Vec3b>(y, x).expect("读取像素失败");
+            }
+        }
+
+        let rgb =
+            super::batch_match_color_impl(&src, vec![gray.clone()], 0.9, ColorDistance::Rgb, 0.0)
+                .expect("执行 batch_match_color_impl 失败");
+        assert_eq!(rgb, Some((0, 20, 10)));
+
+        let rejected =
+            super::batch_match_color_impl(&src, vec![gray], 0.9, ColorDistance::DeltaE2000, 10.0)
+                .expect("执行 batch_match_color_impl 失败");
+        assert_eq!(rejected, None);
+
+        let accepted =
+            super::batch_match_color_impl(&src, vec![red], 0.9, ColorDistance::DeltaE2000, 10.0)
+                .expect("执行 batch_match_color_impl 失败");
+        assert_eq!(accepted, Some((0, 20, 10)));
+    }
+
+    /// 固定输入的分割黄金输出：空隙合并、最小宽度过滤与逐字符上下边界。
+    #[test]
+    fn segment_chars_golden_output() {
+        let mut img = Mat::new_rows_cols_with_default(20, 60, CV_8UC1, Scalar::all(0.0))
+            .expect("创建测试图像失败");
+        for rect in [
+            Rect::new(5, 4, 3, 10),
+            Rect::new(10, 6, 4, 8),
+            Rect::new(20, 2, 6, 14),
+            Rect::new(40, 9, 1, 2),
+        ] {
+            imgproc::rectangle(&mut img, rect, Scalar::all(255.0), -1, imgproc::LINE_8, 0)
+                .expect("绘制字符块失败");
+        }
+
+        let segment = |gap, width| super::segment_single_line_chars_impl(&img, gap, width).unwrap();
+        assert_eq!(
+            segment(2, 1),
+            vec![(5, 4, 3, 10), (10, 6, 4, 8), (20, 2, 6, 14), (40, 9, 1, 2)]
+        );
+        assert_eq!(segment(3, 2), vec![(5, 4, 9, 10), (20, 2, 6, 14)]);
+        // 反色输入（浅底深字）得到相同结果
+        let mut inverted = Mat::default();
+        core::bitwise_not_def(&img, &mut inverted).unwrap();
+        assert_eq!(
+            super::segment_single_line_chars_impl(&inverted, 2, 1).unwrap(),
+            segment(2, 1)
+        );
+        // ROI 视图（非连续内存）按行切片读取
+        let roi = Mat::roi(&img, Rect::new(18, 0, 30, 20)).unwrap();
+        assert_eq!(
+            super::segment_single_line_chars_impl(&roi, 2, 1).unwrap(),
+            vec![(2, 2, 6, 14), (22, 9, 1, 2)]
+        );
+    }
+
+    /// 行切片分割与逐像素参考实现在随机输入上逐框一致。
+    #[test]
+    fn segment_chars_matches_reference() {
+        for seed in 1..=12 {
+            let img = random_text_line(240 + seed as i32 * 7, 24 + seed as i32, seed);
+            for (gap, width) in [(1, 1), (2, 2), (4, 3)] {
+                assert_eq!(
+                    super::segment_single_line_chars_impl(&img, gap, width).unwrap(),
+                    reference_segment(&img, gap, width),
+                    "seed={seed} gap={gap} width={width}"
+                );
+            }
+        }
+    }
+
+    /// HSL 过滤（粗筛 + 分块精算，含并行路径）与逐像素参考实现一致。
+    #[test]
+    fn color_filter_hsl_matches_reference() {
+        let colors = [0xff3020, 0x20c0ff, 0x808080];
+        // 80x60 走串行路径，640x400 超过并行阈值
+        for (cols, rows, seed) in [(80, 60, 3), (640, 400, 4)] {
+            let img = random_bgr(cols, rows, seed);
+            for (tolerance, weights) in [
+                (0.1, [255.0, 180.0, 75.0]),
+                (0.05, [0.0, 0.0, 1.0]),
+                (0.3, [60.0, 255.0, 255.0]),
+            ] {
+                let mask =
+                    super::color_filter_hsl_impl(&img, &colors, tolerance, Some(weights)).unwrap();
+                assert_same_mask(
+                    &mask,
+                    &reference_hsl_mask(&img, &colors, tolerance, weights),
+                );
+            }
+        }
+        // 没有候选像素时直接返回全零掩码
+        let flat = Mat::new_rows_cols_with_default(8, 8, CV_8UC3, Scalar::all(0.0)).unwrap();
+        let mask = super::color_filter_hsl_impl(&flat, &[0xff0000], 0.01, None).unwrap();
+        assert_eq!(core::count_non_zero(&mask).unwrap(), 0);
+    }
+
+    /// 共享源图像的并行模板匹配仍按模板顺序返回首个命中。
+    #[test]
+    fn batch_match_color_returns_first_template_in_order() {
+        let src = random_bgr(200, 120, 7);
+        let crop = |x, y| {
+            Mat::roi(&src, Rect::new(x, y, 16, 16))
+                .unwrap()
+                .try_clone()
+                .unwrap()
+        };
+        let tpls = vec![
+            random_bgr(16, 16, 99),
+            crop(30, 40),
+            random_bgr(16, 16, 100),
+            crop(100, 20),
+        ];
+        let hit = super::batch_match_color_impl(&src, tpls.clone(), 0.95, ColorDistance::Rgb, 0.0)
+            .unwrap();
+        assert_eq!(hit, Some((1, 30, 40)));
+        let hit =
+            super::batch_match_color_impl(&src, tpls, 0.95, ColorDistance::DeltaE76, 1.0).unwrap();
+        assert_eq!(hit, Some((1, 30, 40)));
+    }
+
+    /// 性能对比：`cargo test scan_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn scan_benchmark() {
+        let rounds = 3;
+        let line = random_text_line(3840, 2160, 42);
+        let start = Instant::now();
+        for _ in 0..rounds {
+            reference_segment(&line, 2, 2);
+        }
+        let slow = start.elapsed() / rounds;
+        let start = Instant::now();
+        for _ in 0..rounds {
+            super::segment_single_line_chars_impl(&line, 2, 2).unwrap();
+        }
+        let fast = start.elapsed() / rounds;
+        println!("3840x2160 字符分割: 逐像素 {slow:?}, 行切片 {fast:?}");
+
+        let img = random_bgr(3840, 2160, 42);
+        let colors = [0xff3020, 0x20c0ff];
+        let weights = [255.0, 180.0, 75.0];
+        let start = Instant::now();
+        for _ in 0..rounds {
+            reference_hsl_mask(&img, &colors, 0.1, weights);
+        }
+        let slow = start.elapsed() / rounds;
+        let start = Instant::now();
+        for _ in 0..rounds {
+            super::color_filter_hsl_impl(&img, &colors, 0.1, Some(weights)).unwrap();
+        }
+        let fast = start.elapsed() / rounds;
+        println!("3840x2160 HSL 过滤: 逐像素 {slow:?}, 分块 {fast:?}");
+    }
+
+    /// 验证 aHash/dHash/block-mean 的位布局、长度以及对整体亮度变化的稳定性。
+    #[test]
+    fn image_hash_variants_have_expected_layout() {
+        use super::{ImageHashKind, perceptual_hash_impl};
+
+        let mut gradient = Mat::new_rows_cols_with_default(32, 72, CV_8UC3, Scalar::all(0.0))
+            .expect("创建测试图像失败");
+        for y in 0..32 {
+            for x in 0..72 {
+                let v = (x * 3) as u8;
+                *gradient
+                    .at_2d_mut::<core::Vec3b>(y, x)
+                    .expect("写入像素失败") = core::Vec3b::from([v, v, v]);
+            }
+        }
+        let flat = Mat::new_rows_cols_with_default(32, 32, CV_8UC3, Scalar::all(90.0))
+            .expect("创建测试图像失败");
+
+        let hash = |mat: &Mat, color: bool, kind: ImageHashKind| {
+            perceptual_hash_impl(mat, color, kind).expect("计算哈希失败")
+        };
+
+        // 水平递增梯度：dHash 所有位为 1
+        assert_eq!(
+            hash(&gradient, false, ImageHashKind::DHash),
+            "ffffffffffffffff"
+        );
+        // 纯色图：没有像素大于均值
+        assert_eq!(hash(&flat, false, ImageHashKind::AHash), "0000000000000000");
+        assert_eq!(hash(&gradient, false, ImageHashKind::BlockMean).len(), 64);
+        assert_eq!(hash(&gradient, true, ImageHashKind::BlockMean).len(), 192);
+        assert_eq!(hash(&gradient, true, ImageHashKind::AHash).len(), 48);
+
+        let mut brighter = Mat::default();
+        gradient
+            .convert_to(&mut brighter, -1, 1.0, 20.0)
+            .expect("调整亮度失败");
+        for kind in [
+            ImageHashKind::AHash,
+            ImageHashKind::DHash,
+            ImageHashKind::BlockMean,
+        ] {
+            assert_eq!(hash(&gradient, false, kind), hash(&brighter, false, kind));
+        }
+
+        assert_eq!(
+            ImageHashKind::from_name("Block-Mean"),
+            Some(ImageHashKind::BlockMean)
+        );
+        assert_eq!(
+            ImageHashKind::from_name("dhash").map(ImageHashKind::name),
+            Some("dhash")
+        );
+        assert_eq!(ImageHashKind::from_name("xhash"), None);
+    }
 }
@@ -0,0 +1,105 @@
+//! 单元测试共用的确定性随机数据生成器。
+
+use opencv::{
+    core::{CV_8UC3, Mat, Point, Rect, Scalar, Vec3b},
+    imgproc,
+    prelude::*,
+};
+
+/// 64 位线性同余随机数生成器（PCG 常数）。
+pub(crate) struct Lcg64 {
+    state: u64,
+}
+
+impl Lcg64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// 推进一步并返回新的内部状态
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self
+            .state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        self.state
+    }
+
+    /// 返回 `[0, bound)` 内的随机整数
+    pub(crate) fn below(&mut self, bound: i32) -> i32 {
+        ((self.next_u64() >> 33) % bound as u64) as i32
+    }
+}
+
+/// 随机矩形与圆纹理图的生成参数。
+pub(crate) struct ShapeTexture {
+    /// 底色（三通道相同）
+    pub background: f64,
+    /// 每个图形平均占据的像素面积（图形数量 = 宽 × 高 / 该值）
+    pub area_per_shape: i32,
+    /// 矩形宽高的随机上界（实际为 `4 + [0, bound)`）
+    pub rect_bound: (i32, i32),
+    /// 圆半径的随机上界（实际为 `3 + [0, bound)`）
+    pub radius_bound: i32,
+    /// 颜色各通道下限，避免生成接近纯黑的颜色
+    pub min_channel: i32,
+}
+
+impl ShapeTexture {
+    /// 生成用线性同余随机矩形与圆铺满的纹理丰富的测试图
+    pub(crate) fn render(&self, seed: u64, width: i32, height: i32) -> Mat {
+        let mut mat =
+            Mat::new_rows_cols_with_default(height, width, CV_8UC3, Scalar::all(self.background))
+                .unwrap();
+        let mut rng = Lcg64::new(seed);
+        let channel_bound = 256 - self.min_channel;
+        for _ in 0..width * height / self.area_per_shape {
+            let color = Scalar::new(
+                (self.min_channel + rng.below(channel_bound)) as f64,
+                (self.min_channel + rng.below(channel_bound)) as f64,
+                (self.min_channel + rng.below(channel_bound)) as f64,
+                0.0,
+            );
+            let (x, y) = (rng.below(width), rng.below(height));
+            if rng.below(2) == 0 {
+                let rect = Rect::new(
+                    x,
+                    y,
+                    4 + rng.below(self.rect_bound.0),
+                    4 + rng.below(self.rect_bound.1),
+                );
+                imgproc::rectangle(&mut mat, rect, color, -1, imgproc::LINE_8, 0).unwrap();
+            } else {
+                let radius = 3 + rng.below(self.radius_bound);
+                imgproc::circle(
+                    &mut mat,
+                    Point::new(x, y),
+                    radius,
+                    color,
+                    -1,
+                    imgproc::LINE_8,
+                    0,
+                )
+                .unwrap();
+            }
+        }
+        mat
+    }
+}
+
+/// 用 32 位线性同余生成器填充确定性随机 BGR 图像
+pub(crate) fn random_bgr(cols: i32, rows: i32, seed: u32) -> Mat {
+    let mut state = seed;
+    let mut img = Mat::new_rows_cols_with_default(rows, cols, CV_8UC3, Scalar::all(0.0)).unwrap();
+    for y in 0..rows {
+        for x in 0..cols {
+            let mut px = [0u8; 3];
+            for c in px.iter_mut() {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                *c = (state >> 24) as u8;
+            }
+            *img.at_2d_mut::<Vec3b>(y, x).unwrap() = Vec3b::from(px);
+        }
+    }
+    img
+}
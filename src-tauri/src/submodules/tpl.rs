@@ -1,8 +1,14 @@
 use base64::{Engine as _, engine::general_purpose};
-use opencv::{core::Mat, imgcodecs};
-use std::collections::HashMap;
+use opencv::{
+    core::{self, Mat},
+    imgcodecs, imgproc,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
-use std::sync::{LazyLock, RwLock};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
 use thiserror::Error;
 
 // 自定义错误类型，让调用者可以精准处理不同错误
@@ -19,83 +25,272 @@ pub enum TemplateError {
 
     #[error("Base64 解码失败: {0}")]
     Base64DecodeFailed(String),
+
+    #[error("模板图集无效: {0}")]
+    AtlasInvalid(String),
+
+    #[error("模板图集中不存在该模板: {0}")]
+    AtlasEntryNotFound(String),
+}
+
+/// 模板缓存默认字节预算（像素数据 + 键长度），超出后按最近最少使用淘汰
+pub(crate) const TEMPLATE_CACHE_BUDGET_BYTES: usize = 256 * 1024 * 1024;
+
+/// 缓存键：文件模板使用规范化路径，base64 模板使用原始字符串，图集模板使用清单路径 + 名称
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum TemplateKey {
+    File(PathBuf),
+    Base64(String),
+    Atlas(PathBuf, String),
+}
+
+impl TemplateKey {
+    fn key_bytes(&self) -> usize {
+        match self {
+            Self::File(path) => path.as_os_str().len(),
+            Self::Base64(b64) => b64.len(),
+            Self::Atlas(path, name) => path.as_os_str().len() + name.len(),
+        }
+    }
+}
+
+/// 文件指纹：修改时间 + 文件大小，任一变化即视为模板已被编辑
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> std::io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+struct CacheEntry {
+    mat: Box<Mat>,
+    bytes: usize,
+    /// 来源文件指纹（base64 模板为空；图集模板含清单与图集图片）
+    stamps: Vec<FileStamp>,
+    /// 最近一次访问序号，对应 `TemplateCache::order` 的键
+    tick: u64,
+}
+
+/// 模板缓存统计信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct TemplateCacheStats {
+    pub entries: usize,
+    pub file_entries: usize,
+    pub base64_entries: usize,
+    pub atlas_entries: usize,
+    pub bytes: usize,
+    pub budget_bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub invalidations: u64,
+}
+
+/// 带字节预算的 LRU 模板缓存
+///
+/// 说明：
+/// - 命中时需要刷新访问顺序，因此整体由 `Mutex` 保护（读也是写）；
+/// - `order` 以访问序号为键，首个元素即最近最少使用的条目；
+/// - 单个条目超过预算时不缓存，直接返回给调用方。
+struct TemplateCache {
+    entries: HashMap<TemplateKey, CacheEntry>,
+    order: BTreeMap<u64, TemplateKey>,
+    tick: u64,
+    bytes: usize,
+    budget: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+    invalidations: u64,
+}
+
+impl TemplateCache {
+    fn new(budget: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            bytes: 0,
+            budget,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+            invalidations: 0,
+        }
+    }
+
+    /// 查询缓存并刷新访问顺序；文件指纹不一致时移除旧条目并计为失效
+    fn get(&mut self, key: &TemplateKey, stamps: &[FileStamp]) -> Option<Box<Mat>> {
+        let stale = match self.entries.get(key) {
+            Some(entry) => entry.stamps != stamps,
+            None => {
+                self.misses += 1;
+                return None;
+            }
+        };
+        if stale {
+            self.remove(key);
+            self.invalidations += 1;
+            self.misses += 1;
+            return None;
+        }
+
+        self.tick += 1;
+        let tick = self.tick;
+        let entry = self.entries.get_mut(key)?;
+        self.order.remove(&entry.tick);
+        self.order.insert(tick, key.clone());
+        entry.tick = tick;
+        self.hits += 1;
+        Some(entry.mat.clone())
+    }
+
+    /// 写入缓存；若其他线程已写入相同指纹的条目则返回已有模板
+    fn insert(&mut self, key: TemplateKey, mat: Box<Mat>, stamps: &[FileStamp]) -> Box<Mat> {
+        if let Some(existing) = self.entries.get(&key) {
+            if existing.stamps == stamps {
+                return existing.mat.clone();
+            }
+            self.remove(&key);
+        }
+
+        let bytes = mat_bytes(&mat) + key.key_bytes();
+        if bytes > self.budget {
+            return mat;
+        }
+        while self.bytes + bytes > self.budget {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                self.bytes -= entry.bytes;
+                self.evictions += 1;
+            }
+        }
+
+        self.tick += 1;
+        self.order.insert(self.tick, key.clone());
+        self.bytes += bytes;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                mat: mat.clone(),
+                bytes,
+                stamps: stamps.to_vec(),
+                tick: self.tick,
+            },
+        );
+        mat
+    }
+
+    fn remove(&mut self, key: &TemplateKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.tick);
+            self.bytes -= entry.bytes;
+        }
+    }
+
+    /// 清空所有条目（保留命中统计），返回清除的条目数
+    fn clear(&mut self) -> usize {
+        let count = self.entries.len();
+        self.entries.clear();
+        self.order.clear();
+        self.bytes = 0;
+        count
+    }
+
+    fn stats(&self) -> TemplateCacheStats {
+        let count =
+            |kind: fn(&TemplateKey) -> bool| self.entries.keys().filter(|k| kind(k)).count();
+        TemplateCacheStats {
+            entries: self.entries.len(),
+            file_entries: count(|key| matches!(key, TemplateKey::File(_))),
+            base64_entries: count(|key| matches!(key, TemplateKey::Base64(_))),
+            atlas_entries: count(|key| matches!(key, TemplateKey::Atlas(..))),
+            bytes: self.bytes,
+            budget_bytes: self.budget,
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            invalidations: self.invalidations,
+        }
+    }
 }
 
-// 优化1：使用RwLock替换Mutex（读多写少场景更高效，支持多线程同时读）
-// 优化2：缓存Key改为PathBuf（支持路径规范化，避免重复缓存）
-static TEMPLATE_CACHE: LazyLock<RwLock<HashMap<PathBuf, Box<Mat>>>> =
-    LazyLock::new(|| RwLock::new(HashMap::new()));
+/// Mat 像素数据占用的字节数
+fn mat_bytes(mat: &Mat) -> usize {
+    mat.total() * mat.elem_size().unwrap_or(0)
+}
 
-// Base64 字符串缓存（使用 String 作为键，直接使用 base64 字符串）
-static BASE64_TEMPLATE_CACHE: LazyLock<RwLock<HashMap<String, Box<Mat>>>> =
-    LazyLock::new(|| RwLock::new(HashMap::new()));
+// 文件模板、base64 模板与图集模板共用一个缓存与字节预算
+static TEMPLATE_CACHE: LazyLock<Mutex<TemplateCache>> =
+    LazyLock::new(|| Mutex::new(TemplateCache::new(TEMPLATE_CACHE_BUDGET_BYTES)));
 
-/// 获取模板（优先从缓存读取，未命中则加载文件并缓存）
+fn lock_cache() -> Result<std::sync::MutexGuard<'static, TemplateCache>, TemplateError> {
+    TEMPLATE_CACHE
+        .lock()
+        .map_err(|e| TemplateError::CacheLockFailed(format!("互斥锁: {}", e)))
+}
+
+/// 获取模板（优先从缓存读取，未命中或文件已修改则加载文件并缓存）
+///
+/// `path` 形如 `atlas.json#name` / `atlas.toml#name` 时从模板图集中按名称取出子图。
 /// 返回Result<Box<Mat>, TemplateError>，方便调用者处理错误
 pub(crate) fn get_template(path: &str) -> Result<Box<Mat>, TemplateError> {
+    if let Some((manifest_path, name)) = split_atlas_ref(path) {
+        return get_atlas_template(manifest_path, name);
+    }
+
     // 步骤1：路径规范化（解析./、../，转为绝对路径），避免重复缓存
     let path_buf = Path::new(path)
         .canonicalize() // 规范化路径，失败则返回文件不存在错误
         .map_err(|e| TemplateError::FileNotFound(format!("{}: {}", path, e)))?;
-    let path_str = path_buf.to_string_lossy();
+    get_template_file(path_buf)
+}
 
-    // 步骤2：先查缓存（仅获取读锁，支持多线程同时读取，锁持有时间极短）
-    {
-        // 读锁获取失败（通常是poisoned，即持有锁的线程panic）
-        let cache = TEMPLATE_CACHE
-            .read()
-            .map_err(|e| TemplateError::CacheLockFailed(format!("读锁: {}", e)))?;
-
-        // 缓存命中，直接返回克隆的模板
-        if let Some(item) = cache.get(&path_buf) {
-            // 注意：Mat::clone()是浅拷贝（仅复制矩阵头，不拷贝像素数据），高效但共享底层数据
-            // 若需要深拷贝，请替换为：Ok(Box::new(item.as_ref().clone_with_alloc()?))
-            return Ok(item.clone());
-        }
-    } // 读锁在此处自动释放（代码块结束，变量销毁）
+/// 按规范化路径加载模板文件（带缓存与修改检测）
+fn get_template_file(path_buf: PathBuf) -> Result<Box<Mat>, TemplateError> {
+    let path_str = path_buf.to_string_lossy().into_owned();
+
+    // 步骤2：读取文件指纹（修改时间 + 大小），用于判断缓存是否过期
+    let stamp = FileStamp::of(&path_buf)
+        .map_err(|e| TemplateError::FileNotFound(format!("{}: {}", path_str, e)))?;
+    let key = TemplateKey::File(path_buf);
 
-    // 步骤3：缓存未命中，执行耗时操作（无锁状态，不阻塞其他线程）
-    // 检查文件是否存在（规范化路径后，此处检查更可靠）
-    if !path_buf.exists() {
-        return Err(TemplateError::FileNotFound(path_str.into_owned()));
+    // 步骤3：先查缓存（锁持有时间极短）
+    if let Some(mat) = lock_cache()?.get(&key, &[stamp]) {
+        return Ok(mat);
     }
 
+    // 步骤4：缓存未命中，执行耗时操作（无锁状态，不阻塞其他线程）
     // 加载并解码图像（保持原始通道数，支持带透明度的图像）
     let mat = imgcodecs::imread(&path_str, imgcodecs::IMREAD_UNCHANGED)
         .map_err(|e| TemplateError::ImreadFailed(format!("{}: {:?}", path_str, e)))?;
-    let new_template = Box::new(mat);
-
-    // 步骤4：写入缓存（仅获取写锁，独占访问，锁持有时间极短）
-    {
-        let mut cache = TEMPLATE_CACHE
-            .write()
-            .map_err(|e| TemplateError::CacheLockFailed(format!("写锁: {}", e)))?;
-
-        // 二次检查缓存（解决多线程竞态条件，避免重复插入）
-        if let Some(existing_item) = cache.get(&path_buf) {
-            return Ok(existing_item.clone());
-        }
-
-        // 插入缓存
-        cache.insert(path_buf, new_template.clone());
-    } // 写锁在此处自动释放
+    if mat.empty() {
+        return Err(TemplateError::ImreadFailed(path_str));
+    }
 
-    // 步骤5：返回新加载的模板
-    Ok(new_template)
+    // 步骤5：写入缓存（超出预算时淘汰最近最少使用的模板）
+    Ok(lock_cache()?.insert(key, Box::new(mat), &[stamp]))
 }
 
 /// 从 base64 字符串获取模板（优先从缓存读取，未命中则解码并缓存）
 /// 返回Result<Box<Mat>, TemplateError>，方便调用者处理错误
 pub(crate) fn get_template_b64(b64_str: &str) -> Result<Box<Mat>, TemplateError> {
-    // 步骤1：先查缓存（仅获取读锁，支持多线程同时读取，锁持有时间极短）
-    {
-        let cache = BASE64_TEMPLATE_CACHE
-            .read()
-            .map_err(|e| TemplateError::CacheLockFailed(format!("读锁: {}", e)))?;
+    let key = TemplateKey::Base64(b64_str.to_string());
 
-        if let Some(item) = cache.get(b64_str) {
-            return Ok(item.clone());
-        }
+    // 步骤1：先查缓存（锁持有时间极短）
+    if let Some(mat) = lock_cache()?.get(&key, &[]) {
+        return Ok(mat);
     }
 
     // 步骤2：缓存未命中，执行解码操作（无锁状态，不阻塞其他线程）
@@ -109,20 +304,573 @@ pub(crate) fn get_template_b64(b64_str: &str) -> Result<Box<Mat>, TemplateError>
     )
     .map_err(|e| TemplateError::ImreadFailed(format!("解码图像失败: {:?}", e)))?;
 
-    let new_template = Box::new(mat);
+    // 步骤3：写入缓存（与文件模板共用字节预算）
+    Ok(lock_cache()?.insert(key, Box::new(mat), &[]))
+}
+
+/// 模板图集清单：一张图集图片 + 命名矩形区域
+///
+/// JSON 示例：
+/// ```json
+/// {
+///   "image": "ui.png",
+///   "templates": {
+///     "btn_confirm": { "rect": [0, 0, 64, 24], "threshold": 0.85, "offset": [32, 12] }
+///   }
+/// }
+/// ```
+/// TOML 使用相同字段（`[templates.btn_confirm]`）。
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub(crate) struct TemplateAtlasManifest {
+    /// 图集图片路径（相对于清单所在目录）
+    pub image: String,
+    #[serde(default)]
+    pub templates: BTreeMap<String, AtlasTemplate>,
+}
+
+/// 图集中的单个模板区域
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AtlasTemplate {
+    /// 图集中的区域 `[x, y, w, h]`
+    pub rect: [i32; 4],
+    /// 默认匹配阈值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+    /// 建议搜索区域 `[x, y, w, h]`（游戏画面坐标）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roi: Option<[i32; 4]>,
+    /// 透明色：与该颜色完全一致的像素在模板中 alpha 置 0（匹配时被掩码忽略）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_key: Option<AtlasColor>,
+    /// 点击偏移 `[dx, dy]`（相对模板左上角）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<[i32; 2]>,
+    /// 图集带透明通道时是否保留该区域的 alpha；原图不透明时打包工具写入 `false`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha: Option<bool>,
+}
+
+/// 图集颜色：`0xRRGGBB` 数字或 `"#RRGGBB"` 字符串
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum AtlasColor {
+    Rgb(u32),
+    Hex(String),
+}
+
+impl AtlasColor {
+    /// 解析为 `0xRRGGBB`
+    pub(crate) fn rgb(&self) -> Result<u32, TemplateError> {
+        match self {
+            Self::Rgb(rgb) => Ok(rgb & 0x00FF_FFFF),
+            Self::Hex(text) => {
+                let hex = text.trim().trim_start_matches('#').trim_start_matches("0x");
+                u32::from_str_radix(hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 6)
+                    .ok_or_else(|| TemplateError::AtlasInvalid(format!("颜色格式无效: {text}")))
+            }
+        }
+    }
+}
+
+/// 拆分图集引用 `manifest.json#name`，仅在 `#` 前为 .json/.toml 文件时视为图集
+pub(crate) fn split_atlas_ref(path: &str) -> Option<(&str, &str)> {
+    let (manifest, name) = path.rsplit_once('#')?;
+    let extension = Path::new(manifest).extension()?.to_str()?;
+    let is_manifest =
+        extension.eq_ignore_ascii_case("json") || extension.eq_ignore_ascii_case("toml");
+    (is_manifest && !name.is_empty()).then_some((manifest, name))
+}
+
+/// 按扩展名解析 JSON / TOML 图集清单
+fn parse_atlas_manifest(path: &Path, text: &str) -> Result<TemplateAtlasManifest, TemplateError> {
+    let is_toml = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    if is_toml {
+        toml::from_str(text)
+            .map_err(|e| TemplateError::AtlasInvalid(format!("{}: {e}", path.display())))
+    } else {
+        serde_json::from_str(text)
+            .map_err(|e| TemplateError::AtlasInvalid(format!("{}: {e}", path.display())))
+    }
+}
+
+/// 已解析的图集清单：规范化路径 -> (清单指纹, 清单内容)
+type AtlasManifestCache = HashMap<PathBuf, (FileStamp, Arc<TemplateAtlasManifest>)>;
+
+// 已解析的图集清单（按清单文件指纹失效）
+static ATLAS_MANIFESTS: LazyLock<Mutex<AtlasManifestCache>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 读取图集清单（带缓存），返回规范化路径、清单指纹与清单内容
+fn load_atlas_manifest(
+    manifest_path: &str,
+) -> Result<(PathBuf, FileStamp, Arc<TemplateAtlasManifest>), TemplateError> {
+    let path_buf = Path::new(manifest_path)
+        .canonicalize()
+        .map_err(|e| TemplateError::FileNotFound(format!("{}: {}", manifest_path, e)))?;
+    let stamp = FileStamp::of(&path_buf)
+        .map_err(|e| TemplateError::FileNotFound(format!("{}: {}", path_buf.display(), e)))?;
 
-    // 步骤3：写入缓存（仅获取写锁，独占访问，锁持有时间极短）
+    let mut manifests = ATLAS_MANIFESTS
+        .lock()
+        .map_err(|e| TemplateError::CacheLockFailed(format!("互斥锁: {}", e)))?;
+    if let Some((cached_stamp, manifest)) = manifests.get(&path_buf)
+        && *cached_stamp == stamp
     {
-        let mut cache = BASE64_TEMPLATE_CACHE
-            .write()
-            .map_err(|e| TemplateError::CacheLockFailed(format!("写锁: {}", e)))?;
+        return Ok((path_buf, stamp, manifest.clone()));
+    }
+
+    let text = std::fs::read_to_string(&path_buf)
+        .map_err(|e| TemplateError::FileNotFound(format!("{}: {}", path_buf.display(), e)))?;
+    let manifest = Arc::new(parse_atlas_manifest(&path_buf, &text)?);
+    manifests.insert(path_buf.clone(), (stamp, manifest.clone()));
+    Ok((path_buf, stamp, manifest))
+}
+
+/// 获取图集模板的元数据（阈值、ROI 提示、透明色、点击偏移）
+///
+/// 非图集引用返回 `Ok(None)`。
+pub(crate) fn get_template_meta(path: &str) -> Result<Option<AtlasTemplate>, TemplateError> {
+    let Some((manifest_path, name)) = split_atlas_ref(path) else {
+        return Ok(None);
+    };
+    let (_, _, manifest) = load_atlas_manifest(manifest_path)?;
+    manifest
+        .templates
+        .get(name)
+        .cloned()
+        .map(Some)
+        .ok_or_else(|| TemplateError::AtlasEntryNotFound(format!("{manifest_path}#{name}")))
+}
+
+/// 从图集中取出命名模板（图集图片与裁剪结果均进入模板缓存）
+fn get_atlas_template(manifest_path: &str, name: &str) -> Result<Box<Mat>, TemplateError> {
+    let (manifest_buf, manifest_stamp, manifest) = load_atlas_manifest(manifest_path)?;
+    let entry = manifest
+        .templates
+        .get(name)
+        .ok_or_else(|| TemplateError::AtlasEntryNotFound(format!("{manifest_path}#{name}")))?;
+
+    let image_path = manifest_buf
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(&manifest.image)
+        .canonicalize()
+        .map_err(|e| TemplateError::FileNotFound(format!("{}: {}", manifest.image, e)))?;
+    let image_stamp = FileStamp::of(&image_path)
+        .map_err(|e| TemplateError::FileNotFound(format!("{}: {}", image_path.display(), e)))?;
+    let stamps = [manifest_stamp, image_stamp];
+    let key = TemplateKey::Atlas(manifest_buf, name.to_string());
+
+    if let Some(mat) = lock_cache()?.get(&key, &stamps) {
+        return Ok(mat);
+    }
+
+    let atlas = get_template_file(image_path)?;
+    let template = crop_atlas_template(&atlas, entry)
+        .map_err(|e| TemplateError::AtlasInvalid(format!("{manifest_path}#{name}: {e}")))?;
+    Ok(lock_cache()?.insert(key, Box::new(template), &stamps))
+}
+
+/// 按清单裁剪图集区域，并应用 alpha 开关与透明色
+fn crop_atlas_template(atlas: &Mat, entry: &AtlasTemplate) -> Result<Mat, String> {
+    let [x, y, w, h] = entry.rect;
+    if w <= 0 || h <= 0 || x < 0 || y < 0 || x + w > atlas.cols() || y + h > atlas.rows() {
+        return Err(format!(
+            "区域 [{x}, {y}, {w}, {h}] 超出图集范围 {}x{}",
+            atlas.cols(),
+            atlas.rows()
+        ));
+    }
+    let region = Mat::roi(atlas, core::Rect::new(x, y, w, h))
+        .and_then(|roi| roi.try_clone())
+        .map_err(|e| format!("裁剪图集区域失败: {e}"))?;
+
+    let region = if region.channels() == 4 && entry.alpha == Some(false) {
+        let mut bgr = Mat::default();
+        imgproc::cvt_color(&region, &mut bgr, imgproc::COLOR_BGRA2BGR, 0)
+            .map_err(|e| format!("转换通道失败: {e}"))?;
+        bgr
+    } else {
+        region
+    };
 
-        if let Some(existing_item) = cache.get(b64_str) {
-            return Ok(existing_item.clone());
+    let Some(color_key) = &entry.color_key else {
+        return Ok(region);
+    };
+    let rgb = color_key.rgb().map_err(|e| e.to_string())?;
+    let key_bgr = core::Scalar::new(
+        (rgb & 0xFF) as f64,
+        ((rgb >> 8) & 0xFF) as f64,
+        ((rgb >> 16) & 0xFF) as f64,
+        0.0,
+    );
+
+    // 透明色像素 alpha 置 0，匹配时由掩码忽略
+    let (bgr, mut bgra) = if region.channels() == 4 {
+        let mut bgr = Mat::default();
+        imgproc::cvt_color(&region, &mut bgr, imgproc::COLOR_BGRA2BGR, 0)
+            .map_err(|e| format!("转换通道失败: {e}"))?;
+        (bgr, region)
+    } else {
+        let mut bgra = Mat::default();
+        imgproc::cvt_color(&region, &mut bgra, imgproc::COLOR_BGR2BGRA, 0)
+            .map_err(|e| format!("转换通道失败: {e}"))?;
+        (region, bgra)
+    };
+    let mut mask = Mat::default();
+    core::in_range(&bgr, &key_bgr, &key_bgr, &mut mask)
+        .map_err(|e| format!("生成透明色掩码失败: {e}"))?;
+    bgra.set_to(&core::Scalar::all(0.0), &mask)
+        .map_err(|e| format!("应用透明色失败: {e}"))?;
+    Ok(bgra)
+}
+
+/// 图集打包结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasPackResult {
+    pub manifest_path: String,
+    pub image_path: String,
+    pub packed_templates: usize,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// 打包时模板之间的间隔像素，避免缩放/模糊时相邻模板互相渗色
+const ATLAS_PADDING: i32 = 2;
+
+/// 货架式矩形排布：按高度降序逐行放置，返回每个尺寸对应的位置与画布尺寸
+///
+/// 画布宽度取 `max(最宽模板, sqrt(总面积))`，使图集接近正方形。
+fn shelf_pack(sizes: &[(i32, i32)], padding: i32) -> (Vec<(i32, i32)>, i32, i32) {
+    let area: i64 = sizes
+        .iter()
+        .map(|&(w, h)| i64::from(w + padding) * i64::from(h + padding))
+        .sum();
+    let widest = sizes.iter().map(|&(w, _)| w).max().unwrap_or(0);
+    let width = widest.max((area as f64).sqrt().ceil() as i32);
+
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| (std::cmp::Reverse(sizes[i].1), std::cmp::Reverse(sizes[i].0)));
+
+    let mut positions = vec![(0, 0); sizes.len()];
+    let (mut x, mut y, mut shelf_height, mut used_width) = (0, 0, 0, 0);
+    for index in order {
+        let (w, h) = sizes[index];
+        if x > 0 && x + w > width {
+            y += shelf_height + padding;
+            x = 0;
+            shelf_height = 0;
+        }
+        positions[index] = (x, y);
+        used_width = used_width.max(x + w);
+        shelf_height = shelf_height.max(h);
+        x += w + padding;
+    }
+    (positions, used_width.max(1), (y + shelf_height).max(1))
+}
+
+/// 将目录下的 PNG 模板打包为图集（图片 + 清单）
+///
+/// # 参数
+/// - `source_dir`: 模板目录（仅当前层级的 `.png`，模板名为文件名去扩展名）
+/// - `manifest_path`: 输出清单路径（`.json` 或 `.toml`），图集图片写到同名 `.png`
+///
+/// 说明：任一模板带透明通道时图集保存为 BGRA，不透明模板在清单中标记 `alpha = false`。
+pub fn pack_template_atlas(
+    source_dir: &Path,
+    manifest_path: &Path,
+) -> Result<AtlasPackResult, String> {
+    if !source_dir.is_dir() {
+        return Err(format!("不是目录: {}", source_dir.display()));
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(source_dir)
+        .map_err(|e| format!("读取目录失败: {e}"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+        })
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("目录中没有 PNG 模板: {}", source_dir.display()));
+    }
+
+    let image_path = manifest_path.with_extension("png");
+    let mut templates = Vec::with_capacity(files.len());
+    for file in &files {
+        if file.canonicalize().ok() == image_path.canonicalize().ok() {
+            continue; // 重新打包时跳过上一次输出的图集
+        }
+        let name = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mat = imgcodecs::imread(&file.to_string_lossy(), imgcodecs::IMREAD_UNCHANGED)
+            .map_err(|e| format!("读取模板失败 {}: {e}", file.display()))?;
+        if mat.empty() {
+            return Err(format!("读取模板失败: {}", file.display()));
         }
+        templates.push((name, mat));
+    }
+
+    let has_alpha = templates.iter().any(|(_, mat)| mat.channels() == 4);
+    let sizes: Vec<(i32, i32)> = templates
+        .iter()
+        .map(|(_, mat)| (mat.cols(), mat.rows()))
+        .collect();
+    let (positions, width, height) = shelf_pack(&sizes, ATLAS_PADDING);
 
-        cache.insert(b64_str.to_string(), new_template.clone());
+    let atlas_type = if has_alpha {
+        core::CV_8UC4
+    } else {
+        core::CV_8UC3
+    };
+    let mut atlas =
+        Mat::new_rows_cols_with_default(height, width, atlas_type, core::Scalar::all(0.0))
+            .map_err(|e| format!("创建图集失败: {e}"))?;
+    let mut entries = BTreeMap::new();
+    for ((name, mat), &(x, y)) in templates.iter().zip(&positions) {
+        let code = match (mat.channels(), has_alpha) {
+            (1, false) => Some(imgproc::COLOR_GRAY2BGR),
+            (1, true) => Some(imgproc::COLOR_GRAY2BGRA),
+            (3, true) => Some(imgproc::COLOR_BGR2BGRA),
+            (4, false) => Some(imgproc::COLOR_BGRA2BGR),
+            _ => None,
+        };
+        let converted = match code {
+            Some(code) => {
+                let mut converted = Mat::default();
+                imgproc::cvt_color(mat, &mut converted, code, 0)
+                    .map_err(|e| format!("转换模板通道失败 {name}: {e}"))?;
+                converted
+            }
+            None => mat.clone(),
+        };
+        let rect = core::Rect::new(x, y, mat.cols(), mat.rows());
+        let mut target =
+            Mat::roi_mut(&mut atlas, rect).map_err(|e| format!("写入图集失败: {e}"))?;
+        converted
+            .copy_to(&mut target)
+            .map_err(|e| format!("写入图集失败 {name}: {e}"))?;
+
+        entries.insert(
+            name.clone(),
+            AtlasTemplate {
+                rect: [x, y, mat.cols(), mat.rows()],
+                threshold: None,
+                roi: None,
+                color_key: None,
+                offset: None,
+                alpha: (has_alpha && mat.channels() != 4).then_some(false),
+            },
+        );
+    }
+
+    if let Some(parent) = manifest_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建输出目录失败: {e}"))?;
     }
+    let image_str = image_path.to_string_lossy();
+    let written = imgcodecs::imwrite(&image_str, &atlas, &core::Vector::new())
+        .map_err(|e| format!("保存图集失败: {e}"))?;
+    if !written {
+        return Err(format!("保存图集失败: {image_str}"));
+    }
+
+    let manifest = TemplateAtlasManifest {
+        image: image_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        templates: entries,
+    };
+    let is_toml = manifest_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    let text = if is_toml {
+        toml::to_string_pretty(&manifest).map_err(|e| format!("序列化清单失败: {e}"))?
+    } else {
+        serde_json::to_string_pretty(&manifest).map_err(|e| format!("序列化清单失败: {e}"))?
+    };
+    std::fs::write(manifest_path, text).map_err(|e| format!("保存清单失败: {e}"))?;
+
+    Ok(AtlasPackResult {
+        manifest_path: manifest_path.to_string_lossy().into_owned(),
+        image_path: image_str.into_owned(),
+        packed_templates: manifest.templates.len(),
+        width,
+        height,
+    })
+}
+
+/// 清空模板缓存与已解析的图集清单，返回清除的模板条目数（命中/未命中等计数保留）
+pub(crate) fn clear_template_cache() -> Result<usize, TemplateError> {
+    ATLAS_MANIFESTS
+        .lock()
+        .map_err(|e| TemplateError::CacheLockFailed(format!("互斥锁: {}", e)))?
+        .clear();
+    Ok(lock_cache()?.clear())
+}
+
+/// 获取模板缓存统计信息
+pub(crate) fn template_cache_stats() -> Result<TemplateCacheStats, TemplateError> {
+    Ok(lock_cache()?.stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{CV_8UC1, Scalar};
+
+    fn mat(size: i32) -> Box<Mat> {
+        Box::new(Mat::new_rows_cols_with_default(size, size, CV_8UC1, Scalar::all(0.0)).unwrap())
+    }
+
+    fn b64(name: &str) -> TemplateKey {
+        TemplateKey::Base64(name.to_string())
+    }
+
+    #[test]
+    fn evicts_least_recently_used_within_budget() {
+        // 每个条目 100 像素 + 1 字节键，预算容纳两个
+        let mut cache = TemplateCache::new(210);
+        cache.insert(b64("a"), mat(10), &[]);
+        cache.insert(b64("b"), mat(10), &[]);
+        assert!(cache.get(&b64("a"), &[]).is_some());
+
+        cache.insert(b64("c"), mat(10), &[]);
+        assert!(cache.get(&b64("b"), &[]).is_none());
+        assert!(cache.get(&b64("a"), &[]).is_some());
+        assert!(cache.get(&b64("c"), &[]).is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.bytes, 202);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!((stats.hits, stats.misses), (3, 1));
 
-    Ok(new_template)
+        // 超出预算的单个条目不缓存
+        cache.insert(b64("big"), mat(20), &[]);
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[test]
+    fn invalidates_when_file_stamp_changes() {
+        let key = TemplateKey::File(PathBuf::from("tpl.png"));
+        let old = FileStamp {
+            modified: None,
+            len: 10,
+        };
+        let new = FileStamp {
+            modified: None,
+            len: 11,
+        };
+        let mut cache = TemplateCache::new(TEMPLATE_CACHE_BUDGET_BYTES);
+        cache.insert(key.clone(), mat(4), &[old]);
+        assert!(cache.get(&key, &[old]).is_some());
+        assert!(cache.get(&key, &[new]).is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 0);
+        assert_eq!(stats.bytes, 0);
+        assert_eq!(stats.invalidations, 1);
+        assert_eq!(cache.clear(), 0);
+    }
+
+    #[test]
+    fn atlas_refs_and_manifests_parse() {
+        assert_eq!(
+            split_atlas_ref("ui/atlas.json#btn_confirm"),
+            Some(("ui/atlas.json", "btn_confirm"))
+        );
+        assert_eq!(split_atlas_ref("ui/a#b.png"), None);
+        assert_eq!(split_atlas_ref("atlas.toml#"), None);
+
+        let json = r##"{"image":"ui.png","templates":{"ok":{"rect":[1,2,3,4],"colorKey":"#FF00FF","offset":[1,1]}}}"##;
+        let from_json = parse_atlas_manifest(Path::new("ui.json"), json).unwrap();
+        let toml = r##"
+image = "ui.png"
+
+[templates.ok]
+rect = [1, 2, 3, 4]
+colorKey = "#FF00FF"
+offset = [1, 1]
+"##;
+        let from_toml = parse_atlas_manifest(Path::new("ui.toml"), toml).unwrap();
+        assert_eq!(from_json, from_toml);
+        let entry = &from_json.templates["ok"];
+        assert_eq!(entry.color_key.as_ref().unwrap().rgb().unwrap(), 0xFF00FF);
+        assert_eq!(entry.threshold, None);
+    }
+
+    #[test]
+    fn shelf_pack_places_without_overlap() {
+        let sizes = [(10, 30), (20, 10), (5, 5), (40, 12), (8, 30)];
+        let (positions, width, height) = shelf_pack(&sizes, 2);
+        for (i, (&(x, y), &(w, h))) in positions.iter().zip(&sizes).enumerate() {
+            assert!(x + w <= width && y + h <= height);
+            for (&(ox, oy), &(ow, oh)) in positions.iter().zip(&sizes).skip(i + 1) {
+                let overlap = x < ox + ow && ox < x + w && y < oy + oh && oy < y + h;
+                assert!(
+                    !overlap,
+                    "{:?} 与 {:?} 重叠",
+                    (x, y, w, h),
+                    (ox, oy, ow, oh)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn packed_atlas_round_trips_through_get_template() {
+        use opencv::core::{CV_8UC3, CV_8UC4};
+
+        let dir = std::env::temp_dir().join(format!("dob-atlas-test-{}", std::process::id()));
+        let source = dir.join("src");
+        std::fs::create_dir_all(&source).unwrap();
+        let red = Mat::new_rows_cols_with_default(6, 9, CV_8UC3, Scalar::new(0.0, 0.0, 255.0, 0.0))
+            .unwrap();
+        let ghost =
+            Mat::new_rows_cols_with_default(4, 4, CV_8UC4, Scalar::new(255.0, 0.0, 0.0, 128.0))
+                .unwrap();
+        for (name, mat) in [("red", &red), ("ghost", &ghost)] {
+            let path = source.join(format!("{name}.png"));
+            imgcodecs::imwrite(&path.to_string_lossy(), mat, &core::Vector::new()).unwrap();
+        }
+
+        let manifest = dir.join("ui.json");
+        let result = pack_template_atlas(&source, &manifest).unwrap();
+        assert_eq!(result.packed_templates, 2);
+
+        let manifest_str = manifest.to_string_lossy();
+        let red_tpl = get_template(&format!("{manifest_str}#red")).unwrap();
+        assert_eq!(
+            (red_tpl.cols(), red_tpl.rows(), red_tpl.channels()),
+            (9, 6, 3)
+        );
+        assert_eq!(
+            *red_tpl.at_2d::<core::Vec3b>(0, 0).unwrap(),
+            core::Vec3b::from([0, 0, 255])
+        );
+        let ghost_tpl = get_template(&format!("{manifest_str}#ghost")).unwrap();
+        assert_eq!(ghost_tpl.channels(), 4);
+        assert_eq!(ghost_tpl.at_2d::<core::Vec4b>(1, 1).unwrap()[3], 128);
+        assert!(matches!(
+            get_template(&format!("{manifest_str}#missing")),
+            Err(TemplateError::AtlasEntryNotFound(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -29,20 +29,227 @@ pub(crate) fn match_template(
     template: &Mat,
     tolerance: f64,
 ) -> Result<Option<(i32, i32)>, MatchError> {
-    if img_bgr.rows() <= 0 || img_bgr.cols() <= 0 {
-        return Err(MatchError::ImageProcessing("源图像尺寸无效".to_string()));
+    match_template_with(img_bgr, template, tolerance, &MatchConfig::default())
+}
+
+/// 模板匹配算法（对应 OpenCV `TM_*`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum MatchMethod {
+    SqDiff,
+    SqDiffNormed,
+    CCorr,
+    CCorrNormed,
+    CCoeff,
+    #[default]
+    CCoeffNormed,
+}
+
+impl MatchMethod {
+    /// 解析算法名称，支持 `sqdiff` / `sqdiff_normed` / `ccorr` / `ccorr_normed` /
+    /// `ccoeff` / `ccoeff_normed`（不区分大小写，可带 `TM_` 前缀）
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        let name = name.strip_prefix("tm_").unwrap_or(&name);
+        match name {
+            "sqdiff" => Some(Self::SqDiff),
+            "sqdiff_normed" => Some(Self::SqDiffNormed),
+            "ccorr" => Some(Self::CCorr),
+            "ccorr_normed" => Some(Self::CCorrNormed),
+            "ccoeff" => Some(Self::CCoeff),
+            "ccoeff_normed" => Some(Self::CCoeffNormed),
+            _ => None,
+        }
     }
-    if template.rows() <= 0 || template.cols() <= 0 {
-        return Err(MatchError::ImageProcessing("模板图像尺寸无效".to_string()));
+
+    fn cv_method(self) -> i32 {
+        match self {
+            Self::SqDiff => imgproc::TM_SQDIFF,
+            Self::SqDiffNormed => imgproc::TM_SQDIFF_NORMED,
+            Self::CCorr => imgproc::TM_CCORR,
+            Self::CCorrNormed => imgproc::TM_CCORR_NORMED,
+            Self::CCoeff => imgproc::TM_CCOEFF,
+            Self::CCoeffNormed => imgproc::TM_CCOEFF_NORMED,
+        }
     }
 
-    // 确保模板尺寸小于源图像
-    if img_bgr.rows() < template.rows() || img_bgr.cols() < template.cols() {
-        return Err(MatchError::ImageProcessing(
-            "模板尺寸大于源图像".to_string(),
-        ));
+    /// 把原始得分线性映射为“越高越好”的得分：`score = raw * alpha + beta`
+    ///
+    /// - 归一化算法：`SQDIFF_NORMED` 取 `1 - raw`，其余保持原值；
+    /// - `SQDIFF`：按参与匹配的样本数（像素 × 通道）与 `255²` 缩放后取 `1 - x`；
+    /// - `CCORR` / `CCOEFF`：除以模板与自身的匹配得分 `self_score`（模板能量）。
+    ///
+    /// 各算法在完全一致时得分均为 1。非归一化相关没有上界，亮度或对比度更高的区域可能略高于 1；
+    /// 模板能量为 0（如纯色模板的 `CCOEFF`）时退回按样本数缩放。
+    fn score_transform(self, samples: f64, self_score: f64) -> (f64, f64) {
+        let full_scale = (samples * 255.0 * 255.0).max(1.0);
+        match self {
+            Self::SqDiff => (-1.0 / full_scale, 1.0),
+            Self::SqDiffNormed => (-1.0, 1.0),
+            Self::CCorr | Self::CCoeff if self_score > f64::EPSILON => (1.0 / self_score, 0.0),
+            Self::CCorr | Self::CCoeff => (1.0 / full_scale, 0.0),
+            Self::CCorrNormed | Self::CCoeffNormed => (1.0, 0.0),
+        }
+    }
+
+    /// 是否需要用模板自身的匹配得分做归一化
+    fn needs_self_score(self) -> bool {
+        matches!(self, Self::CCorr | Self::CCoeff)
+    }
+}
+
+/// 模板匹配前对源图像与模板做的预处理
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) enum MatchPreprocess {
+    /// 直接使用 BGR 原图
+    #[default]
+    None,
+    /// 灰度
+    Gray,
+    /// 灰度后做 Canny 边缘检测
+    Canny { low: f64, high: f64 },
+    /// 灰度后做 CLAHE 自适应直方图均衡
+    Clahe { clip_limit: f64, tile_size: i32 },
+    /// 单通道（0=B, 1=G, 2=R）
+    Channel(i32),
+    /// HLS 色彩空间的亮度通道
+    Lightness,
+}
+
+impl MatchPreprocess {
+    /// 解析预处理名称：`none` / `gray` / `canny` / `clahe` / `lightness` /
+    /// `b` / `g` / `r` / `channel`（参数使用默认值，`channel` 默认取 B 通道）
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "none" | "bgr" => Some(Self::None),
+            "gray" | "grey" => Some(Self::Gray),
+            "canny" | "edge" | "edges" => Some(Self::Canny {
+                low: 50.0,
+                high: 150.0,
+            }),
+            "clahe" => Some(Self::Clahe {
+                clip_limit: 2.0,
+                tile_size: 8,
+            }),
+            "lightness" | "hsl" | "hls" => Some(Self::Lightness),
+            "b" | "blue" | "channel" => Some(Self::Channel(0)),
+            "g" | "green" => Some(Self::Channel(1)),
+            "r" | "red" => Some(Self::Channel(2)),
+            _ => None,
+        }
+    }
+
+    /// 对 BGR（或已是单通道）图像执行预处理
+    fn apply(self, img: &Mat) -> Result<Mat, MatchError> {
+        let gray = |img: &Mat| -> Result<Mat, MatchError> {
+            if img.channels() == 1 {
+                return Ok(img.clone());
+            }
+            let mut gray = Mat::default();
+            imgproc::cvt_color(img, &mut gray, imgproc::COLOR_BGR2GRAY, 0)
+                .map_err(|e| MatchError::OpenCV(format!("cvt_color: {e}")))?;
+            Ok(gray)
+        };
+
+        match self {
+            Self::None => Ok(img.clone()),
+            Self::Gray => gray(img),
+            Self::Canny { low, high } => {
+                let mut edges = Mat::default();
+                imgproc::canny(&gray(img)?, &mut edges, low, high, 3, false)
+                    .map_err(|e| MatchError::OpenCV(format!("canny: {e}")))?;
+                Ok(edges)
+            }
+            Self::Clahe {
+                clip_limit,
+                tile_size,
+            } => {
+                let tile = tile_size.max(1);
+                let mut clahe = imgproc::create_clahe(clip_limit, core::Size::new(tile, tile))
+                    .map_err(|e| MatchError::OpenCV(format!("create_clahe: {e}")))?;
+                let mut equalized = Mat::default();
+                clahe
+                    .apply(&gray(img)?, &mut equalized)
+                    .map_err(|e| MatchError::OpenCV(format!("clahe.apply: {e}")))?;
+                Ok(equalized)
+            }
+            Self::Channel(channel) => {
+                if img.channels() == 1 {
+                    return Ok(img.clone());
+                }
+                if !(0..img.channels()).contains(&channel) {
+                    return Err(MatchError::ImageProcessing(format!(
+                        "通道索引越界: {channel}"
+                    )));
+                }
+                let mut single = Mat::default();
+                core::extract_channel(img, &mut single, channel)
+                    .map_err(|e| MatchError::OpenCV(format!("extract_channel: {e}")))?;
+                Ok(single)
+            }
+            Self::Lightness => {
+                if img.channels() == 1 {
+                    return Ok(img.clone());
+                }
+                let mut hls = Mat::default();
+                imgproc::cvt_color(img, &mut hls, imgproc::COLOR_BGR2HLS, 0)
+                    .map_err(|e| MatchError::OpenCV(format!("cvt_color: {e}")))?;
+                let mut lightness = Mat::default();
+                core::extract_channel(&hls, &mut lightness, 1)
+                    .map_err(|e| MatchError::OpenCV(format!("extract_channel: {e}")))?;
+                Ok(lightness)
+            }
+        }
+    }
+}
+
+/// 模板匹配算法与预处理组合
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) struct MatchConfig {
+    pub method: MatchMethod,
+    pub preprocess: MatchPreprocess,
+}
+
+/// 模板匹配函数（可选算法与预处理）
+///
+/// 得分统一为“越高越好”，默认配置（`TM_CCOEFF_NORMED` + 原图）与 [`match_template`] 一致。
+///
+/// # 参数
+/// * `img_bgr` - 源图像（BGR格式）
+/// * `template` - 模板图像（BGR或BGRA格式）
+/// * `tolerance` - 匹配阈值（归一化后的得分）
+/// * `config` - 匹配算法与预处理
+///
+/// # 返回值
+/// * `Result<Option<(i32, i32)>>` - 匹配位置（x, y）或错误
+pub(crate) fn match_template_with(
+    img_bgr: &Mat,
+    template: &Mat,
+    tolerance: f64,
+    config: &MatchConfig,
+) -> Result<Option<(i32, i32)>, MatchError> {
+    validate_match_inputs(img_bgr, template)?;
+
+    let result = compute_match_scores(img_bgr, template, config)?;
+    match best_finite_score(&result)? {
+        Some((score, loc)) if score > tolerance => Ok(Some((loc.x, loc.y))),
+        _ => Ok(None),
     }
+}
 
+/// 计算模板在源图像上每个位置的得分矩阵（自动处理带透明度的模板，得分越高越好）
+///
+/// 调用方需保证两张图尺寸有效且模板不大于源图像。
+pub(crate) fn compute_match_scores(
+    img_bgr: &Mat,
+    template: &Mat,
+    config: &MatchConfig,
+) -> Result<Mat, MatchError> {
+    let img = config.preprocess.apply(img_bgr)?;
+    score_preprocessed(&img, template, config)
+}
+
+/// 在已预处理的源图像上计算模板得分矩阵（模板在此处做同样的预处理）
+fn score_preprocessed(img: &Mat, template: &Mat, config: &MatchConfig) -> Result<Mat, MatchError> {
     // 检查模板通道数，确定是否需要掩码
     let use_mask = template.channels() == 4;
 
@@ -66,10 +273,18 @@ pub(crate) fn match_template(
     } else {
         template.clone()
     };
+    let template_bgr = config.preprocess.apply(&template_bgr)?;
+    if template_bgr.channels() != img.channels() {
+        return Err(MatchError::ImageProcessing(format!(
+            "预处理后通道数不一致: 源图像 {}，模板 {}",
+            img.channels(),
+            template_bgr.channels()
+        )));
+    }
 
     // 创建结果矩阵
-    let result_cols = img_bgr.cols() - template.cols() + 1;
-    let result_rows = img_bgr.rows() - template.rows() + 1;
+    let result_cols = img.cols() - template.cols() + 1;
+    let result_rows = img.rows() - template.rows() + 1;
     let mut result = unsafe {
         Mat::new_rows_cols(result_rows, result_cols, CV_32F)
             .map_err(|e| MatchError::OpenCV(format!("Mat::new_rows_cols: {e}")))?
@@ -77,33 +292,770 @@ pub(crate) fn match_template(
 
     // 执行模板匹配（带或不带掩码）
     imgproc::match_template(
-        img_bgr,
+        img,
         &template_bgr,
         &mut result,
-        imgproc::TM_CCOEFF_NORMED,
+        config.method.cv_method(),
         &mask,
     )
     .map_err(|e| MatchError::OpenCV(format!("match_template: {e}")))?;
 
-    // 找到最佳匹配位置
-    let mut min_val = 0.0;
-    let mut max_val = 0.0;
-    let mut min_loc = Point::new(0, 0);
-    let mut max_loc = Point::new(0, 0);
-    core::min_max_loc(
+    // 统一为“越高越好”
+    let pixels = if use_mask {
+        core::count_non_zero(&mask)
+            .map_err(|e| MatchError::OpenCV(format!("count_non_zero: {e}")))?
+    } else {
+        template_bgr.rows() * template_bgr.cols()
+    };
+    let self_score = if config.method.needs_self_score() {
+        let mut own = Mat::default();
+        imgproc::match_template(
+            &template_bgr,
+            &template_bgr,
+            &mut own,
+            config.method.cv_method(),
+            &mask,
+        )
+        .map_err(|e| MatchError::OpenCV(format!("match_template: {e}")))?;
+        *own.at_2d::<f32>(0, 0)
+            .map_err(|e| MatchError::OpenCV(format!("at_2d: {e}")))? as f64
+    } else {
+        0.0
+    };
+    let (alpha, beta) = config
+        .method
+        .score_transform(pixels as f64 * template_bgr.channels() as f64, self_score);
+    if alpha != 1.0 || beta != 0.0 {
+        let mut scaled = Mat::default();
+        result
+            .convert_to(&mut scaled, CV_32F, alpha, beta)
+            .map_err(|e| MatchError::OpenCV(format!("convert_to: {e}")))?;
+        result = scaled;
+    }
+
+    Ok(result)
+}
+
+/// 返回得分矩阵中最高的有限得分及其位置（跳过带掩码匹配可能产生的 NaN/Inf）
+fn best_finite_score(result: &Mat) -> Result<Option<(f64, Point)>, MatchError> {
+    let cols = result.cols();
+    let scores = result
+        .data_typed::<f32>()
+        .map_err(|e| MatchError::OpenCV(format!("data_typed: {e}")))?;
+
+    let mut best: Option<(f64, Point)> = None;
+    for (index, &score) in scores.iter().enumerate() {
+        if !score.is_finite() || best.is_some_and(|(b, _)| score as f64 <= b) {
+            continue;
+        }
+        let index = index as i32;
+        best = Some((score as f64, Point::new(index % cols, index / cols)));
+    }
+    Ok(best)
+}
+
+/// 仅校验源图像与模板的尺寸是否有效（不要求模板小于源图像）
+fn validate_match_inputs_size(img_bgr: &Mat, template: &Mat) -> Result<(), MatchError> {
+    if img_bgr.rows() <= 0 || img_bgr.cols() <= 0 {
+        return Err(MatchError::ImageProcessing("源图像尺寸无效".to_string()));
+    }
+    if template.rows() <= 0 || template.cols() <= 0 {
+        return Err(MatchError::ImageProcessing("模板图像尺寸无效".to_string()));
+    }
+    Ok(())
+}
+
+/// 校验源图像与模板尺寸
+fn validate_match_inputs(img_bgr: &Mat, template: &Mat) -> Result<(), MatchError> {
+    validate_match_inputs_size(img_bgr, template)?;
+
+    // 确保模板尺寸小于源图像
+    if img_bgr.rows() < template.rows() || img_bgr.cols() < template.cols() {
+        return Err(MatchError::ImageProcessing(
+            "模板尺寸大于源图像".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 单个模板匹配结果（坐标相对于完整源图像）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TemplateMatch {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    pub score: f64,
+}
+
+/// 多目标模板匹配参数
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MatchAllOptions {
+    /// 最低得分，低于该值的候选会被丢弃
+    pub threshold: f64,
+    /// 最多返回的结果数量，0 表示不限制
+    pub max_results: usize,
+    /// 非极大值抑制的 IoU 阈值，与已保留结果重叠超过该值的候选会被丢弃
+    pub nms_iou: f64,
+    /// 搜索区域（源图像坐标），为空时搜索整张图
+    pub roi: Option<core::Rect>,
+    /// 匹配算法与预处理
+    pub config: MatchConfig,
+}
+
+impl Default for MatchAllOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            max_results: 32,
+            nms_iou: 0.3,
+            roi: None,
+            config: MatchConfig::default(),
+        }
+    }
+}
+
+/// 多目标模板匹配：返回所有得分高于阈值的位置，并按得分降序做 IoU 非极大值抑制
+///
+/// # 参数
+/// * `img_bgr` - 源图像（BGR格式）
+/// * `template` - 模板图像（BGR或BGRA格式）
+/// * `options` - 阈值、数量上限、NMS 与搜索区域设置
+///
+/// # 返回值
+/// * `Result<Vec<TemplateMatch>>` - 按得分降序排列的匹配结果；ROI 与图像无交集或小于模板时返回空列表
+pub(crate) fn match_template_all(
+    img_bgr: &Mat,
+    template: &Mat,
+    options: &MatchAllOptions,
+) -> Result<Vec<TemplateMatch>, MatchError> {
+    let full = core::Rect::new(0, 0, img_bgr.cols(), img_bgr.rows());
+    let search = match options.roi {
+        Some(roi) => intersect_rect(roi, full),
+        None => full,
+    };
+    if search.width < template.cols() || search.height < template.rows() {
+        return Ok(Vec::new());
+    }
+
+    let search_img = crop_to_rect(img_bgr, search, full)?;
+    validate_match_inputs(&search_img, template)?;
+    let result = compute_match_scores(&search_img, template, &options.config)?;
+
+    // 3x3 膨胀后与原值相等的点即局部极大值，避免同一目标周围的大量相邻候选
+    let mut dilated = Mat::default();
+    imgproc::dilate(
         &result,
-        Some(&mut min_val),
-        Some(&mut max_val),
-        Some(&mut min_loc),
-        Some(&mut max_loc),
+        &mut dilated,
         &Mat::default(),
+        Point::new(-1, -1),
+        1,
+        core::BORDER_CONSTANT,
+        imgproc::morphology_default_border_value()
+            .map_err(|e| MatchError::OpenCV(format!("morphology_default_border_value: {e}")))?,
     )
-    .map_err(|e| MatchError::OpenCV(format!("min_max_loc: {e}")))?;
+    .map_err(|e| MatchError::OpenCV(format!("dilate: {e}")))?;
+
+    let (w, h) = (template.cols(), template.rows());
+    let mut candidates = Vec::new();
+    for y in 0..result.rows() {
+        let scores = result
+            .at_row::<f32>(y)
+            .map_err(|e| MatchError::OpenCV(format!("at_row: {e}")))?;
+        let peaks = dilated
+            .at_row::<f32>(y)
+            .map_err(|e| MatchError::OpenCV(format!("at_row: {e}")))?;
+        for (x, (&score, &peak)) in scores.iter().zip(peaks).enumerate() {
+            if !score.is_finite() || (score as f64) < options.threshold || score < peak {
+                continue;
+            }
+            candidates.push(TemplateMatch {
+                x: x as i32 + search.x,
+                y: y + search.y,
+                w,
+                h,
+                score: score as f64,
+            });
+        }
+    }
+
+    Ok(non_max_suppression(
+        candidates,
+        options.nms_iou,
+        options.max_results,
+    ))
+}
+
+/// 求两个矩形的交集，无交集时宽高为 0
+fn intersect_rect(a: core::Rect, b: core::Rect) -> core::Rect {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    let w = ((a.x + a.width).min(b.x + b.width) - x).max(0);
+    let h = ((a.y + a.height).min(b.y + b.height) - y).max(0);
+    core::Rect::new(x, y, w, h)
+}
 
-    // 阈值判断：只返回置信度高于阈值的匹配
-    if max_val > tolerance {
-        Ok(Some((max_loc.x, max_loc.y)))
+/// 按得分降序的贪心非极大值抑制
+fn non_max_suppression(
+    mut candidates: Vec<TemplateMatch>,
+    iou_threshold: f64,
+    max_results: usize,
+) -> Vec<TemplateMatch> {
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut kept: Vec<TemplateMatch> = Vec::new();
+    for candidate in candidates {
+        if max_results > 0 && kept.len() >= max_results {
+            break;
+        }
+        if kept.iter().all(|k| iou(k, &candidate) <= iou_threshold) {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+/// 计算两个矩形的交并比
+fn iou(a: &TemplateMatch, b: &TemplateMatch) -> f64 {
+    let ix = (a.x + a.w).min(b.x + b.w) - a.x.max(b.x);
+    let iy = (a.y + a.h).min(b.y + b.h) - a.y.max(b.y);
+    if ix <= 0 || iy <= 0 {
+        return 0.0;
+    }
+    let inter = (ix as f64) * (iy as f64);
+    let union = (a.w as f64) * (a.h as f64) + (b.w as f64) * (b.h as f64) - inter;
+    if union <= 0.0 { 0.0 } else { inter / union }
+}
+
+/// 多尺度 / 多角度模板匹配参数
+#[derive(Debug, Clone)]
+pub(crate) struct ScaledMatchOptions {
+    /// 最低得分，最佳结果低于该值时返回 `None`
+    pub threshold: f64,
+    /// 模板缩放比例候选（相对模板原始尺寸）
+    pub scales: Vec<f64>,
+    /// 模板旋转角度候选（度，逆时针为正）
+    pub angles: Vec<f64>,
+    /// 粗匹配阶段的图像缩放比例，取值 (0, 1]，1 表示不做粗匹配
+    pub coarse_scale: f64,
+    /// 粗匹配后进入全分辨率精修的候选数量
+    pub refine_candidates: usize,
+    /// 搜索区域（源图像坐标），为空时搜索整张图
+    pub roi: Option<core::Rect>,
+    /// 匹配算法与预处理
+    pub config: MatchConfig,
+}
+
+impl Default for ScaledMatchOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            scales: Self::scale_range(0.5, 2.0, 0.1),
+            angles: vec![0.0],
+            coarse_scale: 0.5,
+            refine_candidates: 3,
+            roi: None,
+            config: MatchConfig::default(),
+        }
+    }
+}
+
+impl ScaledMatchOptions {
+    /// 生成 `[min, max]` 区间内按 `step` 递增的缩放比例列表（包含两端）
+    pub(crate) fn scale_range(min: f64, max: f64, step: f64) -> Vec<f64> {
+        let finite = min.is_finite() && max.is_finite() && step.is_finite();
+        if !finite || min <= 0.0 || max < min || step <= 0.0 {
+            return vec![1.0];
+        }
+        let count = ((max - min) / step + 1e-9).floor() as usize;
+        let mut scales: Vec<f64> = (0..=count)
+            .map(|i| ((min + step * i as f64) * 1e6).round() / 1e6)
+            .collect();
+        if scales.last().is_some_and(|&last| (max - last) > 1e-6) {
+            scales.push(max);
+        }
+        scales
+    }
+}
+
+/// 多尺度 / 多角度模板匹配结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ScaledMatch {
+    /// 变换后模板外接矩形（源图像坐标）
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    pub score: f64,
+    /// 命中的缩放比例
+    pub scale: f64,
+    /// 命中的旋转角度（度）
+    pub angle: f64,
+}
+
+/// 粗匹配阶段单个 (缩放, 角度) 组合的最佳位置
+#[derive(Debug, Clone, Copy)]
+struct CoarseCandidate {
+    scale: f64,
+    angle: f64,
+    score: f64,
+    /// 全分辨率坐标下的估计左上角
+    x: i32,
+    y: i32,
+}
+
+/// 粗匹配时模板最短边的下限，再小的模板改为直接全分辨率匹配
+const MIN_COARSE_TEMPLATE_SIDE: i32 = 8;
+
+/// 多尺度 / 多角度模板匹配（由粗到细）
+///
+/// 先在按 `coarse_scale` 缩小的图像上对所有 (缩放, 角度) 组合做整图匹配，
+/// 再取得分最高的若干组合回到全分辨率，在粗定位附近的小窗口内精修，
+/// 精修时额外尝试相邻缩放比例的中点以弥补步长误差。
+///
+/// # 参数
+/// * `img_bgr` - 源图像（BGR格式）
+/// * `template` - 模板图像（BGR或BGRA格式）
+/// * `options` - 缩放/角度候选、粗匹配比例与搜索区域
+///
+/// # 返回值
+/// * `Result<Option<ScaledMatch>>` - 最佳匹配（含缩放与角度），低于阈值时为 `None`
+pub(crate) fn match_template_scaled(
+    img_bgr: &Mat,
+    template: &Mat,
+    options: &ScaledMatchOptions,
+) -> Result<Option<ScaledMatch>, MatchError> {
+    validate_match_inputs_size(img_bgr, template)?;
+    let full = core::Rect::new(0, 0, img_bgr.cols(), img_bgr.rows());
+    let search = match options.roi {
+        Some(roi) => intersect_rect(roi, full),
+        None => full,
+    };
+    if search.width <= 0 || search.height <= 0 {
+        return Ok(None);
+    }
+    let search_img = crop_to_rect(img_bgr, search, full)?;
+
+    let mut scales: Vec<f64> = options
+        .scales
+        .iter()
+        .copied()
+        .filter(|s| s.is_finite() && *s > 0.0)
+        .collect();
+    scales.sort_by(f64::total_cmp);
+    scales.dedup();
+    if scales.is_empty() {
+        scales.push(1.0);
+    }
+    let angles: Vec<f64> = if options.angles.is_empty() {
+        vec![0.0]
+    } else {
+        options.angles.clone()
+    };
+
+    // 粗匹配：缩小整图后评估所有组合
+    let coarse = if options.coarse_scale.is_finite() {
+        options.coarse_scale.clamp(0.05, 1.0)
+    } else {
+        1.0
+    };
+    let coarse_img = if coarse < 1.0 {
+        options
+            .config
+            .preprocess
+            .apply(&resize_by(&search_img, coarse)?)?
+    } else {
+        Mat::default()
+    };
+    let search_img = options.config.preprocess.apply(&search_img)?;
+
+    let mut candidates = Vec::new();
+    for &scale in &scales {
+        for &angle in &angles {
+            let min_side = (template.cols().min(template.rows()) as f64 * scale * coarse) as i32;
+            let (img, factor) = if coarse < 1.0 && min_side >= MIN_COARSE_TEMPLATE_SIDE {
+                (&coarse_img, coarse)
+            } else {
+                (&search_img, 1.0)
+            };
+            let tpl = transform_template(template, scale * factor, angle)?;
+            let Some((score, loc)) = best_score_in(img, &tpl, &options.config)? else {
+                continue;
+            };
+            candidates.push(CoarseCandidate {
+                scale,
+                angle,
+                score,
+                x: (loc.x as f64 / factor).round() as i32,
+                y: (loc.y as f64 / factor).round() as i32,
+            });
+        }
+    }
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates.truncate(options.refine_candidates.max(1));
+
+    // 精修：全分辨率下在粗定位附近搜索，同时尝试相邻缩放比例的中点
+    let margin = ((1.0 / coarse).ceil() as i32) * 2 + 2;
+    let search_full = core::Rect::new(0, 0, search.width, search.height);
+    let mut best: Option<ScaledMatch> = None;
+    for candidate in candidates {
+        let index = scales
+            .iter()
+            .position(|&s| s == candidate.scale)
+            .unwrap_or(0);
+        let mut refine_scales = vec![candidate.scale];
+        if coarse < 1.0 {
+            if index > 0 {
+                refine_scales.push((scales[index - 1] + candidate.scale) / 2.0);
+            }
+            if index + 1 < scales.len() {
+                refine_scales.push((scales[index + 1] + candidate.scale) / 2.0);
+            }
+        }
+
+        for scale in refine_scales {
+            let tpl = transform_template(template, scale, candidate.angle)?;
+            let window = intersect_rect(
+                core::Rect::new(
+                    candidate.x - margin,
+                    candidate.y - margin,
+                    tpl.cols() + margin * 2,
+                    tpl.rows() + margin * 2,
+                ),
+                search_full,
+            );
+            if window.width < tpl.cols() || window.height < tpl.rows() {
+                continue;
+            }
+            let window_img = crop_to_rect(&search_img, window, search_full)?;
+            let Some((score, loc)) = best_score_in(&window_img, &tpl, &options.config)? else {
+                continue;
+            };
+            if best.is_none_or(|b| score > b.score) {
+                best = Some(ScaledMatch {
+                    x: search.x + window.x + loc.x,
+                    y: search.y + window.y + loc.y,
+                    w: tpl.cols(),
+                    h: tpl.rows(),
+                    score,
+                    scale,
+                    angle: candidate.angle,
+                });
+            }
+        }
+    }
+
+    Ok(best.filter(|b| b.score > options.threshold))
+}
+
+/// 裁剪出 `rect` 区域的独立副本；`rect` 覆盖整图时直接克隆
+fn crop_to_rect(img: &Mat, rect: core::Rect, full: core::Rect) -> Result<Mat, MatchError> {
+    if rect == full {
+        return Ok(img.clone());
+    }
+    Mat::roi(img, rect)
+        .and_then(|roi| roi.try_clone())
+        .map_err(|e| MatchError::OpenCV(format!("roi: {e}")))
+}
+
+/// 按比例缩放图像（缩小使用 INTER_AREA，放大使用 INTER_LINEAR）
+fn resize_by(img: &Mat, factor: f64) -> Result<Mat, MatchError> {
+    let size = core::Size::new(
+        ((img.cols() as f64 * factor).round() as i32).max(1),
+        ((img.rows() as f64 * factor).round() as i32).max(1),
+    );
+    let interpolation = if factor < 1.0 {
+        imgproc::INTER_AREA
     } else {
-        Ok(None)
+        imgproc::INTER_LINEAR
+    };
+    let mut resized = Mat::default();
+    imgproc::resize(img, &mut resized, size, 0.0, 0.0, interpolation)
+        .map_err(|e| MatchError::OpenCV(format!("resize: {e}")))?;
+    Ok(resized)
+}
+
+/// 缩放并旋转模板
+///
+/// 旋转时画布扩展到外接矩形，并输出 BGRA：旋转后空出的角落 alpha 为 0，
+/// 由 `score_preprocessed` 作为掩码忽略。
+fn transform_template(template: &Mat, scale: f64, angle: f64) -> Result<Mat, MatchError> {
+    let scaled = if (scale - 1.0).abs() < 1e-9 {
+        template.clone()
+    } else {
+        resize_by(template, scale)?
+    };
+    if angle.rem_euclid(360.0).abs() < 1e-9 {
+        return Ok(scaled);
+    }
+
+    let bgra = if scaled.channels() == 4 {
+        scaled
+    } else {
+        let mut bgra = Mat::default();
+        imgproc::cvt_color(&scaled, &mut bgra, imgproc::COLOR_BGR2BGRA, 0)
+            .map_err(|e| MatchError::OpenCV(format!("cvt_color: {e}")))?;
+        bgra
+    };
+
+    let (w, h) = (bgra.cols() as f64, bgra.rows() as f64);
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let bw = (h * sin.abs() + w * cos.abs()).round().max(1.0);
+    let bh = (h * cos.abs() + w * sin.abs()).round().max(1.0);
+    let center = core::Point2f::new(((w - 1.0) / 2.0) as f32, ((h - 1.0) / 2.0) as f32);
+    let mut matrix = imgproc::get_rotation_matrix_2d(center, angle, 1.0)
+        .map_err(|e| MatchError::OpenCV(format!("get_rotation_matrix_2d: {e}")))?;
+    // 平移到外接矩形中心
+    *matrix
+        .at_2d_mut::<f64>(0, 2)
+        .map_err(|e| MatchError::OpenCV(format!("at_2d_mut: {e}")))? +=
+        (bw - 1.0) / 2.0 - (w - 1.0) / 2.0;
+    *matrix
+        .at_2d_mut::<f64>(1, 2)
+        .map_err(|e| MatchError::OpenCV(format!("at_2d_mut: {e}")))? +=
+        (bh - 1.0) / 2.0 - (h - 1.0) / 2.0;
+
+    let mut rotated = Mat::default();
+    imgproc::warp_affine(
+        &bgra,
+        &mut rotated,
+        &matrix,
+        core::Size::new(bw as i32, bh as i32),
+        imgproc::INTER_LINEAR,
+        core::BORDER_CONSTANT,
+        core::Scalar::all(0.0),
+    )
+    .map_err(|e| MatchError::OpenCV(format!("warp_affine: {e}")))?;
+    Ok(rotated)
+}
+
+/// 在已预处理的图像上匹配模板，返回最高的有限得分及其位置；模板大于图像时返回 `None`
+fn best_score_in(
+    img: &Mat,
+    template: &Mat,
+    config: &MatchConfig,
+) -> Result<Option<(f64, Point)>, MatchError> {
+    if template.cols() > img.cols() || template.rows() > img.rows() {
+        return Ok(None);
+    }
+    let result = score_preprocessed(img, template, config)?;
+    best_finite_score(&result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{CV_8UC3, Scalar, Vec3b};
+
+    /// 生成带棋盘格与渐变纹理的模板
+    fn textured_template(size: i32) -> Mat {
+        let cell = (size / 4).max(1);
+        let step = 240 / size;
+        let mut template =
+            Mat::new_rows_cols_with_default(size, size, CV_8UC3, Scalar::all(0.0)).unwrap();
+        for y in 0..size {
+            for x in 0..size {
+                let v = if (x / cell + y / cell) % 2 == 0 {
+                    230
+                } else {
+                    20
+                };
+                *template.at_2d_mut::<Vec3b>(y, x).unwrap() =
+                    Vec3b::from([v, (x * step) as u8, (y * step) as u8]);
+            }
+        }
+        template
+    }
+
+    /// 生成确定性噪声背景
+    fn noise_scene(cols: i32, rows: i32) -> Mat {
+        let mut scene =
+            Mat::new_rows_cols_with_default(rows, cols, CV_8UC3, Scalar::all(0.0)).unwrap();
+        for y in 0..rows {
+            for x in 0..cols {
+                let n = ((x * 37 + y * 91) % 53) as u8 + 100;
+                *scene.at_2d_mut::<Vec3b>(y, x).unwrap() = Vec3b::from([n, n / 2, 255 - n]);
+            }
+        }
+        scene
+    }
+
+    /// 把 `patch` 贴到 `scene` 的 `(px, py)` 处
+    fn paste(scene: &mut Mat, patch: &Mat, px: i32, py: i32) {
+        for y in 0..patch.rows() {
+            for x in 0..patch.cols() {
+                *scene.at_2d_mut::<Vec3b>(py + y, px + x).unwrap() =
+                    *patch.at_2d::<Vec3b>(y, x).unwrap();
+            }
+        }
+    }
+
+    /// 生成带纹理的测试图：背景为确定性噪声，在指定位置贴入同一个模板
+    fn synthetic_scene(positions: &[(i32, i32)]) -> (Mat, Mat) {
+        let template = textured_template(12);
+        let mut scene = noise_scene(120, 80);
+        for &(px, py) in positions {
+            paste(&mut scene, &template, px, py);
+        }
+        (scene, template)
+    }
+
+    #[test]
+    fn match_template_all_finds_every_instance() {
+        let positions = [(5, 5), (60, 10), (30, 50), (100, 60)];
+        let (scene, template) = synthetic_scene(&positions);
+
+        let matches = match_template_all(&scene, &template, &MatchAllOptions::default()).unwrap();
+        let mut found: Vec<(i32, i32)> = matches.iter().map(|m| (m.x, m.y)).collect();
+        found.sort();
+        let mut expected = positions.to_vec();
+        expected.sort();
+        assert_eq!(found, expected);
+        assert!(matches.iter().all(|m| m.w == 12 && m.h == 12));
+
+        let limited = match_template_all(
+            &scene,
+            &template,
+            &MatchAllOptions {
+                max_results: 2,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(limited.len(), 2);
+
+        let in_roi = match_template_all(
+            &scene,
+            &template,
+            &MatchAllOptions {
+                roi: Some(core::Rect::new(50, 0, 40, 40)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            in_roi.iter().map(|m| (m.x, m.y)).collect::<Vec<_>>(),
+            vec![(60, 10)]
+        );
+    }
+
+    #[test]
+    fn non_max_suppression_drops_overlapping_boxes() {
+        let rect = |x, score| TemplateMatch {
+            x,
+            y: 0,
+            w: 10,
+            h: 10,
+            score,
+        };
+        assert_eq!(iou(&rect(0, 1.0), &rect(5, 1.0)), 50.0 / 150.0);
+        assert_eq!(iou(&rect(0, 1.0), &rect(10, 1.0)), 0.0);
+
+        let kept = non_max_suppression(vec![rect(0, 0.9), rect(2, 0.95), rect(20, 0.85)], 0.3, 0);
+        assert_eq!(kept, vec![rect(2, 0.95), rect(20, 0.85)]);
+    }
+
+    #[test]
+    fn match_template_scaled_recovers_scale() {
+        let template = textured_template(24);
+        let mut scene = noise_scene(200, 150);
+        paste(&mut scene, &resize_by(&template, 1.5).unwrap(), 90, 60);
+
+        let options = ScaledMatchOptions {
+            scales: ScaledMatchOptions::scale_range(0.5, 2.0, 0.25),
+            ..Default::default()
+        };
+        let found = match_template_scaled(&scene, &template, &options)
+            .unwrap()
+            .expect("应命中放大后的模板");
+        assert_eq!((found.x, found.y, found.w, found.h), (90, 60, 36, 36));
+        assert_eq!(found.scale, 1.5);
+        assert_eq!(found.angle, 0.0);
+        assert!(found.score > 0.95);
+    }
+
+    #[test]
+    fn match_template_scaled_recovers_rotation() {
+        let template = textured_template(24);
+        let mut rotated = Mat::default();
+        core::rotate(&template, &mut rotated, core::ROTATE_90_COUNTERCLOCKWISE).unwrap();
+        let mut scene = noise_scene(200, 150);
+        paste(&mut scene, &rotated, 40, 30);
+
+        let options = ScaledMatchOptions {
+            scales: vec![1.0],
+            angles: vec![0.0, 90.0],
+            ..Default::default()
+        };
+        let found = match_template_scaled(&scene, &template, &options)
+            .unwrap()
+            .expect("应命中旋转后的模板");
+        assert_eq!((found.x, found.y), (40, 30));
+        assert_eq!(found.angle, 90.0);
+    }
+
+    #[test]
+    fn scale_range_includes_both_ends() {
+        assert_eq!(
+            ScaledMatchOptions::scale_range(0.5, 1.0, 0.2),
+            vec![0.5, 0.7, 0.9, 1.0]
+        );
+        assert_eq!(ScaledMatchOptions::scale_range(1.0, 0.5, 0.1), vec![1.0]);
+    }
+
+    #[test]
+    fn every_method_scores_exact_match_highest() {
+        let (scene, template) = synthetic_scene(&[(70, 40)]);
+        for method in [
+            MatchMethod::SqDiff,
+            MatchMethod::SqDiffNormed,
+            MatchMethod::CCorr,
+            MatchMethod::CCorrNormed,
+            MatchMethod::CCoeff,
+            MatchMethod::CCoeffNormed,
+        ] {
+            let config = MatchConfig {
+                method,
+                ..Default::default()
+            };
+            let scores = compute_match_scores(&scene, &template, &config).unwrap();
+            let (score, loc) = best_finite_score(&scores).unwrap().unwrap();
+            assert_eq!((loc.x, loc.y), (70, 40), "{method:?}");
+            assert!((score - 1.0).abs() < 1e-3, "{method:?}: {score}");
+        }
+    }
+
+    #[test]
+    fn pointwise_preprocess_keeps_match_position() {
+        let (scene, template) = synthetic_scene(&[(15, 50)]);
+        for preprocess in [
+            MatchPreprocess::Gray,
+            MatchPreprocess::Channel(2),
+            MatchPreprocess::Lightness,
+        ] {
+            let config = MatchConfig {
+                preprocess,
+                ..Default::default()
+            };
+            let found = match_template_with(&scene, &template, 0.9, &config).unwrap();
+            assert_eq!(found, Some((15, 50)), "{preprocess:?}");
+        }
+    }
+
+    #[test]
+    fn match_config_names_parse() {
+        assert_eq!(
+            MatchMethod::from_name("TM_SQDIFF_NORMED"),
+            Some(MatchMethod::SqDiffNormed)
+        );
+        assert_eq!(MatchMethod::from_name("ccorr"), Some(MatchMethod::CCorr));
+        assert_eq!(MatchMethod::from_name("unknown"), None);
+        assert_eq!(
+            MatchPreprocess::from_name("R"),
+            Some(MatchPreprocess::Channel(2))
+        );
+        assert!(matches!(
+            MatchPreprocess::from_name("canny"),
+            Some(MatchPreprocess::Canny { .. })
+        ));
     }
 }
@@ -1,4 +1,5 @@
 use crate::submodules::d3d11::{self, create_d3d_device};
+use crate::submodules::frame_source::with_frame_source;
 use crate::submodules::script::should_stop_current_script;
 use opencv::{
     core::{CV_8UC4, Mat},
@@ -307,14 +308,35 @@ fn normalize_roi_in_bounds(
     Some((start_x, start_y, out_w, out_h))
 }
 
-/// 对已有 Mat 按 ROI 裁剪并返回新图像。
-fn crop_mat_with_roi(mat: &Mat, roi: (i32, i32, i32, i32)) -> Option<Box<Mat>> {
+/// 对已有 Mat 按 ROI 裁剪并写入 `out`（尺寸与类型一致时复用其内存）。
+fn crop_mat_with_roi_into(mat: &Mat, roi: (i32, i32, i32, i32), out: &mut Mat) -> Option<()> {
     let (x, y, w, h) = normalize_roi_in_bounds(mat.cols(), mat.rows(), Some(roi))?;
     let roi_rect = opencv::core::Rect::new(x, y, w, h);
     let roi_view = mat.roi(roi_rect).ok()?;
-    let mut out = Mat::default();
-    roi_view.copy_to(&mut out).ok()?;
-    Some(Box::new(out))
+    roi_view.copy_to(out).ok()
+}
+
+/// 从虚拟窗口（帧源）取帧并写入 `out`，ROI 规则与真实窗口截图一致。
+///
+/// 返回 `None` 表示 `hwnd` 不是虚拟窗口，调用方应继续走真实截图流程。
+fn capture_virtual_window_into(
+    hwnd: HWND,
+    roi: Option<(i32, i32, i32, i32)>,
+    out: &mut Mat,
+) -> Option<bool> {
+    with_frame_source(hwnd.0 as isize, |source| {
+        let captured = source.capture(|frame| match roi {
+            Some(roi_rect) => crop_mat_with_roi_into(frame, roi_rect, out),
+            None => frame.copy_to(out).ok(),
+        });
+        match captured {
+            Ok(result) => result.is_some(),
+            Err(e) => {
+                eprintln!("读取帧源失败: {e}");
+                false
+            }
+        }
+    })
 }
 
 fn create_capture_item(hwnd: HWND) -> Option<GraphicsCaptureItem> {
@@ -413,6 +435,8 @@ struct WgcContext {
     last_frame_ticks: Option<i64>,
     // 复用 CPU 可读纹理，减少每帧 Staging 资源创建开销
     staging_surface: Option<CpuStagingSurface>,
+    // 复用 BGRA 中间缓冲，避免每帧重新分配
+    bgra_buffer: Mat,
     // WGC 到帧事件通知（序号 + 条件变量），用于事件驱动等待，降低轮询 CPU 占用。
     frame_arrived_signal: Arc<(Mutex<u64>, Condvar)>,
     // 已消费的到帧事件序号。
@@ -472,6 +496,7 @@ impl WgcContext {
             cached_at: None,
             last_frame_ticks: None,
             staging_surface: None,
+            bgra_buffer: Mat::default(),
             frame_arrived_signal,
             frame_arrived_seen: 0,
             frame_arrived_token,
@@ -519,15 +544,17 @@ impl WgcContext {
         }
     }
 
-    /// 处理单帧数据并更新缓存。
+    /// 处理单帧数据并更新缓存，结果写入 `out`。
     ///
-    /// - `roi` 为 `None` 时返回完整客户区图像；
-    /// - `roi` 为 `Some(x,y,w,h)` 时，直接在拷贝阶段输出 ROI 小图。
-    fn process_new_frame(
+    /// - `roi` 为 `None` 时输出完整客户区图像；
+    /// - `roi` 为 `Some(x,y,w,h)` 时，直接在拷贝阶段输出 ROI 小图；
+    /// - `out` 尺寸与类型不变时复用其内存。
+    fn process_new_frame_into(
         &mut self,
         frame: Direct3D11CaptureFrame,
         roi: Option<(i32, i32, i32, i32)>,
-    ) -> Option<Box<Mat>> {
+        out: &mut Mat,
+    ) -> Option<()> {
         // WGC 帧时间戳（100ns 单位），用于跨调用判断是否为“新帧”。
         let frame_ticks = frame.SystemRelativeTime().ok().map(|ts| ts.Duration);
 
@@ -619,8 +646,11 @@ impl WgcContext {
         // 计算最终输出 ROI（相对客户区），并在拷贝阶段直接输出小图，避免二次裁剪。
         let (roi_x, roi_y, roi_w, roi_h) = normalize_roi_in_bounds(client_w, client_h, roi)?;
 
-        // 创建 BGRA Mat 并完成客户端/ROI区域裁剪复制
-        let mut mat_bgra = unsafe { Mat::new_rows_cols(roi_h, roi_w, CV_8UC4).ok()? };
+        // 复用 BGRA 中间缓冲并完成客户端/ROI区域裁剪复制，仅在尺寸变化时重新分配
+        if self.bgra_buffer.rows() != roi_h || self.bgra_buffer.cols() != roi_w {
+            self.bgra_buffer = unsafe { Mat::new_rows_cols(roi_h, roi_w, CV_8UC4).ok()? };
+        }
+        let mat_bgra = &mut self.bgra_buffer;
 
         let src_stride = mapped.RowPitch as usize;
         let dst_stride = roi_w as usize * 4;
@@ -649,23 +679,25 @@ impl WgcContext {
         }
 
         // WGC 原始帧为 BGRA(4 通道)，为保持脚本侧一致性统一转换为 BGR(3 通道)
-        let mut mat_bgr = Mat::default();
-        imgproc::cvt_color(&mat_bgra, &mut mat_bgr, imgproc::COLOR_BGRA2BGR, 0).ok()?;
+        imgproc::cvt_color(&*mat_bgra, out, imgproc::COLOR_BGRA2BGR, 0).ok()?;
 
-        let boxed_mat = Box::new(mat_bgr);
         // 仅完整客户区截图更新缓存，避免 ROI 小图污染缓存。
         if roi.is_none() {
-            self.cached_mat = Some(boxed_mat.clone());
+            self.cached_mat = Some(Box::new(out.try_clone().ok()?));
             self.cached_at = Some(Instant::now());
         }
         if let Some(ticks) = frame_ticks {
             self.last_frame_ticks = Some(ticks);
         }
-        Some(boxed_mat)
+        Some(())
     }
 
-    /// 捕获窗口图像，可选 ROI 直接裁剪。
-    fn capture_with_roi(&mut self, roi: Option<(i32, i32, i32, i32)>) -> Option<Box<Mat>> {
+    /// 捕获窗口图像并写入 `out`，可选 ROI 直接裁剪；`out` 尺寸与类型不变时复用其内存。
+    fn capture_with_roi_into(
+        &mut self,
+        roi: Option<(i32, i32, i32, i32)>,
+        out: &mut Mat,
+    ) -> Option<()> {
         // 收到脚本停止请求时立即退出，避免无意义的采集与转换开销。
         if should_stop_current_script() {
             return None;
@@ -675,12 +707,10 @@ impl WgcContext {
         let current_size = match self.item.Size() {
             Ok(s) => s,
             Err(_) => {
-                return if let Some(roi_rect) = roi {
-                    self.cached_mat
-                        .as_ref()
-                        .and_then(|cached| crop_mat_with_roi(cached.as_ref(), roi_rect))
-                } else {
-                    self.cached_mat.clone()
+                let cached = self.cached_mat.as_ref()?;
+                return match roi {
+                    Some(roi_rect) => crop_mat_with_roi_into(cached, roi_rect, out),
+                    None => cached.copy_to(out).ok(),
                 };
             } // 窗口可能被关闭了
         };
@@ -772,19 +802,16 @@ impl WgcContext {
 
         if latest_frame.is_some() && latest_ticks > baseline_ticks {
             if let Some(frame) = latest_frame {
-                return self.process_new_frame(frame, roi);
+                return self.process_new_frame_into(frame, roi, out);
             }
         }
 
         // 3. 短间隔优先吞吐：未拿到新帧时直接回退缓存，避免每轮都走 GDI 导致 FPS 降低。
-        if !long_gap {
-            if let Some(roi_rect) = roi {
-                if let Some(cached) = self.cached_mat.as_ref() {
-                    return crop_mat_with_roi(cached.as_ref(), roi_rect);
-                }
-            } else if let Some(cached) = self.cached_mat.as_ref() {
-                return Some(cached.clone());
-            }
+        if !long_gap && let Some(cached) = self.cached_mat.as_ref() {
+            return match roi {
+                Some(roi_rect) => crop_mat_with_roi_into(cached, roi_rect, out),
+                None => cached.copy_to(out).ok(),
+            };
         }
 
         // 4. 长间隔或无缓存时，回退到 GDI 强制抓当前帧，避免滞后图像。
@@ -799,16 +826,15 @@ impl WgcContext {
         } {
             self.cached_mat = Some(mat.clone());
             self.cached_at = Some(Instant::now());
-            return Some(mat);
+            *out = *mat;
+            return Some(());
         }
 
         // 5. 最后兜底：返回最近缓存
-        if let Some(roi_rect) = roi {
-            self.cached_mat
-                .as_ref()
-                .and_then(|cached| crop_mat_with_roi(cached.as_ref(), roi_rect))
-        } else {
-            self.cached_mat.clone()
+        let cached = self.cached_mat.as_ref()?;
+        match roi {
+            Some(roi_rect) => crop_mat_with_roi_into(cached, roi_rect, out),
+            None => cached.copy_to(out).ok(),
         }
     }
 }
@@ -846,8 +872,24 @@ fn capture_window_wgc_roi_internal(
     hwnd: HWND,
     roi: Option<(i32, i32, i32, i32)>,
 ) -> Option<Box<Mat>> {
+    let mut out = Mat::default();
+    capture_window_wgc_into(hwnd, roi, &mut out).then(|| Box::new(out))
+}
+
+/// WGC 截图并写入调用方提供的缓冲（可选 ROI）。
+///
+/// 说明：轮询等待等高频场景可反复传入同一个 `out`，尺寸不变时不会重新分配图像内存。
+/// 返回 `false` 表示截图失败或脚本已请求停止，此时 `out` 内容未定义。
+pub(crate) fn capture_window_wgc_into(
+    hwnd: HWND,
+    roi: Option<(i32, i32, i32, i32)>,
+    out: &mut Mat,
+) -> bool {
     if should_stop_current_script() {
-        return None;
+        return false;
+    }
+    if let Some(captured) = capture_virtual_window_into(hwnd, roi, out) {
+        return captured;
     }
 
     CAPTURER.with(|cell| {
@@ -866,7 +908,7 @@ fn capture_window_wgc_roi_internal(
                 Some(ctx) => *capturer_opt = Some(ctx),
                 None => {
                     eprintln!("初始化 WGC 失败");
-                    return None;
+                    return false;
                 }
             }
 
@@ -879,21 +921,21 @@ fn capture_window_wgc_roi_internal(
 
         // 2. 执行捕获
         if let Some(ctx) = capturer_opt.as_mut() {
-            let result = ctx.capture_with_roi(roi);
+            let result = ctx.capture_with_roi_into(roi, out);
 
             // 如果第一次捕获就因为没有帧而失败（Result None, Cache None）
             // 我们可以尝试再等一下并在内部重试
             if result.is_none() && ctx.cached_mat.is_none() {
                 if should_stop_current_script() {
-                    return None;
+                    return false;
                 }
                 std::thread::sleep(std::time::Duration::from_millis(50));
-                return ctx.capture_with_roi(roi);
+                return ctx.capture_with_roi_into(roi, out).is_some();
             }
-            return result;
+            return result.is_some();
         }
 
-        None
+        false
     })
 }
 
@@ -912,6 +954,10 @@ fn capture_window_with_roi_internal(
     hwnd: HWND,
     roi: Option<(i32, i32, i32, i32)>,
 ) -> Option<Box<Mat>> {
+    let mut virtual_frame = Mat::default();
+    if let Some(captured) = capture_virtual_window_into(hwnd, roi, &mut virtual_frame) {
+        return captured.then(|| Box::new(virtual_frame));
+    }
     unsafe {
         // 获取窗口矩形
         let (window_rect, client_rect, offset_x, offset_y) = match get_window_and_client_rect(hwnd)
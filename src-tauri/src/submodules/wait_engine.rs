@@ -0,0 +1,558 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use opencv::core::Mat;
+use regex::Regex;
+
+use crate::submodules::{
+    color::{ColorDistance, rgb_to_lab},
+    color_match::{check_color_distance, check_color_mat},
+    ocr::{OcrOptions, ocr_text_from_mat},
+    script_vision::{
+        ImageHashKind, hamming_distance_hex, normalize_hash_hex, perceptual_hash_impl,
+    },
+    tpl_match::{MatchAllOptions, MatchConfig, TemplateMatch, match_template_all},
+};
+
+/// 轮询间隔内检查停止请求的最大粒度，避免长间隔时停止响应变慢。
+const STOP_CHECK_SLICE: Duration = Duration::from_millis(30);
+
+/// 颜色条件中的单个取色点（坐标相对客户区）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ColorPoint {
+    pub x: i32,
+    pub y: i32,
+    /// 目标颜色（0xRRGGBB）
+    pub color: u32,
+}
+
+/// 轮询等待的条件
+#[derive(Debug)]
+pub(crate) enum WaitCondition {
+    /// 模板出现，得分高于 `threshold`
+    Template {
+        template: Mat,
+        threshold: f64,
+        config: MatchConfig,
+    },
+    /// 所有取色点同时满足颜色条件（`Rgb` 下 `tolerance` 为逐通道容差，ΔE 下为最大色差）
+    Colors {
+        points: Vec<ColorPoint>,
+        tolerance: f64,
+        distance: ColorDistance,
+    },
+    /// 画面感知哈希与目标哈希的汉明距离不超过 `max_distance`
+    Hash {
+        hash: String,
+        kind: ImageHashKind,
+        color: bool,
+        max_distance: u32,
+    },
+    /// OCR 识别文本匹配正则（`options` 可限定解码字符集）
+    Text { pattern: Regex, options: OcrOptions },
+}
+
+/// 条件命中时的详情
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum WaitMatch {
+    /// 模板位置（客户区坐标）与得分
+    Template(TemplateMatch),
+    /// 所有取色点均已满足
+    Colors,
+    /// 当前画面哈希与距离
+    Hash { hash: String, distance: u32 },
+    /// OCR 全文、正则命中的文本与各捕获组（未参与匹配的组为 `None`）
+    Text {
+        text: String,
+        matched: String,
+        groups: Vec<Option<String>>,
+    },
+}
+
+/// 轮询参数
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WaitOptions {
+    /// 截图左上角在客户区中的坐标（按 ROI 截图时为 ROI 起点），用于换算取色点与命中位置
+    pub origin: (i32, i32),
+    /// 两次截图之间的间隔
+    pub interval: Duration,
+    /// 超时时间
+    pub timeout: Duration,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        Self {
+            origin: (0, 0),
+            interval: Duration::from_millis(50),
+            timeout: Duration::from_millis(20_000),
+        }
+    }
+}
+
+/// 轮询结果
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WaitOutcome {
+    /// 命中详情；超时或被停止时为 `None`
+    pub found: Option<WaitMatch>,
+    /// 成功截图并检查的次数
+    pub polls: u32,
+    /// 总耗时
+    pub elapsed: Duration,
+}
+
+impl WaitCondition {
+    /// 构造哈希条件，目标哈希会先标准化为小写十六进制。
+    pub(crate) fn hash(
+        hash: &str,
+        kind: ImageHashKind,
+        color: bool,
+        max_distance: u32,
+    ) -> Result<Self, String> {
+        Ok(Self::Hash {
+            hash: normalize_hash_hex(hash)?.into_owned(),
+            kind,
+            color,
+            max_distance,
+        })
+    }
+
+    /// 对单帧检查条件。
+    ///
+    /// # 参数
+    /// - `frame`: 当前截图（BGR）
+    /// - `origin`: 截图左上角在客户区中的坐标
+    ///
+    /// # 返回值
+    /// 命中时返回详情，未命中返回 `None`。
+    pub(crate) fn check(
+        &self,
+        frame: &Mat,
+        origin: (i32, i32),
+    ) -> Result<Option<WaitMatch>, String> {
+        let (ox, oy) = origin;
+        match self {
+            Self::Template {
+                template,
+                threshold,
+                config,
+            } => {
+                let options = MatchAllOptions {
+                    threshold: *threshold,
+                    max_results: 1,
+                    config: *config,
+                    ..MatchAllOptions::default()
+                };
+                let best = match_template_all(frame, template, &options)
+                    .map_err(|e| format!("模板匹配失败: {e}"))?;
+                Ok(best.into_iter().next().map(|m| {
+                    WaitMatch::Template(TemplateMatch {
+                        x: m.x + ox,
+                        y: m.y + oy,
+                        ..m
+                    })
+                }))
+            }
+            Self::Colors {
+                points,
+                tolerance,
+                distance,
+            } => {
+                let matched = points.iter().all(|point| {
+                    let (x, y) = (point.x - ox, point.y - oy);
+                    match distance {
+                        ColorDistance::Rgb => check_color_mat(
+                            frame,
+                            x,
+                            y,
+                            point.color,
+                            tolerance.clamp(0.0, 255.0) as u8,
+                        ),
+                        _ => check_color_distance(
+                            frame,
+                            x,
+                            y,
+                            rgb_to_lab(point.color),
+                            *tolerance,
+                            *distance,
+                        ),
+                    }
+                });
+                Ok(matched.then_some(WaitMatch::Colors))
+            }
+            Self::Hash {
+                hash,
+                kind,
+                color,
+                max_distance,
+            } => {
+                let current = perceptual_hash_impl(frame, *color, *kind)?;
+                let distance = hamming_distance_hex(&current, hash)?;
+                Ok((distance <= *max_distance).then_some(WaitMatch::Hash {
+                    hash: current,
+                    distance,
+                }))
+            }
+            Self::Text { pattern, options } => {
+                Ok(match_text(pattern, &ocr_text_from_mat(frame, options)?))
+            }
+        }
+    }
+}
+
+/// 用正则匹配 OCR 文本，命中时返回全文、命中文本与捕获组。
+fn match_text(pattern: &Regex, text: &str) -> Option<WaitMatch> {
+    let captures = pattern.captures(text)?;
+    Some(WaitMatch::Text {
+        text: text.to_string(),
+        matched: captures.get(0)?.as_str().to_string(),
+        groups: captures
+            .iter()
+            .skip(1)
+            .map(|group| group.map(|m| m.as_str().to_string()))
+            .collect(),
+    })
+}
+
+/// 睡眠 `duration`，期间按 `STOP_CHECK_SLICE` 粒度检查停止请求；被停止时返回 `false`。
+fn _sleep_unless_stopped(duration: Duration, should_stop: &impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + duration;
+    loop {
+        if should_stop() {
+            return false;
+        }
+        let remain = deadline.saturating_duration_since(Instant::now());
+        if remain.is_zero() {
+            return true;
+        }
+        thread::sleep(remain.min(STOP_CHECK_SLICE));
+    }
+}
+
+/// 通用轮询等待：反复截图并检查条件，直到命中、超时或被停止。
+///
+/// # 参数
+/// - `condition`: 等待条件
+/// - `options`: 截图原点、轮询间隔与超时
+/// - `capture`: 截图回调，将画面写入传入的缓冲并返回是否成功；整个等待过程复用同一块缓冲，
+///   尺寸不变时不会重复分配图像内存
+/// - `should_stop`: 停止检查（脚本停止快照 / AbortSignal）
+///
+/// # 返回值
+/// 轮询结果；超时或被停止时 `found` 为 `None`，条件检查出错时返回错误。
+pub(crate) fn wait_until(
+    condition: &WaitCondition,
+    options: &WaitOptions,
+    mut capture: impl FnMut(&mut Mat) -> bool,
+    should_stop: impl Fn() -> bool,
+) -> Result<WaitOutcome, String> {
+    let start = Instant::now();
+    let mut frame = Mat::default();
+    let mut polls = 0u32;
+    let outcome = |found, polls| WaitOutcome {
+        found,
+        polls,
+        elapsed: start.elapsed(),
+    };
+
+    loop {
+        if should_stop() {
+            return Ok(outcome(None, polls));
+        }
+        if capture(&mut frame) {
+            polls += 1;
+            if let Some(found) = condition.check(&frame, options.origin)? {
+                return Ok(outcome(Some(found), polls));
+            }
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= options.timeout {
+            return Ok(outcome(None, polls));
+        }
+        let pause = options.interval.min(options.timeout - elapsed);
+        if !_sleep_unless_stopped(pause, &should_stop) {
+            return Ok(outcome(None, polls));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorPoint, WaitCondition, WaitMatch, WaitOptions, match_text, wait_until};
+    use crate::submodules::{
+        color::ColorDistance,
+        script_vision::{ImageHashKind, perceptual_hash_impl},
+        tpl_match::MatchConfig,
+    };
+    use opencv::{
+        core::{CV_8UC3, Mat, Rect, Scalar},
+        imgproc,
+        prelude::{MatTraitConst, MatTraitConstManual},
+    };
+    use regex::Regex;
+    use std::{cell::Cell, time::Duration};
+
+    /// 构造纯色背景帧，可选在指定位置绘制一个带纹理的方块。
+    fn frame_with_marker(marker: Option<(i32, i32)>) -> Mat {
+        let mut mat = Mat::new_rows_cols_with_default(80, 120, CV_8UC3, Scalar::all(30.0))
+            .expect("创建测试图像失败");
+        if let Some((x, y)) = marker {
+            for (dx, dy, color) in [
+                (0, 0, Scalar::new(0.0, 0.0, 255.0, 0.0)),
+                (8, 0, Scalar::new(0.0, 255.0, 0.0, 0.0)),
+                (0, 8, Scalar::new(255.0, 0.0, 0.0, 0.0)),
+                (8, 8, Scalar::all(255.0)),
+            ] {
+                imgproc::rectangle(
+                    &mut mat,
+                    Rect::new(x + dx, y + dy, 8, 8),
+                    color,
+                    -1,
+                    imgproc::LINE_8,
+                    0,
+                )
+                .expect("绘制标记失败");
+            }
+        }
+        mat
+    }
+
+    fn fast_options() -> WaitOptions {
+        WaitOptions {
+            interval: Duration::from_millis(1),
+            timeout: Duration::from_millis(2_000),
+            ..WaitOptions::default()
+        }
+    }
+
+    #[test]
+    fn template_appears_after_a_few_polls() {
+        let template = Mat::roi(&frame_with_marker(Some((0, 0))), Rect::new(0, 0, 16, 16))
+            .and_then(|roi| roi.try_clone())
+            .expect("裁剪模板失败");
+        let condition = WaitCondition::Template {
+            template,
+            threshold: 0.9,
+            config: MatchConfig::default(),
+        };
+        let frames = [
+            frame_with_marker(None),
+            frame_with_marker(None),
+            frame_with_marker(Some((50, 30))),
+        ];
+        let index = Cell::new(0usize);
+        let options = WaitOptions {
+            origin: (100, 200),
+            ..fast_options()
+        };
+
+        let outcome = wait_until(
+            &condition,
+            &options,
+            |buf| {
+                let frame = &frames[index.get().min(frames.len() - 1)];
+                index.set(index.get() + 1);
+                frame.copy_to(buf).is_ok()
+            },
+            || false,
+        )
+        .expect("等待失败");
+
+        assert_eq!(outcome.polls, 3);
+        let Some(WaitMatch::Template(found)) = outcome.found else {
+            panic!("未命中模板: {:?}", outcome.found);
+        };
+        // 命中位置已按截图原点换算为客户区坐标
+        assert_eq!((found.x, found.y, found.w, found.h), (150, 230, 16, 16));
+        assert!(found.score > 0.99);
+    }
+
+    #[test]
+    fn reuses_capture_buffer_between_polls() {
+        let frame = frame_with_marker(None);
+        let condition = WaitCondition::Colors {
+            points: vec![ColorPoint {
+                x: 0,
+                y: 0,
+                color: 0xffffff,
+            }],
+            tolerance: 0.0,
+            distance: ColorDistance::Rgb,
+        };
+        let buffers = Cell::new((std::ptr::null::<u8>(), 0u32));
+        let options = WaitOptions {
+            timeout: Duration::from_millis(30),
+            ..fast_options()
+        };
+
+        let outcome = wait_until(
+            &condition,
+            &options,
+            |buf| {
+                frame.copy_to(buf).expect("写入缓冲失败");
+                let ptr = buf.data_bytes().expect("读取缓冲失败").as_ptr();
+                let (first, mismatches) = buffers.get();
+                if first.is_null() {
+                    buffers.set((ptr, 0));
+                } else if first != ptr {
+                    buffers.set((first, mismatches + 1));
+                }
+                true
+            },
+            || false,
+        )
+        .expect("等待失败");
+
+        assert!(outcome.found.is_none());
+        assert!(outcome.polls > 1);
+        assert_eq!(buffers.get().1, 0, "轮询期间缓冲被重新分配");
+    }
+
+    #[test]
+    fn colors_use_client_coordinates() {
+        // 标记左上角红色块位于截图 (10, 20)，截图原点为客户区 (40, 5)
+        let frame = frame_with_marker(Some((10, 20)));
+        let check = |points: Vec<ColorPoint>, distance| {
+            WaitCondition::Colors {
+                points,
+                tolerance: 4.0,
+                distance,
+            }
+            .check(&frame, (40, 5))
+            .expect("检查失败")
+        };
+        let red = ColorPoint {
+            x: 52,
+            y: 27,
+            color: 0xff0000,
+        };
+        let white = ColorPoint {
+            x: 60,
+            y: 35,
+            color: 0xffffff,
+        };
+        assert_eq!(
+            check(vec![red, white], ColorDistance::Rgb),
+            Some(WaitMatch::Colors)
+        );
+        assert_eq!(
+            check(vec![red, white], ColorDistance::DeltaE76),
+            Some(WaitMatch::Colors)
+        );
+        // 任意一点不满足即不命中；截图范围外的点视为不满足
+        let outside = ColorPoint { x: 0, y: 0, ..red };
+        assert_eq!(check(vec![red, outside], ColorDistance::Rgb), None);
+        assert_eq!(
+            check(
+                vec![
+                    white,
+                    ColorPoint {
+                        color: 0x00ff00,
+                        ..white
+                    }
+                ],
+                ColorDistance::Rgb
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn hash_condition_matches_within_distance() {
+        let target = frame_with_marker(Some((40, 20)));
+        let hash =
+            perceptual_hash_impl(&target, false, ImageHashKind::DHash).expect("计算哈希失败");
+        let condition = |max_distance| {
+            WaitCondition::hash(
+                &format!("0x{}", hash.to_uppercase()),
+                ImageHashKind::DHash,
+                false,
+                max_distance,
+            )
+            .expect("构造哈希条件失败")
+        };
+        let flat = frame_with_marker(None);
+
+        let Some(WaitMatch::Hash { distance, .. }) =
+            condition(0).check(&target, (0, 0)).expect("检查失败")
+        else {
+            panic!("相同画面应命中");
+        };
+        assert_eq!(distance, 0);
+        assert_eq!(condition(0).check(&flat, (0, 0)).expect("检查失败"), None);
+        // 放宽距离后不同画面也可命中，并返回当前画面哈希
+        let Some(WaitMatch::Hash {
+            hash: current,
+            distance,
+        }) = condition(64).check(&flat, (0, 0)).expect("检查失败")
+        else {
+            panic!("距离上限为 64 时应命中");
+        };
+        assert!(distance > 0);
+        assert_eq!(
+            current,
+            perceptual_hash_impl(&flat, false, ImageHashKind::DHash).unwrap()
+        );
+        assert!(WaitCondition::hash("xyz", ImageHashKind::DHash, false, 4).is_err());
+    }
+
+    #[test]
+    fn times_out_or_stops_without_match() {
+        let frame = frame_with_marker(None);
+        let condition = WaitCondition::Colors {
+            points: vec![ColorPoint {
+                x: 0,
+                y: 0,
+                color: 0xff0000,
+            }],
+            tolerance: 0.0,
+            distance: ColorDistance::Rgb,
+        };
+        let options = WaitOptions {
+            interval: Duration::from_millis(5),
+            timeout: Duration::from_millis(40),
+            ..WaitOptions::default()
+        };
+        let outcome = wait_until(
+            &condition,
+            &options,
+            |buf| frame.copy_to(buf).is_ok(),
+            || false,
+        )
+        .expect("等待失败");
+        assert!(outcome.found.is_none());
+        assert!(outcome.polls >= 2);
+        assert!(outcome.elapsed >= Duration::from_millis(40));
+
+        // 截图失败不计入轮询次数；停止请求在下一次检查时生效
+        let calls = Cell::new(0u32);
+        let outcome = wait_until(
+            &condition,
+            &WaitOptions::default(),
+            |_| {
+                calls.set(calls.get() + 1);
+                false
+            },
+            || calls.get() >= 3,
+        )
+        .expect("等待失败");
+        assert_eq!((outcome.found, outcome.polls, calls.get()), (None, 0, 3));
+    }
+
+    #[test]
+    fn text_match_reports_groups() {
+        let pattern = Regex::new(r"(\d+)/(\d+)(x)?").expect("编译正则失败");
+        assert_eq!(
+            match_text(&pattern, "HP 120/300\n"),
+            Some(WaitMatch::Text {
+                text: "HP 120/300\n".to_string(),
+                matched: "120/300".to_string(),
+                groups: vec![Some("120".to_string()), Some("300".to_string()), None],
+            })
+        );
+        assert_eq!(match_text(&pattern, "HP --"), None);
+    }
+}
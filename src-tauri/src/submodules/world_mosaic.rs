@@ -0,0 +1,1003 @@
+use opencv::{
+    core::{self, CV_8UC3, Mat, Point2f, Rect, Scalar, Size, Vector},
+    imgcodecs, imgproc,
+    prelude::{MatTraitConst, MatTraitConstManual},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use crate::submodules::script_vision::{
+    _to_bgr_mat, _to_gray_mat, overlay_warped_patch, sift_patch_homography,
+};
+
+/// 索引文件名
+const INDEX_FILE: &str = "index.json";
+/// 瓦片子目录
+const TILE_DIR: &str = "tiles";
+/// 索引格式版本
+const INDEX_VERSION: u32 = 1;
+/// 全局搜索时粗配准图的最大边长（像素），超过此尺寸的拼图不再整图全分辨率配准
+const COARSE_MAX_SIDE: i32 = 2048;
+
+/// 拼图参数
+#[derive(Debug, Clone, Copy)]
+pub struct MosaicConfig {
+    /// 瓦片边长（像素）；打开已有拼图时以索引中记录的值为准
+    pub tile_size: i32,
+    /// 跟踪搜索时在上一帧外扩的边距（像素，<=0 表示取帧的长边）
+    pub search_margin: i32,
+    /// 最少优质匹配数
+    pub min_good_matches: i32,
+    /// 最少 RANSAC 内点数
+    pub min_inliers: i32,
+    /// 最低内点比例（内点数 / 优质匹配数）
+    pub min_inlier_ratio: f64,
+    /// 允许的最大缩放变化（`1 / x ..= x`）
+    pub max_scale_change: f64,
+    /// 单应矩阵透视项 `h20` / `h21` 的绝对值上限
+    pub max_perspective: f64,
+    /// RANSAC 重投影阈值（像素）
+    pub ransac_threshold: f64,
+    /// 内存中最多缓存的瓦片数；超出时淘汰最久未用的瓦片，修改过的瓦片先写回磁盘
+    pub max_cached_tiles: usize,
+}
+
+impl Default for MosaicConfig {
+    fn default() -> Self {
+        Self {
+            tile_size: 512,
+            search_margin: 0,
+            min_good_matches: 12,
+            min_inliers: 10,
+            min_inlier_ratio: 0.3,
+            max_scale_change: 1.3,
+            max_perspective: 0.002,
+            ransac_threshold: 3.0,
+            max_cached_tiles: 64,
+        }
+    }
+}
+
+/// 磁盘索引（`index.json`）
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MosaicIndex {
+    version: u32,
+    tile_size: i32,
+    /// 已绘制内容的全局包围盒 `[minX, minY, maxX, maxY)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bounds: Option<[i32; 4]>,
+    /// 全局坐标到导出图像素坐标的偏移（`导出坐标 = 全局坐标 + offset`）
+    offset: [i32; 2],
+    /// 已接受的帧数
+    frames: u64,
+    /// 最近一次接受的帧包围盒 `[x, y, w, h]`（全局坐标）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_frame: Option<[i32; 4]>,
+    /// 已存在的瓦片 `[col, row]`
+    tiles: Vec<[i32; 2]>,
+}
+
+/// 加帧结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct MosaicFrameResult {
+    /// 是否已写入拼图
+    pub accepted: bool,
+    /// 拒绝原因（接受时为 `None`）
+    pub reason: Option<String>,
+    /// 帧在全局坐标中的包围盒（拒绝时为 `None`）
+    pub bbox: Option<Rect>,
+    /// 优质匹配数（首帧为 0）
+    pub good_matches: i32,
+    /// RANSAC 内点数（首帧为 0）
+    pub inliers: i32,
+}
+
+/// "我在哪"查询结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct MosaicLocation {
+    /// 查询图像中心在全局坐标中的位置
+    pub center: (f64, f64),
+    /// 查询图像在全局坐标中的包围盒
+    pub bbox: Rect,
+    /// 查询图像四角在全局坐标中的位置
+    pub corners: Vec<Point2f>,
+    /// 优质匹配数
+    pub good_matches: i32,
+    /// RANSAC 内点数
+    pub inliers: i32,
+}
+
+/// 内存中的瓦片及其最近一次访问的时刻
+struct CachedTile {
+    mat: Mat,
+    last_used: u64,
+}
+
+type Homography = [[f64; 3]; 3];
+/// 配准结果：成功时为 (全局单应矩阵, 优质匹配数, 内点数)，失败时为拒绝原因
+type Registration = Result<(Homography, i32, i32), String>;
+
+/// 读取 3x3 单应矩阵
+fn read_homography(mat: &Mat) -> Result<Homography, String> {
+    let mut h = [[0.0; 3]; 3];
+    for (row, values) in h.iter_mut().enumerate() {
+        for (col, value) in values.iter_mut().enumerate() {
+            *value = *mat
+                .at_2d::<f64>(row as i32, col as i32)
+                .map_err(|e| format!("读取单应矩阵失败: {e}"))?;
+        }
+    }
+    Ok(h)
+}
+
+/// 左乘平移：返回 `T(dx, dy) * h`
+fn translate_homography(h: &Homography, dx: f64, dy: f64) -> Homography {
+    let mut out = *h;
+    for col in 0..3 {
+        out[0][col] += dx * h[2][col];
+        out[1][col] += dy * h[2][col];
+    }
+    out
+}
+
+/// 用单应矩阵变换一个点
+fn project(h: &Homography, x: f64, y: f64) -> (f64, f64) {
+    let w = h[2][0] * x + h[2][1] * y + h[2][2];
+    (
+        (h[0][0] * x + h[0][1] * y + h[0][2]) / w,
+        (h[1][0] * x + h[1][1] * y + h[1][2]) / w,
+    )
+}
+
+/// 图像四角变换后的坐标（顺时针：左上、右上、右下、左下）
+fn project_corners(h: &Homography, width: i32, height: i32) -> [(f64, f64); 4] {
+    let (w, hgt) = (width as f64, height as f64);
+    [
+        project(h, 0.0, 0.0),
+        project(h, w, 0.0),
+        project(h, w, hgt),
+        project(h, 0.0, hgt),
+    ]
+}
+
+/// 四角坐标的整数包围盒
+fn corners_bbox(corners: &[(f64, f64); 4]) -> Rect {
+    let min_x = corners
+        .iter()
+        .map(|p| p.0)
+        .fold(f64::INFINITY, f64::min)
+        .floor();
+    let min_y = corners
+        .iter()
+        .map(|p| p.1)
+        .fold(f64::INFINITY, f64::min)
+        .floor();
+    let max_x = corners
+        .iter()
+        .map(|p| p.0)
+        .fold(f64::NEG_INFINITY, f64::max)
+        .ceil();
+    let max_y = corners
+        .iter()
+        .map(|p| p.1)
+        .fold(f64::NEG_INFINITY, f64::max)
+        .ceil();
+    Rect::new(
+        min_x as i32,
+        min_y as i32,
+        (max_x - min_x) as i32,
+        (max_y - min_y) as i32,
+    )
+}
+
+/// 两个矩形的交集（无交集时宽高为 0）
+fn intersect(a: Rect, b: Rect) -> Rect {
+    let x0 = a.x.max(b.x);
+    let y0 = a.y.max(b.y);
+    let x1 = (a.x + a.width).min(b.x + b.width);
+    let y1 = (a.y + a.height).min(b.y + b.height);
+    Rect::new(x0, y0, (x1 - x0).max(0), (y1 - y0).max(0))
+}
+
+/// 检查单应矩阵是否可信，返回拒绝原因
+///
+/// 说明：在匹配统计（优质匹配、内点数与内点比例）之外，
+/// 还要求透视项足够小、缩放接近 1、无镜像翻转，且变换后的四边形为凸。
+fn homography_rejection(
+    h: &Homography,
+    width: i32,
+    height: i32,
+    good_matches: i32,
+    inliers: i32,
+    config: &MosaicConfig,
+) -> Option<String> {
+    if good_matches < config.min_good_matches {
+        return Some(format!(
+            "优质匹配不足: {good_matches} < {}",
+            config.min_good_matches
+        ));
+    }
+    if inliers < config.min_inliers {
+        return Some(format!("内点不足: {inliers} < {}", config.min_inliers));
+    }
+    let ratio = inliers as f64 / good_matches.max(1) as f64;
+    if ratio < config.min_inlier_ratio {
+        return Some(format!(
+            "内点比例过低: {ratio:.2} < {:.2}",
+            config.min_inlier_ratio
+        ));
+    }
+    if h[2][0].abs() > config.max_perspective || h[2][1].abs() > config.max_perspective {
+        return Some("透视畸变过大".to_string());
+    }
+    let det = h[0][0] * h[1][1] - h[0][1] * h[1][0];
+    if !det.is_finite() || det <= 0.0 {
+        return Some("单应矩阵退化或镜像翻转".to_string());
+    }
+    let scale = det.sqrt();
+    let max_scale = config.max_scale_change.max(1.0);
+    if scale > max_scale || scale < 1.0 / max_scale {
+        return Some(format!("缩放异常: {scale:.2}"));
+    }
+    let corners = project_corners(h, width, height);
+    let convex = (0..4).all(|idx| {
+        let (a, b, c) = (corners[idx], corners[(idx + 1) % 4], corners[(idx + 2) % 4]);
+        (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0) > 0.0
+    });
+    if !convex {
+        return Some("变换后四边形非凸".to_string());
+    }
+    None
+}
+
+/// 持久化的世界地图拼图
+///
+/// 说明：
+/// - 全局坐标以首帧左上角为原点，可向任意方向生长（瓦片行列可为负）；
+/// - 画布按 `tile_size` 切成 PNG 瓦片存于 `tiles/{col}_{row}.png`，`index.json` 记录包围盒、偏移与瓦片列表；
+/// - 新帧先在上一帧附近的窗口内配准，失败再经缩小的粗配准定位后在其附近精配准；配准结果需通过
+///   `homography_rejection` 的检查才会写入；
+/// - 瓦片按需从磁盘加载，最多缓存 `max_cached_tiles` 张；修改后在 `save` 或被淘汰时写回。
+pub struct WorldMosaic {
+    dir: PathBuf,
+    config: MosaicConfig,
+    tiles: HashMap<(i32, i32), CachedTile>,
+    /// 瓦片访问计数，用于最近最少使用淘汰
+    tile_clock: u64,
+    dirty: HashSet<(i32, i32)>,
+    known: BTreeSet<(i32, i32)>,
+    bounds: Option<Rect>,
+    last_frame: Option<Rect>,
+    frames: u64,
+}
+
+impl std::fmt::Debug for WorldMosaic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorldMosaic")
+            .field("dir", &self.dir)
+            .field("tile_size", &self.config.tile_size)
+            .field("tiles", &self.known.len())
+            .field("bounds", &self.bounds)
+            .field("frames", &self.frames)
+            .finish()
+    }
+}
+
+impl WorldMosaic {
+    /// 打开拼图目录；目录或索引不存在时创建空拼图（首次 `save` 时落盘）
+    pub fn open(dir: &Path, config: MosaicConfig) -> Result<Self, String> {
+        let mut mosaic = Self {
+            dir: dir.to_path_buf(),
+            config,
+            tiles: HashMap::new(),
+            tile_clock: 0,
+            dirty: HashSet::new(),
+            known: BTreeSet::new(),
+            bounds: None,
+            last_frame: None,
+            frames: 0,
+        };
+        let index_path = dir.join(INDEX_FILE);
+        if index_path.is_file() {
+            let text = std::fs::read_to_string(&index_path)
+                .map_err(|e| format!("读取拼图索引失败 {}: {e}", index_path.display()))?;
+            let index: MosaicIndex = serde_json::from_str(&text)
+                .map_err(|e| format!("解析拼图索引失败 {}: {e}", index_path.display()))?;
+            if index.version != INDEX_VERSION {
+                return Err(format!("不支持的拼图索引版本: {}", index.version));
+            }
+            if index.tile_size <= 0 {
+                return Err(format!("拼图瓦片尺寸无效: {}", index.tile_size));
+            }
+            mosaic.config.tile_size = index.tile_size;
+            mosaic.bounds = index
+                .bounds
+                .map(|[x0, y0, x1, y1]| Rect::new(x0, y0, x1 - x0, y1 - y0));
+            mosaic.last_frame = index.last_frame.map(|[x, y, w, h]| Rect::new(x, y, w, h));
+            mosaic.frames = index.frames;
+            mosaic.known = index.tiles.iter().map(|[c, r]| (*c, *r)).collect();
+        } else if config.tile_size <= 0 {
+            return Err(format!("拼图瓦片尺寸无效: {}", config.tile_size));
+        }
+        Ok(mosaic)
+    }
+
+    /// 已绘制内容的全局包围盒
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+
+    /// 全局坐标到导出图像素坐标的偏移
+    pub fn offset(&self) -> (i32, i32) {
+        self.bounds.map_or((0, 0), |b| (-b.x, -b.y))
+    }
+
+    /// 已接受的帧数
+    pub fn frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    /// 瓦片数量
+    pub fn tile_count(&self) -> usize {
+        self.known.len()
+    }
+
+    fn tile_path(&self, key: (i32, i32)) -> PathBuf {
+        self.dir
+            .join(TILE_DIR)
+            .join(format!("{}_{}.png", key.0, key.1))
+    }
+
+    /// 从磁盘读取瓦片（不放入缓存）
+    fn read_tile(&self, key: (i32, i32)) -> Result<Mat, String> {
+        let path = self.tile_path(key);
+        let tile = imgcodecs::imread(&path.to_string_lossy(), imgcodecs::IMREAD_COLOR)
+            .map_err(|e| format!("读取瓦片失败 {}: {e}", path.display()))?;
+        let size = self.config.tile_size;
+        if tile.rows() != size || tile.cols() != size {
+            return Err(format!("瓦片尺寸与索引不一致: {}", path.display()));
+        }
+        Ok(tile)
+    }
+
+    /// 将缓存中的瓦片写回磁盘并清除修改标记
+    fn write_tile(&mut self, key: (i32, i32)) -> Result<(), String> {
+        let tile_dir = self.dir.join(TILE_DIR);
+        std::fs::create_dir_all(&tile_dir)
+            .map_err(|e| format!("创建拼图目录失败 {}: {e}", tile_dir.display()))?;
+        let path = self.tile_path(key);
+        let Some(tile) = self.tiles.get(&key) else {
+            return Err(format!("瓦片不在内存中，无法写入: {}", path.display()));
+        };
+        let written = imgcodecs::imwrite(&path.to_string_lossy(), &tile.mat, &Vector::new())
+            .map_err(|e| format!("写入瓦片失败 {}: {e}", path.display()))?;
+        if !written {
+            return Err(format!("写入瓦片失败: {}", path.display()));
+        }
+        self.dirty.remove(&key);
+        Ok(())
+    }
+
+    /// 放入缓存并标记为最近使用，超出上限时淘汰最久未用的其它瓦片
+    fn cache_tile(&mut self, key: (i32, i32), mat: Mat) -> Result<(), String> {
+        self.tile_clock += 1;
+        self.tiles.insert(
+            key,
+            CachedTile {
+                mat,
+                last_used: self.tile_clock,
+            },
+        );
+        while self.tiles.len() > self.config.max_cached_tiles.max(1) {
+            let Some(oldest) = self
+                .tiles
+                .iter()
+                .filter(|(k, _)| **k != key)
+                .min_by_key(|(_, tile)| tile.last_used)
+                .map(|(k, _)| *k)
+            else {
+                break;
+            };
+            if self.dirty.contains(&oldest) {
+                self.write_tile(oldest)?;
+            }
+            self.tiles.remove(&oldest);
+        }
+        Ok(())
+    }
+
+    /// 确保瓦片已加载到内存，返回该瓦片是否存在
+    fn ensure_tile(&mut self, key: (i32, i32)) -> Result<bool, String> {
+        if let Some(tile) = self.tiles.get_mut(&key) {
+            self.tile_clock += 1;
+            tile.last_used = self.tile_clock;
+            return Ok(true);
+        }
+        if !self.known.contains(&key) {
+            return Ok(false);
+        }
+        let tile = self.read_tile(key)?;
+        self.cache_tile(key, tile)?;
+        Ok(true)
+    }
+
+    /// 覆盖矩形区域的瓦片行列范围
+    fn tile_range(
+        &self,
+        rect: Rect,
+    ) -> (std::ops::RangeInclusive<i32>, std::ops::RangeInclusive<i32>) {
+        let size = self.config.tile_size;
+        let cols = rect.x.div_euclid(size)..=(rect.x + rect.width - 1).div_euclid(size);
+        let rows = rect.y.div_euclid(size)..=(rect.y + rect.height - 1).div_euclid(size);
+        (cols, rows)
+    }
+
+    /// 将全局矩形区域渲染为一张 BGR 图（未绘制区域为黑色）
+    pub fn render_region(&mut self, rect: Rect) -> Result<Mat, String> {
+        if rect.width <= 0 || rect.height <= 0 {
+            return Err("渲染区域尺寸无效".to_string());
+        }
+        let mut out =
+            Mat::new_rows_cols_with_default(rect.height, rect.width, CV_8UC3, Scalar::all(0.0))
+                .map_err(|e| format!("创建渲染画布失败: {e}"))?;
+        let size = self.config.tile_size;
+        let (cols, rows) = self.tile_range(rect);
+        for row in rows {
+            for col in cols.clone() {
+                if !self.ensure_tile((col, row))? {
+                    continue;
+                }
+                let tile_rect = Rect::new(col * size, row * size, size, size);
+                let overlap = intersect(tile_rect, rect);
+                if overlap.width == 0 || overlap.height == 0 {
+                    continue;
+                }
+                let tile = &self.tiles[&(col, row)].mat;
+                let src = Mat::roi(
+                    tile,
+                    Rect::new(
+                        overlap.x - tile_rect.x,
+                        overlap.y - tile_rect.y,
+                        overlap.width,
+                        overlap.height,
+                    ),
+                )
+                .map_err(|e| format!("获取瓦片 ROI 失败: {e}"))?;
+                let mut dst = Mat::roi_mut(
+                    &mut out,
+                    Rect::new(
+                        overlap.x - rect.x,
+                        overlap.y - rect.y,
+                        overlap.width,
+                        overlap.height,
+                    ),
+                )
+                .map_err(|e| format!("获取画布 ROI 失败: {e}"))?;
+                src.copy_to(&mut dst)
+                    .map_err(|e| format!("复制瓦片失败: {e}"))?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// 将帧按全局单应矩阵绘制到所有覆盖到的瓦片上
+    fn paint(&mut self, frame: &Mat, gray: &Mat, h: &Homography) -> Result<Rect, String> {
+        let bbox = corners_bbox(&project_corners(h, frame.cols(), frame.rows()));
+        let size = self.config.tile_size;
+        let (cols, rows) = self.tile_range(bbox);
+        for row in rows {
+            for col in cols.clone() {
+                let key = (col, row);
+                let tile_h = translate_homography(h, -(col * size) as f64, -(row * size) as f64);
+                let tile_h = Mat::from_slice_2d(&tile_h)
+                    .map_err(|e| format!("创建瓦片单应矩阵失败: {e}"))?;
+                // 只借用缓存中的瓦片，合成成功后再替换，失败时保留原瓦片及其未保存的修改
+                let blank;
+                let tile = if self.ensure_tile(key)? {
+                    &self.tiles[&key].mat
+                } else {
+                    blank = Mat::new_rows_cols_with_default(size, size, CV_8UC3, Scalar::all(0.0))
+                        .map_err(|e| format!("创建瓦片失败: {e}"))?;
+                    &blank
+                };
+                let merged = overlay_warped_patch(tile, frame, gray, &tile_h)?;
+                self.dirty.insert(key);
+                self.known.insert(key);
+                self.cache_tile(key, merged)?;
+            }
+        }
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => {
+                let x0 = bounds.x.min(bbox.x);
+                let y0 = bounds.y.min(bbox.y);
+                let x1 = (bounds.x + bounds.width).max(bbox.x + bbox.width);
+                let y1 = (bounds.y + bounds.height).max(bbox.y + bbox.height);
+                Rect::new(x0, y0, x1 - x0, y1 - y0)
+            }
+            None => bbox,
+        });
+        self.last_frame = Some(bbox);
+        self.frames += 1;
+        Ok(bbox)
+    }
+
+    /// 在拼图的指定区域内配准图像
+    fn register(&mut self, region: Rect, gray: &Mat) -> Result<Registration, String> {
+        let crop = self.render_region(region)?;
+        let crop_gray = _to_gray_mat(&crop)?;
+        let Some((homography, good_matches, inliers)) =
+            sift_patch_homography(&crop_gray, gray, self.config.ransac_threshold)?
+        else {
+            return Ok(Err("特征匹配不足".to_string()));
+        };
+        let h = read_homography(&homography)?;
+        if let Some(reason) = homography_rejection(
+            &h,
+            gray.cols(),
+            gray.rows(),
+            good_matches,
+            inliers,
+            &self.config,
+        ) {
+            return Ok(Err(reason));
+        }
+        Ok(Ok((
+            translate_homography(&h, region.x as f64, region.y as f64),
+            good_matches,
+            inliers,
+        )))
+    }
+
+    /// 先在 `window` 内配准，失败再用缩小的粗配准定位后在其附近精配准
+    ///
+    /// 说明：全分辨率配准只在有界窗口内进行；拼图长边不超过 `COARSE_MAX_SIDE` 时直接配准整张拼图，
+    /// 否则把拼图与查询图按同一倍率缩小做一次粗配准，再在粗定位结果外扩查询图长边的窗口内精配准。
+    fn register_with_fallback(
+        &mut self,
+        window: Option<Rect>,
+        gray: &Mat,
+    ) -> Result<Registration, String> {
+        let Some(bounds) = self.bounds else {
+            return Ok(Err("拼图为空".to_string()));
+        };
+        let window = window
+            .map(|window| intersect(window, bounds))
+            .filter(|window| window.width > 0 && window.height > 0 && *window != bounds);
+        if let Some(window) = window {
+            let result = self.register(window, gray)?;
+            if result.is_ok() {
+                return Ok(result);
+            }
+        }
+        if bounds.width.max(bounds.height) <= COARSE_MAX_SIDE {
+            return self.register(bounds, gray);
+        }
+        match self.coarse_window(bounds, gray)? {
+            Some(window) => self.register(window, gray),
+            None => Ok(Err("粗配准特征匹配不足".to_string())),
+        }
+    }
+
+    /// 在缩小的整张拼图上粗配准，返回精配准用的全局窗口
+    fn coarse_window(&self, bounds: Rect, gray: &Mat) -> Result<Option<Rect>, String> {
+        let zoom = COARSE_MAX_SIDE as f64 / bounds.width.max(bounds.height) as f64;
+        let coarse = self.export(zoom)?;
+        let coarse_gray = _to_gray_mat(&coarse)?;
+        let mut query = Mat::default();
+        imgproc::resize(
+            gray,
+            &mut query,
+            Size::new(
+                ((gray.cols() as f64) * zoom).round().max(1.0) as i32,
+                ((gray.rows() as f64) * zoom).round().max(1.0) as i32,
+            ),
+            0.0,
+            0.0,
+            imgproc::INTER_AREA,
+        )
+        .map_err(|e| format!("缩放查询图像失败: {e}"))?;
+        let Some((homography, _, _)) =
+            sift_patch_homography(&coarse_gray, &query, self.config.ransac_threshold)?
+        else {
+            return Ok(None);
+        };
+        let h = read_homography(&homography)?;
+        let coarse_bbox = corners_bbox(&project_corners(&h, query.cols(), query.rows()));
+        let margin = gray.cols().max(gray.rows());
+        let x0 = bounds.x + (coarse_bbox.x as f64 / zoom).floor() as i32 - margin;
+        let y0 = bounds.y + (coarse_bbox.y as f64 / zoom).floor() as i32 - margin;
+        let x1 =
+            bounds.x + ((coarse_bbox.x + coarse_bbox.width) as f64 / zoom).ceil() as i32 + margin;
+        let y1 =
+            bounds.y + ((coarse_bbox.y + coarse_bbox.height) as f64 / zoom).ceil() as i32 + margin;
+        let window = intersect(Rect::new(x0, y0, x1 - x0, y1 - y0), bounds);
+        // 粗配准结果异常时窗口可能退化或过大，此时放弃而不是退回全分辨率整图
+        let max_side = margin * 4;
+        if window.width <= 0
+            || window.height <= 0
+            || window.width > max_side
+            || window.height > max_side
+        {
+            return Ok(None);
+        }
+        Ok(Some(window))
+    }
+
+    /// 加入一帧（小地图或屏幕截图）
+    ///
+    /// 说明：首帧直接放在全局原点；之后的帧需配准成功且单应矩阵通过检查才会写入，
+    /// 被拒绝的帧不改变拼图。纯黑像素视为透明，不覆盖已有内容。
+    pub fn add_frame(&mut self, frame: &Mat) -> Result<MosaicFrameResult, String> {
+        if frame.rows() <= 0 || frame.cols() <= 0 {
+            return Err("帧图像尺寸无效".to_string());
+        }
+        let bgr = _to_bgr_mat(frame)?;
+        let gray = _to_gray_mat(&bgr)?;
+
+        if self.bounds.is_none() {
+            let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+            let bbox = self.paint(&bgr, &gray, &identity)?;
+            return Ok(MosaicFrameResult {
+                accepted: true,
+                reason: None,
+                bbox: Some(bbox),
+                good_matches: 0,
+                inliers: 0,
+            });
+        }
+
+        let margin = if self.config.search_margin > 0 {
+            self.config.search_margin
+        } else {
+            frame.cols().max(frame.rows())
+        };
+        let window = self.last_frame.map(|last| {
+            Rect::new(
+                last.x - margin,
+                last.y - margin,
+                last.width + margin * 2,
+                last.height + margin * 2,
+            )
+        });
+        match self.register_with_fallback(window, &gray)? {
+            Ok((h, good_matches, inliers)) => {
+                let bbox = self.paint(&bgr, &gray, &h)?;
+                Ok(MosaicFrameResult {
+                    accepted: true,
+                    reason: None,
+                    bbox: Some(bbox),
+                    good_matches,
+                    inliers,
+                })
+            }
+            Err(reason) => Ok(MosaicFrameResult {
+                accepted: false,
+                reason: Some(reason),
+                bbox: None,
+                good_matches: 0,
+                inliers: 0,
+            }),
+        }
+    }
+
+    /// 查询图像在拼图中的位置（"我在哪"）
+    ///
+    /// # 参数
+    /// - `frame`: 查询图像
+    /// - `hint`: 预估的全局中心位置；提供时先在其附近 `radius` 范围内搜索，失败再粗配准整张拼图后就近精配准
+    /// - `radius`: 搜索半径（<=0 时取查询图像长边的 2 倍）
+    ///
+    /// # 返回值
+    /// 配准失败或结果未通过单应矩阵检查时返回 `None`。
+    pub fn locate(
+        &mut self,
+        frame: &Mat,
+        hint: Option<(f64, f64)>,
+        radius: f64,
+    ) -> Result<Option<MosaicLocation>, String> {
+        if frame.rows() <= 0 || frame.cols() <= 0 {
+            return Err("查询图像尺寸无效".to_string());
+        }
+        let gray = _to_gray_mat(frame)?;
+        let radius = if radius > 0.0 {
+            radius
+        } else {
+            2.0 * frame.cols().max(frame.rows()) as f64
+        };
+        let window = hint.map(|(x, y)| {
+            Rect::new(
+                (x - radius).floor() as i32,
+                (y - radius).floor() as i32,
+                (radius * 2.0).ceil() as i32,
+                (radius * 2.0).ceil() as i32,
+            )
+        });
+        let Ok((h, good_matches, inliers)) = self.register_with_fallback(window, &gray)? else {
+            return Ok(None);
+        };
+        let corners = project_corners(&h, frame.cols(), frame.rows());
+        Ok(Some(MosaicLocation {
+            center: project(&h, frame.cols() as f64 / 2.0, frame.rows() as f64 / 2.0),
+            bbox: corners_bbox(&corners),
+            corners: corners
+                .iter()
+                .map(|(x, y)| Point2f::new(*x as f32, *y as f32))
+                .collect(),
+            good_matches,
+            inliers,
+        }))
+    }
+
+    /// 导出整张拼图
+    ///
+    /// # 参数
+    /// - `zoom`: 缩放倍率（`(0, 4]`，1 为原始分辨率）；导出图左上角对应全局坐标 `-offset`
+    ///
+    /// 说明：逐瓦片读取并写入导出图，未缓存的瓦片直接从磁盘读取且不放入缓存，
+    /// 内存中只保留导出图本身与当前处理的一张瓦片。
+    pub fn export(&self, zoom: f64) -> Result<Mat, String> {
+        let bounds = self.bounds.ok_or_else(|| "拼图为空".to_string())?;
+        if !zoom.is_finite() || zoom <= 0.0 || zoom > 4.0 {
+            return Err(format!("导出缩放倍率无效: {zoom}"));
+        }
+
+        let out_w = ((bounds.width as f64) * zoom).ceil().max(1.0) as i32;
+        let out_h = ((bounds.height as f64) * zoom).ceil().max(1.0) as i32;
+        let mut out = Mat::new_rows_cols_with_default(out_h, out_w, CV_8UC3, Scalar::all(0.0))
+            .map_err(|e| format!("创建导出画布失败: {e}"))?;
+        let out_rect = Rect::new(0, 0, out_w, out_h);
+        let size = self.config.tile_size;
+        let interpolation = if zoom < 1.0 {
+            imgproc::INTER_AREA
+        } else {
+            imgproc::INTER_LINEAR
+        };
+        for &key in &self.known {
+            // 瓦片边界按同一公式取整，保证相邻瓦片在导出图中无缝衔接
+            let scaled =
+                |global: i32, origin: i32| ((global - origin) as f64 * zoom).floor() as i32;
+            let x0 = scaled(key.0 * size, bounds.x);
+            let y0 = scaled(key.1 * size, bounds.y);
+            let x1 = scaled((key.0 + 1) * size, bounds.x);
+            let y1 = scaled((key.1 + 1) * size, bounds.y);
+            if x1 <= x0 || y1 <= y0 {
+                continue;
+            }
+            let dst_rect = Rect::new(x0, y0, x1 - x0, y1 - y0);
+            let visible = intersect(dst_rect, out_rect);
+            if visible.width == 0 || visible.height == 0 {
+                continue;
+            }
+            let loaded;
+            let tile = match self.tiles.get(&key) {
+                Some(cached) => &cached.mat,
+                None => {
+                    loaded = self.read_tile(key)?;
+                    &loaded
+                }
+            };
+            let mut resized = Mat::default();
+            let scaled_tile = if dst_rect.width == size && dst_rect.height == size {
+                tile
+            } else {
+                imgproc::resize(
+                    tile,
+                    &mut resized,
+                    Size::new(dst_rect.width, dst_rect.height),
+                    0.0,
+                    0.0,
+                    interpolation,
+                )
+                .map_err(|e| format!("缩放瓦片失败: {e}"))?;
+                &resized
+            };
+            let src = Mat::roi(
+                scaled_tile,
+                Rect::new(
+                    visible.x - x0,
+                    visible.y - y0,
+                    visible.width,
+                    visible.height,
+                ),
+            )
+            .map_err(|e| format!("获取缩放瓦片 ROI 失败: {e}"))?;
+            let mut dst =
+                Mat::roi_mut(&mut out, visible).map_err(|e| format!("获取导出 ROI 失败: {e}"))?;
+            src.copy_to(&mut dst)
+                .map_err(|e| format!("复制缩放瓦片失败: {e}"))?;
+        }
+        Ok(out)
+    }
+
+    /// 将修改过的瓦片与索引写回磁盘
+    pub fn save(&mut self) -> Result<(), String> {
+        let tile_dir = self.dir.join(TILE_DIR);
+        std::fs::create_dir_all(&tile_dir)
+            .map_err(|e| format!("创建拼图目录失败 {}: {e}", tile_dir.display()))?;
+        let mut dirty: Vec<(i32, i32)> = self.dirty.iter().copied().collect();
+        dirty.sort();
+        for key in dirty {
+            self.write_tile(key)?;
+        }
+
+        let index = MosaicIndex {
+            version: INDEX_VERSION,
+            tile_size: self.config.tile_size,
+            bounds: self
+                .bounds
+                .map(|b| [b.x, b.y, b.x + b.width, b.y + b.height]),
+            offset: {
+                let (x, y) = self.offset();
+                [x, y]
+            },
+            frames: self.frames,
+            last_frame: self.last_frame.map(|r| [r.x, r.y, r.width, r.height]),
+            tiles: self.known.iter().map(|(c, r)| [*c, *r]).collect(),
+        };
+        let text =
+            serde_json::to_string_pretty(&index).map_err(|e| format!("序列化拼图索引失败: {e}"))?;
+        let index_path = self.dir.join(INDEX_FILE);
+        std::fs::write(&index_path, text)
+            .map_err(|e| format!("写入拼图索引失败 {}: {e}", index_path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submodules::test_support::ShapeTexture;
+
+    /// 参考地图的纹理参数（颜色非纯黑，避免被当作透明）
+    const TEXTURE: ShapeTexture = ShapeTexture {
+        background: 30.0,
+        area_per_shape: 1500,
+        rect_bound: (40, 40),
+        radius_bound: 18,
+        min_channel: 20,
+    };
+
+    fn crop(map: &Mat, x: i32, y: i32, size: i32) -> Mat {
+        Mat::roi(map, Rect::new(x, y, size, size))
+            .unwrap()
+            .try_clone()
+            .unwrap()
+    }
+
+    fn mean_abs_diff(a: &Mat, b: &Mat) -> f64 {
+        let mut diff = Mat::default();
+        core::absdiff(a, b, &mut diff).unwrap();
+        let mean = core::mean(&diff, &Mat::default()).unwrap();
+        (mean[0] + mean[1] + mean[2]) / 3.0
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dob-mosaic-{name}-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        dir
+    }
+
+    #[test]
+    fn builds_persists_and_locates() {
+        let map = TEXTURE.render(11, 900, 700);
+        let dir = temp_dir("build");
+        // 缓存上限小于单帧覆盖的瓦片数，拼接过程中会不断淘汰并写回瓦片
+        let config = MosaicConfig {
+            tile_size: 128,
+            max_cached_tiles: 4,
+            ..MosaicConfig::default()
+        };
+        let mut mosaic = WorldMosaic::open(&dir, config).unwrap();
+
+        // 首帧左上角 (300, 250) 成为全局原点，之后的帧向四周移动
+        let origin = (300, 250);
+        let path = [
+            (300, 250),
+            (400, 250),
+            (500, 300),
+            (400, 400),
+            (250, 380),
+            (160, 300),
+        ];
+        for (x, y) in path {
+            let result = mosaic.add_frame(&crop(&map, x, y, 200)).unwrap();
+            assert!(result.accepted, "({x}, {y}): {:?}", result.reason);
+            let bbox = result.bbox.unwrap();
+            assert!((bbox.x - (x - origin.0)).abs() <= 2, "{bbox:?}");
+            assert!((bbox.y - (y - origin.1)).abs() <= 2, "{bbox:?}");
+        }
+        assert!(mosaic.tiles.len() <= 4);
+        assert!(mosaic.tile_count() > 4);
+        let bounds = mosaic.bounds().unwrap();
+        assert_eq!(mosaic.offset(), (-bounds.x, -bounds.y));
+        assert!(bounds.x <= -139 && bounds.y <= 0, "{bounds:?}");
+
+        // 导出图与参考地图对应区域一致
+        let exported = mosaic.export(1.0).unwrap();
+        let (ox, oy) = mosaic.offset();
+        let sample = Rect::new(ox + 120, oy + 60, 120, 120);
+        let expected = crop(&map, origin.0 + 120, origin.1 + 60, 120);
+        let actual = Mat::roi(&exported, sample).unwrap().try_clone().unwrap();
+        assert!(mean_abs_diff(&expected, &actual) < 6.0);
+
+        let half = mosaic.export(0.5).unwrap();
+        assert_eq!(half.cols(), (exported.cols() + 1) / 2);
+
+        mosaic.save().unwrap();
+        let mut reopened = WorldMosaic::open(&dir, MosaicConfig::default()).unwrap();
+        assert_eq!(reopened.frame_count(), path.len() as u64);
+        assert_eq!(reopened.tile_count(), mosaic.tile_count());
+        assert_eq!(reopened.bounds(), mosaic.bounds());
+
+        let query = crop(&map, 420, 320, 140);
+        let expected_center = ((420 + 70 - origin.0) as f64, (320 + 70 - origin.1) as f64);
+        for hint in [None, Some(expected_center)] {
+            let location = reopened.locate(&query, hint, 0.0).unwrap().unwrap();
+            assert!(
+                (location.center.0 - expected_center.0).abs() < 2.0
+                    && (location.center.1 - expected_center.1).abs() < 2.0,
+                "{location:?}"
+            );
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn locates_in_large_mosaic_via_coarse_pass() {
+        // 首帧直接放在原点，拼图长边超过 COARSE_MAX_SIDE，无提示的查询走粗配准
+        let map = TEXTURE.render(17, COARSE_MAX_SIDE + 900, 500);
+        let config = MosaicConfig {
+            tile_size: 256,
+            ..MosaicConfig::default()
+        };
+        let mut mosaic = WorldMosaic::open(&temp_dir("coarse"), config).unwrap();
+        assert!(mosaic.add_frame(&map).unwrap().accepted);
+
+        let query = crop(&map, 2400, 150, 240);
+        let location = mosaic.locate(&query, None, 0.0).unwrap().unwrap();
+        assert!(
+            (location.center.0 - 2520.0).abs() < 2.0 && (location.center.1 - 270.0).abs() < 2.0,
+            "{location:?}"
+        );
+    }
+
+    #[test]
+    fn rejects_unrelated_frames_without_changing_the_map() {
+        let map = TEXTURE.render(11, 600, 500);
+        let other = TEXTURE.render(29, 1200, 900);
+        let mut mosaic = WorldMosaic::open(&temp_dir("reject"), MosaicConfig::default()).unwrap();
+        mosaic.add_frame(&crop(&map, 100, 100, 200)).unwrap();
+        let bounds = mosaic.bounds();
+
+        let result = mosaic.add_frame(&crop(&other, 900, 600, 200)).unwrap();
+        assert!(!result.accepted);
+        assert!(result.reason.is_some());
+        assert_eq!(mosaic.bounds(), bounds);
+        assert_eq!(mosaic.frame_count(), 1);
+    }
+
+    #[test]
+    fn homography_checks_flag_degenerate_transforms() {
+        let config = MosaicConfig::default();
+        let identity = [[1.0, 0.0, 5.0], [0.0, 1.0, 7.0], [0.0, 0.0, 1.0]];
+        assert_eq!(
+            homography_rejection(&identity, 100, 100, 40, 30, &config),
+            None
+        );
+
+        let mirrored = [[-1.0, 0.0, 100.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        assert!(homography_rejection(&mirrored, 100, 100, 40, 30, &config).is_some());
+
+        let zoomed = [[2.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 1.0]];
+        assert!(homography_rejection(&zoomed, 100, 100, 40, 30, &config).is_some());
+
+        let perspective = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.01, 0.0, 1.0]];
+        assert!(homography_rejection(&perspective, 100, 100, 40, 30, &config).is_some());
+
+        assert!(homography_rejection(&identity, 100, 100, 40, 5, &config).is_some());
+        assert!(homography_rejection(&identity, 100, 100, 100, 20, &config).is_some());
+    }
+}
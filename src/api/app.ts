@@ -320,6 +320,19 @@ export async function packPakFolder(
     }
 }
 
+/**
+ * 将目录下的 PNG 模板打包为模板图集（脚本中通过 `getTemplate("atlas.json#name")` 使用）
+ * @param sourceDir 模板目录（仅当前层级的 .png，模板名为文件名）
+ * @param manifestPath 输出清单路径（.json 或 .toml），图集图片写到同名 .png
+ * @returns 打包结果
+ */
+export async function packTemplateAtlas(
+    sourceDir: string,
+    manifestPath: string
+): Promise<{ manifestPath: string; imagePath: string; packedTemplates: number; width: number; height: number }> {
+    return await invoke("pack_template_atlas", { sourceDir, manifestPath })
+}
+
 /**
  * 移动/重命名文件（跨盘时后端回退为复制后删除）
  * @param sourcePath 源文件路径
@@ -390,10 +403,11 @@ export async function unwatchFile(filePath: string) {
 /**
  * 运行指定的脚本文件
  * @param filePath 脚本文件路径
+ * @param profile 是否启用性能分析（汇总输出到脚本控制台，折叠栈写入运行日志目录）
  * @returns 脚本返回值字符串（无返回值时为空字符串）
  */
-export async function runScript(scriptPath: string) {
-    return await invoke<string>("run_script", { scriptPath })
+export async function runScript(scriptPath: string, profile?: boolean) {
+    return await invoke<string>("run_script", { scriptPath, profile })
 }
 
 /**
@@ -568,6 +582,65 @@ export async function clearScriptMcpConsole(scriptPath?: string, includeGlobal?:
     return await invoke<ScriptMcpOperationResult>("clear_script_mcp_console", { scriptPath, includeGlobal })
 }
 
+/**
+ * 脚本文件日志运行记录摘要。
+ */
+export interface ScriptLogRunInfo {
+    runId: string
+    /** 运行记录目录 */
+    path: string
+    /** 开始时间（毫秒时间戳） */
+    startedAt: number
+    /** 目录总大小（字节） */
+    size: number
+}
+
+/**
+ * 列出脚本的文件日志运行记录（按时间从新到旧）。
+ * @param scriptPath 脚本完整路径
+ * @returns 运行记录列表
+ */
+export async function listScriptLogRuns(scriptPath: string) {
+    return await invoke<ScriptLogRunInfo[]>("list_script_log_runs", { scriptPath })
+}
+
+/**
+ * 导出脚本某次运行的日志包（JSONL 日志与状态截图，zip 格式）。
+ * @param scriptPath 脚本完整路径
+ * @param outputPath 输出 zip 路径
+ * @param runId 运行记录 ID，不传则导出最近一次运行
+ * @returns 实际导出的运行记录 ID
+ */
+export async function exportScriptLogBundle(scriptPath: string, outputPath: string, runId?: string) {
+    return await invoke<string>("export_script_log_bundle", { scriptPath, runId, outputPath })
+}
+
+/**
+ * 脚本网络访问权限（fetch/WebSocket）。
+ */
+export interface ScriptNetworkPermission {
+    enabled: boolean
+    /** 允许访问的主机列表，为空表示不限制 */
+    allowedHosts: string[]
+}
+
+/**
+ * 读取后端当前生效的脚本网络访问权限。
+ * @returns 网络访问权限
+ */
+export async function getScriptNetworkPermission() {
+    return await invoke<ScriptNetworkPermission>("get_script_network_permission")
+}
+
+/**
+ * 设置脚本网络访问权限（仅保存在后端内存中，启动时需由前端重新下发）。
+ * @param enabled 是否允许脚本访问网络
+ * @param allowedHosts 允许访问的主机列表，为空表示不限制
+ */
+export async function setScriptNetworkPermission(enabled: boolean, allowedHosts?: string[]) {
+    return await invoke<void>("set_script_network_permission", { enabled, allowedHosts })
+}
+
 /**
  * 同步脚本热键绑定。
  * @param bindings 完整绑定列表（会覆盖后端当前配置）
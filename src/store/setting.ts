@@ -6,6 +6,7 @@ import {
     isLaunchAtStartupEnabled,
     listSystemFonts,
     setLaunchAtStartupEnabled,
+    setScriptNetworkPermission,
     startHeartbeat,
     stopHeartbeat,
     tauriFetch,
@@ -57,6 +58,10 @@ export const useSettingStore = defineStore("setting", {
             autoSign: useLocalStorage("setting_auto_sign", false),
             launchAtStartup: useLocalStorage("setting_launch_at_startup", false),
             initScriptHotkeysAtStartup: useLocalStorage("setting_init_script_hotkeys_at_startup", true),
+            // 脚本网络访问（fetch/WebSocket），后端只保存在内存中，启动时由 syncScriptNetworkPermission 下发
+            scriptNetworkEnabled: useLocalStorage("setting_script_network_enabled", false),
+            // 允许访问的主机（逗号或换行分隔），空字符串表示不限制
+            scriptNetworkAllowedHosts: useLocalStorage("setting_script_network_allowed_hosts", ""),
             nextSignCheckTime: useLocalStorage("setting_next_sign_check_time", 0),
             // 剧情文本替换设置
             protagonistName1: useLocalStorage("story_protagonist_name_1", "维塔"),
@@ -237,6 +242,28 @@ export const useSettingStore = defineStore("setting", {
             this.launchAtStartup = nextState
             return nextState
         },
+        /**
+         * 将本地保存的脚本网络访问权限下发到后端（启动时及设置变更后调用）。
+         */
+        async syncScriptNetworkPermission() {
+            const allowedHosts = this.scriptNetworkAllowedHosts
+                .split(/[\s,]+/)
+                .map(host => host.trim())
+                .filter(Boolean)
+            await setScriptNetworkPermission(this.scriptNetworkEnabled, allowedHosts)
+        },
+        /**
+         * 更新脚本网络访问权限并同步到后端。
+         * @param enabled 是否允许脚本访问网络
+         * @param allowedHosts 允许访问的主机（逗号或换行分隔），不传则保持不变
+         */
+        async setScriptNetworkPermission(enabled: boolean, allowedHosts?: string) {
+            this.scriptNetworkEnabled = enabled
+            if (allowedHosts !== undefined) {
+                this.scriptNetworkAllowedHosts = allowedHosts
+            }
+            await this.syncScriptNetworkPermission()
+        },
         setWinMaterial(mat: string) {
             this.winMaterial = mat
             applyMaterial(this.winMaterial as any)